@@ -192,6 +192,538 @@ fn bench_vcf_parsing(c: &mut Criterion) {
     });
 }
 
+/// Benchmark sequential vs per-chromosome parallel WIG conversion
+/// on a synthetic whole-genome file (identity chain)
+fn bench_wig_conversion(c: &mut Criterion) {
+    use fast_crossmap::core::parse_chain_bytes;
+    use fast_crossmap::formats::{convert_wig, convert_wig_parallel, WigAggregation};
+    use std::fmt::Write as _;
+    
+    const CHROM_SIZE: u64 = 10_000_000;
+    const POINTS_PER_CHROM: u64 = 20_000;
+    
+    let mut chain = String::new();
+    let mut wig = String::new();
+    for (i, n) in (1..=22).enumerate() {
+        let chrom = format!("chr{}", n);
+        writeln!(chain, "chain 1000 {c} {s} + 0 {s} {c} {s} + 0 {s} {}", i + 1, c = chrom, s = CHROM_SIZE).unwrap();
+        writeln!(chain, "{}\n", CHROM_SIZE).unwrap();
+        
+        writeln!(wig, "variableStep chrom={} span=10", chrom).unwrap();
+        for p in 0..POINTS_PER_CHROM {
+            writeln!(wig, "{}\t{}", p * 100 + 1, p % 7).unwrap();
+        }
+    }
+    
+    let index = ChainIndex::from_chain_data(parse_chain_bytes(chain.as_bytes()).unwrap());
+    let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("genome.wig");
+    std::fs::write(&input, wig).unwrap();
+    let output = dir.path().join("out");
+    
+    let mut group = c.benchmark_group("wig_conversion");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(22 * POINTS_PER_CHROM));
+    
+    group.bench_function("sequential", |b| {
+        b.iter(|| black_box(convert_wig(&input, &output, &mapper).unwrap()))
+    });
+    for threads in [2, 4, 8] {
+        group.bench_with_input(BenchmarkId::new("parallel", threads), &threads, |b, &threads| {
+            b.iter(|| black_box(convert_wig_parallel(&input, &output, &mapper, threads, WigAggregation::First).unwrap()))
+        });
+    }
+    
+    group.finish();
+}
+
+/// Benchmark sequential vs parallel parsing of a ~50 MB in-memory chain file
+fn bench_chain_parsing(c: &mut Criterion) {
+    use fast_crossmap::core::{parse_chain_bytes, parse_chain_bytes_parallel};
+    use std::fmt::Write as _;
+    
+    const TARGET_BYTES: usize = 50 * 1024 * 1024;
+    
+    let mut chain = String::with_capacity(TARGET_BYTES + 4096);
+    let mut id = 0u64;
+    while chain.len() < TARGET_BYTES {
+        let chrom = format!("chr{}", id % 22 + 1);
+        let start = (id / 22) * 3000;
+        writeln!(chain, "chain {} {c} 250000000 + {s} {e} {c} 250000000 + {s} {e} {}", 1000 + id % 5000, id + 1, c = chrom, s = start, e = start + 2300).unwrap();
+        for _ in 0..20 {
+            chain.push_str("100 10 10\n");
+        }
+        chain.push_str("100\n\n");
+        id += 1;
+    }
+    
+    let mut group = c.benchmark_group("chain_parsing");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(chain.len() as u64));
+    
+    group.bench_function("sequential", |b| {
+        b.iter(|| black_box(parse_chain_bytes(chain.as_bytes()).unwrap()))
+    });
+    for threads in [2, 4, 8] {
+        group.bench_with_input(BenchmarkId::new("parallel", threads), &threads, |b, &threads| {
+            b.iter(|| black_box(parse_chain_bytes_parallel(chain.as_bytes(), threads).unwrap()))
+        });
+    }
+    
+    group.finish();
+}
+
+/// Benchmark bedGraph merging on sorted (typical WIG) and shuffled input
+fn bench_bedgraph_merge(c: &mut Criterion) {
+    use fast_crossmap::formats::{merge_bedgraph_records, BedGraphRecord, WigAggregation};
+    
+    let sorted: Vec<BedGraphRecord> = (0..1_000_000u64)
+        .map(|i| BedGraphRecord {
+            chrom: format!("chr{}", i / 100_000 + 1),
+            start: (i % 100_000) * 10,
+            end: (i % 100_000) * 10 + 10,
+            value: ((i / 3) % 5) as f64 - 2.0,
+        })
+        .collect();
+    
+    // Deterministic shuffle: reverse every block of 1000 records
+    let mut shuffled = sorted.clone();
+    for chunk in shuffled.chunks_mut(1000) {
+        chunk.reverse();
+    }
+    
+    let mut group = c.benchmark_group("bedgraph_merge");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(sorted.len() as u64));
+    
+    group.bench_function("sorted", |b| {
+        b.iter(|| black_box(merge_bedgraph_records(sorted.clone(), WigAggregation::First)))
+    });
+    group.bench_function("unsorted", |b| {
+        b.iter(|| black_box(merge_bedgraph_records(shuffled.clone(), WigAggregation::First)))
+    });
+    
+    group.finish();
+}
+
+/// Benchmark reading dense per-base coverage: run-length encoded fixedStep
+/// (step=1, span=1) vs the same values as variableStep (one point per base)
+fn bench_wig_dense_reading(c: &mut Criterion) {
+    use fast_crossmap::formats::WigReader;
+    use std::fmt::Write as _;
+    
+    const BASES: u64 = 1_000_000;
+    
+    // Coverage changes every 50 bases, as in typical read depth tracks
+    let mut fixed = String::from("fixedStep chrom=chr1 start=1 step=1 span=1\n");
+    let mut variable = String::from("variableStep chrom=chr1\n");
+    for i in 0..BASES {
+        let depth = (i / 50) % 30;
+        writeln!(fixed, "{}", depth).unwrap();
+        writeln!(variable, "{}\t{}", i + 1, depth).unwrap();
+    }
+    
+    let mut group = c.benchmark_group("wig_dense_reading");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(BASES));
+    
+    group.bench_function("fixed_step_rle", |b| {
+        b.iter(|| black_box(WigReader::new(std::io::Cursor::new(fixed.as_bytes())).count()))
+    });
+    group.bench_function("variable_step_per_base", |b| {
+        b.iter(|| black_box(WigReader::new(std::io::Cursor::new(variable.as_bytes())).count()))
+    });
+    
+    group.finish();
+}
+
+/// Benchmark the two parallel BED strategies on 10M records: collecting all
+/// output lines in memory vs per-thread part files merged at the end
+fn bench_bed_parallel_strategies(c: &mut Criterion) {
+    use fast_crossmap::core::parse_chain_bytes;
+    use fast_crossmap::formats::{convert_bed_with_options, BedConvertOptions};
+    use std::io::Write as _;
+    
+    const RECORDS: u64 = 10_000_000;
+    const CHROM_SIZE: u64 = 100_000_000;
+    
+    let chain = format!("chain 1000 chr1 {s} + 0 {s} chr1 {s} + 0 {s} 1\n{s}\n", s = CHROM_SIZE);
+    let index = ChainIndex::from_chain_data(parse_chain_bytes(chain.as_bytes()).unwrap());
+    let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("large.bed");
+    {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(&input).unwrap());
+        for i in 0..RECORDS {
+            let start = (i * 9) % (CHROM_SIZE - 100);
+            writeln!(writer, "chr1\t{}\t{}\tr{}\t0\t+", start, start + 100, i).unwrap();
+        }
+    }
+    let output = dir.path().join("out.bed");
+    let unmap = dir.path().join("out.bed.unmap");
+    
+    let mut group = c.benchmark_group("bed_parallel_strategies");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(RECORDS));
+    
+    for per_thread_output in [false, true] {
+        let name = if per_thread_output { "per_thread_files" } else { "collect" };
+        let options = BedConvertOptions { threads: 8, per_thread_output, ..Default::default() };
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(convert_bed_with_options(&input, &output, &unmap, &mapper, &options).unwrap()))
+        });
+    }
+    
+    group.finish();
+}
+
+/// Benchmark channel-based streaming against batch-parallel BED conversion
+/// on 10M records
+fn bench_bed_channel_vs_batch(c: &mut Criterion) {
+    use fast_crossmap::core::parse_chain_bytes;
+    use fast_crossmap::formats::{convert_bed_with_options, BedConvertOptions};
+    use std::io::Write as _;
+    
+    const RECORDS: u64 = 10_000_000;
+    const CHROM_SIZE: u64 = 100_000_000;
+    
+    let chain = format!("chain 1000 chr1 {s} + 0 {s} chr1 {s} + 0 {s} 1\n{s}\n", s = CHROM_SIZE);
+    let index = ChainIndex::from_chain_data(parse_chain_bytes(chain.as_bytes()).unwrap());
+    let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("large.bed");
+    {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(&input).unwrap());
+        for i in 0..RECORDS {
+            let start = (i * 9) % (CHROM_SIZE - 100);
+            writeln!(writer, "chr1\t{}\t{}\tr{}\t0\t+", start, start + 100, i).unwrap();
+        }
+    }
+    let output = dir.path().join("out.bed");
+    let unmap = dir.path().join("out.bed.unmap");
+    
+    let mut group = c.benchmark_group("bed_channel_vs_batch");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(RECORDS));
+    
+    for streaming in [false, true] {
+        let name = if streaming { "channel" } else { "batch" };
+        let options = BedConvertOptions { threads: 8, streaming, ..Default::default() };
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(convert_bed_with_options(&input, &output, &unmap, &mapper, &options).unwrap()))
+        });
+    }
+    
+    group.finish();
+}
+
+/// Benchmark the parallel BED chunk size on 1M BED6 records
+fn bench_bed_chunk_sizes(c: &mut Criterion) {
+    use fast_crossmap::core::parse_chain_bytes;
+    use fast_crossmap::formats::{convert_bed_with_options, BedConvertOptions};
+    use std::io::Write as _;
+    
+    const RECORDS: u64 = 1_000_000;
+    const CHROM_SIZE: u64 = 100_000_000;
+    
+    let chain = format!("chain 1000 chr1 {s} + 0 {s} chr1 {s} + 0 {s} 1\n{s}\n", s = CHROM_SIZE);
+    let index = ChainIndex::from_chain_data(parse_chain_bytes(chain.as_bytes()).unwrap());
+    let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("bed6.bed");
+    {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(&input).unwrap());
+        for i in 0..RECORDS {
+            let start = (i * 97) % (CHROM_SIZE - 100);
+            writeln!(writer, "chr1\t{}\t{}\tr{}\t0\t+", start, start + 100, i).unwrap();
+        }
+    }
+    let output = dir.path().join("out.bed");
+    let unmap = dir.path().join("out.bed.unmap");
+    
+    let mut group = c.benchmark_group("bed_chunk_size");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(RECORDS));
+    
+    for chunk_size in [1_000usize, 5_000, 10_000, 50_000, 100_000] {
+        let options = BedConvertOptions { threads: 8, chunk_size, ..Default::default() };
+        group.bench_with_input(BenchmarkId::from_parameter(chunk_size), &options, |b, options| {
+            b.iter(|| black_box(convert_bed_with_options(&input, &output, &unmap, &mapper, options).unwrap()))
+        });
+    }
+    
+    group.finish();
+}
+
+/// Benchmark index queries on sparse and dense synthetic chains
+///
+/// Run once with and once without `--features flat-index` to compare the
+/// sorted-Vec store against rust-lapper.
+fn bench_index_layouts(c: &mut Criterion) {
+    use fast_crossmap::core::parse_chain_bytes;
+    use std::fmt::Write as _;
+    
+    const CHROM_SIZE: u64 = 100_000_000;
+    let store = if cfg!(feature = "flat-index") { "flat" } else { "lapper" };
+    
+    // Sparse: 100 blocks of ~1 Mb; dense: 200,000 blocks of 400 bp
+    let chain_with_blocks = |blocks: u64| {
+        let step = CHROM_SIZE / blocks;
+        let size = step * 4 / 5;
+        let mut chain = format!("chain 1000 chr1 {s} + 0 {e} chr1 {s} + 0 {e} 1\n", s = CHROM_SIZE, e = step * blocks - (step - size));
+        for i in 0..blocks {
+            if i + 1 < blocks {
+                writeln!(chain, "{}\t{}\t{}", size, step - size, step - size).unwrap();
+            } else {
+                writeln!(chain, "{}", size).unwrap();
+            }
+        }
+        ChainIndex::from_chain_data(parse_chain_bytes(chain.as_bytes()).unwrap())
+    };
+    
+    let mut group = c.benchmark_group(format!("index_query_{}", store));
+    for (name, blocks) in [("sparse", 100u64), ("dense", 200_000u64)] {
+        let index = chain_with_blocks(blocks);
+        let queries: Vec<u64> = (0..10_000u64).map(|i| (i * 9_973) % (CHROM_SIZE - 1_000)).collect();
+        
+        group.throughput(Throughput::Elements(queries.len() as u64));
+        group.bench_with_input(BenchmarkId::new(name, blocks), &queries, |b, queries| {
+            b.iter(|| {
+                let mut hits = 0;
+                for &start in queries {
+                    hits += index.query_intervals(black_box("chr1"), start, start + 1_000).len();
+                }
+                black_box(hits)
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark single-point lookups on a dense synthetic chain: the interval
+/// store vs binary search over `ChainIndex::sorted_intervals`
+fn bench_point_queries(c: &mut Criterion) {
+    use fast_crossmap::core::parse_chain_bytes;
+    use std::fmt::Write as _;
+    
+    const BLOCKS: u64 = 200_000;
+    const STEP: u64 = 500;
+    let mut chain = format!("chain 1000 chr1 {s} + 0 {e} chr1 {s} + 0 {e} 1\n", s = BLOCKS * STEP, e = BLOCKS * STEP - 100);
+    for i in 0..BLOCKS {
+        if i + 1 < BLOCKS {
+            writeln!(chain, "400\t100\t100").unwrap();
+        } else {
+            writeln!(chain, "400").unwrap();
+        }
+    }
+    let index = ChainIndex::from_chain_data(parse_chain_bytes(chain.as_bytes()).unwrap());
+    let queries: Vec<u64> = (0..10_000u64).map(|i| (i * 9_973) % (BLOCKS * STEP)).collect();
+    
+    let mut group = c.benchmark_group("point_query");
+    group.throughput(Throughput::Elements(queries.len() as u64));
+    group.bench_function("interval_store", |b| {
+        b.iter(|| {
+            let mut hits = 0;
+            for &pos in &queries {
+                hits += index.query_intervals(black_box("chr1"), pos, pos + 1).len();
+            }
+            black_box(hits)
+        })
+    });
+    group.bench_function("sorted_binary_search", |b| {
+        b.iter(|| {
+            let sorted = index.sorted_intervals(black_box("chr1"));
+            let mut hits = 0;
+            for &pos in &queries {
+                let i = sorted.partition_point(|iv| iv.start <= pos);
+                if i > 0 && pos < sorted[i - 1].stop {
+                    hits += 1;
+                }
+            }
+            black_box(hits)
+        })
+    });
+    group.finish();
+}
+
+/// Benchmark reference access for 10k GVCF variant records: per-record
+/// seeks through the .fai index vs a chromosome preloaded with `load_chrom`,
+/// plus the full conversion
+fn bench_gvcf_reference(c: &mut Criterion) {
+    use fast_crossmap::core::parse_chain_bytes;
+    use fast_crossmap::formats::convert_gvcf;
+    use fast_crossmap::formats::gvcf::fasta::FastaReader;
+    use std::io::Write as _;
+    
+    const RECORDS: u64 = 10_000;
+    const CHROM_SIZE: u64 = 10_000_000;
+    
+    let chain = format!("chain 1000 chr1 {s} + 0 {s} chr1 {s} + 0 {s} 1\n{s}\n", s = CHROM_SIZE);
+    let index = ChainIndex::from_chain_data(parse_chain_bytes(chain.as_bytes()).unwrap());
+    let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let fasta = dir.path().join("ref.fa");
+    {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(&fasta).unwrap());
+        writeln!(writer, ">chr1").unwrap();
+        for i in 0..CHROM_SIZE / 60 {
+            let line: String = (0..60).map(|j| b"ACGT"[((i + j) % 4) as usize] as char).collect();
+            writeln!(writer, "{}", line).unwrap();
+        }
+    }
+    let positions: Vec<u64> = (0..RECORDS).map(|i| i * (CHROM_SIZE / 60 * 60 / RECORDS)).collect();
+    let input = dir.path().join("in.g.vcf");
+    {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(&input).unwrap());
+        writeln!(writer, "##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO").unwrap();
+        for &pos in &positions {
+            writeln!(writer, "chr1\t{}\t.\tA\tG,<NON_REF>\t50\tPASS\tDP=10", pos + 1).unwrap();
+        }
+    }
+    let output = dir.path().join("out.g.vcf");
+    
+    let mut group = c.benchmark_group("gvcf_reference");
+    group.throughput(Throughput::Elements(RECORDS));
+    
+    group.bench_function("fetch_seek", |b| {
+        let reader = FastaReader::open(&fasta).unwrap();
+        b.iter(|| {
+            for &pos in &positions {
+                black_box(reader.fetch("chr1", pos, pos + 1));
+            }
+        })
+    });
+    group.bench_function("fetch_preloaded", |b| {
+        let reader = FastaReader::open(&fasta).unwrap();
+        reader.load_chrom("chr1").unwrap();
+        b.iter(|| {
+            for &pos in &positions {
+                black_box(reader.fetch("chr1", pos, pos + 1));
+            }
+        })
+    });
+    group.sample_size(10);
+    group.bench_function("convert", |b| {
+        b.iter(|| black_box(convert_gvcf(&input, &output, &mapper, Some(&fasta), false, None, 1).unwrap()))
+    });
+    
+    group.finish();
+}
+
+/// Benchmark the first query on each of 24 chromosomes with a cold cache,
+/// after `prefetch_all`, and with the cache already warm from the same
+/// queries
+///
+/// The small index fits in cache, so prefetching brings the first queries
+/// close to warm latency; the large one (48,000 blocks) only partly fits.
+fn bench_mapper_prefetch(c: &mut Criterion) {
+    use criterion::BatchSize;
+    use fast_crossmap::core::parse_chain_bytes;
+    use std::fmt::Write as _;
+    
+    const CHROMS: usize = 24;
+    
+    let build_mapper = |blocks: u64| {
+        let chrom_size = blocks * 500;
+        let mut chain = String::new();
+        for c in 1..=CHROMS {
+            writeln!(chain, "chain 1000 chr{c} {s} + 0 {e} chr{c} {s} + 0 {e} {c}", c = c, s = chrom_size, e = chrom_size - 100).unwrap();
+            for i in 0..blocks {
+                if i + 1 < blocks {
+                    writeln!(chain, "400\t100\t100").unwrap();
+                } else {
+                    writeln!(chain, "400").unwrap();
+                }
+            }
+            chain.push('\n');
+        }
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(chain.as_bytes()).unwrap());
+        (CoordinateMapper::new(index, ChromStyle::AsIs), chrom_size)
+    };
+    let chroms: Vec<String> = (1..=CHROMS).map(|c| format!("chr{}", c)).collect();
+    
+    // Overwrite the CPU caches with an unrelated buffer
+    let mut scratch = vec![0u8; 64 * 1024 * 1024];
+    let mut evict = move || {
+        for (i, b) in scratch.iter_mut().enumerate().step_by(64) {
+            *b = b.wrapping_add(i as u8);
+        }
+        black_box(&scratch);
+    };
+    
+    let mut group = c.benchmark_group("mapper_first_query");
+    group.throughput(Throughput::Elements(CHROMS as u64));
+    for (name, blocks) in [("small", 20u64), ("large", 2_000u64)] {
+        let (mapper, chrom_size) = build_mapper(blocks);
+        let first_queries = || {
+            for (i, chrom) in chroms.iter().enumerate() {
+                let start = (i as u64 * 7_919) % (chrom_size - 1_000);
+                black_box(mapper.map(chrom, start, start + 200, Strand::Plus));
+            }
+        };
+        
+        group.bench_function(BenchmarkId::new("cold", name), |b| {
+            b.iter_batched(&mut evict, |_| first_queries(), BatchSize::PerIteration)
+        });
+        group.bench_function(BenchmarkId::new("prefetched", name), |b| {
+            b.iter_batched(|| { evict(); mapper.prefetch_all(); }, |_| first_queries(), BatchSize::PerIteration)
+        });
+        group.bench_function(BenchmarkId::new("warm", name), |b| {
+            b.iter_batched(&first_queries, |_| first_queries(), BatchSize::PerIteration)
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark `map` in a loop vs `map_batch` on 1M queries on one chromosome
+fn bench_map_batch(c: &mut Criterion) {
+    use fast_crossmap::core::parse_chain_bytes;
+    use std::fmt::Write as _;
+    
+    const BLOCKS: u64 = 200_000;
+    const STEP: u64 = 500;
+    const RECORDS: u64 = 1_000_000;
+    let mut chain = format!("chain 1000 chr1 {s} + 0 {e} chr1 {s} + 0 {e} 1\n", s = BLOCKS * STEP, e = BLOCKS * STEP - 100);
+    for i in 0..BLOCKS {
+        if i + 1 < BLOCKS {
+            writeln!(chain, "400\t100\t100").unwrap();
+        } else {
+            writeln!(chain, "400").unwrap();
+        }
+    }
+    let index = ChainIndex::from_chain_data(parse_chain_bytes(chain.as_bytes()).unwrap());
+    let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+    let records: Vec<(&str, u64, u64, Strand)> = (0..RECORDS)
+        .map(|i| {
+            let pos = (i * 97) % (BLOCKS * STEP - 200);
+            ("chr1", pos, pos + 50, Strand::Plus)
+        })
+        .collect();
+    
+    let mut group = c.benchmark_group("map_batch");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(RECORDS));
+    group.bench_function("map_loop", |b| {
+        b.iter(|| {
+            let results: Vec<_> = records
+                .iter()
+                .map(|&(chrom, start, end, strand)| mapper.map(chrom, start, end, strand))
+                .collect();
+            black_box(results)
+        })
+    });
+    group.bench_function("map_batch", |b| {
+        b.iter(|| black_box(mapper.map_batch(black_box(&records))))
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_chain_loading,
@@ -202,6 +734,18 @@ criterion_group!(
     bench_revcomp,
     bench_bed_parsing,
     bench_vcf_parsing,
+    bench_wig_conversion,
+    bench_chain_parsing,
+    bench_bedgraph_merge,
+    bench_wig_dense_reading,
+    bench_bed_parallel_strategies,
+    bench_bed_channel_vs_batch,
+    bench_bed_chunk_sizes,
+    bench_index_layouts,
+    bench_point_queries,
+    bench_gvcf_reference,
+    bench_mapper_prefetch,
+    bench_map_batch,
 );
 
 criterion_main!(benches);
@@ -0,0 +1,230 @@
+//! Batch conversion
+//!
+//! Runs several conversions described in a TOML file against one loaded
+//! chain, so the (slow) chain load is paid once:
+//!
+//! ```toml
+//! [[convert]]
+//! type = "bed"
+//! input = "peaks.bed"
+//! output = "peaks.hg38.bed"
+//!
+//! [[convert]]
+//! type = "vcf"
+//! input = "calls.vcf"
+//! output = "calls.hg38.vcf"
+//! refgenome = "hg38.fa"
+//! ```
+//!
+//! Relative paths are resolved against the directory of the config file.
+
+use crate::core::CoordinateMapper;
+use crate::formats::{self, GenomicFormat};
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Batch configuration error
+#[derive(Debug, thiserror::Error)]
+pub enum BatchError {
+    #[error("Invalid batch config: {0}")]
+    InvalidConfig(String),
+
+    #[error("Task {index}: {message}")]
+    InvalidTask { index: usize, message: String },
+
+    #[error("Conversion of {input:?} failed: {message}")]
+    Conversion { input: PathBuf, message: String },
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// One conversion from the `[[convert]]` list
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionTask {
+    pub format: GenomicFormat,
+    pub input: PathBuf,
+    /// Output file (output prefix for Wiggle)
+    pub output: PathBuf,
+    /// Target reference FASTA (VCF only, optional)
+    pub refgenome: Option<PathBuf>,
+}
+
+/// Record counts of one finished task
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TaskSummary {
+    pub total: usize,
+    pub success: usize,
+    pub failed: usize,
+}
+
+/// Batch configuration file reader
+pub struct BatchConfig;
+
+impl BatchConfig {
+    /// Read the `[[convert]]` tasks of a TOML config file
+    pub fn from_toml<P: AsRef<Path>>(path: P) -> Result<Vec<ConversionTask>, BatchError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        Self::parse(&text, base)
+    }
+
+    /// Parse config text, resolving relative paths against `base`
+    pub fn parse(text: &str, base: &Path) -> Result<Vec<ConversionTask>, BatchError> {
+        let table: toml::Table = text
+            .parse()
+            .map_err(|e: toml::de::Error| BatchError::InvalidConfig(e.message().to_string()))?;
+
+        let entries = match table.get("convert") {
+            Some(toml::Value::Array(entries)) => entries,
+            Some(_) => return Err(BatchError::InvalidConfig("'convert' must be an array of tables".to_string())),
+            None => return Err(BatchError::InvalidConfig("no [[convert]] entries".to_string())),
+        };
+
+        entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| parse_task(index + 1, entry, base))
+            .collect()
+    }
+}
+
+/// Parse one `[[convert]]` table (`index` is 1-based)
+fn parse_task(index: usize, entry: &toml::Value, base: &Path) -> Result<ConversionTask, BatchError> {
+    let invalid = |message: String| BatchError::InvalidTask { index, message };
+    let table = entry.as_table().ok_or_else(|| invalid("not a table".to_string()))?;
+
+    let string = |key: &str| -> Result<Option<&str>, BatchError> {
+        match table.get(key) {
+            None => Ok(None),
+            Some(toml::Value::String(s)) => Ok(Some(s.as_str())),
+            Some(_) => Err(invalid(format!("'{}' must be a string", key))),
+        }
+    };
+    let required = |key: &str| string(key)?.ok_or_else(|| invalid(format!("missing '{}'", key)));
+    let resolve = |p: &str| if p == "-" { PathBuf::from(p) } else { base.join(p) };
+
+    let format = match required("type")?.to_ascii_lowercase().as_str() {
+        "bed" => GenomicFormat::Bed,
+        "vcf" => GenomicFormat::Vcf,
+        "gff" | "gff3" => GenomicFormat::Gff,
+        "gtf" => GenomicFormat::Gtf,
+        "wig" | "bedgraph" => GenomicFormat::Wig,
+        other => return Err(invalid(format!("unsupported type '{}' (expected bed, vcf, gff, gtf or wig)", other))),
+    };
+
+    Ok(ConversionTask {
+        format,
+        input: resolve(required("input")?),
+        output: resolve(required("output")?),
+        refgenome: string("refgenome")?.map(resolve),
+    })
+}
+
+/// Run one task with the default options of its format
+pub fn run_task(task: &ConversionTask, mapper: &CoordinateMapper) -> Result<TaskSummary, BatchError> {
+    let failed = |message: String| BatchError::Conversion { input: task.input.clone(), message };
+
+    let (total, success, failed_count) = match task.format {
+        GenomicFormat::Bed => {
+            let unmap = task.output.with_extension("bed.unmap");
+            let s = formats::convert_bed_with_options(&task.input, &task.output, &unmap, mapper, &Default::default())
+                .map_err(|e| failed(e.to_string()))?;
+            (s.total, s.success, s.failed)
+        }
+        GenomicFormat::Vcf => {
+            let s = formats::convert_vcf_with_options(
+                task.input.as_path(),
+                task.output.as_path(),
+                mapper,
+                task.refgenome.as_deref(),
+                &Default::default(),
+            )
+            .map_err(|e| failed(e.to_string()))?;
+            (s.total, s.success, s.failed)
+        }
+        GenomicFormat::Gff | GenomicFormat::Gtf => {
+            let s = formats::convert_gff_with_options(&task.input, &task.output, mapper, &Default::default())
+                .map_err(|e| failed(e.to_string()))?;
+            (s.total, s.success, s.failed)
+        }
+        GenomicFormat::Wig => {
+            let s = formats::convert_wig_with_options(&task.input, &task.output, mapper, &Default::default())
+                .map_err(|e| failed(e.to_string()))?;
+            (s.total, s.success, s.failed)
+        }
+        other => return Err(failed(format!("{} is not supported in batch mode", other))),
+    };
+
+    Ok(TaskSummary { total, success, failed: failed_count })
+}
+
+/// Run all tasks against one mapper, returning results in task order
+///
+/// With `parallel`, tasks run concurrently on the rayon pool (each task
+/// itself stays single-threaded).
+pub fn run_batch(
+    tasks: &[ConversionTask],
+    mapper: &CoordinateMapper,
+    parallel: bool,
+) -> Vec<Result<TaskSummary, BatchError>> {
+    if parallel {
+        tasks.par_iter().map(|task| run_task(task, mapper)).collect()
+    } else {
+        tasks.iter().map(|task| run_task(task, mapper)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = r#"
+[[convert]]
+type = "bed"
+input = "in.bed"
+output = "out/in.bed"
+
+[[convert]]
+type = "VCF"
+input = "/data/calls.vcf"
+output = "-"
+refgenome = "hg38.fa"
+"#;
+        let tasks = BatchConfig::parse(config, Path::new("/work")).unwrap();
+        assert_eq!(tasks, vec![
+            ConversionTask {
+                format: GenomicFormat::Bed,
+                input: PathBuf::from("/work/in.bed"),
+                output: PathBuf::from("/work/out/in.bed"),
+                refgenome: None,
+            },
+            ConversionTask {
+                format: GenomicFormat::Vcf,
+                input: PathBuf::from("/data/calls.vcf"),
+                output: PathBuf::from("-"),
+                refgenome: Some(PathBuf::from("/work/hg38.fa")),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_config_errors() {
+        let base = Path::new("");
+        assert!(matches!(BatchConfig::parse("", base), Err(BatchError::InvalidConfig(_))));
+        assert!(matches!(BatchConfig::parse("convert = 1", base), Err(BatchError::InvalidConfig(_))));
+        assert!(matches!(BatchConfig::parse("[[convert", base), Err(BatchError::InvalidConfig(_))));
+
+        let missing_output = "[[convert]]\ntype = \"bed\"\ninput = \"a.bed\"\n";
+        assert!(matches!(
+            BatchConfig::parse(missing_output, base),
+            Err(BatchError::InvalidTask { index: 1, ref message }) if message == "missing 'output'"
+        ));
+
+        let bad_type = "[[convert]]\ntype = \"bed\"\ninput = \"a\"\noutput = \"b\"\n[[convert]]\ntype = \"bam\"\ninput = \"a\"\noutput = \"b\"\n";
+        assert!(matches!(BatchConfig::parse(bad_type, base), Err(BatchError::InvalidTask { index: 2, .. })));
+    }
+}
@@ -0,0 +1,454 @@
+//! BGZF (blocked gzip) reading and writing
+//!
+//! BGZF files are a series of independent gzip members of at most 64 KB,
+//! each carrying its compressed size in a `BC` extra subfield. That makes
+//! them valid gzip files and allows random access: a virtual offset
+//! `(compressed block offset << 16) | offset within the block` addresses
+//! any uncompressed byte. Used for bgzip-compressed reference FASTA
+//! (`.fa.gz` + `.fa.gz.fai` + `.fa.gz.gzi`).
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+/// Uncompressed bytes per block written by [`BgzfWriter`], as in htslib
+pub const MAX_BLOCK_DATA: usize = 0xff00;
+
+/// Empty block marking the end of a BGZF file
+pub const EOF_BLOCK: [u8; 28] = [
+    0x1f, 0x8b, 0x08, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0xff, 0x06, 0x00, 0x42, 0x43, 0x02, 0x00,
+    0x1b, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+];
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// Check whether `bytes` start with a BGZF block header
+///
+/// A gzip header (`1f 8b 08`) with FEXTRA set and a `BC` subfield first
+/// in the extra field, as written by bgzip and htslib. Needs the first
+/// 16 bytes of the file.
+pub fn is_bgzf_header(bytes: &[u8]) -> bool {
+    bytes.len() >= 16
+        && bytes[..3] == [0x1f, 0x8b, 0x08]
+        && bytes[3] & 0x04 != 0
+        && bytes[12..14] == *b"BC"
+        && bytes[14..16] == [2, 0]
+}
+
+/// Check whether the file at `path` is BGZF-compressed
+pub fn is_bgzf_file<P: AsRef<Path>>(path: P) -> io::Result<bool> {
+    let mut header = [0u8; 16];
+    let read = read_full(&mut File::open(path)?, &mut header)?;
+    Ok(is_bgzf_header(&header[..read]))
+}
+
+/// Combine a compressed block offset and an offset within that block
+#[inline]
+pub fn virtual_offset(block_offset: u64, within_block: u16) -> u64 {
+    (block_offset << 16) | within_block as u64
+}
+
+/// Read until `buf` is full or the reader is exhausted
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+/// Read and inflate one block into `data`
+///
+/// Returns the compressed size of the block, or None at end of input.
+fn read_block<R: Read>(reader: &mut R, data: &mut Vec<u8>) -> io::Result<Option<u64>> {
+    let mut header = [0u8; 12];
+    match read_full(reader, &mut header)? {
+        0 => return Ok(None),
+        12 => {}
+        _ => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated BGZF block header")),
+    }
+    if header[..3] != [0x1f, 0x8b, 0x08] || header[3] & 0x04 == 0 {
+        return Err(invalid_data("not a BGZF block (missing gzip extra field)"));
+    }
+
+    let xlen = u16::from_le_bytes([header[10], header[11]]) as usize;
+    let mut extra = vec![0u8; xlen];
+    reader.read_exact(&mut extra)?;
+    let mut block_size = None;
+    let mut rest = &extra[..];
+    while rest.len() >= 4 {
+        let len = u16::from_le_bytes([rest[2], rest[3]]) as usize;
+        if rest[..2] == *b"BC" && len == 2 && rest.len() >= 6 {
+            block_size = Some(u16::from_le_bytes([rest[4], rest[5]]) as usize + 1);
+        }
+        rest = &rest[(4 + len).min(rest.len())..];
+    }
+    let block_size = block_size.ok_or_else(|| invalid_data("not a BGZF block (missing BC subfield)"))?;
+    let cdata_len = block_size
+        .checked_sub(header.len() + xlen + 8)
+        .ok_or_else(|| invalid_data(format!("invalid BGZF block size {}", block_size)))?;
+
+    let mut cdata = vec![0u8; cdata_len];
+    reader.read_exact(&mut cdata)?;
+    let mut trailer = [0u8; 8];
+    reader.read_exact(&mut trailer)?;
+    let crc32 = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let isize = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]) as usize;
+
+    data.clear();
+    data.reserve(isize);
+    DeflateDecoder::new(&cdata[..]).read_to_end(data)?;
+    let mut crc = Crc::new();
+    crc.update(data);
+    if data.len() != isize || crc.sum() != crc32 {
+        return Err(invalid_data("corrupt BGZF block (size or CRC mismatch)"));
+    }
+    Ok(Some(block_size as u64))
+}
+
+/// Sequential BGZF decoder, with virtual-offset seeking on seekable input
+pub struct BgzfReader<R> {
+    inner: R,
+    /// Decompressed data of the current block
+    block: Vec<u8>,
+    /// Read position within `block`
+    pos: usize,
+    /// Compressed offset of the current block
+    block_offset: u64,
+    /// Compressed offset of the next block
+    next_offset: u64,
+}
+
+impl<R: Read> BgzfReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self { inner, block: Vec::new(), pos: 0, block_offset: 0, next_offset: 0 }
+    }
+
+    /// Virtual offset of the next byte to be read
+    pub fn virtual_position(&self) -> u64 {
+        virtual_offset(self.block_offset, self.pos as u16)
+    }
+
+    /// Load the next block; false at end of input
+    fn load_block(&mut self) -> io::Result<bool> {
+        self.block_offset = self.next_offset;
+        self.pos = 0;
+        match read_block(&mut self.inner, &mut self.block)? {
+            Some(size) => {
+                self.next_offset += size;
+                Ok(true)
+            }
+            None => {
+                self.block.clear();
+                Ok(false)
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> BgzfReader<R> {
+    /// Position the reader at a virtual offset
+    pub fn seek_virtual(&mut self, voffset: u64) -> io::Result<()> {
+        let block_offset = voffset >> 16;
+        let within = (voffset & 0xffff) as usize;
+        self.inner.seek(SeekFrom::Start(block_offset))?;
+        self.next_offset = block_offset;
+        self.load_block()?;
+        if within > self.block.len() {
+            return Err(invalid_data(format!("virtual offset {} lies past the end of its block", voffset)));
+        }
+        self.pos = within;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for BgzfReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.consume(n);
+        Ok(n)
+    }
+}
+
+impl<R: Read> BufRead for BgzfReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        // Skips empty blocks, including the EOF marker
+        while self.pos >= self.block.len() {
+            if !self.load_block()? {
+                break;
+            }
+        }
+        Ok(&self.block[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.block.len());
+    }
+}
+
+/// Compressed and uncompressed start offsets of every block
+///
+/// Translates uncompressed file positions (as stored in `.fai` files of
+/// bgzipped FASTA) into virtual offsets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BgzfIndex {
+    /// `(compressed offset, uncompressed offset)` per non-empty block, ascending
+    blocks: Vec<(u64, u64)>,
+}
+
+impl BgzfIndex {
+    /// Build the index by decompressing the whole file once
+    pub fn build<R: Read>(reader: R) -> io::Result<Self> {
+        let mut reader = reader;
+        let mut blocks = Vec::new();
+        let mut data = Vec::new();
+        let (mut compressed, mut uncompressed) = (0u64, 0u64);
+        while let Some(size) = read_block(&mut reader, &mut data)? {
+            if !data.is_empty() {
+                blocks.push((compressed, uncompressed));
+            }
+            compressed += size;
+            uncompressed += data.len() as u64;
+        }
+        Ok(Self { blocks })
+    }
+
+    /// Read a `.gzi` index as written by `bgzip -i` / `samtools faidx`
+    ///
+    /// Little-endian entry count followed by `(compressed, uncompressed)`
+    /// pairs; the first block at `(0, 0)` is implicit.
+    pub fn read_gzi<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut word = [0u8; 8];
+        reader.read_exact(&mut word)?;
+        let count = u64::from_le_bytes(word);
+        let mut blocks = vec![(0, 0)];
+        for _ in 0..count {
+            reader.read_exact(&mut word)?;
+            let compressed = u64::from_le_bytes(word);
+            reader.read_exact(&mut word)?;
+            blocks.push((compressed, u64::from_le_bytes(word)));
+        }
+        Ok(Self { blocks })
+    }
+
+    /// Virtual offset of uncompressed position `pos`
+    ///
+    /// Fails if `pos` lies past the end of its block: beyond the next
+    /// block's start, or for the last block beyond the 64 KB a block
+    /// offset can address.
+    pub fn virtual_offset(&self, pos: u64) -> io::Result<u64> {
+        let i = self.blocks.partition_point(|&(_, start)| start <= pos);
+        let Some((compressed, start)) = i.checked_sub(1).map(|i| self.blocks[i]) else {
+            return Ok(0);
+        };
+        let block_len = match self.blocks.get(i) {
+            Some(&(_, next)) => next - start,
+            None => 1 << 16,
+        };
+        let within = pos - start;
+        if within >= block_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("uncompressed offset {} is past the end of the BGZF block starting at {}", pos, start),
+            ));
+        }
+        Ok(virtual_offset(compressed, within as u16))
+    }
+
+    /// Number of indexed blocks
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+}
+
+/// Compress `data` (at most [`MAX_BLOCK_DATA`] bytes) into one BGZF block
+fn compress_block(data: &[u8], level: Compression) -> io::Result<Vec<u8>> {
+    let mut encoder = DeflateEncoder::new(Vec::with_capacity(data.len() / 2 + 64), level);
+    encoder.write_all(data)?;
+    let cdata = encoder.finish()?;
+    let mut crc = Crc::new();
+    crc.update(data);
+
+    let block_size = 18 + cdata.len() + 8;
+    let bsize = u16::try_from(block_size - 1).map_err(|_| invalid_data("BGZF block exceeds 64 KB"))?;
+    let mut block = Vec::with_capacity(block_size);
+    block.extend_from_slice(&[0x1f, 0x8b, 0x08, 0x04, 0, 0, 0, 0, 0, 0xff, 6, 0, b'B', b'C', 2, 0]);
+    block.extend_from_slice(&bsize.to_le_bytes());
+    block.extend_from_slice(&cdata);
+    block.extend_from_slice(&crc.sum().to_le_bytes());
+    block.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    Ok(block)
+}
+
+/// BGZF encoder
+///
+/// Buffers up to one block of input and writes it as an independent gzip
+/// member. [`finish`](Self::finish) (or dropping the writer) writes the
+/// remaining data and the EOF marker block.
+pub struct BgzfWriter<W: Write> {
+    inner: Option<W>,
+    buf: Vec<u8>,
+    block_size: usize,
+    level: Compression,
+}
+
+impl<W: Write> BgzfWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self::with_block_size(inner, MAX_BLOCK_DATA)
+    }
+
+    /// Writer emitting blocks of `block_size` uncompressed bytes (capped at
+    /// [`MAX_BLOCK_DATA`])
+    pub fn with_block_size(inner: W, block_size: usize) -> Self {
+        let block_size = block_size.clamp(1, MAX_BLOCK_DATA);
+        Self { inner: Some(inner), buf: Vec::with_capacity(block_size), block_size, level: Compression::default() }
+    }
+
+    fn write_block(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let block = compress_block(&self.buf, self.level)?;
+        if let Some(inner) = self.inner.as_mut() {
+            inner.write_all(&block)?;
+        }
+        self.buf.clear();
+        Ok(())
+    }
+
+    /// Write the pending block and the EOF marker, returning the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_block()?;
+        let mut inner = self.inner.take().expect("BgzfWriter already finished");
+        inner.write_all(&EOF_BLOCK)?;
+        inner.flush()?;
+        Ok(inner)
+    }
+}
+
+impl<W: Write> Write for BgzfWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(self.block_size - self.buf.len());
+        self.buf.extend_from_slice(&buf[..n]);
+        if self.buf.len() == self.block_size {
+            self.write_block()?;
+        }
+        Ok(n)
+    }
+
+    /// Writes the pending data as a (possibly short) block
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_block()?;
+        match self.inner.as_mut() {
+            Some(inner) => inner.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> Drop for BgzfWriter<W> {
+    fn drop(&mut self) {
+        if self.inner.is_some() {
+            let _ = self.write_block();
+            if let Some(inner) = self.inner.as_mut() {
+                let _ = inner.write_all(&EOF_BLOCK);
+                let _ = inner.flush();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn compress(data: &[u8], block_size: usize) -> Vec<u8> {
+        let mut writer = BgzfWriter::with_block_size(Vec::new(), block_size);
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let data: Vec<u8> = (0..200_000u32).map(|i| b"ACGT\n"[(i % 5) as usize]).collect();
+        let compressed = compress(&data, MAX_BLOCK_DATA);
+        assert!(is_bgzf_header(&compressed));
+        assert!(compressed.ends_with(&EOF_BLOCK));
+
+        let mut decoded = Vec::new();
+        BgzfReader::new(&compressed[..]).read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, data);
+
+        // Plain gzip decoders read BGZF too
+        let mut gunzipped = Vec::new();
+        flate2::read::MultiGzDecoder::new(&compressed[..]).read_to_end(&mut gunzipped).unwrap();
+        assert_eq!(gunzipped, data);
+    }
+
+    #[test]
+    fn test_is_bgzf_header() {
+        let mut gz = flate2::write::GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(b"plain gzip").unwrap();
+        assert!(!is_bgzf_header(&gz.finish().unwrap()));
+        assert!(!is_bgzf_header(b"ACGT"));
+        assert!(is_bgzf_header(&EOF_BLOCK));
+    }
+
+    #[test]
+    fn test_index_and_seek() {
+        let data: Vec<u8> = (0..1000u32).map(|i| b'a' + (i % 26) as u8).collect();
+        let compressed = compress(&data, 100);
+        let index = BgzfIndex::build(&compressed[..]).unwrap();
+        assert_eq!(index.len(), 10);
+
+        let mut reader = BgzfReader::new(Cursor::new(&compressed));
+        for pos in [0u64, 99, 100, 101, 555, 999] {
+            reader.seek_virtual(index.virtual_offset(pos).unwrap()).unwrap();
+            let mut buf = vec![0u8; (1000 - pos).min(150) as usize];
+            reader.read_exact(&mut buf).unwrap();
+            assert_eq!(buf, &data[pos as usize..pos as usize + buf.len()], "pos {}", pos);
+        }
+        // Past what the last block (starting at 900) can address
+        assert!(index.virtual_offset(900 + 0xffff).is_ok());
+        let err = index.virtual_offset(900 + 0x10000).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        // The same index from a .gzi file
+        let dir = tempfile::tempdir().unwrap();
+        let gzi = dir.path().join("x.gz.gzi");
+        let mut bytes = 9u64.to_le_bytes().to_vec();
+        for &(compressed, uncompressed) in &index.blocks[1..] {
+            bytes.extend_from_slice(&compressed.to_le_bytes());
+            bytes.extend_from_slice(&uncompressed.to_le_bytes());
+        }
+        std::fs::write(&gzi, bytes).unwrap();
+        assert_eq!(BgzfIndex::read_gzi(&gzi).unwrap(), index);
+    }
+
+    #[test]
+    fn test_corrupt_block() {
+        let mut compressed = compress(b"ACGTACGT", MAX_BLOCK_DATA);
+        let crc_at = compressed.len() - EOF_BLOCK.len() - 8;
+        compressed[crc_at] ^= 0xff;
+        let err = BgzfReader::new(&compressed[..]).read_to_end(&mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
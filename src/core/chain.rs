@@ -16,9 +16,11 @@
 //! - Last data line has only size (no gaps)
 
 use crate::core::Strand;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::io::{BufRead, BufReader};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 /// Error type for chain file parsing
 /// 
@@ -32,8 +34,23 @@ pub struct ChainParseError {
     pub line_number: Option<usize>,
     /// The kind of error that occurred
     pub kind: ChainParseErrorKind,
-    /// The problematic content (if available)
+    /// The problematic content (if available), cut to
+    /// [`MAX_ERROR_CONTENT_CHARS`] characters with a trailing `...`
     pub content: Option<String>,
+    /// Character range of the offending field within `content`
+    /// (start, length), underlined with `^` when displayed
+    pub field_span: Option<(usize, usize)>,
+}
+
+/// Longest line content kept in a `ChainParseError`
+pub const MAX_ERROR_CONTENT_CHARS: usize = 256;
+
+/// Cut a line to `MAX_ERROR_CONTENT_CHARS`, marking the cut with `...`
+fn truncate_content(content: &str) -> String {
+    match content.char_indices().nth(MAX_ERROR_CONTENT_CHARS) {
+        Some((cut, _)) => format!("{}...", &content[..cut]),
+        None => content.to_string(),
+    }
 }
 
 /// Specific kinds of chain parsing errors
@@ -57,14 +74,37 @@ pub enum ChainParseErrorKind {
     UnsupportedCompression,
     /// Coordinate validation error (e.g., start > end)
     InvalidCoordinates,
+    /// Parsing was stopped through a `CancellationToken`
+    Cancelled,
 }
 
+/// Shows the line number, the kind and the message, followed by the line
+/// content and a `^^^` marker under the offending field when known:
+///
+/// ```text
+/// Line 3 [InvalidNumber]: Invalid score value 'abc': expected a non-negative integer
+///   chain abc chr1 1000 + 0 100 chr1 1000 + 0 100 1
+///         ^^^
+/// ```
 impl std::fmt::Display for ChainParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.line_number {
-            Some(line) => write!(f, "Line {}: {}", line, self.message),
-            None => write!(f, "{}", self.message),
+            Some(line) => write!(f, "Line {} [{:?}]: {}", line, self.kind, self.message)?,
+            None => write!(f, "[{:?}]: {}", self.kind, self.message)?,
         }
+        if let Some(content) = &self.content {
+            write!(f, "\n  {}", content)?;
+            if let Some((start, len)) = self.field_span {
+                // Keep tabs so the marker lines up with tab-separated fields
+                let indent: String = content
+                    .chars()
+                    .take(start)
+                    .map(|c| if c == '\t' { '\t' } else { ' ' })
+                    .collect();
+                write!(f, "\n  {}{}", indent, "^".repeat(len.max(1)))?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -78,6 +118,7 @@ impl ChainParseError {
             line_number: None,
             kind: ChainParseErrorKind::InvalidHeader,
             content: None,
+            field_span: None,
         }
     }
 
@@ -88,6 +129,7 @@ impl ChainParseError {
             line_number: Some(line_number),
             kind: ChainParseErrorKind::InvalidHeader,
             content: None,
+            field_span: None,
         }
     }
     
@@ -103,6 +145,7 @@ impl ChainParseError {
             line_number: Some(line_number),
             kind,
             content,
+            field_span: None,
         }
     }
     
@@ -112,7 +155,7 @@ impl ChainParseError {
             message,
             line_number,
             ChainParseErrorKind::InvalidHeader,
-            Some(content.chars().take(100).collect()),
+            Some(truncate_content(content)),
         )
     }
     
@@ -122,7 +165,7 @@ impl ChainParseError {
             message,
             line_number,
             ChainParseErrorKind::InvalidDataLine,
-            Some(content.chars().take(100).collect()),
+            Some(truncate_content(content)),
         )
     }
     
@@ -153,6 +196,7 @@ impl ChainParseError {
             line_number: None,
             kind: ChainParseErrorKind::FileNotFound,
             content: None,
+            field_span: None,
         }
     }
     
@@ -166,12 +210,97 @@ impl ChainParseError {
         )
     }
     
+    /// Create a cancellation error, raised after `line_number` lines
+    pub fn cancelled(line_number: usize) -> Self {
+        Self::with_context(
+            "Chain parsing cancelled",
+            line_number,
+            ChainParseErrorKind::Cancelled,
+            None,
+        )
+    }
+    
+    /// Attach the line content and point at its `field`-th
+    /// whitespace-separated field (0-based)
+    ///
+    /// The marker is dropped if the field lies past the truncation point.
+    pub fn at_field(mut self, line: &str, field: usize) -> Self {
+        let content = truncate_content(line);
+        let kept = content.chars().count().min(MAX_ERROR_CONTENT_CHARS);
+        self.field_span = field_char_span(line, field).filter(|&(start, len)| start + len <= kept);
+        self.content = Some(content);
+        self
+    }
+    
     /// Check if this is a specific kind of error
     pub fn is_kind(&self, kind: ChainParseErrorKind) -> bool {
         self.kind == kind
     }
 }
 
+/// Character range (start, length) of the `field`-th whitespace-separated
+/// field of `line`
+fn field_char_span(line: &str, field: usize) -> Option<(usize, usize)> {
+    let mut index = 0;
+    let mut start = None;
+    for (pos, c) in line.chars().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(pos),
+            (true, Some(begin)) => {
+                if index == field {
+                    return Some((begin, pos - begin));
+                }
+                index += 1;
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    match start {
+        Some(begin) if index == field => Some((begin, line.chars().count() - begin)),
+        _ => None,
+    }
+}
+
+/// Structural problem found by [`ChainFile::validate`]
+/// 
+/// `line` is the data line of the offending block (0 if unknown).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ChainValidationError {
+    /// Block of size 0
+    #[error("line {line}: block size must be greater than 0")]
+    EmptyBlock { line: usize },
+    
+    /// Source block ends past the source chromosome
+    #[error("line {line}: source block end {end} exceeds {chrom} size {size}")]
+    SourceOutOfBounds { line: usize, chrom: String, end: u64, size: u64 },
+    
+    /// Target block ends past the target chromosome
+    #[error("line {line}: target block end {end} exceeds {chrom} size {size}")]
+    TargetOutOfBounds { line: usize, chrom: String, end: u64, size: u64 },
+    
+    /// Target start not below target end
+    #[error("line {line}: target start {start} is not less than target end {end}")]
+    InvalidTargetRange { line: usize, start: u64, end: u64 },
+    
+    /// Source block overlaps an earlier block on the same chromosome
+    #[error("line {line}: source block {chrom}:{start}-{end} overlaps the block at line {other_line}")]
+    OverlappingSource { line: usize, other_line: usize, chrom: String, start: u64, end: u64 },
+}
+
+impl ChainValidationError {
+    /// Line number of the offending block
+    pub fn line(&self) -> usize {
+        match self {
+            ChainValidationError::EmptyBlock { line }
+            | ChainValidationError::SourceOutOfBounds { line, .. }
+            | ChainValidationError::TargetOutOfBounds { line, .. }
+            | ChainValidationError::InvalidTargetRange { line, .. }
+            | ChainValidationError::OverlappingSource { line, .. } => *line,
+        }
+    }
+}
+
 impl From<std::io::Error> for ChainParseError {
     fn from(e: std::io::Error) -> Self {
         Self {
@@ -179,6 +308,7 @@ impl From<std::io::Error> for ChainParseError {
             line_number: None,
             kind: ChainParseErrorKind::IoError,
             content: None,
+            field_span: None,
         }
     }
 }
@@ -220,6 +350,13 @@ impl ChainHeader {
     /// Note: UCSC "target" (t) = our "source" (user input coordinates)
     ///       UCSC "query" (q) = our "target" (mapping output coordinates)
     pub fn parse(line: &str, line_number: usize) -> Result<Self, ChainParseError> {
+        let header = Self::parse_unchecked(line, line_number)?;
+        header.check_coordinates(line, line_number)?;
+        Ok(header)
+    }
+    
+    /// Parse the header fields without checking start <= end <= size
+    fn parse_unchecked(line: &str, line_number: usize) -> Result<Self, ChainParseError> {
         let fields: Vec<&str> = line.split_whitespace().collect();
         
         if fields.len() < 12 {
@@ -235,79 +372,51 @@ impl ChainHeader {
                 format!("Expected 'chain' keyword, got '{}'", fields[0]),
                 line_number,
                 line,
-            ));
+            ).at_field(line, 0));
         }
         
         let score = fields[1].parse::<u64>().map_err(|_| {
-            ChainParseError::invalid_number("score", fields[1], line_number)
+            ChainParseError::invalid_number("score", fields[1], line_number).at_field(line, 1)
         })?;
         
         // UCSC "target" = our "source" (user input coordinates)
         let source_name = fields[2].to_string();
         let source_size = fields[3].parse::<u64>().map_err(|_| {
-            ChainParseError::invalid_number("source size", fields[3], line_number)
+            ChainParseError::invalid_number("source size", fields[3], line_number).at_field(line, 3)
         })?;
         
         let source_strand_char = fields[4].chars().next().unwrap_or('?');
         let source_strand = Strand::from_char(source_strand_char).ok_or_else(|| {
-            ChainParseError::invalid_strand(source_strand_char, line_number)
+            ChainParseError::invalid_strand(source_strand_char, line_number).at_field(line, 4)
         })?;
         
         let source_start = fields[5].parse::<u64>().map_err(|_| {
-            ChainParseError::invalid_number("source start", fields[5], line_number)
+            ChainParseError::invalid_number("source start", fields[5], line_number).at_field(line, 5)
         })?;
         
         let source_end = fields[6].parse::<u64>().map_err(|_| {
-            ChainParseError::invalid_number("source end", fields[6], line_number)
+            ChainParseError::invalid_number("source end", fields[6], line_number).at_field(line, 6)
         })?;
         
-        // Validate source coordinates
-        if source_start > source_end {
-            return Err(ChainParseError::invalid_coordinates(
-                format!("Source start ({}) > source end ({})", source_start, source_end),
-                line_number,
-            ));
-        }
-        if source_end > source_size {
-            return Err(ChainParseError::invalid_coordinates(
-                format!("Source end ({}) > source size ({})", source_end, source_size),
-                line_number,
-            ));
-        }
-        
         // UCSC "query" = our "target" (mapping output coordinates)
         let target_name = fields[7].to_string();
         let target_size = fields[8].parse::<u64>().map_err(|_| {
-            ChainParseError::invalid_number("target size", fields[8], line_number)
+            ChainParseError::invalid_number("target size", fields[8], line_number).at_field(line, 8)
         })?;
         
         let target_strand_char = fields[9].chars().next().unwrap_or('?');
         let target_strand = Strand::from_char(target_strand_char).ok_or_else(|| {
-            ChainParseError::invalid_strand(target_strand_char, line_number)
+            ChainParseError::invalid_strand(target_strand_char, line_number).at_field(line, 9)
         })?;
         
         let target_start = fields[10].parse::<u64>().map_err(|_| {
-            ChainParseError::invalid_number("target start", fields[10], line_number)
+            ChainParseError::invalid_number("target start", fields[10], line_number).at_field(line, 10)
         })?;
         
         let target_end = fields[11].parse::<u64>().map_err(|_| {
-            ChainParseError::invalid_number("target end", fields[11], line_number)
+            ChainParseError::invalid_number("target end", fields[11], line_number).at_field(line, 11)
         })?;
         
-        // Validate target coordinates
-        if target_start > target_end {
-            return Err(ChainParseError::invalid_coordinates(
-                format!("Target start ({}) > target end ({})", target_start, target_end),
-                line_number,
-            ));
-        }
-        if target_end > target_size {
-            return Err(ChainParseError::invalid_coordinates(
-                format!("Target end ({}) > target size ({})", target_end, target_size),
-                line_number,
-            ));
-        }
-        
         // Chain ID is optional (field 12)
         let chain_id = fields.get(12).map(|s| s.to_string()).unwrap_or_default();
         
@@ -326,13 +435,42 @@ impl ChainHeader {
             chain_id,
         })
     }
+    
+    /// Check that start <= end <= size on both sides
+    fn check_coordinates(&self, line: &str, line_number: usize) -> Result<(), ChainParseError> {
+        if self.source_start > self.source_end {
+            return Err(ChainParseError::invalid_coordinates(
+                format!("Source start ({}) > source end ({})", self.source_start, self.source_end),
+                line_number,
+            ).at_field(line, 5));
+        }
+        if self.source_end > self.source_size {
+            return Err(ChainParseError::invalid_coordinates(
+                format!("Source end ({}) > source size ({})", self.source_end, self.source_size),
+                line_number,
+            ).at_field(line, 6));
+        }
+        if self.target_start > self.target_end {
+            return Err(ChainParseError::invalid_coordinates(
+                format!("Target start ({}) > target end ({})", self.target_start, self.target_end),
+                line_number,
+            ).at_field(line, 10));
+        }
+        if self.target_end > self.target_size {
+            return Err(ChainParseError::invalid_coordinates(
+                format!("Target end ({}) > target size ({})", self.target_end, self.target_size),
+                line_number,
+            ).at_field(line, 11));
+        }
+        Ok(())
+    }
 }
 
 
 /// A single alignment block from a chain file
 ///
 /// Represents a contiguous aligned region between source and target genomes.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ChainBlock {
     /// Source chromosome name
     pub source_chrom: String,
@@ -348,6 +486,8 @@ pub struct ChainBlock {
     pub target_end: u64,
     /// Target strand direction
     pub target_strand: Strand,
+    /// Score of the chain this block belongs to
+    pub score: u64,
 }
 
 /// Data line in a chain file (size, dt, dq)
@@ -367,20 +507,25 @@ struct DataLine {
 impl DataLine {
     /// Parse a data line (middle line with 3 fields or last line with 1 field)
     fn parse(line: &str, line_number: usize) -> Result<Self, ChainParseError> {
+        Self::parse_with(line, line_number, true)
+    }
+    
+    /// Parse a data line; with `checked`, a block size of 0 is an error
+    fn parse_with(line: &str, line_number: usize, checked: bool) -> Result<Self, ChainParseError> {
         let fields: Vec<&str> = line.split_whitespace().collect();
         
         match fields.len() {
             1 => {
                 // Last line: only size
                 let size = fields[0].parse::<u64>().map_err(|_| {
-                    ChainParseError::invalid_number("block size", fields[0], line_number)
+                    ChainParseError::invalid_number("block size", fields[0], line_number).at_field(line, 0)
                 })?;
-                if size == 0 {
+                if checked && size == 0 {
                     return Err(ChainParseError::invalid_data_line(
                         "Block size must be greater than 0",
                         line_number,
                         line,
-                    ));
+                    ).at_field(line, 0));
                 }
                 Ok(Self {
                     size,
@@ -393,20 +538,20 @@ impl DataLine {
                 // dt = gap in UCSC target = gap in our source
                 // dq = gap in UCSC query = gap in our target
                 let size = fields[0].parse::<u64>().map_err(|_| {
-                    ChainParseError::invalid_number("block size", fields[0], line_number)
+                    ChainParseError::invalid_number("block size", fields[0], line_number).at_field(line, 0)
                 })?;
-                if size == 0 {
+                if checked && size == 0 {
                     return Err(ChainParseError::invalid_data_line(
                         "Block size must be greater than 0",
                         line_number,
                         line,
-                    ));
+                    ).at_field(line, 0));
                 }
                 let source_gap = fields[1].parse::<u64>().map_err(|_| {
-                    ChainParseError::invalid_number("source gap (dt)", fields[1], line_number)
+                    ChainParseError::invalid_number("source gap (dt)", fields[1], line_number).at_field(line, 1)
                 })?;
                 let target_gap = fields[2].parse::<u64>().map_err(|_| {
-                    ChainParseError::invalid_number("target gap (dq)", fields[2], line_number)
+                    ChainParseError::invalid_number("target gap (dq)", fields[2], line_number).at_field(line, 2)
                 })?;
                 Ok(Self {
                     size,
@@ -433,6 +578,13 @@ pub struct ChainFile {
     pub target_chrom_sizes: HashMap<String, u64>,
     /// Source chromosome sizes
     pub source_chrom_sizes: HashMap<String, u64>,
+    /// Score of every chain header, in file order
+    pub chain_scores: Vec<u64>,
+    /// Blocks dropped by `dedup_blocks` so far
+    pub removed_duplicates: usize,
+    /// Input line number of each block's data line, parallel to `blocks`
+    /// (empty when the blocks did not come from chain text)
+    pub block_lines: Vec<usize>,
 }
 
 impl ChainFile {
@@ -442,7 +594,341 @@ impl ChainFile {
             blocks: Vec::new(),
             target_chrom_sizes: HashMap::new(),
             source_chrom_sizes: HashMap::new(),
+            chain_scores: Vec::new(),
+            removed_duplicates: 0,
+            block_lines: Vec::new(),
+        }
+    }
+    
+    /// Remove blocks identical to an earlier block, keeping the first
+    /// occurrence
+    /// 
+    /// Two blocks are duplicates when source and target chromosome, start,
+    /// end and target strand all match; the chain score is ignored. Such
+    /// blocks come from imperfect alignment pipelines and would map a query
+    /// twice to the same place. Returns the number of blocks removed, which
+    /// is also added to `removed_duplicates`.
+    pub fn dedup_blocks(&mut self) -> usize {
+        let mut seen = HashSet::with_capacity(self.blocks.len());
+        let keep: Vec<bool> = self.blocks
+            .iter()
+            .map(|b| seen.insert((
+                &b.source_chrom, b.source_start, b.source_end,
+                &b.target_chrom, b.target_start, b.target_end, b.target_strand,
+            )))
+            .collect();
+        
+        let before = self.blocks.len();
+        if self.block_lines.len() == before {
+            let mut keep = keep.iter();
+            self.block_lines.retain(|_| *keep.next().unwrap_or(&true));
+        }
+        let mut keep = keep.into_iter();
+        self.blocks.retain(|_| keep.next().unwrap_or(true));
+        
+        let removed = before - self.blocks.len();
+        self.removed_duplicates += removed;
+        removed
+    }
+    
+    /// Check the blocks for structural problems, reporting all of them
+    /// 
+    /// Flags empty blocks, blocks whose source or target end lies past the
+    /// chromosome size, blocks with target start >= target end, and source
+    /// blocks overlapping another block on the same chromosome. Line
+    /// numbers come from `block_lines` (0 when unknown). Load the file with
+    /// [`parse_chain_file_unchecked`] so that problems the parser rejects
+    /// are reported here instead of stopping at the first one.
+    pub fn validate(&self) -> Vec<ChainValidationError> {
+        let line_of = |i: usize| self.block_lines.get(i).copied().unwrap_or(0);
+        let mut errors = Vec::new();
+        let mut by_chrom: HashMap<&str, Vec<usize>> = HashMap::new();
+        
+        for (i, block) in self.blocks.iter().enumerate() {
+            let line = line_of(i);
+            if block.source_start >= block.source_end {
+                errors.push(ChainValidationError::EmptyBlock { line });
+            } else {
+                by_chrom.entry(&block.source_chrom).or_default().push(i);
+            }
+            if let Some(&size) = self.source_chrom_sizes.get(&block.source_chrom) {
+                if block.source_end > size {
+                    errors.push(ChainValidationError::SourceOutOfBounds {
+                        line, chrom: block.source_chrom.clone(), end: block.source_end, size,
+                    });
+                }
+            }
+            if let Some(&size) = self.target_chrom_sizes.get(&block.target_chrom) {
+                if block.target_end > size {
+                    errors.push(ChainValidationError::TargetOutOfBounds {
+                        line, chrom: block.target_chrom.clone(), end: block.target_end, size,
+                    });
+                }
+            }
+            if block.target_start >= block.target_end {
+                errors.push(ChainValidationError::InvalidTargetRange {
+                    line, start: block.target_start, end: block.target_end,
+                });
+            }
+        }
+        
+        for (chrom, mut indices) in by_chrom {
+            indices.sort_by_key(|&i| (self.blocks[i].source_start, self.blocks[i].source_end));
+            // Block reaching furthest right so far
+            let mut furthest: Option<usize> = None;
+            for i in indices {
+                let block = &self.blocks[i];
+                if let Some(prev) = furthest {
+                    if block.source_start < self.blocks[prev].source_end {
+                        errors.push(ChainValidationError::OverlappingSource {
+                            line: line_of(i),
+                            other_line: line_of(prev),
+                            chrom: chrom.to_string(),
+                            start: block.source_start,
+                            end: block.source_end,
+                        });
+                    }
+                }
+                if furthest.is_none_or(|prev| block.source_end > self.blocks[prev].source_end) {
+                    furthest = Some(i);
+                }
+            }
+        }
+        
+        errors.sort_by_key(|e| e.line());
+        errors
+    }
+    
+    /// Build chain data from minimap2 PAF output with `cg:Z:` CIGAR tags
+    /// 
+    /// Every PAF line becomes one chain, split into alignment blocks by its
+    /// CIGAR instead of being treated as a single ungapped block. As with
+    /// paf2chain, the PAF target is the source assembly and the PAF query the
+    /// target assembly, so runs of `M`/`=`/`X` are aligned blocks, `D` (and
+    /// `N`) skip source bases and `I` skips target bases. The chain score is
+    /// the `AS:i:` tag when present, otherwise the number of matching bases.
+    /// 
+    /// Lines without a CIGAR, with other CIGAR operations, or whose CIGAR
+    /// does not cover the PAF coordinates exactly are rejected.
+    pub fn from_paf_with_cigar(reader: impl BufRead) -> Result<ChainFile, ChainParseError> {
+        let mut result = ChainFile::new();
+        
+        for (index, line_result) in reader.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line_result?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 12 {
+                return Err(ChainParseError::invalid_data_line(
+                    format!("PAF line has {} fields, expected at least 12", fields.len()),
+                    line_number,
+                    &line,
+                ));
+            }
+            let number = |field: usize, name: &str| {
+                fields[field].parse::<u64>().map_err(|_| {
+                    ChainParseError::invalid_number(name, fields[field], line_number).at_field(&line, field)
+                })
+            };
+            let query_size = number(1, "query length")?;
+            let query_start = number(2, "query start")?;
+            let query_end = number(3, "query end")?;
+            let strand = match fields[4] {
+                "+" => Strand::Plus,
+                "-" => Strand::Minus,
+                other => {
+                    let c = other.chars().next().unwrap_or(' ');
+                    return Err(ChainParseError::invalid_strand(c, line_number).at_field(&line, 4));
+                }
+            };
+            let ref_size = number(6, "target length")?;
+            let ref_start = number(7, "target start")?;
+            let ref_end = number(8, "target end")?;
+            let matches = number(9, "residue matches")?;
+            if query_start > query_end || query_end > query_size || ref_start > ref_end || ref_end > ref_size {
+                return Err(ChainParseError::invalid_coordinates(
+                    format!("PAF coordinates out of range: query {}-{} of {}, target {}-{} of {}",
+                        query_start, query_end, query_size, ref_start, ref_end, ref_size),
+                    line_number,
+                ));
+            }
+            
+            let tags = &fields[12..];
+            let cigar = tags.iter().find_map(|t| t.strip_prefix("cg:Z:")).ok_or_else(|| {
+                ChainParseError::invalid_data_line("PAF line has no cg:Z: CIGAR tag", line_number, &line)
+            })?;
+            let score = tags
+                .iter()
+                .find_map(|t| t.strip_prefix("AS:i:"))
+                .and_then(|s| s.parse::<i64>().ok())
+                .map_or(matches, |s| s.max(0) as u64);
+            
+            let source_chrom = fields[5];
+            let target_chrom = fields[0];
+            result.source_chrom_sizes.insert(source_chrom.to_string(), ref_size);
+            result.target_chrom_sizes.insert(target_chrom.to_string(), query_size);
+            result.chain_scores.push(score);
+            
+            // Walk the alignment; the target position is on the aligned strand
+            let mut source_pos = ref_start;
+            let mut target_pos = if strand == Strand::Plus { query_start } else { query_size - query_end };
+            let mut run = 0u64;
+            let flush = |source_pos: u64, target_pos: u64, run: u64, blocks: &mut Vec<ChainBlock>| {
+                if run == 0 {
+                    return;
+                }
+                let (target_start, target_end) = match strand {
+                    Strand::Plus => (target_pos - run, target_pos),
+                    Strand::Minus => (query_size - target_pos, query_size - (target_pos - run)),
+                };
+                blocks.push(ChainBlock {
+                    source_chrom: source_chrom.to_string(),
+                    source_start: source_pos - run,
+                    source_end: source_pos,
+                    target_chrom: target_chrom.to_string(),
+                    target_start,
+                    target_end,
+                    target_strand: strand,
+                    score,
+                });
+            };
+            
+            let mut digits_start = 0;
+            for (i, op) in cigar.char_indices() {
+                if op.is_ascii_digit() {
+                    continue;
+                }
+                let len = cigar[digits_start..i].parse::<u64>().map_err(|_| {
+                    ChainParseError::invalid_data_line(format!("Invalid CIGAR length before '{}'", op), line_number, &line)
+                })?;
+                digits_start = i + 1;
+                match op {
+                    'M' | '=' | 'X' => {
+                        source_pos += len;
+                        target_pos += len;
+                        run += len;
+                    }
+                    'D' | 'N' | 'I' => {
+                        flush(source_pos, target_pos, run, &mut result.blocks);
+                        run = 0;
+                        if op == 'I' {
+                            target_pos += len;
+                        } else {
+                            source_pos += len;
+                        }
+                    }
+                    _ => {
+                        return Err(ChainParseError::invalid_data_line(
+                            format!("Unsupported CIGAR operation '{}'", op),
+                            line_number,
+                            &line,
+                        ));
+                    }
+                }
+            }
+            flush(source_pos, target_pos, run, &mut result.blocks);
+            
+            let target_span_end = if strand == Strand::Plus { query_end } else { query_size - query_start };
+            if digits_start != cigar.len() || source_pos != ref_end || target_pos != target_span_end {
+                return Err(ChainParseError::invalid_data_line(
+                    "CIGAR does not cover the PAF alignment coordinates",
+                    line_number,
+                    &line,
+                ));
+            }
+        }
+        
+        Ok(result)
+    }
+    
+    /// Build chain data from BLAST tabular output (`-outfmt 6`)
+    /// 
+    /// Expects the default twelve columns: qseqid, sseqid, pident, length,
+    /// mismatch, gapopen, qstart, qend, sstart, send, evalue, bitscore. The
+    /// query is the source assembly and the subject the target assembly.
+    /// BLAST coordinates are 1-based and inclusive; `sstart > send` marks a
+    /// reverse-strand hit, and `default_strand` is used when the orientation
+    /// cannot be told (single-base hits). Extra columns and `#` comment lines
+    /// (`-outfmt 7`) are ignored.
+    /// 
+    /// Format 6 does not record where the gaps are, so every alignment
+    /// becomes a single ungapped block. When `gapopen > 0` makes the query
+    /// and subject spans differ, the block is trimmed to the shorter span,
+    /// anchored at the alignment start. The chain score is the bit score
+    /// rounded down. Sequence lengths are not part of the format either, so
+    /// chromosome sizes are the furthest aligned position seen.
+    pub fn from_blast_tabular(reader: impl BufRead, default_strand: Strand) -> Result<ChainFile, ChainParseError> {
+        let mut result = ChainFile::new();
+        
+        for (index, line_result) in reader.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line_result?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 12 {
+                return Err(ChainParseError::invalid_data_line(
+                    format!("BLAST line has {} fields, expected 12", fields.len()),
+                    line_number,
+                    &line,
+                ));
+            }
+            let position = |field: usize, name: &str| {
+                fields[field]
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|&pos| pos > 0)
+                    .ok_or_else(|| ChainParseError::invalid_number(name, fields[field], line_number).at_field(&line, field))
+            };
+            let (qstart, qend) = (position(6, "qstart")?, position(7, "qend")?);
+            let (sstart, send) = (position(8, "sstart")?, position(9, "send")?);
+            let bitscore = fields[11].trim().parse::<f64>().map_err(|_| {
+                ChainParseError::invalid_number("bitscore", fields[11], line_number).at_field(&line, 11)
+            })?;
+            
+            // Orientation of the subject relative to the query; a single
+            // aligned base has none
+            let strand = if qstart == qend || sstart == send {
+                default_strand
+            } else if (qstart > qend) != (sstart > send) {
+                Strand::Minus
+            } else {
+                Strand::Plus
+            };
+            
+            let len = (qstart.abs_diff(qend) + 1).min(sstart.abs_diff(send) + 1);
+            // Anchor at the alignment start, where qstart pairs with sstart
+            let source_start = if qstart <= qend { qstart - 1 } else { qstart - len };
+            let target_start = if sstart <= send { sstart - 1 } else { sstart - len };
+            let (query_high, subject_high) = (qstart.max(qend), sstart.max(send));
+            
+            let source_chrom = fields[0];
+            let target_chrom = fields[1];
+            let source_size = result.source_chrom_sizes.entry(source_chrom.to_string()).or_insert(0);
+            *source_size = (*source_size).max(query_high);
+            let target_size = result.target_chrom_sizes.entry(target_chrom.to_string()).or_insert(0);
+            *target_size = (*target_size).max(subject_high);
+            
+            let score = bitscore.max(0.0) as u64;
+            result.chain_scores.push(score);
+            result.blocks.push(ChainBlock {
+                source_chrom: source_chrom.to_string(),
+                source_start,
+                source_end: source_start + len,
+                target_chrom: target_chrom.to_string(),
+                target_start,
+                target_end: target_start + len,
+                target_strand: strand,
+                score,
+            });
         }
+        
+        Ok(result)
     }
 }
 
@@ -452,10 +938,91 @@ impl Default for ChainFile {
     }
 }
 
+/// Number of lines between progress callbacks while parsing
+pub const PROGRESS_INTERVAL_LINES: usize = 10_000;
+
+/// Number of lines between cancellation checks while parsing
+pub const CANCEL_CHECK_INTERVAL_LINES: usize = 1000;
+
+/// Shared flag for stopping a running chain parse from another thread
+///
+/// Clones share the flag, so one clone can be handed to the parsing thread
+/// and the other kept to call [`cancel`](Self::cancel).
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Ask every parse holding this token to stop
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    
+    /// Whether `cancel` has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
 /// Parse a chain file from a reader
 ///
 /// This function handles the core parsing logic, supporting any `BufRead` source.
 pub fn parse_chain_reader<R: BufRead>(reader: R) -> Result<ChainFile, ChainParseError> {
+    parse_chain_reader_with_cancel(reader, &CancellationToken::new())
+}
+
+/// Parse a chain file from a reader, stopping early when `cancel` is set
+///
+/// The token is checked every [`CANCEL_CHECK_INTERVAL_LINES`] lines; a
+/// cancelled parse returns a [`ChainParseErrorKind::Cancelled`] error. A
+/// reader that blocks inside a single read is not interrupted.
+pub fn parse_chain_reader_with_cancel<R: BufRead>(
+    reader: R,
+    cancel: &CancellationToken,
+) -> Result<ChainFile, ChainParseError> {
+    parse_chain_lines(reader, |_| {}, true, cancel)
+}
+
+/// Parse a chain file from a reader, reporting progress
+///
+/// `callback` receives the number of lines read so far every
+/// [`PROGRESS_INTERVAL_LINES`] lines.
+pub fn parse_chain_reader_with_progress<R: BufRead>(
+    reader: R,
+    callback: impl Fn(usize),
+) -> Result<ChainFile, ChainParseError> {
+    parse_chain_lines(reader, callback, true, &CancellationToken::new())
+}
+
+/// Parse a chain file from a reader without coordinate checks
+/// 
+/// Header coordinates outside the chromosome, zero-size blocks and blocks
+/// running past the chromosome end are loaded as-is instead of failing
+/// the parse, for [`ChainFile::validate`] to report. Syntax errors (field
+/// counts, numbers, strands) are still fatal. Blocks on the minus strand
+/// that run past the start of the chromosome keep their unflipped
+/// coordinates.
+pub fn parse_chain_reader_unchecked<R: BufRead>(reader: R) -> Result<ChainFile, ChainParseError> {
+    parse_chain_lines(reader, |_| {}, false, &CancellationToken::new())
+}
+
+/// Flip `[pos, pos + size)` to forward-strand coordinates on a sequence of
+/// `seq_size` bases; None if the block runs past the end of the sequence
+fn flip_block(pos: u64, size: u64, seq_size: u64) -> Option<(u64, u64)> {
+    let start = seq_size.checked_sub(pos + size)?;
+    Some((start, seq_size - pos))
+}
+
+fn parse_chain_lines<R: BufRead>(
+    reader: R,
+    callback: impl Fn(usize),
+    checked: bool,
+    cancel: &CancellationToken,
+) -> Result<ChainFile, ChainParseError> {
     let mut result = ChainFile::new();
     let mut current_header: Option<ChainHeader> = None;
     let mut source_pos: u64 = 0;
@@ -464,6 +1031,12 @@ pub fn parse_chain_reader<R: BufRead>(reader: R) -> Result<ChainFile, ChainParse
     
     for line_result in reader.lines() {
         line_number += 1;
+        if line_number.is_multiple_of(PROGRESS_INTERVAL_LINES) {
+            callback(line_number);
+        }
+        if line_number.is_multiple_of(CANCEL_CHECK_INTERVAL_LINES) && cancel.is_cancelled() {
+            return Err(ChainParseError::cancelled(line_number));
+        }
         let line = line_result?;
         let trimmed = line.trim();
         
@@ -478,11 +1051,15 @@ pub fn parse_chain_reader<R: BufRead>(reader: R) -> Result<ChainFile, ChainParse
         
         if trimmed.starts_with("chain") {
             // Parse header line
-            let header = ChainHeader::parse(trimmed, line_number)?;
+            let header = ChainHeader::parse_unchecked(trimmed, line_number)?;
+            if checked {
+                header.check_coordinates(trimmed, line_number)?;
+            }
             
             // Store chromosome sizes
             result.target_chrom_sizes.insert(header.target_name.clone(), header.target_size);
             result.source_chrom_sizes.insert(header.source_name.clone(), header.source_size);
+            result.chain_scores.push(header.score);
             
             // Initialize positions
             source_pos = header.source_start;
@@ -490,7 +1067,15 @@ pub fn parse_chain_reader<R: BufRead>(reader: R) -> Result<ChainFile, ChainParse
             current_header = Some(header);
         } else if let Some(ref header) = current_header {
             // Parse data line
-            let data = DataLine::parse(trimmed, line_number)?;
+            let data = if checked {
+                DataLine::parse(trimmed, line_number)?
+            } else {
+                DataLine::parse_with(trimmed, line_number, false)?
+            };
+            let past_end = || ChainParseError::invalid_coordinates(
+                format!("Block of size {} runs past the chromosome end", data.size),
+                line_number,
+            );
             
             // Calculate target coordinates based on strand
             let (block_target_start, block_target_end) = if header.target_strand == Strand::Plus {
@@ -500,9 +1085,11 @@ pub fn parse_chain_reader<R: BufRead>(reader: R) -> Result<ChainFile, ChainParse
                 // Negative strand: flip coordinates
                 // target_start = target_size - (target_pos + size)
                 // target_end = target_size - target_pos
-                let flipped_start = header.target_size - (target_pos + data.size);
-                let flipped_end = header.target_size - target_pos;
-                (flipped_start, flipped_end)
+                match flip_block(target_pos, data.size, header.target_size) {
+                    Some(flipped) => flipped,
+                    None if checked => return Err(past_end()),
+                    None => (target_pos, target_pos + data.size),
+                }
             };
             
             // Calculate source coordinates based on strand
@@ -511,9 +1098,11 @@ pub fn parse_chain_reader<R: BufRead>(reader: R) -> Result<ChainFile, ChainParse
                 (source_pos, source_pos + data.size)
             } else {
                 // Negative strand: flip coordinates
-                let flipped_start = header.source_size - (source_pos + data.size);
-                let flipped_end = header.source_size - source_pos;
-                (flipped_start, flipped_end)
+                match flip_block(source_pos, data.size, header.source_size) {
+                    Some(flipped) => flipped,
+                    None if checked => return Err(past_end()),
+                    None => (source_pos, source_pos + data.size),
+                }
             };
             
             // Create alignment block
@@ -525,9 +1114,11 @@ pub fn parse_chain_reader<R: BufRead>(reader: R) -> Result<ChainFile, ChainParse
                 target_start: block_target_start,
                 target_end: block_target_end,
                 target_strand: header.target_strand,
+                score: header.score,
             };
             
             result.blocks.push(block);
+            result.block_lines.push(line_number);
             
             // Update positions for next block
             source_pos += data.size + data.source_gap;
@@ -544,8 +1135,33 @@ pub fn parse_chain_reader<R: BufRead>(reader: R) -> Result<ChainFile, ChainParse
 /// Automatically detects and handles compression:
 /// - .gz extension or gzip magic bytes (1f 8b)
 /// - .bz2 extension or bzip2 magic bytes (42 5a 68)
+/// - .xz extension or xz magic bytes (fd 37 7a 58 5a 00)
 /// - Plain text otherwise
 pub fn parse_chain_file(path: &Path) -> Result<ChainFile, ChainParseError> {
+    parse_chain_file_with_progress(path, |_| {})
+}
+
+/// Parse a chain file from a path, reporting progress
+///
+/// Same as [`parse_chain_file`], but calls `callback` with the current line
+/// count every [`PROGRESS_INTERVAL_LINES`] lines.
+pub fn parse_chain_file_with_progress(
+    path: &Path,
+    callback: impl Fn(usize),
+) -> Result<ChainFile, ChainParseError> {
+    parse_chain_reader_with_progress(open_chain_file(path)?, callback)
+}
+
+/// Parse a chain file from a path without coordinate checks
+/// 
+/// See [`parse_chain_reader_unchecked`]; compression is detected as in
+/// [`parse_chain_file`].
+pub fn parse_chain_file_unchecked(path: &Path) -> Result<ChainFile, ChainParseError> {
+    parse_chain_reader_unchecked(open_chain_file(path)?)
+}
+
+/// Open a chain file, decompressing gzip, bzip2 or xz input
+fn open_chain_file(path: &Path) -> Result<Box<dyn BufRead>, ChainParseError> {
     use std::fs::File;
     use std::io::Read;
     
@@ -553,7 +1169,7 @@ pub fn parse_chain_file(path: &Path) -> Result<ChainFile, ChainParseError> {
     let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
     
     // Read first few bytes to detect compression format
-    let mut magic = [0u8; 3];
+    let mut magic = [0u8; 6];
     let bytes_read = file.read(&mut magic)?;
     
     // Reset file position
@@ -566,26 +1182,18 @@ pub fn parse_chain_file(path: &Path) -> Result<ChainFile, ChainParseError> {
     } else if extension == "bz2" || (bytes_read >= 3 && magic[0] == 0x42 && magic[1] == 0x5a && magic[2] == 0x68) {
         // BZ2 magic: "BZh" (0x42 0x5a 0x68)
         CompressionFormat::Bzip2
+    } else if extension == "xz" || (bytes_read >= 6 && magic == XZ_MAGIC) {
+        CompressionFormat::Xz
     } else {
         CompressionFormat::Plain
     };
     
-    match format {
-        CompressionFormat::Gzip => {
-            let decoder = flate2::read::GzDecoder::new(file);
-            let reader = BufReader::with_capacity(128 * 1024, decoder);
-            parse_chain_reader(reader)
-        }
-        CompressionFormat::Bzip2 => {
-            let decoder = bzip2::read::BzDecoder::new(file);
-            let reader = BufReader::with_capacity(128 * 1024, decoder);
-            parse_chain_reader(reader)
-        }
-        CompressionFormat::Plain => {
-            let reader = BufReader::with_capacity(128 * 1024, file);
-            parse_chain_reader(reader)
-        }
-    }
+    Ok(match format {
+        CompressionFormat::Gzip => Box::new(BufReader::with_capacity(128 * 1024, flate2::read::GzDecoder::new(file))),
+        CompressionFormat::Bzip2 => Box::new(BufReader::with_capacity(128 * 1024, bzip2::read::BzDecoder::new(file))),
+        CompressionFormat::Xz => Box::new(BufReader::with_capacity(128 * 1024, xz2::read::XzDecoder::new_multi_decoder(file))),
+        CompressionFormat::Plain => Box::new(BufReader::with_capacity(128 * 1024, file)),
+    })
 }
 
 /// Compression format for chain files
@@ -597,8 +1205,13 @@ pub enum CompressionFormat {
     Gzip,
     /// Bzip2 compressed (.bz2)
     Bzip2,
+    /// Xz compressed (.xz)
+    Xz,
 }
 
+/// Xz stream header magic: 0xFD "7zXZ" 0x00
+const XZ_MAGIC: [u8; 6] = [0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00];
+
 /// Detect compression format from file path and/or content
 pub fn detect_compression(path: &Path) -> Result<CompressionFormat, ChainParseError> {
     use std::fs::File;
@@ -613,10 +1226,13 @@ pub fn detect_compression(path: &Path) -> Result<CompressionFormat, ChainParseEr
     if extension == "bz2" {
         return Ok(CompressionFormat::Bzip2);
     }
+    if extension == "xz" {
+        return Ok(CompressionFormat::Xz);
+    }
     
     // Then check by magic bytes
     let mut file = File::open(path)?;
-    let mut magic = [0u8; 3];
+    let mut magic = [0u8; 6];
     let bytes_read = file.read(&mut magic)?;
     
     if bytes_read >= 2 && magic[0] == 0x1f && magic[1] == 0x8b {
@@ -625,6 +1241,9 @@ pub fn detect_compression(path: &Path) -> Result<CompressionFormat, ChainParseEr
     if bytes_read >= 3 && magic[0] == 0x42 && magic[1] == 0x5a && magic[2] == 0x68 {
         return Ok(CompressionFormat::Bzip2);
     }
+    if bytes_read >= 6 && magic == XZ_MAGIC {
+        return Ok(CompressionFormat::Xz);
+    }
     
     Ok(CompressionFormat::Plain)
 }
@@ -635,6 +1254,76 @@ pub fn parse_chain_bytes(data: &[u8]) -> Result<ChainFile, ChainParseError> {
     parse_chain_reader(reader)
 }
 
+/// Number of sections per thread in `parse_chain_bytes_parallel`, so that
+/// uneven chain sizes still balance across threads
+const PARALLEL_SECTIONS_PER_THREAD: usize = 8;
+
+/// Byte offsets splitting `data` into about `sections` pieces, each cut
+/// placed at the start of a `chain` header line
+fn chain_section_bounds(data: &[u8], sections: usize) -> Vec<usize> {
+    let target = data.len().div_ceil(sections.max(1)).max(1);
+    let finder = memchr::memmem::Finder::new(b"\nchain");
+    let mut bounds = vec![0];
+    let mut from = target;
+    while from < data.len() {
+        match finder.find(&data[from - 1..]) {
+            Some(pos) => {
+                let cut = from + pos;
+                bounds.push(cut);
+                from = cut + target;
+            }
+            None => break,
+        }
+    }
+    bounds.push(data.len());
+    bounds
+}
+
+/// Parse an in-memory chain file on `threads` threads (0 = all cores)
+/// 
+/// The data is cut at chain header lines into sections that are parsed
+/// independently and merged in file order, so the result (block order,
+/// block line numbers, chromosome sizes, the first error reported) is the
+/// same as [`parse_chain_bytes`]. When a chromosome size differs between
+/// headers the last header wins, as in the sequential parser.
+pub fn parse_chain_bytes_parallel(data: &[u8], threads: usize) -> Result<ChainFile, ChainParseError> {
+    use rayon::prelude::*;
+    
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|e| ChainParseError::new(format!("Failed to create thread pool: {}", e)))?;
+    let bounds = chain_section_bounds(data, pool.current_num_threads() * PARALLEL_SECTIONS_PER_THREAD);
+    
+    let sections: Vec<(Result<ChainFile, ChainParseError>, usize)> = pool.install(|| {
+        bounds
+            .par_windows(2)
+            .map(|w| {
+                let section = &data[w[0]..w[1]];
+                let parsed = parse_chain_lines(BufReader::new(section), |_| {}, true, &CancellationToken::new());
+                (parsed, memchr::memchr_iter(b'\n', section).count())
+            })
+            .collect()
+    });
+    
+    let mut result = ChainFile::new();
+    let mut line_offset = 0;
+    for (parsed, lines) in sections {
+        let mut part = parsed.map_err(|mut e| {
+            e.line_number = e.line_number.map(|line| line + line_offset);
+            e
+        })?;
+        result.blocks.append(&mut part.blocks);
+        result.block_lines.extend(part.block_lines.iter().map(|line| line + line_offset));
+        result.chain_scores.append(&mut part.chain_scores);
+        result.source_chrom_sizes.extend(part.source_chrom_sizes);
+        result.target_chrom_sizes.extend(part.target_chrom_sizes);
+        line_offset += lines;
+    }
+    
+    Ok(result)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -708,6 +1397,28 @@ mod tests {
     }
 
     
+    #[test]
+    fn test_parse_chain_progress_callback() {
+        use std::cell::RefCell;
+        
+        // 1 header + 24_999 data lines = 25_000 lines -> callbacks at 10k and 20k
+        let mut data = String::from("chain 1000 chr1 1000000 + 0 25000 chr1 1000000 + 0 25000 1\n");
+        for _ in 0..24_998 {
+            data.push_str("1 0 0\n");
+        }
+        data.push_str("2\n");
+        
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("progress.chain");
+        std::fs::write(&path, &data).unwrap();
+        
+        let calls = RefCell::new(Vec::new());
+        let result = parse_chain_file_with_progress(&path, |n| calls.borrow_mut().push(n)).unwrap();
+        
+        assert_eq!(result.blocks.len(), 24_999);
+        assert_eq!(calls.into_inner(), vec![10_000, 20_000]);
+    }
+    
     #[test]
     fn test_parse_simple_chain() {
         let chain_data = b"\
@@ -908,6 +1619,357 @@ invalid_data
         assert!(display.contains("Line 42"));
         assert!(display.contains("Test error message"));
     }
+    
+    #[test]
+    fn test_error_display_header_kinds() {
+        let display = |line: &str| ChainHeader::parse(line, 7).unwrap_err().to_string();
+        
+        assert_eq!(
+            display("chain 1000 chr1 1000 + 0 100"),
+            "Line 7 [InvalidHeader]: Expected 12+ fields, got 7\n  chain 1000 chr1 1000 + 0 100"
+        );
+        assert_eq!(
+            display("chain abc chr1 1000 + 0 100 chr1 1000 + 0 100 1"),
+            "Line 7 [InvalidNumber]: Invalid score value 'abc': expected a non-negative integer\n  \
+             chain abc chr1 1000 + 0 100 chr1 1000 + 0 100 1\n        ^^^"
+        );
+        assert_eq!(
+            display("chain 1000 chr1 1000 + 0 100 chr1 1000 x 0 100 1"),
+            "Line 7 [InvalidStrand]: Invalid strand character 'x', expected '+' or '-'\n  \
+             chain 1000 chr1 1000 + 0 100 chr1 1000 x 0 100 1\n                                         ^"
+        );
+        assert_eq!(
+            display("chain 1000 chr1 1000 + 0 2000 chr1 1000 + 0 100 1"),
+            "Line 7 [InvalidCoordinates]: Source end (2000) > source size (1000)\n  \
+             chain 1000 chr1 1000 + 0 2000 chr1 1000 + 0 100 1\n                           ^^^^"
+        );
+        // Tabs are kept in front of the marker, other characters become spaces
+        assert_eq!(
+            display("chain\t1\tchr1\t1000\t+\t0\t100\tchr1\t1000\t+\t0\tx"),
+            "Line 7 [InvalidNumber]: Invalid target end value 'x': expected a non-negative integer\n  \
+             chain\t1\tchr1\t1000\t+\t0\t100\tchr1\t1000\t+\t0\tx\n       \t \t    \t    \t \t \t   \t    \t    \t \t \t^"
+        );
+    }
+    
+    #[test]
+    fn test_error_display_data_line_kinds() {
+        assert_eq!(
+            DataLine::parse("100 50", 10).unwrap_err().to_string(),
+            "Line 10 [InvalidDataLine]: Expected 1 or 3 fields, got 2\n  100 50"
+        );
+        assert_eq!(
+            DataLine::parse("100 5x 50", 10).unwrap_err().to_string(),
+            "Line 10 [InvalidNumber]: Invalid source gap (dt) value '5x': expected a non-negative integer\n  \
+             100 5x 50\n      ^^"
+        );
+        assert_eq!(
+            DataLine::parse("0", 11).unwrap_err().to_string(),
+            "Line 11 [InvalidDataLine]: Block size must be greater than 0\n  0\n  ^"
+        );
+    }
+    
+    #[test]
+    fn test_error_display_other_kinds() {
+        let err = ChainParseError::from(std::io::Error::other("disk gone"));
+        assert_eq!(err.to_string(), "[IoError]: IO error: disk gone");
+        
+        let err = ChainParseError::file_not_found(Path::new("missing.chain"));
+        assert_eq!(err.to_string(), "[FileNotFound]: Chain file not found: missing.chain");
+        
+        let err = ChainParseError::with_context("Unexpected end of file", 3, ChainParseErrorKind::UnexpectedEof, None);
+        assert_eq!(err.to_string(), "Line 3 [UnexpectedEof]: Unexpected end of file");
+        
+        let err = ChainParseError::with_context(
+            "Unsupported compression",
+            1,
+            ChainParseErrorKind::UnsupportedCompression,
+            Some("PK".to_string()),
+        );
+        assert_eq!(err.to_string(), "Line 1 [UnsupportedCompression]: Unsupported compression\n  PK");
+    }
+    
+    #[test]
+    fn test_error_display_truncates_content() {
+        let long = format!("chain 1000 chr1 1000 + 0 100 chr1 1000 + 0 100 {}", "x".repeat(300));
+        let err = ChainParseError::invalid_header("Bad header", 1, &long);
+        let content = err.content.as_deref().unwrap();
+        assert_eq!(content.chars().count(), MAX_ERROR_CONTENT_CHARS + 3);
+        assert!(content.ends_with("..."));
+        
+        // A field past the cut gets no marker; one before it does
+        let err = ChainParseError::invalid_number("id", "x", 1).at_field(&long, 12);
+        assert_eq!(err.field_span, None);
+        let err = ChainParseError::invalid_number("score", "1000", 1).at_field(&long, 1);
+        assert_eq!(err.field_span, Some((6, 4)));
+        assert!(err.to_string().ends_with("\n        ^^^^"));
+    }
+    
+    #[test]
+    fn test_validate_valid_chain() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 300 chr1 1000 + 0 300 1
+100\t100\t100
+100
+
+chain 900 chr1 1000 + 500 600 chr2 500 - 0 100 2
+100
+";
+        let chain_file = parse_chain_bytes(chain_data).unwrap();
+        assert!(chain_file.validate().is_empty());
+        assert_eq!(chain_file.block_lines, vec![2, 3, 6]);
+    }
+    
+    #[test]
+    fn test_validate_reports_all_violations() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 300 chr1 1000 + 0 300 1
+100\t100\t100
+0\t0\t0
+100
+
+chain 900 chr1 1000 + 250 1050 chr2 500 + 0 800 2
+800
+
+chain 800 chr3 100 + 0 50 chr3 40 - 0 50 3
+50
+";
+        // The checked parser stops at the first problem
+        assert!(parse_chain_bytes(chain_data).is_err());
+        
+        let chain_file = parse_chain_reader_unchecked(&chain_data[..]).unwrap();
+        let errors = chain_file.validate();
+        assert_eq!(errors, vec![
+            ChainValidationError::EmptyBlock { line: 3 },
+            ChainValidationError::InvalidTargetRange { line: 3, start: 200, end: 200 },
+            ChainValidationError::SourceOutOfBounds { line: 7, chrom: "chr1".into(), end: 1050, size: 1000 },
+            ChainValidationError::TargetOutOfBounds { line: 7, chrom: "chr2".into(), end: 800, size: 500 },
+            ChainValidationError::OverlappingSource { line: 7, other_line: 4, chrom: "chr1".into(), start: 250, end: 1050 },
+            ChainValidationError::TargetOutOfBounds { line: 10, chrom: "chr3".into(), end: 50, size: 40 },
+        ]);
+        assert_eq!(errors[4].to_string(), "line 7: source block chr1:250-1050 overlaps the block at line 4");
+    }
+    
+    #[test]
+    fn test_parse_minus_strand_block_past_end() {
+        // Used to underflow while flipping the block
+        let chain_data = b"chain 800 chr3 100 + 0 50 chr3 40 - 0 40 3\n50\n";
+        let err = parse_chain_bytes(chain_data).unwrap_err();
+        assert_eq!(err.kind, ChainParseErrorKind::InvalidCoordinates);
+        assert_eq!(err.line_number, Some(2));
+    }
+    
+    #[test]
+    fn test_dedup_blocks() {
+        // Second chain repeats the first block; the third differs only in strand
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 300 chr1 1000 + 0 300 1
+100\t100\t100
+100
+
+chain 900 chr1 1000 + 0 100 chr1 1000 + 0 100 2
+100
+
+chain 800 chr1 1000 + 0 100 chr1 1000 - 900 1000 3
+100
+";
+        let mut chain_file = parse_chain_bytes(chain_data).unwrap();
+        assert_eq!(chain_file.blocks.len(), 4);
+        
+        assert_eq!(chain_file.dedup_blocks(), 1);
+        assert_eq!(chain_file.removed_duplicates, 1);
+        assert_eq!(chain_file.blocks.len(), 3);
+        assert_eq!((chain_file.blocks[0].source_start, chain_file.blocks[1].source_start), (0, 200));
+        assert_eq!(chain_file.blocks[2].target_strand, Strand::Minus);
+        assert_eq!(chain_file.block_lines, vec![2, 3, 9]);
+        
+        // Idempotent
+        assert_eq!(chain_file.dedup_blocks(), 0);
+        assert_eq!(chain_file.removed_duplicates, 1);
+    }
+    
+    #[test]
+    fn test_from_paf_with_cigar() {
+        let paf = "\
+q1\t1000\t100\t180\t+\tt1\t2000\t500\t585\t70\t90\t60\tNM:i:15\tAS:i:120\tcg:Z:30M10D20=5I25M
+q2\t1000\t100\t150\t-\tt2\t500\t10\t50\t40\t50\t60\tcg:Z:20M10I20M
+";
+        let chain_file = ChainFile::from_paf_with_cigar(paf.as_bytes()).unwrap();
+        let blocks: Vec<_> = chain_file
+            .blocks
+            .iter()
+            .map(|b| (
+                b.source_chrom.as_str(), b.source_start, b.source_end,
+                b.target_chrom.as_str(), b.target_start, b.target_end, b.target_strand, b.score,
+            ))
+            .collect();
+        assert_eq!(blocks, vec![
+            ("t1", 500, 530, "q1", 100, 130, Strand::Plus, 120),
+            ("t1", 540, 560, "q1", 130, 150, Strand::Plus, 120),
+            ("t1", 560, 585, "q1", 155, 180, Strand::Plus, 120),
+            // Reverse strand: the query is walked from its end
+            ("t2", 10, 30, "q2", 130, 150, Strand::Minus, 40),
+            ("t2", 30, 50, "q2", 100, 120, Strand::Minus, 40),
+        ]);
+        assert_eq!(chain_file.chain_scores, vec![120, 40]);
+        assert_eq!(chain_file.source_chrom_sizes.get("t1"), Some(&2000));
+        assert_eq!(chain_file.target_chrom_sizes.get("q2"), Some(&1000));
+    }
+    
+    #[test]
+    fn test_from_paf_with_cigar_errors() {
+        let line = |cigar_tag: &str| format!("q1\t1000\t100\t180\t+\tt1\t2000\t500\t585\t70\t90\t60\t{}\n", cigar_tag);
+        
+        for (tag, message) in [
+            ("NM:i:0", "no cg:Z: CIGAR tag"),
+            ("cg:Z:80M", "does not cover"),
+            ("cg:Z:5S75M", "Unsupported CIGAR operation 'S'"),
+            ("cg:Z:M80", "Invalid CIGAR length"),
+        ] {
+            let err = ChainFile::from_paf_with_cigar(line(tag).as_bytes()).unwrap_err();
+            assert!(err.is_kind(ChainParseErrorKind::InvalidDataLine), "{}", err);
+            assert!(err.message.contains(message), "{}", err.message);
+            assert_eq!(err.line_number, Some(1));
+        }
+        
+        let err = ChainFile::from_paf_with_cigar("q1\t1000\t1x0\t180\t+\tt1\t2000\t500\t585\t70\t90\t60\n".as_bytes()).unwrap_err();
+        assert!(err.is_kind(ChainParseErrorKind::InvalidNumber));
+    }
+    
+    #[test]
+    fn test_from_blast_tabular() {
+        let blast = "\
+# BLASTN 2.15.0+
+q1\ts1\t99.00\t100\t1\t0\t1\t100\t501\t600\t1e-50\t180.5
+q1\ts2\t95.00\t50\t2\t0\t201\t250\t800\t751\t2e-20\t90.0
+q2\ts1\t90.00\t60\t3\t1\t11\t70\t1001\t1055\t1e-10\t70.2
+q3\ts1\t100.00\t1\t0\t0\t5\t5\t20\t20\t5.0\t2.0
+";
+        let chain_file = ChainFile::from_blast_tabular(blast.as_bytes(), Strand::Minus).unwrap();
+        let blocks: Vec<_> = chain_file
+            .blocks
+            .iter()
+            .map(|b| (
+                b.source_chrom.as_str(), b.source_start, b.source_end,
+                b.target_chrom.as_str(), b.target_start, b.target_end, b.target_strand, b.score,
+            ))
+            .collect();
+        assert_eq!(blocks, vec![
+            ("q1", 0, 100, "s1", 500, 600, Strand::Plus, 180),
+            // sstart > send: reverse strand
+            ("q1", 200, 250, "s2", 750, 800, Strand::Minus, 90),
+            // Gapped: trimmed to the 55-base subject span
+            ("q2", 10, 65, "s1", 1000, 1055, Strand::Plus, 70),
+            // Single base: default strand
+            ("q3", 4, 5, "s1", 19, 20, Strand::Minus, 2),
+        ]);
+        assert_eq!(chain_file.chain_scores, vec![180, 90, 70, 2]);
+        assert_eq!(chain_file.source_chrom_sizes.get("q1"), Some(&250));
+        assert_eq!(chain_file.target_chrom_sizes.get("s1"), Some(&1055));
+        assert!(chain_file.validate().is_empty());
+    }
+    
+    #[test]
+    fn test_from_blast_tabular_errors() {
+        let err = ChainFile::from_blast_tabular("q1\ts1\t99.00\t100\n".as_bytes(), Strand::Plus).unwrap_err();
+        assert!(err.is_kind(ChainParseErrorKind::InvalidDataLine), "{}", err);
+        assert_eq!(err.line_number, Some(1));
+        
+        for line in [
+            "q1\ts1\t99.00\t100\t1\t0\t0\t100\t501\t600\t1e-50\t180.5",
+            "q1\ts1\t99.00\t100\t1\t0\t1\t100\t501\t6x0\t1e-50\t180.5",
+            "q1\ts1\t99.00\t100\t1\t0\t1\t100\t501\t600\t1e-50\tNA",
+        ] {
+            let err = ChainFile::from_blast_tabular(format!("\n{}\n", line).as_bytes(), Strand::Plus).unwrap_err();
+            assert!(err.is_kind(ChainParseErrorKind::InvalidNumber), "{}", err);
+            assert_eq!(err.line_number, Some(2));
+        }
+    }
+    
+    #[test]
+    fn test_parse_chain_bytes_parallel_matches_sequential() {
+        use std::fmt::Write as _;
+        
+        let mut data = String::from("# comment\n\n");
+        for i in 0..200u64 {
+            let strand = if i % 3 == 0 { '-' } else { '+' };
+            let chrom = format!("chr{}", i % 5 + 1);
+            writeln!(data, "chain {} {c} 100000 + {s} {e} {c} 100000 {} {s} {e} {}", 1000 + i, strand, i + 1, c = chrom, s = i * 400, e = i * 400 + 230).unwrap();
+            for _ in 0..i % 4 {
+                writeln!(data, "50 10 10").unwrap();
+            }
+            writeln!(data, "{}", 230 - (i % 4) * 60).unwrap();
+            if i % 7 != 0 {
+                data.push('\n');
+            }
+        }
+        
+        let sequential = parse_chain_bytes(data.as_bytes()).unwrap();
+        for threads in [1, 2, 3, 8] {
+            let parallel = parse_chain_bytes_parallel(data.as_bytes(), threads).unwrap();
+            assert_eq!(parallel.blocks.len(), sequential.blocks.len());
+            assert_eq!(parallel.blocks, sequential.blocks);
+            assert_eq!(parallel.block_lines, sequential.block_lines);
+            assert_eq!(parallel.chain_scores, sequential.chain_scores);
+            assert_eq!(parallel.source_chrom_sizes, sequential.source_chrom_sizes);
+            assert_eq!(parallel.target_chrom_sizes, sequential.target_chrom_sizes);
+        }
+        
+        // The first error is reported at the same line
+        let broken = data.replacen("50 10 10", "50 x 10", 40);
+        let expected = parse_chain_bytes(broken.as_bytes()).unwrap_err();
+        let err = parse_chain_bytes_parallel(broken.as_bytes(), 4).unwrap_err();
+        assert_eq!((err.kind, err.line_number), (expected.kind, expected.line_number));
+        
+        assert!(parse_chain_bytes_parallel(b"", 2).unwrap().blocks.is_empty());
+        assert_eq!(chain_section_bounds(b"", 4), vec![0, 0]);
+        assert_eq!(chain_section_bounds(b"chain a\n1\nchain b\n1\n", 4), vec![0, 10, 20]);
+    }
+    
+    /// Endless chain: one header, then `1 0 0` data lines, each read
+    /// taking a little time
+    struct SlowChainReader {
+        pending: Vec<u8>,
+    }
+    
+    impl std::io::Read for SlowChainReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() {
+                std::thread::sleep(std::time::Duration::from_micros(50));
+                self.pending.extend_from_slice(b"1 0 0\n");
+            }
+            let n = self.pending.len().min(buf.len());
+            buf[..n].copy_from_slice(&self.pending[..n]);
+            self.pending.drain(..n);
+            Ok(n)
+        }
+    }
+    
+    #[test]
+    fn test_parse_chain_reader_cancel() {
+        let cancel = CancellationToken::new();
+        let token = cancel.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let reader = BufReader::with_capacity(16, SlowChainReader {
+                pending: b"chain 1 chr1 1000000000000 + 0 1000000000000 chr2 1000000000000 + 0 1000000000000 1\n".to_vec(),
+            });
+            let _ = tx.send(parse_chain_reader_with_cancel(reader, &token));
+        });
+        
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!cancel.is_cancelled());
+        cancel.cancel();
+        
+        let result = rx.recv_timeout(std::time::Duration::from_secs(10)).expect("parse did not stop after cancel");
+        let err = result.unwrap_err();
+        assert!(err.is_kind(ChainParseErrorKind::Cancelled), "{}", err);
+        assert_eq!(err.line_number.unwrap() % CANCEL_CHECK_INTERVAL_LINES, 0);
+        
+        // A token that is never cancelled does not affect parsing
+        let data = b"chain 1000 chr1 1000 + 0 100 chr1 1000 + 0 100 1\n100\n";
+        let chain_file = parse_chain_reader_with_cancel(&data[..], &CancellationToken::new()).unwrap();
+        assert_eq!(chain_file.blocks.len(), 1);
+    }
 }
 
 
@@ -1032,6 +2094,49 @@ chain 1000 chr1 1000 + 100 400 chr1 1000 + 100 400 1
         }
     }
     
+    /// Test that xz and plain text parsing produce identical results
+    #[test]
+    fn test_xz_plain_equivalence() {
+        use std::io::Write;
+        use xz2::write::XzEncoder;
+        
+        let chain_data = b"\
+chain 1000 chr1 1000 + 100 400 chr1 1000 + 100 400 1
+100 50 50
+100
+
+chain 500 chr2 2000 + 0 200 chr7 2000 - 0 200 2
+100 50 50
+50
+";
+        
+        // Parse plain text
+        let plain_result = parse_chain_bytes(chain_data).unwrap();
+        
+        // Create xz compressed version
+        let mut encoder = XzEncoder::new(Vec::new(), 6);
+        encoder.write_all(chain_data).unwrap();
+        let xz_data = encoder.finish().unwrap();
+        
+        let dir = tempfile::tempdir().unwrap();
+        let xz_path = dir.path().join("test_chain.chain.xz");
+        std::fs::write(&xz_path, &xz_data).unwrap();
+        // Same data without the extension is detected by its magic bytes
+        let no_ext_path = dir.path().join("test_chain_xz_no_ext");
+        std::fs::write(&no_ext_path, &xz_data).unwrap();
+        
+        assert_eq!(detect_compression(&xz_path).unwrap(), CompressionFormat::Xz);
+        assert_eq!(detect_compression(&no_ext_path).unwrap(), CompressionFormat::Xz);
+        
+        for path in [&xz_path, &no_ext_path] {
+            let xz_result = parse_chain_file(path).unwrap();
+            assert_eq!(plain_result.blocks, xz_result.blocks);
+            assert_eq!(plain_result.source_chrom_sizes, xz_result.source_chrom_sizes);
+            assert_eq!(plain_result.target_chrom_sizes, xz_result.target_chrom_sizes);
+            assert_eq!(plain_result.chain_scores, xz_result.chain_scores);
+        }
+    }
+    
     /// Test format detection by magic bytes (without extension)
     #[test]
     fn test_format_detection_by_magic() {
@@ -1061,4 +2166,5 @@ chain 1000 chr1 1000 + 100 400 chr1 1000 + 100 400 1
         // Clean up
         let _ = std::fs::remove_file(&path_no_ext);
     }
+
 }
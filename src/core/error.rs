@@ -20,6 +20,10 @@ pub enum FastCrossMapError {
     #[error("Conversion error: {0}")]
     Conversion(#[from] ConversionError),
 
+    /// Input format could not be detected
+    #[error("Cannot detect input format of {0:?}; use the format-specific subcommand")]
+    UnknownFormat(PathBuf),
+
     /// I/O errors
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -83,8 +87,104 @@ pub enum MappingError {
         start: u64,
         end: u64,
     },
+
+    /// Query extends past the end of the source chromosome
+    #[error("Query end {end} exceeds source chromosome size {size} for {chrom}")]
+    OutOfBounds {
+        chrom: String,
+        end: u64,
+        size: u64,
+    },
 }
 
+/// Why a record could not be lifted over
+///
+/// Shared by all formats so the same failure gets the same label in every
+/// unmap file. VCF-like formats write [`to_vcf_annotation`](Self::to_vcf_annotation)
+/// (`Fail(Unmap)`), region/BED output writes
+/// [`to_bed_annotation`](Self::to_bed_annotation) (`Unmap`). The strings
+/// follow CrossMap where CrossMap has an equivalent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LiftoverFailureReason {
+    /// No chain block covers the record
+    Unmapped,
+    /// The record maps to more than one target location
+    MultipleHits,
+    /// The record's pieces land on different target chromosomes
+    CrossChromosome,
+    /// Too little of the record maps (below the minimum ratio)
+    LowCoverage,
+    /// The input line could not be parsed
+    ParseError,
+    /// A lifted coordinate does not fit the output field
+    CoordinateOverflow,
+    /// A sub-feature block (BED12 exon) could not be mapped consistently
+    InvalidBlock,
+    /// The target chromosome is missing from the reference genome
+    ReferenceKeyError,
+    /// The lifted REF allele equals the ALT allele
+    RefEqualsAlt,
+    /// An inversion landed on the minus strand (`--inversion-strict`)
+    InversionStrand,
+    /// The mate of a breakend failed to lift over
+    MateUnmapped,
+    /// A gVCF record below the minimum genotype quality
+    LowGenotypeQuality,
+    /// The record parsed but failed input validation (`--strict-input`)
+    InvalidRecord,
+}
+
+impl LiftoverFailureReason {
+    /// Short name of the reason, as written inside `Fail(...)`
+    fn label(&self) -> &'static str {
+        match self {
+            LiftoverFailureReason::Unmapped => "Unmap",
+            LiftoverFailureReason::MultipleHits => "Multiple_hits",
+            LiftoverFailureReason::CrossChromosome => "CrossChroms",
+            LiftoverFailureReason::LowCoverage => "LowRatio",
+            LiftoverFailureReason::ParseError => "ParseError",
+            LiftoverFailureReason::CoordinateOverflow => "PosOverflow",
+            LiftoverFailureReason::InvalidBlock => "InvalidBlock",
+            LiftoverFailureReason::ReferenceKeyError => "KeyError",
+            LiftoverFailureReason::RefEqualsAlt => "REF==ALT",
+            LiftoverFailureReason::InversionStrand => "InversionStrand",
+            LiftoverFailureReason::MateUnmapped => "BndMateUnmap",
+            LiftoverFailureReason::LowGenotypeQuality => "LowGQ",
+            LiftoverFailureReason::InvalidRecord => "InvalidRecord",
+        }
+    }
+
+    /// Annotation appended to VCF/gVCF records in the unmap file, e.g. `Fail(Unmap)`
+    pub fn to_vcf_annotation(&self) -> String {
+        format!("Fail({})", self.label())
+    }
+
+    /// Reason column of region/BED unmap records, e.g. `Unmap`
+    ///
+    /// Parse errors keep the `InvalidFormat` label region output has always used.
+    pub fn to_bed_annotation(&self) -> &'static str {
+        match self {
+            LiftoverFailureReason::ParseError => "InvalidFormat",
+            other => other.label(),
+        }
+    }
+}
+
+impl From<&MappingError> for LiftoverFailureReason {
+    fn from(err: &MappingError) -> Self {
+        match err {
+            MappingError::ChromosomeNotFound(_) | MappingError::NoMapping { .. } => LiftoverFailureReason::Unmapped,
+            MappingError::InvalidRange { .. } => LiftoverFailureReason::ParseError,
+            MappingError::OutOfBounds { .. } => LiftoverFailureReason::CoordinateOverflow,
+        }
+    }
+}
+
+/// Error returned when parsing a strand that is neither `+` nor `-`
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("Invalid strand '{0}', expected '+' or '-'")]
+pub struct ParseStrandError(pub String);
+
 /// Errors that can occur during format conversion
 #[derive(Debug, Error)]
 pub enum ConversionError {
@@ -128,3 +228,37 @@ pub type MappingResult<T> = std::result::Result<T, MappingError>;
 
 /// Result type alias for conversion operations
 pub type ConversionResult<T> = std::result::Result<T, ConversionError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_reason_annotations() {
+        assert_eq!(LiftoverFailureReason::Unmapped.to_vcf_annotation(), "Fail(Unmap)");
+        assert_eq!(LiftoverFailureReason::MultipleHits.to_vcf_annotation(), "Fail(Multiple_hits)");
+        assert_eq!(LiftoverFailureReason::ReferenceKeyError.to_vcf_annotation(), "Fail(KeyError)");
+        assert_eq!(LiftoverFailureReason::CoordinateOverflow.to_vcf_annotation(), "Fail(PosOverflow)");
+        assert_eq!(LiftoverFailureReason::RefEqualsAlt.to_vcf_annotation(), "Fail(REF==ALT)");
+        assert_eq!(LiftoverFailureReason::LowGenotypeQuality.to_vcf_annotation(), "Fail(LowGQ)");
+
+        assert_eq!(LiftoverFailureReason::Unmapped.to_bed_annotation(), "Unmap");
+        assert_eq!(LiftoverFailureReason::CrossChromosome.to_bed_annotation(), "CrossChroms");
+        assert_eq!(LiftoverFailureReason::LowCoverage.to_bed_annotation(), "LowRatio");
+        assert_eq!(LiftoverFailureReason::ParseError.to_bed_annotation(), "InvalidFormat");
+        assert_eq!(LiftoverFailureReason::InvalidBlock.to_bed_annotation(), "InvalidBlock");
+        assert_eq!(LiftoverFailureReason::InvalidRecord.to_bed_annotation(), "InvalidRecord");
+    }
+
+    #[test]
+    fn test_failure_reason_from_mapping_error() {
+        let reason = |err: MappingError| LiftoverFailureReason::from(&err);
+        assert_eq!(reason(MappingError::ChromosomeNotFound("chrUn".into())), LiftoverFailureReason::Unmapped);
+        assert_eq!(reason(MappingError::NoMapping { chrom: "chr1".into(), start: 0, end: 1 }), LiftoverFailureReason::Unmapped);
+        assert_eq!(reason(MappingError::InvalidRange { start: 5, end: 1 }), LiftoverFailureReason::ParseError);
+        assert_eq!(
+            reason(MappingError::OutOfBounds { chrom: "chr1".into(), end: 20, size: 10 }),
+            LiftoverFailureReason::CoordinateOverflow
+        );
+    }
+}
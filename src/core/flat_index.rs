@@ -0,0 +1,109 @@
+//! Sorted-vector interval store (`flat-index` feature)
+//!
+//! Drop-in replacement for the rust-lapper tree used by `ChainIndex`: the
+//! intervals of one chromosome are kept in a `Vec` sorted by start, and a
+//! query binary-searches the first candidate with `partition_point`. Chain
+//! blocks on one chromosome rarely overlap, so the scan after the search is
+//! short for both sparse and dense chains.
+
+use rust_lapper::Interval;
+
+/// Intervals of one chromosome, sorted by (start, stop)
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlatIntervals<T: Eq + Clone + Send + Sync> {
+    intervals: Vec<Interval<u64, T>>,
+    /// Length of the longest interval, bounding how far back an overlap can start
+    max_len: u64,
+}
+
+impl<T: Eq + Clone + Send + Sync> FlatIntervals<T> {
+    /// Sort the intervals (stable, same order as `Lapper::new`)
+    pub fn new(mut intervals: Vec<Interval<u64, T>>) -> Self {
+        intervals.sort_by_key(|iv| (iv.start, iv.stop));
+        let max_len = intervals.iter().map(|iv| iv.stop.saturating_sub(iv.start)).max().unwrap_or(0);
+        Self { intervals, max_len }
+    }
+
+    /// Intervals overlapping `[start, stop)`, in sorted order
+    pub fn find(&self, start: u64, stop: u64) -> impl Iterator<Item = &Interval<u64, T>> {
+        let from = start.saturating_sub(self.max_len);
+        let first = self.intervals.partition_point(|iv| iv.start < from);
+        let last = self.intervals.partition_point(|iv| iv.start < stop);
+        self.intervals[first..last.max(first)]
+            .iter()
+            .filter(move |iv| iv.stop > start)
+    }
+
+    /// All intervals in sorted order
+    pub fn iter(&self) -> std::slice::Iter<'_, Interval<u64, T>> {
+        self.intervals.iter()
+    }
+
+    /// All intervals as a sorted slice
+    pub fn as_slice(&self) -> &[Interval<u64, T>] {
+        &self.intervals
+    }
+
+    /// Consume the store, returning the sorted intervals
+    pub fn into_intervals(self) -> Vec<Interval<u64, T>> {
+        self.intervals
+    }
+
+    /// Number of intervals
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_lapper::Lapper;
+
+    fn intervals(ranges: &[(u64, u64)]) -> Vec<Interval<u64, u32>> {
+        ranges
+            .iter()
+            .enumerate()
+            .map(|(i, &(start, stop))| Interval { start, stop, val: i as u32 })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_matches_lapper() {
+        let ranges = [(500, 600), (0, 100), (150, 400), (150, 160), (390, 395), (1000, 5000), (600, 700)];
+        let flat = FlatIntervals::new(intervals(&ranges));
+        let lapper = Lapper::new(intervals(&ranges));
+        assert_eq!(flat.len(), lapper.len());
+
+        for start in (0..5200).step_by(37) {
+            for len in [0, 1, 10, 250, 3000] {
+                let expected: Vec<u32> = lapper.find(start, start + len).map(|iv| iv.val).collect();
+                let actual: Vec<u32> = flat.find(start, start + len).map(|iv| iv.val).collect();
+                assert_eq!(actual, expected, "query {}-{}", start, start + len);
+            }
+        }
+
+        let order: Vec<u32> = flat.iter().map(|iv| iv.val).collect();
+        let lapper_order: Vec<u32> = lapper.iter().map(|iv| iv.val).collect();
+        assert_eq!(order, lapper_order);
+        let bounds: Vec<(u64, u64)> = flat.as_slice().iter().map(|iv| (iv.start, iv.stop)).collect();
+        let lapper_bounds: Vec<(u64, u64)> = lapper.intervals.iter().map(|iv| (iv.start, iv.stop)).collect();
+        assert_eq!(bounds, lapper_bounds);
+        let owned: Vec<(u64, u64)> = flat.into_intervals().iter().map(|iv| (iv.start, iv.stop)).collect();
+        assert_eq!(owned, lapper_bounds);
+    }
+
+    #[test]
+    fn test_find_empty() {
+        let flat: FlatIntervals<u32> = FlatIntervals::new(Vec::new());
+        assert_eq!(flat.len(), 0);
+        assert_eq!(flat.find(0, 100).count(), 0);
+
+        // Half-open: touching intervals do not overlap
+        let flat = FlatIntervals::new(intervals(&[(10, 20)]));
+        assert_eq!(flat.find(20, 30).count(), 0);
+        assert_eq!(flat.find(0, 10).count(), 0);
+        assert_eq!(flat.find(19, 20).count(), 1);
+    }
+}
@@ -1,15 +1,20 @@
 //! Interval index for efficient coordinate queries
 //!
-//! Uses rust-lapper for O(log n + k) interval queries.
+//! Uses rust-lapper for O(log n + k) interval queries, or a sorted `Vec`
+//! with binary search when built with the `flat-index` feature.
 
-use crate::core::chain::{parse_chain_file, ChainFile, ChainParseError};
-use crate::core::Strand;
-use rust_lapper::{Interval, Lapper};
+use crate::core::chain::{parse_chain_file, ChainBlock, ChainFile, ChainParseError};
+use crate::core::{normalize_chrom, Strand};
+use rust_lapper::Interval;
+#[cfg(not(feature = "flat-index"))]
+use rust_lapper::Lapper;
 use std::collections::HashMap;
+use std::io::{self, Write};
 use std::path::Path;
 
 /// Value stored in each interval - target mapping information
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IntervalValue {
     /// Target chromosome name
     pub target_chrom: String,
@@ -21,31 +26,231 @@ pub struct IntervalValue {
     pub target_strand: Strand,
     /// Source chromosome name (for reference)
     pub source_chrom: String,
+    /// Score of the chain the block came from
+    pub score: u64,
 }
 
 /// Type alias for chain intervals
 pub type ChainInterval = Interval<u64, IntervalValue>;
 
+/// Per-chromosome interval store
+#[cfg(not(feature = "flat-index"))]
+type IntervalStore = Lapper<u64, IntervalValue>;
+#[cfg(feature = "flat-index")]
+type IntervalStore = crate::core::flat_index::FlatIntervals<IntervalValue>;
+
+/// Per-chromosome coverage statistics of the chain blocks
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChromOverlapStats {
+    /// Source chromosome size (0 if unknown)
+    pub total_bases: u64,
+    /// Number of source bases covered by at least one block
+    pub covered_bases: u64,
+    /// covered_bases / total_bases (0.0 if the size is unknown)
+    pub coverage_fraction: f64,
+    /// Largest block size
+    pub max_block_size: u64,
+    /// Mean block size
+    pub mean_block_size: f64,
+    /// Number of blocks on this chromosome
+    pub block_count: usize,
+}
+
+/// Min / max / mean / median of a set of values
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Distribution {
+    pub min: u64,
+    pub max: u64,
+    pub mean: f64,
+    pub median: f64,
+}
+
+impl Distribution {
+    /// Summarize `values` (all zero for an empty set)
+    fn from_values(mut values: Vec<u64>) -> Self {
+        if values.is_empty() {
+            return Self::default();
+        }
+        values.sort_unstable();
+        let n = values.len();
+        let median = if n % 2 == 1 {
+            values[n / 2] as f64
+        } else {
+            (values[n / 2 - 1] as f64 + values[n / 2] as f64) / 2.0
+        };
+        Self {
+            min: values[0],
+            max: values[n - 1],
+            mean: values.iter().map(|&v| v as f64).sum::<f64>() / n as f64,
+            median,
+        }
+    }
+}
+
+/// Bases of one chromosome covered by chain blocks
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChromCoverage {
+    pub chrom: String,
+    /// Chromosome size from the chain headers
+    pub size: u64,
+    /// Bases covered by at least one block
+    pub covered_bases: u64,
+}
+
+/// Number of bins in `ChainStatistics::coverage_histogram`
+pub const COVERAGE_HISTOGRAM_BINS: usize = 10;
+
+/// Whole-index summary used to judge chain file quality
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainStatistics {
+    /// Number of chains (headers) in the file
+    pub chain_count: usize,
+    /// Number of ungapped alignment blocks
+    pub block_count: usize,
+    /// Chain header scores
+    pub scores: Distribution,
+    /// Block sizes in bases
+    pub block_sizes: Distribution,
+    /// Source-side coverage, sorted by chromosome name
+    pub source_coverage: Vec<ChromCoverage>,
+    /// Target-side coverage, sorted by chromosome name
+    pub target_coverage: Vec<ChromCoverage>,
+    /// Source chromosomes per coverage fraction bin: `[0, 10%)`, ...,
+    /// `[90%, 100%]`
+    pub coverage_histogram: [usize; COVERAGE_HISTOGRAM_BINS],
+}
+
+impl ChainStatistics {
+    /// Render as a single JSON object
+    pub fn to_json(&self) -> String {
+        let distribution = |d: &Distribution| {
+            format!("{{\"min\":{},\"max\":{},\"mean\":{:.2},\"median\":{:.1}}}", d.min, d.max, d.mean, d.median)
+        };
+        let coverage = |c: &[ChromCoverage]| {
+            let items: Vec<String> = c
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{{\"chrom\":{},\"size\":{},\"covered_bases\":{}}}",
+                        json_string(&c.chrom), c.size, c.covered_bases
+                    )
+                })
+                .collect();
+            format!("[{}]", items.join(","))
+        };
+        let histogram: Vec<String> = self.coverage_histogram.iter().map(|n| n.to_string()).collect();
+        
+        format!(
+            "{{\"chain_count\":{},\"block_count\":{},\"scores\":{},\"block_sizes\":{},\"source_coverage\":{},\"target_coverage\":{},\"coverage_histogram\":[{}]}}",
+            self.chain_count,
+            self.block_count,
+            distribution(&self.scores),
+            distribution(&self.block_sizes),
+            coverage(&self.source_coverage),
+            coverage(&self.target_coverage),
+            histogram.join(",")
+        )
+    }
+}
+
+/// Compact overview of a chain file: extent on both assemblies and block
+/// score range
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainSummary {
+    /// Number of ungapped alignment blocks
+    pub block_count: usize,
+    /// Source chromosomes with at least one block, sorted
+    pub source_chroms: Vec<String>,
+    /// Target chromosomes with at least one block, sorted
+    pub target_chroms: Vec<String>,
+    /// Source bases covered by at least one block, over all chromosomes
+    pub total_source_covered: u64,
+    /// Target bases covered by at least one block, over all chromosomes
+    pub total_target_covered: u64,
+    /// Lowest block score (the score of the block's chain; 0 without blocks)
+    pub min_score: u64,
+    /// Highest block score
+    pub max_score: u64,
+    /// Mean block score
+    pub mean_score: f64,
+}
+
+impl ChainSummary {
+    /// Render as a single JSON object
+    pub fn to_json(&self) -> String {
+        let chroms = |c: &[String]| c.iter().map(|c| json_string(c)).collect::<Vec<_>>().join(",");
+        format!(
+            "{{\"block_count\":{},\"source_chroms\":[{}],\"target_chroms\":[{}],\"total_source_covered\":{},\"total_target_covered\":{},\"min_score\":{},\"max_score\":{},\"mean_score\":{:.2}}}",
+            self.block_count,
+            chroms(&self.source_chroms),
+            chroms(&self.target_chroms),
+            self.total_source_covered,
+            self.total_target_covered,
+            self.min_score,
+            self.max_score,
+            self.mean_score
+        )
+    }
+}
+
+/// Quote a string for JSON output
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Options for building a `ChainIndex`
+#[derive(Debug, Clone, Default)]
+pub struct ChainIndexOptions {
+    /// Drop duplicate blocks with `ChainFile::dedup_blocks` before indexing
+    pub dedup_blocks: bool,
+}
+
 /// Interval index organized by source chromosome
 /// 
 /// Provides O(log n + k) interval queries where n is the number of
 /// intervals and k is the number of overlapping results.
+/// 
+/// With the `serde` feature the index can be cached in a binary file with
+/// [`save_to_file`](Self::save_to_file) and
+/// [`load_from_file`](Self::load_from_file).
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChainIndex {
-    /// Source chromosome -> interval tree (using Lapper)
-    maps: HashMap<String, Lapper<u64, IntervalValue>>,
+    /// Source chromosome -> interval store (Lapper, or sorted Vec with `flat-index`)
+    maps: HashMap<String, IntervalStore>,
     /// Target chromosome sizes
     pub target_sizes: HashMap<String, u64>,
     /// Source chromosome sizes
     pub source_sizes: HashMap<String, u64>,
-    /// Normalized chromosome name mapping (lowercase -> original)
+    /// Secondary index: `normalize_chrom` key (case-insensitive, no `chr`
+    /// prefix, M == MT) -> chromosome name as written in the chain file
     chrom_aliases: HashMap<String, String>,
+    /// Target chromosome -> the same blocks keyed by target coordinates,
+    /// with source and target swapped in the payload (see [`inverse_maps`])
+    target_maps: HashMap<String, IntervalStore>,
+    /// `normalize_chrom` key -> target chromosome name
+    target_aliases: HashMap<String, String>,
+    /// Chain header scores, in file order
+    chain_scores: Vec<u64>,
+    /// Duplicate blocks removed while building (see `ChainIndexOptions`)
+    removed_duplicates: usize,
 }
 
 
 impl ChainIndex {
     /// Build index from a chain file
     /// 
-    /// Automatically handles gzip and bzip2 compression.
+    /// Automatically handles gzip, bzip2 and xz compression.
     /// 
     /// # Example
     /// ```ignore
@@ -58,49 +263,57 @@ impl ChainIndex {
     
     /// Build index from parsed chain data
     pub fn from_chain_data(chain_file: ChainFile) -> Self {
+        Self::from_chain_data_with_options(chain_file, &ChainIndexOptions::default())
+    }
+    
+    /// Build index from parsed chain data with extended options
+    pub fn from_chain_data_with_options(mut chain_file: ChainFile, options: &ChainIndexOptions) -> Self {
+        if options.dedup_blocks {
+            chain_file.dedup_blocks();
+        }
+        
         // Group blocks by source chromosome
         let mut blocks_by_chrom: HashMap<String, Vec<ChainInterval>> = HashMap::new();
         
         for block in chain_file.blocks {
-            let interval = Interval {
-                start: block.source_start,
-                stop: block.source_end,
-                val: IntervalValue {
-                    target_chrom: block.target_chrom,
-                    target_start: block.target_start,
-                    target_end: block.target_end,
-                    target_strand: block.target_strand,
-                    source_chrom: block.source_chrom.clone(),
-                },
-            };
-            
+            let chrom = block.source_chrom.clone();
             blocks_by_chrom
-                .entry(block.source_chrom)
+                .entry(chrom)
                 .or_default()
-                .push(interval);
+                .push(block_interval(block));
         }
         
-        // Build Lapper for each chromosome
+        // Build the interval store for each chromosome
         let mut maps = HashMap::new();
         let mut chrom_aliases = HashMap::new();
         
         for (chrom, intervals) in blocks_by_chrom {
             // Store chromosome aliases for flexible lookup
-            let normalized = normalize_chrom_key(&chrom);
+            let normalized = normalize_chrom(&chrom);
             chrom_aliases.insert(normalized, chrom.clone());
             
             // Build the interval tree
-            maps.insert(chrom, Lapper::new(intervals));
+            maps.insert(chrom, IntervalStore::new(intervals));
         }
+        let (target_maps, target_aliases) = inverse_maps(&maps);
         
         Self {
             maps,
             target_sizes: chain_file.target_chrom_sizes,
             source_sizes: chain_file.source_chrom_sizes,
             chrom_aliases,
+            target_maps,
+            target_aliases,
+            chain_scores: chain_file.chain_scores,
+            removed_duplicates: chain_file.removed_duplicates,
         }
     }
     
+    /// Number of duplicate blocks removed from the chain data
+    pub fn removed_duplicates(&self) -> usize {
+        self.removed_duplicates
+    }
+    
     /// Query intervals overlapping the given range
     /// 
     /// Automatically handles chromosome name variants (chr1, 1, CHR1).
@@ -115,45 +328,107 @@ impl ChainIndex {
     }
     
     /// Query intervals and return full Interval structs
+    /// 
+    /// The intervals are sorted by `(start, stop)` ascending, so callers
+    /// can walk them left to right without re-sorting.
     pub fn query_intervals(&self, chrom: &str, start: u64, end: u64) -> Vec<&ChainInterval> {
-        let lapper = self.find_lapper(chrom);
-        
-        match lapper {
-            Some(l) => l.find(start, end).collect(),
+        match self.find_lapper(chrom) {
+            Some(l) => query_store(l, start, end),
             None => vec![],
         }
     }
     
-    /// Find the Lapper for a chromosome, trying different naming styles
-    fn find_lapper(&self, chrom: &str) -> Option<&Lapper<u64, IntervalValue>> {
-        // Try exact match first
-        if let Some(l) = self.maps.get(chrom) {
-            return Some(l);
+    /// Interval store of a chromosome, resolved once for a run of queries
+    /// with [`query_store`] (see `CoordinateMapper::map_batch`)
+    pub(crate) fn chrom_store(&self, chrom: &str) -> Option<&IntervalStore> {
+        self.find_lapper(chrom)
+    }
+    
+    /// All intervals of a chromosome, sorted by `(start, stop)`
+    /// 
+    /// Both interval stores keep their intervals sorted from construction,
+    /// so this borrows the store directly. Chain blocks on one chromosome
+    /// rarely overlap, which makes `partition_point` on the start a cheap
+    /// point lookup. Unknown chromosomes give an empty slice.
+    pub fn sorted_intervals(&self, chrom: &str) -> &[ChainInterval] {
+        match self.find_lapper(chrom) {
+            #[cfg(not(feature = "flat-index"))]
+            Some(l) => &l.intervals,
+            #[cfg(feature = "flat-index")]
+            Some(l) => l.as_slice(),
+            None => &[],
         }
-        
-        // Try normalized lookup
-        let normalized = normalize_chrom_key(chrom);
-        if let Some(original) = self.chrom_aliases.get(&normalized) {
-            return self.maps.get(original);
+    }
+    
+    /// Query blocks by target coordinates, for lifting target positions
+    /// back to the source assembly
+    /// 
+    /// In the returned intervals `start`/`stop` are the target range and the
+    /// payload's `target_*` fields hold the source block, so they can be fed
+    /// to the same offset arithmetic as [`query_intervals`](Self::query_intervals).
+    /// Sorted by `(start, stop)`.
+    pub fn query_target_intervals(&self, chrom: &str, start: u64, end: u64) -> Vec<&ChainInterval> {
+        let mut intervals: Vec<&ChainInterval> = match find_store(&self.target_maps, &self.target_aliases, chrom) {
+            Some(l) => l.find(start, end).collect(),
+            None => vec![],
+        };
+        intervals.sort_by_key(|iv| (iv.start, iv.stop));
+        intervals
+    }
+    
+    /// Check if a target chromosome exists (same name matching as `has_chrom`)
+    pub fn has_target_chrom(&self, chrom: &str) -> bool {
+        find_store(&self.target_maps, &self.target_aliases, chrom).is_some()
+    }
+    
+    /// Swap source and target, giving an index that lifts target
+    /// coordinates back to the source assembly
+    pub fn into_inverse(self) -> Self {
+        Self {
+            maps: self.target_maps,
+            target_sizes: self.source_sizes,
+            source_sizes: self.target_sizes,
+            chrom_aliases: self.target_aliases,
+            target_maps: self.maps,
+            target_aliases: self.chrom_aliases,
+            chain_scores: self.chain_scores,
+            removed_duplicates: self.removed_duplicates,
         }
+    }
+    
+    /// Copy of the index keeping only blocks whose chain score is at least
+    /// `min_score`
+    /// 
+    /// Chromosomes left without blocks are dropped, so queries on them
+    /// behave like queries on a chromosome the chain file never mentions.
+    /// Chromosome sizes are kept.
+    pub fn filter_by_score(&self, min_score: u64) -> ChainIndex {
+        let maps: HashMap<String, IntervalStore> = self
+            .maps
+            .iter()
+            .filter_map(|(chrom, store)| {
+                let kept: Vec<ChainInterval> = store.iter().filter(|iv| iv.val.score >= min_score).cloned().collect();
+                (!kept.is_empty()).then(|| (chrom.clone(), IntervalStore::new(kept)))
+            })
+            .collect();
+        let chrom_aliases = maps.keys().map(|chrom| (normalize_chrom(chrom), chrom.clone())).collect();
+        let (target_maps, target_aliases) = inverse_maps(&maps);
         
-        // Try common variants
-        let variants = [
-            chrom.to_string(),
-            chrom.replace("chr", ""),
-            chrom.replace("Chr", ""),
-            chrom.replace("CHR", ""),
-            format!("chr{}", chrom),
-            format!("Chr{}", chrom),
-        ];
-        
-        for variant in &variants {
-            if let Some(l) = self.maps.get(variant) {
-                return Some(l);
-            }
+        Self {
+            maps,
+            target_sizes: self.target_sizes.clone(),
+            source_sizes: self.source_sizes.clone(),
+            chrom_aliases,
+            target_maps,
+            target_aliases,
+            chain_scores: self.chain_scores.iter().copied().filter(|&score| score >= min_score).collect(),
+            removed_duplicates: self.removed_duplicates,
         }
-        
-        None
+    }
+    
+    /// Find the interval store for a chromosome, trying different naming styles
+    fn find_lapper(&self, chrom: &str) -> Option<&IntervalStore> {
+        find_store(&self.maps, &self.chrom_aliases, chrom)
     }
     
     /// Get the canonical chromosome name used in the index
@@ -162,11 +437,14 @@ impl ChainIndex {
             return self.maps.keys().find(|k| *k == chrom).map(|s| s.as_str());
         }
         
-        let normalized = normalize_chrom_key(chrom);
+        let normalized = normalize_chrom(chrom);
         self.chrom_aliases.get(&normalized).map(|s| s.as_str())
     }
     
     /// Check if a chromosome exists in the index
+    /// 
+    /// Falls back to the normalized name when there is no exact match, so
+    /// `CHR1`, `Chr1` and `1` all find an index built from `chr1`.
     pub fn has_chrom(&self, chrom: &str) -> bool {
         self.find_lapper(chrom).is_some()
     }
@@ -176,6 +454,18 @@ impl ChainIndex {
         self.maps.keys().map(|s| s.as_str())
     }
     
+    /// Source chromosomes with at least one indexed block, as written in
+    /// the chain file (unordered)
+    pub fn chromosomes_source(&self) -> impl Iterator<Item = &str> {
+        self.source_chroms()
+    }
+    
+    /// Target chromosomes named in the chain headers, as written in the
+    /// chain file (unordered)
+    pub fn chromosomes_target(&self) -> impl Iterator<Item = &str> {
+        self.target_sizes.keys().map(|s| s.as_str())
+    }
+    
     /// Get the number of intervals for a chromosome
     pub fn interval_count(&self, chrom: &str) -> usize {
         self.find_lapper(chrom).map(|l| l.len()).unwrap_or(0)
@@ -204,20 +494,591 @@ impl ChainIndex {
             .or_else(|| self.source_sizes.get(&chrom.replace("chr", "")).copied())
             .or_else(|| self.source_sizes.get(&format!("chr{}", chrom)).copied())
     }
+    
+    /// Compute block coverage statistics for a source chromosome
+    /// 
+    /// Blocks are walked in start order; overlapping blocks (from different
+    /// chains) are only counted once in `covered_bases`.
+    /// Returns None if the chromosome is not in the index.
+    pub fn overlap_stats(&self, chrom: &str) -> Option<ChromOverlapStats> {
+        let lapper = self.find_lapper(chrom)?;
+        
+        let mut max_block_size: u64 = 0;
+        let mut sum_block_size: u64 = 0;
+        let mut block_count: usize = 0;
+        
+        for iv in lapper.iter() {
+            let size = iv.stop - iv.start;
+            max_block_size = max_block_size.max(size);
+            sum_block_size += size;
+            block_count += 1;
+        }
+        let covered_bases = merged_length(lapper.iter().map(|iv| (iv.start, iv.stop)).collect());
+        
+        let canonical = self.get_canonical_chrom(chrom).unwrap_or(chrom);
+        let total_bases = self.source_chrom_size(canonical).unwrap_or(0);
+        let coverage_fraction = if total_bases > 0 {
+            covered_bases as f64 / total_bases as f64
+        } else {
+            0.0
+        };
+        let mean_block_size = if block_count > 0 {
+            sum_block_size as f64 / block_count as f64
+        } else {
+            0.0
+        };
+        
+        Some(ChromOverlapStats {
+            total_bases,
+            covered_bases,
+            coverage_fraction,
+            max_block_size,
+            mean_block_size,
+            block_count,
+        })
+    }
+    
+    /// Summarize the whole index: block and score distributions and
+    /// per-chromosome coverage on both sides
+    pub fn statistics(&self) -> ChainStatistics {
+        let mut block_sizes = Vec::with_capacity(self.total_intervals());
+        let mut target_blocks: HashMap<&str, Vec<(u64, u64)>> = HashMap::new();
+        let mut source_coverage = Vec::with_capacity(self.maps.len());
+        let mut coverage_histogram = [0; COVERAGE_HISTOGRAM_BINS];
+        
+        for (chrom, lapper) in &self.maps {
+            for iv in lapper.iter() {
+                block_sizes.push(iv.stop - iv.start);
+                target_blocks
+                    .entry(iv.val.target_chrom.as_str())
+                    .or_default()
+                    .push((iv.val.target_start, iv.val.target_end));
+            }
+            
+            let size = self.source_sizes.get(chrom).copied().unwrap_or(0);
+            let covered_bases = merged_length(lapper.iter().map(|iv| (iv.start, iv.stop)).collect());
+            if size > 0 {
+                let fraction = covered_bases as f64 / size as f64;
+                let bin = ((fraction * COVERAGE_HISTOGRAM_BINS as f64) as usize).min(COVERAGE_HISTOGRAM_BINS - 1);
+                coverage_histogram[bin] += 1;
+            }
+            source_coverage.push(ChromCoverage { chrom: chrom.clone(), size, covered_bases });
+        }
+        
+        let mut target_coverage: Vec<ChromCoverage> = target_blocks
+            .into_iter()
+            .map(|(chrom, blocks)| ChromCoverage {
+                chrom: chrom.to_string(),
+                size: self.target_sizes.get(chrom).copied().unwrap_or(0),
+                covered_bases: merged_length(blocks),
+            })
+            .collect();
+        source_coverage.sort_by(|a, b| a.chrom.cmp(&b.chrom));
+        target_coverage.sort_by(|a, b| a.chrom.cmp(&b.chrom));
+        
+        ChainStatistics {
+            chain_count: self.chain_scores.len(),
+            block_count: block_sizes.len(),
+            scores: Distribution::from_values(self.chain_scores.clone()),
+            block_sizes: Distribution::from_values(block_sizes),
+            source_coverage,
+            target_coverage,
+            coverage_histogram,
+        }
+    }
+    
+    /// Block count, covered bases on both sides and block score range
+    /// 
+    /// Scores are counted per block, so a chain with many blocks weighs
+    /// more in `mean_score` than in `statistics().scores`.
+    pub fn summary(&self) -> ChainSummary {
+        let mut target_blocks: HashMap<&str, Vec<(u64, u64)>> = HashMap::new();
+        let mut source_chroms = Vec::with_capacity(self.maps.len());
+        let mut total_source_covered = 0;
+        let mut block_count = 0;
+        let mut score_sum = 0.0;
+        let (mut min_score, mut max_score) = (u64::MAX, 0);
+        
+        for (chrom, lapper) in &self.maps {
+            for iv in lapper.iter() {
+                block_count += 1;
+                score_sum += iv.val.score as f64;
+                min_score = min_score.min(iv.val.score);
+                max_score = max_score.max(iv.val.score);
+                target_blocks
+                    .entry(iv.val.target_chrom.as_str())
+                    .or_default()
+                    .push((iv.val.target_start, iv.val.target_end));
+            }
+            total_source_covered += merged_length(lapper.iter().map(|iv| (iv.start, iv.stop)).collect());
+            source_chroms.push(chrom.clone());
+        }
+        
+        let mut target_chroms: Vec<String> = target_blocks.keys().map(|c| c.to_string()).collect();
+        source_chroms.sort_unstable();
+        target_chroms.sort_unstable();
+        
+        ChainSummary {
+            block_count,
+            source_chroms,
+            target_chroms,
+            total_source_covered,
+            total_target_covered: target_blocks.into_values().map(merged_length).sum(),
+            min_score: if block_count == 0 { 0 } else { min_score },
+            max_score,
+            mean_score: if block_count == 0 { 0.0 } else { score_sum / block_count as f64 },
+        }
+    }
+    
+    /// Histogram of block scores (the score of each block's chain)
+    /// 
+    /// Splits `[min_score, max_score]` into `bins` equal-width buckets and
+    /// returns `(lower_bound, block_count)` per bucket, so the counts sum to
+    /// `total_intervals()`. Useful for picking a minimum chain score.
+    /// Returns an empty vector when the index has no blocks or `bins` is 0.
+    pub fn score_histogram(&self, bins: usize) -> Vec<(u64, usize)> {
+        let scores = self.maps.values().flat_map(|l| l.iter().map(|iv| iv.val.score));
+        equal_width_histogram(scores.collect(), bins)
+    }
+    
+    /// Histogram of block sizes, bucketed like [`score_histogram`](Self::score_histogram)
+    pub fn block_size_histogram(&self, bins: usize) -> Vec<(u64, usize)> {
+        let sizes = self.maps.values().flat_map(|l| l.iter().map(|iv| iv.stop - iv.start));
+        equal_width_histogram(sizes.collect(), bins)
+    }
+    
+    /// Render block coverage of a source chromosome as an ASCII bar
+    /// 
+    /// The chromosome is split into `width` bins; a bin is `#` if any block
+    /// overlaps it and `.` otherwise. Unknown chromosomes render as all gaps.
+    pub fn visualize_chrom(&self, chrom: &str, width: usize) -> String {
+        let Some(lapper) = self.find_lapper(chrom) else {
+            return ".".repeat(width);
+        };
+        let canonical = self.get_canonical_chrom(chrom).unwrap_or(chrom);
+        let blocks = lapper.iter().map(|iv| (iv.start, iv.stop));
+        
+        render_coverage(self.source_chrom_size(canonical), blocks, width)
+    }
+    
+    /// Render block coverage of a target chromosome as an ASCII bar
+    /// 
+    /// Same layout as `visualize_chrom`, using the target side of each block.
+    pub fn visualize_target(&self, chrom: &str, width: usize) -> String {
+        let blocks = self
+            .maps
+            .values()
+            .flat_map(|lapper| lapper.iter())
+            .filter(|iv| iv.val.target_chrom == chrom)
+            .map(|iv| (iv.val.target_start, iv.val.target_end));
+        
+        render_coverage(self.target_chrom_size(chrom), blocks, width)
+    }
+    
+    /// Write the source side of every block on `chrom` as BED3 records
+    /// 
+    /// Records are sorted by start and named with the chromosome name used
+    /// in the chain file. Nothing is written for unknown chromosomes.
+    pub fn to_bed(&self, chrom: &str, writer: &mut impl Write) -> io::Result<()> {
+        let Some(lapper) = self.find_lapper(chrom) else {
+            return Ok(());
+        };
+        for iv in lapper.iter() {
+            writeln!(writer, "{}\t{}\t{}", iv.val.source_chrom, iv.start, iv.stop)?;
+        }
+        Ok(())
+    }
+    
+    /// Write the target side of every block landing on `chrom` as BED3 records
+    /// 
+    /// Same layout as `to_bed`; negative-strand blocks are written in
+    /// forward target coordinates.
+    pub fn to_target_bed(&self, chrom: &str, writer: &mut impl Write) -> io::Result<()> {
+        let mut blocks: Vec<(u64, u64)> = self
+            .maps
+            .values()
+            .flat_map(|lapper| lapper.iter())
+            .filter(|iv| iv.val.target_chrom == chrom)
+            .map(|iv| (iv.val.target_start, iv.val.target_end))
+            .collect();
+        blocks.sort_unstable();
+        for (start, end) in blocks {
+            writeln!(writer, "{}\t{}\t{}", chrom, start, end)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ChainIndex {
+    /// Write the index to a bincode cache file
+    /// 
+    /// Loading the cache skips chain parsing and interval tree building,
+    /// which dominate start-up time for large chain files.
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(std::fs::File::create(path)?);
+        bincode::serialize_into(&mut writer, self).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        writer.flush()
+    }
+    
+    /// Read an index written by [`save_to_file`](Self::save_to_file)
+    /// 
+    /// The cache format is tied to the build: a cache written with or
+    /// without the `flat-index` feature only loads in the same configuration.
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let reader = io::BufReader::new(std::fs::File::open(path)?);
+        bincode::deserialize_from(reader).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// Default number of blocks [`ChainIndexBuilder`] buffers per chromosome
+/// before merging them into the chromosome's interval store
+pub const DEFAULT_MERGE_THRESHOLD: usize = 4096;
+
+/// Incremental `ChainIndex` construction from blocks in source order
+/// 
+/// Blocks of the chromosome currently receiving them are buffered; once
+/// the buffer reaches the merge threshold, or a block for another
+/// chromosome arrives, the buffered blocks are merged into that
+/// chromosome's interval store. Blocks added in order only extend the
+/// store's sorted intervals, so apart from the index being built the
+/// builder holds at most one threshold's worth of blocks, instead of the
+/// whole block list that `ChainIndex::from_chain_data` groups at the end.
+/// Each merge rebuilds the chromosome's store, so a smaller threshold
+/// trades build time for buffer memory.
+/// 
+/// Input that breaks the ordering is merged rather than rejected: a block
+/// starting before the previous one is inserted at its sorted position, and
+/// a block for an already merged chromosome is merged into its store.
+pub struct ChainIndexBuilder {
+    /// Merged chromosomes
+    maps: HashMap<String, IntervalStore>,
+    /// Chromosome receiving blocks, with its buffered intervals sorted by `(start, stop)`
+    open: Option<(String, Vec<ChainInterval>)>,
+    /// Buffered blocks that trigger a merge
+    merge_threshold: usize,
+    target_sizes: HashMap<String, u64>,
+    source_sizes: HashMap<String, u64>,
+    chain_scores: Vec<u64>,
+}
+
+impl Default for ChainIndexBuilder {
+    fn default() -> Self {
+        Self {
+            maps: HashMap::new(),
+            open: None,
+            merge_threshold: DEFAULT_MERGE_THRESHOLD,
+            target_sizes: HashMap::new(),
+            source_sizes: HashMap::new(),
+            chain_scores: Vec::new(),
+        }
+    }
+}
+
+impl ChainIndexBuilder {
+    /// Empty builder
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Empty builder merging every `threshold` buffered blocks (at least 1)
+    pub fn with_merge_threshold(threshold: usize) -> Self {
+        Self {
+            merge_threshold: threshold.max(1),
+            ..Self::default()
+        }
+    }
+    
+    /// Add a block, expected in source coordinate order per chromosome
+    pub fn add_block_sorted(&mut self, block: ChainBlock) {
+        if self.open.as_ref().is_none_or(|(chrom, _)| *chrom != block.source_chrom) {
+            self.merge_pending();
+            self.open = Some((block.source_chrom.clone(), Vec::new()));
+        }
+        
+        let Some((_, pending)) = self.open.as_mut() else {
+            unreachable!("a chromosome was opened above");
+        };
+        let interval = block_interval(block);
+        let key = (interval.start, interval.stop);
+        if pending.last().is_none_or(|last| (last.start, last.stop) <= key) {
+            pending.push(interval);
+        } else {
+            let at = pending.partition_point(|iv| (iv.start, iv.stop) <= key);
+            pending.insert(at, interval);
+        }
+        if pending.len() >= self.merge_threshold {
+            self.merge_pending();
+        }
+    }
+    
+    /// Record the size of a source chromosome
+    pub fn set_source_size(&mut self, chrom: &str, size: u64) {
+        self.source_sizes.insert(chrom.to_string(), size);
+    }
+    
+    /// Record the size of a target chromosome
+    pub fn set_target_size(&mut self, chrom: &str, size: u64) {
+        self.target_sizes.insert(chrom.to_string(), size);
+    }
+    
+    /// Record the header score of a chain (used by `ChainIndex::statistics`)
+    pub fn add_chain_score(&mut self, score: u64) {
+        self.chain_scores.push(score);
+    }
+    
+    /// Number of buffered blocks not yet merged into an interval store
+    /// 
+    /// Always below the merge threshold between calls to `add_block_sorted`.
+    pub fn pending_blocks(&self) -> usize {
+        self.open.as_ref().map_or(0, |(_, pending)| pending.len())
+    }
+    
+    /// Merge the remaining blocks and build the index
+    pub fn build(mut self) -> ChainIndex {
+        self.merge_pending();
+        let chrom_aliases = self
+            .maps
+            .keys()
+            .map(|chrom| (normalize_chrom(chrom), chrom.clone()))
+            .collect();
+        let (target_maps, target_aliases) = inverse_maps(&self.maps);
+        
+        ChainIndex {
+            maps: self.maps,
+            target_sizes: self.target_sizes,
+            source_sizes: self.source_sizes,
+            chrom_aliases,
+            target_maps,
+            target_aliases,
+            chain_scores: self.chain_scores,
+            removed_duplicates: 0,
+        }
+    }
+    
+    /// Merge the buffered blocks into the open chromosome's interval store
+    fn merge_pending(&mut self) {
+        let Some((chrom, pending)) = self.open.as_mut() else {
+            return;
+        };
+        if pending.is_empty() {
+            return;
+        }
+        let pending = std::mem::take(pending);
+        let intervals = match self.maps.remove(chrom.as_str()) {
+            Some(store) => merge_sorted_intervals(store_into_intervals(store), pending),
+            None => pending,
+        };
+        self.maps.insert(chrom.clone(), IntervalStore::new(intervals));
+    }
+}
+
+/// Merge two interval lists sorted by `(start, stop)`, keeping `existing`
+/// first among equal keys
+fn merge_sorted_intervals(mut existing: Vec<ChainInterval>, pending: Vec<ChainInterval>) -> Vec<ChainInterval> {
+    let key = |iv: &ChainInterval| (iv.start, iv.stop);
+    // In-order input only appends
+    if existing.last().zip(pending.first()).is_none_or(|(last, first)| key(last) <= key(first)) {
+        existing.extend(pending);
+        return existing;
+    }
+    
+    let mut merged = Vec::with_capacity(existing.len() + pending.len());
+    let mut pending = pending.into_iter().peekable();
+    for interval in existing {
+        while let Some(next) = pending.next_if(|next| key(next) < key(&interval)) {
+            merged.push(next);
+        }
+        merged.push(interval);
+    }
+    merged.extend(pending);
+    merged
 }
 
-/// Normalize chromosome name for flexible matching
+/// Find a chromosome's store, trying the exact name, the `normalize_chrom`
+/// alias and common `chr` prefix variants
+fn find_store<'a>(
+    maps: &'a HashMap<String, IntervalStore>,
+    aliases: &HashMap<String, String>,
+    chrom: &str,
+) -> Option<&'a IntervalStore> {
+    // Try exact match first
+    if let Some(l) = maps.get(chrom) {
+        return Some(l);
+    }
+    
+    // Try normalized lookup
+    let normalized = normalize_chrom(chrom);
+    if let Some(original) = aliases.get(&normalized) {
+        return maps.get(original);
+    }
+    
+    // Try common variants
+    let variants = [
+        chrom.to_string(),
+        chrom.replace("chr", ""),
+        chrom.replace("Chr", ""),
+        chrom.replace("CHR", ""),
+        format!("chr{}", chrom),
+        format!("Chr{}", chrom),
+    ];
+    
+    variants.iter().find_map(|variant| maps.get(variant))
+}
+
+/// Intervals of `store` overlapping `[start, end)`, sorted by `(start, stop)`
+pub(crate) fn query_store(store: &IntervalStore, start: u64, end: u64) -> Vec<&ChainInterval> {
+    let mut intervals: Vec<&ChainInterval> = store.find(start, end).collect();
+    // Both stores already yield this order; the stable sort is a single
+    // pass over sorted input
+    intervals.sort_by_key(|iv| (iv.start, iv.stop));
+    intervals
+}
+
+/// Re-key every block by its target range, with source and target swapped
+/// in the payload
 /// 
-/// Converts to lowercase and removes common prefixes.
-fn normalize_chrom_key(chrom: &str) -> String {
-    let lower = chrom.to_lowercase();
-    if lower.starts_with("chr") {
-        lower[3..].to_string()
-    } else {
-        lower
+/// A block keeps its strand: a minus-strand block maps the target range
+/// back onto the source range reversed, which is the same offset
+/// arithmetic as the forward direction. Returns the stores per target
+/// chromosome and their `normalize_chrom` aliases.
+fn inverse_maps(maps: &HashMap<String, IntervalStore>) -> (HashMap<String, IntervalStore>, HashMap<String, String>) {
+    let mut by_target: HashMap<String, Vec<ChainInterval>> = HashMap::new();
+    for (source_chrom, store) in maps {
+        for iv in store.iter() {
+            by_target.entry(iv.val.target_chrom.clone()).or_default().push(Interval {
+                start: iv.val.target_start,
+                stop: iv.val.target_end,
+                val: IntervalValue {
+                    target_chrom: source_chrom.clone(),
+                    target_start: iv.start,
+                    target_end: iv.stop,
+                    target_strand: iv.val.target_strand,
+                    source_chrom: iv.val.target_chrom.clone(),
+                    score: iv.val.score,
+                },
+            });
+        }
     }
+    
+    let aliases = by_target.keys().map(|chrom| (normalize_chrom(chrom), chrom.clone())).collect();
+    let stores = by_target
+        .into_iter()
+        .map(|(chrom, mut intervals)| {
+            // Blocks from different source chromosomes may share a target
+            // range; a full sort keeps their order independent of hashing
+            intervals.sort_by(|a, b| {
+                (a.start, a.stop, &a.val.target_chrom, a.val.target_start)
+                    .cmp(&(b.start, b.stop, &b.val.target_chrom, b.val.target_start))
+            });
+            (chrom, IntervalStore::new(intervals))
+        })
+        .collect();
+    (stores, aliases)
 }
 
+/// Interval for a chain block, keyed by its source range
+fn block_interval(block: ChainBlock) -> ChainInterval {
+    Interval {
+        start: block.source_start,
+        stop: block.source_end,
+        val: IntervalValue {
+            target_chrom: block.target_chrom,
+            target_start: block.target_start,
+            target_end: block.target_end,
+            target_strand: block.target_strand,
+            source_chrom: block.source_chrom,
+            score: block.score,
+        },
+    }
+}
+
+/// Take the sorted intervals back out of a store
+#[cfg(not(feature = "flat-index"))]
+fn store_into_intervals(store: IntervalStore) -> Vec<ChainInterval> {
+    store.intervals
+}
+
+/// Take the sorted intervals back out of a store
+#[cfg(feature = "flat-index")]
+fn store_into_intervals(store: IntervalStore) -> Vec<ChainInterval> {
+    store.into_intervals()
+}
+
+/// Total length of the union of `[start, end)` intervals
+fn merged_length(mut blocks: Vec<(u64, u64)>) -> u64 {
+    blocks.sort_unstable();
+    let mut total = 0;
+    let mut run: Option<(u64, u64)> = None;
+    for (start, end) in blocks {
+        run = match run {
+            Some((run_start, run_end)) if start <= run_end => Some((run_start, run_end.max(end))),
+            Some((run_start, run_end)) => {
+                total += run_end - run_start;
+                Some((start, end))
+            }
+            None => Some((start, end)),
+        };
+    }
+    total + run.map_or(0, |(start, end)| end - start)
+}
+
+/// Count `values` into `bins` equal-width buckets spanning `[min, max]`
+/// 
+/// The width is rounded up so every value lands in a bucket; each entry is
+/// the bucket's lower bound and its count.
+fn equal_width_histogram(values: Vec<u64>, bins: usize) -> Vec<(u64, usize)> {
+    let (Some(&min), Some(&max)) = (values.iter().min(), values.iter().max()) else {
+        return Vec::new();
+    };
+    if bins == 0 {
+        return Vec::new();
+    }
+    let span = (max - min) as u128 + 1;
+    let width = span.div_ceil(bins as u128).max(1);
+    let mut counts = vec![0usize; bins];
+    for v in values {
+        counts[((v - min) as u128 / width) as usize] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| ((min as u128 + i as u128 * width).min(u64::MAX as u128) as u64, count))
+        .collect()
+}
+
+/// Draw `width` bins over `[0, size)`, marking bins overlapped by a block
+/// 
+/// When the chromosome size is unknown the furthest block end is used.
+fn render_coverage<I: Iterator<Item = (u64, u64)>>(size: Option<u64>, blocks: I, width: usize) -> String {
+    let blocks: Vec<(u64, u64)> = blocks.filter(|(start, end)| end > start).collect();
+    let size = size
+        .filter(|&s| s > 0)
+        .or_else(|| blocks.iter().map(|&(_, end)| end).max())
+        .unwrap_or(0);
+    
+    let mut bins = vec![b'.'; width];
+    if size == 0 || width == 0 {
+        return String::from_utf8(bins).unwrap();
+    }
+    
+    // Bin range [first, last) overlapped by [start, end)
+    let (w, n) = (width as u128, size as u128);
+    for (start, end) in blocks {
+        if start >= size {
+            continue;
+        }
+        let first = (start as u128 * w / n) as usize;
+        let last = ((end.min(size) as u128 * w).div_ceil(n) as usize).max(first + 1);
+        for bin in &mut bins[first..last] {
+            *bin = b'#';
+        }
+    }
+    
+    String::from_utf8(bins).unwrap()
+}
+
+
 
 #[cfg(test)]
 mod tests {
@@ -250,6 +1111,26 @@ chain 500 chr2 2000 + 0 200 chr2 2000 + 0 200 2
         assert_eq!(index.total_intervals(), 5); // 3 from chr1 + 2 from chr2
     }
     
+    #[test]
+    fn test_dedup_blocks_option() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 100 chr1 1000 + 0 100 1
+100
+
+chain 1000 chr1 1000 + 0 100 chr1 1000 + 0 100 2
+100
+";
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap());
+        assert_eq!(index.query("chr1", 10, 20).len(), 2);
+        assert_eq!(index.removed_duplicates(), 0);
+        
+        let options = ChainIndexOptions { dedup_blocks: true };
+        let index = ChainIndex::from_chain_data_with_options(parse_chain_bytes(chain_data).unwrap(), &options);
+        assert_eq!(index.query("chr1", 10, 20).len(), 1);
+        assert_eq!(index.total_intervals(), 1);
+        assert_eq!(index.removed_duplicates(), 1);
+    }
+    
     #[test]
     fn test_query_basic() {
         let index = create_test_index();
@@ -295,6 +1176,32 @@ chain 500 chr2 2000 + 0 200 chr2 2000 + 0 200 2
         assert_eq!(results1.len(), results2.len());
     }
     
+    #[test]
+    fn test_has_chrom_case_insensitive() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 100 chr1 1000 + 0 100 1
+100
+
+chain 500 chrM 16569 + 0 100 chrM 16569 + 0 100 2
+100
+";
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap());
+        
+        assert!(index.has_chrom("CHR1"));
+        assert!(index.has_chrom("cHr1"));
+        assert_eq!(index.query_intervals("CHR1", 10, 20).len(), 1);
+        assert_eq!(index.query_intervals("CHR1", 10, 20)[0].val.target_chrom, "chr1");
+        assert_eq!(index.get_canonical_chrom("CHR1"), Some("chr1"));
+        
+        // M and MT are the same mitochondrial chromosome
+        assert!(index.has_chrom("MT"));
+        assert!(index.has_chrom("chrMT"));
+        assert_eq!(index.query_intervals("MT", 10, 20).len(), 1);
+        
+        assert!(!index.has_chrom("CHR2"));
+        assert!(index.query_intervals("CHR2", 10, 20).is_empty());
+    }
+    
     #[test]
     fn test_chrom_sizes() {
         let index = create_test_index();
@@ -334,6 +1241,470 @@ chain 500 chr2 2000 + 0 200 chr2 2000 + 0 200 2
         assert_eq!(index.interval_count("chr2"), 2);
         assert_eq!(index.interval_count("chr3"), 0);
     }
+    
+    #[test]
+    fn test_overlap_stats() {
+        let index = create_test_index();
+        
+        // chr1 blocks: 100-200, 250-350, 400-500 on a 1000bp chromosome
+        let stats = index.overlap_stats("chr1").unwrap();
+        assert_eq!(stats.total_bases, 1000);
+        assert_eq!(stats.covered_bases, 300);
+        assert!((stats.coverage_fraction - 0.3).abs() < 1e-9);
+        assert_eq!(stats.max_block_size, 100);
+        assert!((stats.mean_block_size - 100.0).abs() < 1e-9);
+        assert_eq!(stats.block_count, 3);
+        
+        // chr2 blocks: 0-100, 150-200 on a 2000bp chromosome
+        let stats = index.overlap_stats("2").unwrap();
+        assert_eq!(stats.total_bases, 2000);
+        assert_eq!(stats.covered_bases, 150);
+        assert!((stats.coverage_fraction - 0.075).abs() < 1e-9);
+        assert_eq!(stats.max_block_size, 100);
+        assert!((stats.mean_block_size - 75.0).abs() < 1e-9);
+        assert_eq!(stats.block_count, 2);
+        
+        assert!(index.overlap_stats("chr3").is_none());
+    }
+    
+    #[test]
+    fn test_overlap_stats_overlapping_chains() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 100 300 chr1 1000 + 100 300 1
+200
+
+chain 500 chr1 1000 + 200 400 chr5 1000 + 0 200 2
+200
+";
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap());
+        
+        // 100-300 and 200-400 overlap: union is 100-400
+        let stats = index.overlap_stats("chr1").unwrap();
+        assert_eq!(stats.covered_bases, 300);
+        assert_eq!(stats.block_count, 2);
+        assert_eq!(stats.max_block_size, 200);
+    }
+    
+    #[test]
+    fn test_statistics() {
+        let stats = create_test_index().statistics();
+        
+        assert_eq!(stats.chain_count, 2);
+        assert_eq!(stats.block_count, 5);
+        assert_eq!(stats.scores, Distribution { min: 500, max: 1000, mean: 750.0, median: 750.0 });
+        assert_eq!(stats.block_sizes, Distribution { min: 50, max: 100, mean: 90.0, median: 100.0 });
+        
+        let chr1 = ChromCoverage { chrom: "chr1".to_string(), size: 1000, covered_bases: 300 };
+        let chr2 = ChromCoverage { chrom: "chr2".to_string(), size: 2000, covered_bases: 150 };
+        assert_eq!(stats.source_coverage, vec![chr1.clone(), chr2.clone()]);
+        assert_eq!(stats.target_coverage, vec![chr1, chr2]);
+        
+        // chr1 is 30% covered, chr2 7.5%
+        assert_eq!(stats.coverage_histogram, [1, 0, 0, 1, 0, 0, 0, 0, 0, 0]);
+        
+        let json = stats.to_json();
+        assert!(json.starts_with("{\"chain_count\":2,\"block_count\":5,\"scores\":{\"min\":500,\"max\":1000,\"mean\":750.00,\"median\":750.0}"));
+        assert!(json.contains("\"source_coverage\":[{\"chrom\":\"chr1\",\"size\":1000,\"covered_bases\":300},"));
+        assert!(json.ends_with("\"coverage_histogram\":[1,0,0,1,0,0,0,0,0,0]}"));
+        assert_eq!(json_string("a\"b\\c"), "\"a\\\"b\\\\c\"");
+    }
+    
+    #[test]
+    fn test_statistics_empty() {
+        let stats = ChainIndex::from_chain_data(ChainFile::new()).statistics();
+        assert_eq!((stats.chain_count, stats.block_count), (0, 0));
+        assert_eq!(stats.scores, Distribution::default());
+        assert!(stats.source_coverage.is_empty());
+    }
+    
+    #[test]
+    fn test_summary() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 100 500 chr5 1000 + 100 500 1
+100 50 50
+100 50 50
+100
+
+chain 400 chr2 2000 + 0 200 chr5 3000 - 0 200 2
+100 50 50
+50
+
+chain 100 chr2 2000 + 500 600 chrX 600 + 0 100 3
+100
+";
+        let summary = ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap()).summary();
+        
+        assert_eq!(summary.block_count, 6);
+        assert_eq!(summary.source_chroms, vec!["chr1", "chr2"]);
+        assert_eq!(summary.target_chroms, vec!["chr5", "chrX"]);
+        // chr1: 300, chr2: 150 + 100
+        assert_eq!(summary.total_source_covered, 550);
+        // chr5: 300 + 150 (different halves of chr5), chrX: 100
+        assert_eq!(summary.total_target_covered, 550);
+        assert_eq!((summary.min_score, summary.max_score), (100, 1000));
+        // (3 * 1000 + 2 * 400 + 100) / 6
+        assert!((summary.mean_score - 650.0).abs() < 1e-9);
+        
+        assert_eq!(
+            summary.to_json(),
+            "{\"block_count\":6,\"source_chroms\":[\"chr1\",\"chr2\"],\"target_chroms\":[\"chr5\",\"chrX\"],\"total_source_covered\":550,\"total_target_covered\":550,\"min_score\":100,\"max_score\":1000,\"mean_score\":650.00}"
+        );
+        
+        let empty = ChainIndex::from_chain_data(ChainFile::new()).summary();
+        assert_eq!((empty.block_count, empty.min_score, empty.max_score, empty.mean_score), (0, 0, 0, 0.0));
+        assert!(empty.source_chroms.is_empty() && empty.target_chroms.is_empty());
+    }
+    
+    #[test]
+    fn test_chromosomes() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 100 chr5 1000 + 0 100 1
+100
+
+chain 500 chr2 2000 + 0 100 chrX 900 - 0 100 2
+100
+
+chain 200 chr3 3000 + 0 100 chrX 900 + 500 600 3
+100
+";
+        let chain_file = parse_chain_bytes(chain_data).unwrap();
+        let mut source_keys: Vec<String> = chain_file.source_chrom_sizes.keys().cloned().collect();
+        let mut target_keys: Vec<String> = chain_file.target_chrom_sizes.keys().cloned().collect();
+        source_keys.sort();
+        target_keys.sort();
+        let index = ChainIndex::from_chain_data(chain_file);
+        
+        let mut source: Vec<&str> = index.chromosomes_source().collect();
+        let mut target: Vec<&str> = index.chromosomes_target().collect();
+        source.sort_unstable();
+        target.sort_unstable();
+        assert_eq!(source, source_keys);
+        assert_eq!(target, target_keys);
+        assert_eq!(source, vec!["chr1", "chr2", "chr3"]);
+        assert_eq!(target, vec!["chr5", "chrX"]);
+        assert!(source.iter().all(|chrom| index.has_chrom(chrom)));
+        
+        let empty = ChainIndex::from_chain_data(ChainFile::new());
+        assert_eq!(empty.chromosomes_source().count(), 0);
+        assert_eq!(empty.chromosomes_target().count(), 0);
+    }
+    
+    #[test]
+    fn test_filter_by_score() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 100 500 chr1 1000 + 100 500 1
+100 50 50
+100 50 50
+100
+
+chain 20 chr1 1000 + 600 700 chr9 1000 - 0 100 2
+100
+
+chain 500 chr2 2000 + 0 200 chr2 2000 + 0 200 3
+100 50 50
+50
+";
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap());
+        assert_eq!(index.total_intervals(), 6);
+        
+        let filtered = index.filter_by_score(500);
+        assert_eq!(filtered.total_intervals(), 5);
+        assert_eq!(filtered.interval_count("chr1"), 3);
+        assert!(filtered.query("chr1", 600, 700).is_empty());
+        assert!(!filtered.has_target_chrom("chr9"));
+        assert_eq!(filtered.statistics().chain_count, 2);
+        assert_eq!(filtered.source_chrom_size("chr1"), Some(1000));
+        
+        // A region covered only by the low-score chain maps nowhere
+        let mapper = crate::core::CoordinateMapper::new(filtered, crate::core::ChromStyle::AsIs);
+        assert_eq!(mapper.map("chr1", 620, 640, Strand::Plus), Some(vec![]));
+        assert_eq!(mapper.map("chr1", 120, 140, Strand::Plus).unwrap().len(), 1);
+        
+        let filtered = index.filter_by_score(501);
+        assert_eq!(filtered.total_intervals(), 3);
+        assert!(!filtered.has_chrom("chr2"));
+        assert!(!filtered.has_chrom("2"));
+        
+        assert_eq!(index.filter_by_score(0).total_intervals(), 6);
+        assert_eq!(index.filter_by_score(u64::MAX).total_intervals(), 0);
+    }
+    
+    #[test]
+    fn test_histograms() {
+        let index = create_test_index();
+        
+        // Scores 1000 (3 blocks) and 500 (2 blocks): width ceil(501 / 5) = 101
+        let scores = index.score_histogram(5);
+        assert_eq!(scores, vec![(500, 2), (601, 0), (702, 0), (803, 0), (904, 3)]);
+        let sizes = index.block_size_histogram(2);
+        assert_eq!(sizes, vec![(50, 1), (76, 4)]);
+        
+        for bins in [1, 2, 3, 7, 100, 1000] {
+            for hist in [index.score_histogram(bins), index.block_size_histogram(bins)] {
+                assert_eq!(hist.len(), bins);
+                assert_eq!(hist.iter().map(|&(_, n)| n).sum::<usize>(), index.total_intervals());
+                assert!(hist.windows(2).all(|w| w[0].0 < w[1].0));
+            }
+        }
+        
+        assert!(index.score_histogram(0).is_empty());
+        assert!(ChainIndex::from_chain_data(ChainFile::new()).score_histogram(10).is_empty());
+    }
+    
+    #[test]
+    fn test_visualize_fully_covered() {
+        let chain_data = b"chain 1000 chr1 1000 + 0 1000 chr7 1000 + 0 1000 1\n1000\n";
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap());
+        
+        assert_eq!(index.visualize_chrom("chr1", 40), "#".repeat(40));
+        assert_eq!(index.visualize_target("chr7", 40), "#".repeat(40));
+        // More bins than bases still renders every bin
+        assert_eq!(index.visualize_chrom("chr1", 2000), "#".repeat(2000));
+    }
+    
+    #[test]
+    fn test_visualize_gaps() {
+        let index = create_test_index();
+        
+        // chr1 blocks: 100-200, 250-350, 400-500 on a 1000bp chromosome
+        assert_eq!(index.visualize_chrom("chr1", 10), ".####.....");
+        assert_eq!(index.visualize_chrom("chr3", 5), ".....");
+        assert_eq!(index.visualize_target("chrUnknown", 3), "...");
+    }
+    
+    #[test]
+    fn test_sorted_intervals() {
+        let index = create_test_index();
+        
+        let sorted = index.sorted_intervals("chr1");
+        let bounds: Vec<(u64, u64)> = sorted.iter().map(|iv| (iv.start, iv.stop)).collect();
+        assert_eq!(bounds, vec![(100, 200), (250, 350), (400, 500)]);
+        let queried: Vec<(u64, u64)> = index.query_intervals("chr1", 0, 1000).iter().map(|iv| (iv.start, iv.stop)).collect();
+        assert_eq!(bounds, queried);
+        assert_eq!(index.sorted_intervals("1").len(), 3);
+        assert!(index.sorted_intervals("chr3").is_empty());
+        
+        // Binary search point lookup agrees with the interval store
+        for pos in 0..600 {
+            let i = sorted.partition_point(|iv| iv.start <= pos);
+            let hit = i.checked_sub(1).map(|i| &sorted[i]).filter(|iv| pos < iv.stop);
+            let expected = index.query_intervals("chr1", pos, pos + 1).first().copied();
+            assert_eq!(hit.map(|iv| iv.start), expected.map(|iv| iv.start), "pos {}", pos);
+        }
+    }
+    
+    #[test]
+    fn test_to_bed() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 100 500 chr1 1000 + 100 500 1
+100 50 50
+100 50 50
+100
+
+chain 500 chr2 2000 + 0 200 chr7 2000 - 0 200 2
+100 50 50
+50
+";
+        let chain_file = parse_chain_bytes(chain_data).unwrap();
+        let index = ChainIndex::from_chain_data(chain_file.clone());
+        
+        let mut out = Vec::new();
+        index.to_bed("chr1", &mut out).unwrap();
+        let expected: String = chain_file
+            .blocks
+            .iter()
+            .filter(|b| b.source_chrom == "chr1")
+            .map(|b| format!("chr1\t{}\t{}\n", b.source_start, b.source_end))
+            .collect();
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+        assert_eq!(expected, "chr1\t100\t200\nchr1\t250\t350\nchr1\t400\t500\n");
+        
+        // Alias lookup writes the chain file's name
+        let mut out = Vec::new();
+        index.to_bed("2", &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "chr2\t0\t100\nchr2\t150\t200\n");
+        
+        // Minus-strand target blocks come out flipped and sorted:
+        // [0,100) -> [1900,2000), [150,200) -> [1800,1850)
+        let mut out = Vec::new();
+        index.to_target_bed("chr7", &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "chr7\t1800\t1850\nchr7\t1900\t2000\n");
+        
+        let mut out = Vec::new();
+        index.to_bed("chr3", &mut out).unwrap();
+        index.to_target_bed("chr3", &mut out).unwrap();
+        assert!(out.is_empty());
+    }
+    
+    fn incremental_block(chrom: &str, start: u64, end: u64) -> ChainBlock {
+        ChainBlock {
+            source_chrom: chrom.to_string(),
+            source_start: start,
+            source_end: end,
+            target_chrom: chrom.to_string(),
+            target_start: start + 10,
+            target_end: end + 10,
+            target_strand: Strand::Plus,
+            score: 1,
+        }
+    }
+    
+    #[test]
+    fn test_builder_matches_batch_build() {
+        let chain_file = parse_chain_bytes(b"\
+chain 1000 chr1 1000 + 100 500 chr1 1000 + 100 500 1
+100 50 50
+100 50 50
+100
+
+chain 500 chr2 2000 + 0 200 chr7 2000 - 0 200 2
+100 50 50
+50
+").unwrap();
+        let mut builder = ChainIndexBuilder::new();
+        for (chrom, &size) in &chain_file.source_chrom_sizes {
+            builder.set_source_size(chrom, size);
+        }
+        for (chrom, &size) in &chain_file.target_chrom_sizes {
+            builder.set_target_size(chrom, size);
+        }
+        for &score in &chain_file.chain_scores {
+            builder.add_chain_score(score);
+        }
+        for block in chain_file.blocks.iter().cloned() {
+            builder.add_block_sorted(block);
+        }
+        let incremental = builder.build();
+        let batch = ChainIndex::from_chain_data(chain_file);
+        
+        assert_eq!(incremental.total_intervals(), batch.total_intervals());
+        assert_eq!(incremental.statistics(), batch.statistics());
+        for chrom in ["chr1", "chr2", "2", "CHR1"] {
+            assert_eq!(incremental.sorted_intervals(chrom), batch.sorted_intervals(chrom));
+            assert_eq!(incremental.query(chrom, 0, 2000), batch.query(chrom, 0, 2000));
+        }
+        assert_eq!(incremental.target_chrom_size("chr7"), Some(2000));
+    }
+    
+    #[test]
+    fn test_builder_merges_out_of_order_blocks() {
+        let mut builder = ChainIndexBuilder::new();
+        builder.add_block_sorted(incremental_block("chr1", 0, 10));
+        builder.add_block_sorted(incremental_block("chr1", 50, 60));
+        builder.add_block_sorted(incremental_block("chr1", 20, 30));
+        builder.add_block_sorted(incremental_block("chr2", 0, 10));
+        // Reopens the finished chr1
+        builder.add_block_sorted(incremental_block("chr1", 40, 45));
+        let index = builder.build();
+        
+        let bounds: Vec<(u64, u64)> = index.sorted_intervals("chr1").iter().map(|iv| (iv.start, iv.stop)).collect();
+        assert_eq!(bounds, vec![(0, 10), (20, 30), (40, 45), (50, 60)]);
+        assert_eq!(index.query("chr1", 42, 43)[0].target_start, 50);
+        assert_eq!(index.interval_count("chr2"), 1);
+        assert!(index.has_chrom("2"));
+    }
+    
+    #[test]
+    fn test_builder_memory_bounded() {
+        let chroms = ["chr1", "chr2", "chr3", "chr4"];
+        let per_chrom = 1000u64;
+        let threshold = 64;
+        
+        let mut builder = ChainIndexBuilder::with_merge_threshold(threshold);
+        let mut max_pending = 0;
+        for chrom in chroms {
+            for i in 0..per_chrom {
+                builder.add_block_sorted(incremental_block(chrom, i * 100, i * 100 + 50));
+                max_pending = max_pending.max(builder.pending_blocks());
+            }
+            // Only the blocks since the last merge are buffered
+            assert_eq!(builder.pending_blocks(), per_chrom as usize % threshold);
+        }
+        // The buffer never reaches the threshold
+        assert_eq!(max_pending, threshold - 1);
+        
+        let index = builder.build();
+        assert_eq!(index.total_intervals(), chroms.len() * per_chrom as usize);
+        for chrom in chroms {
+            assert_eq!(index.query(chrom, 12_345, 12_346)[0].target_start, 12_310);
+        }
+    }
+    
+    #[test]
+    fn test_builder_merges_match_batch_build() {
+        // Overlapping blocks on several chromosomes, partly out of order and
+        // with chromosomes revisited, merged every few blocks
+        let mut blocks = Vec::new();
+        for round in 0..3u64 {
+            for chrom in ["chr1", "chr2", "chr3"] {
+                for i in 0..40u64 {
+                    let start = (i * 37 + round * 11) % 1000;
+                    blocks.push(incremental_block(chrom, start, start + 5 + i % 30));
+                }
+            }
+        }
+        let chain_file = ChainFile {
+            blocks: blocks.clone(),
+            target_chrom_sizes: HashMap::new(),
+            source_chrom_sizes: HashMap::new(),
+            chain_scores: Vec::new(),
+            removed_duplicates: 0,
+            block_lines: Vec::new(),
+        };
+        let batch = ChainIndex::from_chain_data(chain_file);
+        
+        for threshold in [1, 7, DEFAULT_MERGE_THRESHOLD] {
+            let mut builder = ChainIndexBuilder::with_merge_threshold(threshold);
+            for block in blocks.iter().cloned() {
+                builder.add_block_sorted(block);
+            }
+            let incremental = builder.build();
+            
+            assert_eq!(incremental.total_intervals(), batch.total_intervals());
+            for chrom in ["chr1", "chr2", "chr3", "chr4"] {
+                assert_eq!(incremental.sorted_intervals(chrom), batch.sorted_intervals(chrom));
+                for pos in (0..1100).step_by(13) {
+                    assert_eq!(incremental.query(chrom, pos, pos + 20), batch.query(chrom, pos, pos + 20));
+                }
+            }
+        }
+    }
+    
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_load_round_trip() {
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(b"\
+chain 1000 chr1 1000 + 100 500 chr1 1000 + 100 500 1
+100 50 50
+100 50 50
+100
+
+chain 500 chr2 2000 + 0 200 chr7 2000 - 0 200 2
+100 50 50
+50
+").unwrap());
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.cache");
+        index.save_to_file(&path).unwrap();
+        let loaded = ChainIndex::load_from_file(&path).unwrap();
+        
+        assert_eq!(loaded.total_intervals(), index.total_intervals());
+        assert_eq!(loaded.target_sizes, index.target_sizes);
+        assert_eq!(loaded.source_sizes, index.source_sizes);
+        assert_eq!(loaded.statistics(), index.statistics());
+        for chrom in ["chr1", "chr2", "1", "CHR2", "chr3"] {
+            for start in (0..2000).step_by(25) {
+                for len in [1, 30, 300] {
+                    assert_eq!(loaded.query(chrom, start, start + len), index.query(chrom, start, start + len));
+                    assert_eq!(loaded.query_intervals(chrom, start, start + len), index.query_intervals(chrom, start, start + len));
+                }
+            }
+        }
+        
+        std::fs::write(&path, b"not a cache").unwrap();
+        let err = ChainIndex::load_from_file(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
 }
 
 #[cfg(test)]
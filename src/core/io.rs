@@ -1,12 +1,15 @@
 //! High-performance I/O abstraction layer
 //!
 //! Provides optimized file reading with configurable buffer sizes
-//! and optional memory mapping for large files.
+//! and optional memory mapping for large files. BGZF-compressed input
+//! (bgzip `.vcf.gz`, `.bed.gz`, ...) is detected from the file header and
+//! decompressed transparently, and outputs can be BGZF-compressed on request.
 
+use crate::core::bgzf::{self, BgzfReader, BgzfWriter};
 use memmap2::Mmap;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader, Read};
-use std::path::Path;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
 
 /// Default buffer size for BufReader (128KB)
 pub const DEFAULT_BUFFER_SIZE: usize = 128 * 1024;
@@ -40,6 +43,8 @@ pub enum SmartReader {
     Buffered(BufReader<File>),
     /// Memory-mapped reader for large files
     Mapped(MappedReader),
+    /// BGZF-compressed file, decompressed block by block
+    Bgzf(BgzfReader<BufReader<File>>),
 }
 
 /// Memory-mapped file reader
@@ -100,10 +105,21 @@ impl BufRead for MappedReader {
 
 impl SmartReader {
     /// Open a file with the specified I/O strategy
+    /// 
+    /// BGZF files are always read through a decompressing reader (with the
+    /// `Buffered` size, or the default buffer size for the other strategies).
     pub fn open<P: AsRef<Path>>(path: P, strategy: IoStrategy) -> io::Result<Self> {
         let file = File::open(path.as_ref())?;
         let metadata = file.metadata()?;
         let file_size = metadata.len();
+        
+        if bgzf::is_bgzf_file(path.as_ref())? {
+            let buf_size = match strategy {
+                IoStrategy::Buffered(buf_size) => buf_size,
+                _ => DEFAULT_BUFFER_SIZE,
+            };
+            return Ok(SmartReader::Bgzf(BgzfReader::new(BufReader::with_capacity(buf_size, file))));
+        }
 
         match strategy {
             IoStrategy::Buffered(buf_size) => {
@@ -143,6 +159,11 @@ impl SmartReader {
     pub fn is_mapped(&self) -> bool {
         matches!(self, SmartReader::Mapped(_))
     }
+    
+    /// Check if the file is BGZF-compressed
+    pub fn is_bgzf(&self) -> bool {
+        matches!(self, SmartReader::Bgzf(_))
+    }
 }
 
 impl Read for SmartReader {
@@ -150,6 +171,7 @@ impl Read for SmartReader {
         match self {
             SmartReader::Buffered(reader) => reader.read(buf),
             SmartReader::Mapped(reader) => reader.read(buf),
+            SmartReader::Bgzf(reader) => reader.read(buf),
         }
     }
 }
@@ -159,6 +181,7 @@ impl BufRead for SmartReader {
         match self {
             SmartReader::Buffered(reader) => reader.fill_buf(),
             SmartReader::Mapped(reader) => reader.fill_buf(),
+            SmartReader::Bgzf(reader) => reader.fill_buf(),
         }
     }
 
@@ -166,6 +189,7 @@ impl BufRead for SmartReader {
         match self {
             SmartReader::Buffered(reader) => reader.consume(amt),
             SmartReader::Mapped(reader) => reader.consume(amt),
+            SmartReader::Bgzf(reader) => reader.consume(amt),
         }
     }
 }
@@ -185,6 +209,139 @@ pub fn create_buf_reader_with_capacity<P: AsRef<Path>>(
     Ok(BufReader::with_capacity(capacity, file))
 }
 
+/// Path that stands for stdin (as input) or stdout (as output)
+pub const STDIO_PATH: &str = "-";
+
+/// Check whether a path is `-` (stdin/stdout)
+pub fn is_stdio<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref().as_os_str() == STDIO_PATH
+}
+
+/// Upper bound of the auto-detected thread count
+pub const MAX_AUTO_THREADS: usize = 16;
+
+/// Thread count for a `threads` option, where 0 means auto-detect
+///
+/// Auto-detection uses the number of logical CPUs, capped at
+/// [`MAX_AUTO_THREADS`]. Other values are returned unchanged.
+pub fn resolve_threads(threads: usize) -> usize {
+    if threads == 0 {
+        num_cpus::get().clamp(1, MAX_AUTO_THREADS)
+    } else {
+        threads
+    }
+}
+
+/// Open an input file for buffered reading, or stdin for `-`
+/// 
+/// BGZF-compressed input (file or stdin) is decompressed on the fly.
+pub fn open_input<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<Box<dyn BufRead + Send>> {
+    if is_stdio(&path) {
+        let mut reader = BufReader::with_capacity(capacity, io::stdin());
+        if bgzf::is_bgzf_header(reader.fill_buf()?) {
+            Ok(Box::new(BgzfReader::new(reader)))
+        } else {
+            Ok(Box::new(reader))
+        }
+    } else {
+        Ok(Box::new(SmartReader::open_buffered(path, capacity)?))
+    }
+}
+
+/// Create an output file for buffered writing, or stdout for `-`
+pub fn create_output<P: AsRef<Path>>(path: P, capacity: usize) -> io::Result<Box<dyn Write + Send>> {
+    if is_stdio(&path) {
+        Ok(Box::new(BufWriter::with_capacity(capacity, io::stdout())))
+    } else {
+        Ok(Box::new(BufWriter::with_capacity(capacity, File::create(path)?)))
+    }
+}
+
+/// Output created by [`create_output_compressed`]
+///
+/// Dropping it flushes on a best-effort basis and discards any error; call
+/// [`finish`](Self::finish) once everything is written.
+pub enum CompressedOutput {
+    Plain(Box<dyn Write + Send>),
+    Bgzf(BgzfWriter<Box<dyn Write + Send>>),
+}
+
+impl CompressedOutput {
+    /// Flush the output, writing the BGZF EOF marker when compressed
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            CompressedOutput::Plain(mut writer) => writer.flush(),
+            CompressedOutput::Bgzf(writer) => writer.finish().map(drop),
+        }
+    }
+}
+
+impl Write for CompressedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            CompressedOutput::Plain(writer) => writer.write(buf),
+            CompressedOutput::Bgzf(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            CompressedOutput::Plain(writer) => writer.flush(),
+            CompressedOutput::Bgzf(writer) => writer.flush(),
+        }
+    }
+}
+
+/// Create an output like [`create_output`], BGZF-compressing it when
+/// `compress` is set (stdout included)
+pub fn create_output_compressed<P: AsRef<Path>>(path: P, capacity: usize, compress: bool) -> io::Result<CompressedOutput> {
+    let writer = create_output(path, capacity)?;
+    if compress {
+        Ok(CompressedOutput::Bgzf(BgzfWriter::new(writer)))
+    } else {
+        Ok(CompressedOutput::Plain(writer))
+    }
+}
+
+/// `path` with `.gz` appended, unless it is stdout or already ends in `.gz`
+pub fn compressed_output_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let path = path.as_ref();
+    if is_stdio(path) || path.extension().is_some_and(|ext| ext == "gz") {
+        path.to_path_buf()
+    } else {
+        let mut name = path.as_os_str().to_os_string();
+        name.push(".gz");
+        PathBuf::from(name)
+    }
+}
+
+/// Unmap file next to `output`, named by replacing its extension with `extension`
+///
+/// Writing to stdout leaves no file to put the unmap file next to, so an
+/// empty path is returned and [`create_unmap_output`] falls back to stderr.
+pub fn unmap_path_for<P: AsRef<Path>>(output: P, extension: &str) -> PathBuf {
+    let output = output.as_ref();
+    if is_stdio(output) {
+        PathBuf::new()
+    } else {
+        output.with_extension(extension)
+    }
+}
+
+/// Create the unmap output at `unmap`
+///
+/// The given path is always honoured, even when the main output goes to
+/// stdout. Only an empty path (no unmap file) falls back to stderr, so
+/// unmapped records are never silently discarded.
+pub fn create_unmap_output<P: AsRef<Path>>(unmap: P, capacity: usize) -> io::Result<Box<dyn Write + Send>> {
+    let unmap = unmap.as_ref();
+    if unmap.as_os_str().is_empty() {
+        Ok(Box::new(BufWriter::with_capacity(capacity, io::stderr())))
+    } else {
+        create_output(unmap, capacity)
+    }
+}
+
 /// Line iterator that reuses a buffer to avoid allocations
 pub struct LineIterator<R: BufRead> {
     reader: R,
@@ -301,6 +458,34 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_smart_reader_bgzf() -> io::Result<()> {
+        let content = "#header\nchr1\t100\t200\n".repeat(5000);
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("in.bed.gz");
+        let mut writer = bgzf::BgzfWriter::new(File::create(&path)?);
+        writer.write_all(content.as_bytes())?;
+        writer.finish()?;
+        
+        for strategy in [IoStrategy::Auto, IoStrategy::Buffered(1024), IoStrategy::MemoryMapped] {
+            let mut reader = SmartReader::open(&path, strategy)?;
+            assert!(reader.is_bgzf() && !reader.is_mapped());
+            let mut decoded = String::new();
+            reader.read_to_string(&mut decoded)?;
+            assert_eq!(decoded, content);
+        }
+        
+        let mut decoded = String::new();
+        open_input(&path, 1024)?.read_to_string(&mut decoded)?;
+        assert_eq!(decoded, content);
+        
+        // Plain files are not mistaken for BGZF
+        let plain = dir.path().join("in.bed");
+        std::fs::write(&plain, &content)?;
+        assert!(!SmartReader::open_auto(&plain)?.is_bgzf());
+        Ok(())
+    }
+
     #[test]
     fn test_line_iterator() -> io::Result<()> {
         let mut temp = NamedTempFile::new()?;
@@ -337,6 +522,53 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_stdio_paths() -> io::Result<()> {
+        assert!(is_stdio("-"));
+        assert!(!is_stdio("-.bed"));
+        assert!(!is_stdio("out.bed"));
+
+        let dir = tempfile::tempdir()?;
+        let out = dir.path().join("out.bed");
+        let unmap = dir.path().join("out.bed.unmap");
+        create_output(&out, 1024)?.write_all(b"chr1\t1\t2\n")?;
+        create_unmap_output(&unmap, 1024)?.flush()?;
+        assert!(unmap.exists());
+
+        let mut line = String::new();
+        open_input(&out, 1024)?.read_line(&mut line)?;
+        assert_eq!(line, "chr1\t1\t2\n");
+
+        // Writing to stdout leaves no derived unmap path; an explicit one is kept
+        assert_eq!(unmap_path_for("-", "bed.unmap"), PathBuf::new());
+        assert_eq!(unmap_path_for(&out, "bed.unmap"), dir.path().join("out.bed.unmap"));
+        let stdout_unmap = dir.path().join("stdout.unmap");
+        create_unmap_output(&stdout_unmap, 1024)?.write_all(b"kept")?;
+        assert_eq!(std::fs::read_to_string(&stdout_unmap)?, "kept");
+        Ok(())
+    }
+
+    #[test]
+    fn test_compressed_output() -> io::Result<()> {
+        assert_eq!(compressed_output_path("out.vcf"), PathBuf::from("out.vcf.gz"));
+        assert_eq!(compressed_output_path("out.vcf.gz"), PathBuf::from("out.vcf.gz"));
+        assert_eq!(compressed_output_path("-"), PathBuf::from("-"));
+
+        let dir = tempfile::tempdir()?;
+        let out = dir.path().join("out.bed.gz");
+        {
+            let mut writer = create_output_compressed(&out, 1024, true)?;
+            writer.write_all(b"chr1\t1\t2\n")?;
+            writer.finish()?;
+        }
+        assert!(bgzf::is_bgzf_file(&out)?);
+        assert!(std::fs::read(&out)?.ends_with(&bgzf::EOF_BLOCK));
+        let mut text = String::new();
+        open_input(&out, 1024)?.read_to_string(&mut text)?;
+        assert_eq!(text, "chr1\t1\t2\n");
+        Ok(())
+    }
+
     #[test]
     fn test_mapped_reader_len() -> io::Result<()> {
         let mut temp = NamedTempFile::new()?;
@@ -351,4 +583,12 @@ mod tests {
         assert_eq!(reader.as_bytes(), b"test content");
         Ok(())
     }
+
+    #[test]
+    fn test_resolve_threads() {
+        let auto = resolve_threads(0);
+        assert!((1..=MAX_AUTO_THREADS).contains(&auto), "{}", auto);
+        assert_eq!(resolve_threads(1), 1);
+        assert_eq!(resolve_threads(32), 32);
+    }
 }
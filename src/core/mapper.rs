@@ -8,8 +8,13 @@
 //! 3. Calculate target coordinates using offset formulas
 //! 4. Handle strand direction combinations
 
-use crate::core::index::IntervalValue;
-use crate::core::ChainIndex;
+use crate::core::index::{query_store, ChainInterval, IntervalValue};
+use crate::core::{ChainIndex, MappingError, MappingResult, ParseStrandError};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use tracing::{debug, warn};
 
 /// Compatibility mode for CrossMap behavior
 /// 
@@ -45,10 +50,14 @@ impl CompatMode {
 }
 
 /// Strand orientation
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+///
+/// Serializes as the `"+"` / `"-"` string used in chain and annotation files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash, Serialize, Deserialize)]
 pub enum Strand {
     #[default]
+    #[serde(rename = "+")]
     Plus,
+    #[serde(rename = "-")]
     Minus,
 }
 
@@ -142,6 +151,46 @@ impl std::fmt::Display for Strand {
     }
 }
 
+impl std::str::FromStr for Strand {
+    type Err = ParseStrandError;
+
+    /// Parse `"+"` or `"-"`
+    ///
+    /// # Examples
+    /// ```
+    /// use fast_crossmap::core::Strand;
+    /// assert_eq!("-".parse::<Strand>(), Ok(Strand::Minus));
+    /// assert!(".".parse::<Strand>().is_err());
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "+" => Ok(Strand::Plus),
+            "-" => Ok(Strand::Minus),
+            _ => Err(ParseStrandError(s.to_string())),
+        }
+    }
+}
+
+impl From<bool> for Strand {
+    /// `true` is `Plus`, `false` is `Minus` (e.g. a forward-strand flag)
+    fn from(forward: bool) -> Self {
+        if forward {
+            Strand::Plus
+        } else {
+            Strand::Minus
+        }
+    }
+}
+
+impl TryFrom<u8> for Strand {
+    type Error = ParseStrandError;
+
+    /// Convert from a `b'+'` / `b'-'` byte
+    fn try_from(b: u8) -> Result<Self, Self::Error> {
+        Strand::from_byte(b).ok_or_else(|| ParseStrandError(char::from(b).to_string()))
+    }
+}
+
 /// Chromosome ID style for output formatting
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum ChromStyle {
@@ -285,6 +334,18 @@ pub struct MappingSegment {
     pub source: MapResult,
     /// Target region after mapping
     pub target: MapResult,
+    /// Share of the query passed to `map` covered by this segment:
+    /// `(source.end - source.start) / (query_end - query_start)`
+    pub coverage_fraction: f64,
+}
+
+/// Mapping result together with the unmappable parts of the query
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MappingWithGaps {
+    /// Successfully mapped segments (same as `CoordinateMapper::map`)
+    pub mapped: Vec<MappingSegment>,
+    /// Source sub-intervals [start, end) not covered by any chain block, in order
+    pub gaps: Vec<(u64, u64)>,
 }
 
 /// Compute the intersection of two intervals on the same chromosome
@@ -305,11 +366,44 @@ pub fn intersect_intervals(start1: u64, end1: u64, start2: u64, end2: u64) -> Op
     Some((start1.max(start2), end1.min(end2)))
 }
 
+/// Drop segments whose target interval and strand repeat an earlier one,
+/// keeping the segment that covers more of the source query. Returns the
+/// number of segments removed.
+fn dedup_segments(segments: &mut Vec<MappingSegment>) -> usize {
+    let before = segments.len();
+    let mut kept: Vec<MappingSegment> = Vec::with_capacity(before);
+    for seg in segments.drain(..) {
+        let duplicate = kept.iter_mut().find(|k| {
+            k.target.chrom == seg.target.chrom
+                && k.target.start == seg.target.start
+                && k.target.end == seg.target.end
+                && k.target.strand == seg.target.strand
+        });
+        match duplicate {
+            Some(k) => {
+                if seg.source.end - seg.source.start > k.source.end - k.source.start {
+                    *k = seg;
+                }
+            }
+            None => kept.push(seg),
+        }
+    }
+    *segments = kept;
+    before - segments.len()
+}
+
 /// Coordinate mapper using chain index
 pub struct CoordinateMapper {
     index: ChainIndex,
     chrom_style: ChromStyle,
     compat_mode: CompatMode,
+    strict_bounds: bool,
+    /// Segments dropped because another block gave the same target interval
+    dedup_count: AtomicUsize,
+    /// Queries that ran past the end of their source chromosome
+    out_of_bounds_count: AtomicUsize,
+    /// Chromosomes already warned about for out-of-bounds queries
+    out_of_bounds_warned: Mutex<HashSet<String>>,
 }
 
 impl CoordinateMapper {
@@ -318,6 +412,10 @@ impl CoordinateMapper {
             index, 
             chrom_style,
             compat_mode: CompatMode::default(),
+            strict_bounds: false,
+            dedup_count: AtomicUsize::new(0),
+            out_of_bounds_count: AtomicUsize::new(0),
+            out_of_bounds_warned: Mutex::new(HashSet::new()),
         }
     }
     
@@ -327,6 +425,10 @@ impl CoordinateMapper {
             index, 
             chrom_style,
             compat_mode,
+            strict_bounds: false,
+            dedup_count: AtomicUsize::new(0),
+            out_of_bounds_count: AtomicUsize::new(0),
+            out_of_bounds_warned: Mutex::new(HashSet::new()),
         }
     }
     
@@ -340,6 +442,31 @@ impl CoordinateMapper {
         self.compat_mode
     }
 
+    /// Number of duplicate segments dropped from `map` results so far
+    pub fn dedup_count(&self) -> usize {
+        self.dedup_count.load(Ordering::Relaxed)
+    }
+
+    /// Number of queries past the end of their source chromosome so far
+    pub fn out_of_bounds_count(&self) -> usize {
+        self.out_of_bounds_count.load(Ordering::Relaxed)
+    }
+
+    /// Treat queries past the end of the source chromosome as errors
+    /// 
+    /// By default such queries are counted in [`out_of_bounds_count`](Self::out_of_bounds_count)
+    /// and log a warning once per chromosome. In strict mode
+    /// `try_map` returns `MappingError::OutOfBounds` and `map` reports
+    /// the query as unmappable.
+    pub fn set_strict_bounds(&mut self, strict: bool) {
+        self.strict_bounds = strict;
+    }
+    
+    /// Whether out-of-bounds queries are errors
+    pub fn strict_bounds(&self) -> bool {
+        self.strict_bounds
+    }
+
     /// Get the chromosome style
     pub fn chrom_style(&self) -> ChromStyle {
         self.chrom_style
@@ -354,6 +481,39 @@ impl CoordinateMapper {
     pub fn target_sizes(&self) -> &std::collections::HashMap<String, u64> {
         &self.index.target_sizes
     }
+    
+    /// Get source chromosome sizes
+    pub fn source_sizes(&self) -> &std::collections::HashMap<String, u64> {
+        &self.index.source_sizes
+    }
+
+    /// Warm up the interval stores of `chroms` before the first real query
+    /// 
+    /// Runs a query spanning each chromosome, pulling its interval nodes
+    /// into CPU cache. Useful in long-running services where the latency of
+    /// the first query on a chromosome matters. Chromosomes missing from the
+    /// index are skipped. Returns the number of intervals touched.
+    pub fn prefetch(&self, chroms: &[&str]) -> usize {
+        chroms.iter().map(|chrom| self.prefetch_chrom(chrom)).sum()
+    }
+    
+    /// Warm up every source chromosome of the index (see `prefetch`)
+    pub fn prefetch_all(&self) -> usize {
+        self.index.source_chroms().map(|chrom| self.prefetch_chrom(chrom)).sum()
+    }
+    
+    /// Touch every interval of one chromosome, then run a one-base mapping
+    /// to warm the lookup path (chromosome tables, size checks)
+    fn prefetch_chrom(&self, chrom: &str) -> usize {
+        let intervals = self.index.query_intervals(chrom, 0, u64::MAX);
+        for iv in &intervals {
+            std::hint::black_box((iv.start, iv.stop, iv.val.target_start, iv.val.target_chrom.as_bytes()));
+        }
+        if let Some(first) = intervals.first() {
+            std::hint::black_box(self.map(chrom, first.start, first.start + 1, Strand::Plus));
+        }
+        intervals.len()
+    }
 
     /// Map coordinates from source to target assembly
     /// 
@@ -382,16 +542,99 @@ impl CoordinateMapper {
         end: u64,
         strand: Strand,
     ) -> Option<Vec<MappingSegment>> {
+        self.try_map(chrom, start, end, strand).ok().flatten()
+    }
+    
+    /// Map coordinates, failing on out-of-bounds queries in strict mode
+    /// 
+    /// Same as `map`, but a query whose end exceeds the source chromosome
+    /// size returns `MappingError::OutOfBounds` when strict bounds are set.
+    /// Without strict bounds the mismatch is only logged.
+    pub fn try_map(
+        &self,
+        chrom: &str,
+        start: u64,
+        end: u64,
+        strand: Strand,
+    ) -> MappingResult<Option<Vec<MappingSegment>>> {
         // Check if chromosome exists
         if !self.index.has_chrom(chrom) {
-            return None;
+            return Ok(None);
         }
         
+        self.check_bounds(chrom, end)?;
+        
         // Query overlapping intervals
         let intervals = self.index.query_intervals(chrom, start, end);
+        Ok(Some(self.segments_from_intervals(chrom, start, end, strand, intervals)))
+    }
+    
+    /// Map many queries, looking each chromosome up only once
+    /// 
+    /// Records are grouped by chromosome; the interval store and size of
+    /// each chromosome are resolved once, then its queries run one after
+    /// another. Results are in input order and equal to calling `map` on
+    /// each record.
+    pub fn map_batch<S: AsRef<str>>(&self, records: &[(S, u64, u64, Strand)]) -> Vec<Option<Vec<MappingSegment>>> {
+        let mut by_chrom: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, record) in records.iter().enumerate() {
+            by_chrom.entry(record.0.as_ref()).or_default().push(i);
+        }
         
+        let mut results: Vec<Option<Vec<MappingSegment>>> = (0..records.len()).map(|_| None).collect();
+        for (chrom, indices) in by_chrom {
+            let Some(store) = self.index.chrom_store(chrom) else {
+                continue;
+            };
+            let size = self.index.source_chrom_size(chrom);
+            for i in indices {
+                let (_, start, end, strand) = records[i];
+                if self.check_bounds_with_size(chrom, end, size).is_err() {
+                    continue;
+                }
+                let intervals = query_store(store, start, end);
+                results[i] = Some(self.segments_from_intervals(chrom, start, end, strand, intervals));
+            }
+        }
+        results
+    }
+    
+    /// Map coordinates from the target assembly back to the source assembly
+    /// 
+    /// Uses the target-keyed copy of the blocks built with the index, so
+    /// the reverse chain file is not needed. Segments read like those of
+    /// `map`: `source` is the query on the target assembly and `target` the
+    /// lifted region on the source assembly. For plus-strand blocks
+    /// `source_start = block_source_start + left_offset`, for minus-strand
+    /// blocks `source_start = block_source_end - left_offset - size`.
+    /// 
+    /// Returns None if the chromosome is not a target chromosome of the
+    /// chain, and an empty Vec if no block overlaps the query.
+    pub fn map_inverse(
+        &self,
+        chrom: &str,
+        start: u64,
+        end: u64,
+        strand: Strand,
+    ) -> Option<Vec<MappingSegment>> {
+        if !self.index.has_target_chrom(chrom) {
+            return None;
+        }
+        let intervals = self.index.query_target_intervals(chrom, start, end);
+        Some(self.segments_from_intervals(chrom, start, end, strand, intervals))
+    }
+    
+    /// Lift `[start, end)` on `chrom` through the overlapping `intervals`
+    fn segments_from_intervals(
+        &self,
+        chrom: &str,
+        start: u64,
+        end: u64,
+        strand: Strand,
+        intervals: Vec<&ChainInterval>,
+    ) -> Vec<MappingSegment> {
         if intervals.is_empty() {
-            return Some(vec![]);
+            return vec![];
         }
         
         let mut results = Vec::with_capacity(intervals.len());
@@ -464,10 +707,46 @@ impl CoordinateMapper {
                     end: target_end,
                     strand: final_strand,
                 },
+                coverage_fraction: size as f64 / (end - start).max(1) as f64,
             });
         }
-        
-        Some(results)
+
+        // CrossMap reports every overlapping block, even when two blocks give
+        // the same target interval; only strict mode keeps those duplicates
+        if !self.compat_mode.is_strict() && results.len() > 1 {
+            let removed = dedup_segments(&mut results);
+            if removed > 0 {
+                self.dedup_count.fetch_add(removed, Ordering::Relaxed);
+                debug!(chrom, start, end, removed, "dropped duplicate mapping segments");
+            }
+        }
+
+        results
+    }
+    
+    /// Compare a query end against the source chromosome size
+    fn check_bounds(&self, chrom: &str, end: u64) -> MappingResult<()> {
+        self.check_bounds_with_size(chrom, end, self.index.source_chrom_size(chrom))
+    }
+    
+    /// `check_bounds` with the source chromosome size already looked up
+    fn check_bounds_with_size(&self, chrom: &str, end: u64, size: Option<u64>) -> MappingResult<()> {
+        if let Some(size) = size {
+            if end > size {
+                if self.strict_bounds {
+                    return Err(MappingError::OutOfBounds { chrom: chrom.to_string(), end, size });
+                }
+                self.out_of_bounds_count.fetch_add(1, Ordering::Relaxed);
+                let first = self.out_of_bounds_warned.lock().map(|mut warned| warned.insert(chrom.to_string())).unwrap_or(false);
+                if first {
+                    warn!(
+                        "Query end {} exceeds source chromosome size {} for {} (further queries past its end are not reported)",
+                        end, size, chrom
+                    );
+                }
+            }
+        }
+        Ok(())
     }
     
     /// Calculate target coordinates based on strand direction
@@ -505,6 +784,37 @@ impl CoordinateMapper {
         }
     }
     
+    /// Map coordinates and report the unmapped sub-intervals of the query
+    /// 
+    /// `gaps` is the complement of the mapped source ranges within
+    /// [start, end). If the chromosome is not in the index, the whole
+    /// query is reported as a single gap.
+    pub fn map_with_gaps(
+        &self,
+        chrom: &str,
+        start: u64,
+        end: u64,
+        strand: Strand,
+    ) -> MappingWithGaps {
+        let mapped = self.map(chrom, start, end, strand).unwrap_or_default();
+        
+        // Segments follow `query_intervals` order, so source starts are
+        // ascending; blocks from different chains may still overlap
+        let mut gaps = Vec::new();
+        let mut cursor = start;
+        for seg in &mapped {
+            if seg.source.start > cursor {
+                gaps.push((cursor, seg.source.start));
+            }
+            cursor = cursor.max(seg.source.end);
+        }
+        if cursor < end {
+            gaps.push((cursor, end));
+        }
+        
+        MappingWithGaps { mapped, gaps }
+    }
+    
     /// Map a single position (useful for VCF)
     /// 
     /// Returns the first mapping result for a single base position.
@@ -586,6 +896,44 @@ chain 500 chr2 2000 + 0 200 chr2 2000 + 0 200 2
         assert_eq!(format!("{}", Strand::Minus), "-");
     }
 
+    #[test]
+    fn test_strand_from_str() {
+        assert_eq!("+".parse::<Strand>(), Ok(Strand::Plus));
+        assert_eq!("-".parse::<Strand>(), Ok(Strand::Minus));
+        assert_eq!(".".parse::<Strand>(), Err(ParseStrandError(".".to_string())));
+        assert!("".parse::<Strand>().is_err());
+        assert!("++".parse::<Strand>().is_err());
+        // Display and FromStr round-trip
+        for strand in [Strand::Plus, Strand::Minus] {
+            assert_eq!(strand.to_string().parse::<Strand>(), Ok(strand));
+        }
+    }
+
+    #[test]
+    fn test_strand_from_bool() {
+        assert_eq!(Strand::from(true), Strand::Plus);
+        assert_eq!(Strand::from(false), Strand::Minus);
+    }
+
+    #[test]
+    fn test_strand_try_from_u8() {
+        assert_eq!(Strand::try_from(b'+'), Ok(Strand::Plus));
+        assert_eq!(Strand::try_from(b'-'), Ok(Strand::Minus));
+        let err = Strand::try_from(b'.').unwrap_err();
+        assert_eq!(err.to_string(), "Invalid strand '.', expected '+' or '-'");
+    }
+
+    #[test]
+    fn test_strand_serde_json() {
+        assert_eq!(serde_json::to_string(&Strand::Plus).unwrap(), "\"+\"");
+        assert_eq!(serde_json::to_string(&Strand::Minus).unwrap(), "\"-\"");
+        for strand in [Strand::Plus, Strand::Minus] {
+            let json = serde_json::to_string(&strand).unwrap();
+            assert_eq!(serde_json::from_str::<Strand>(&json).unwrap(), strand);
+        }
+        assert!(serde_json::from_str::<Strand>("\"Plus\"").is_err());
+    }
+
     #[test]
     fn test_chrom_style_from_str() {
         assert_eq!(ChromStyle::from_str("asis"), Some(ChromStyle::AsIs));
@@ -767,4 +1115,293 @@ chain 500 chr2 2000 + 0 200 chr2 2000 + 0 200 2
         assert_eq!(results[0].target.chrom, "1");
         assert_eq!(results[0].source.chrom, "1");
     }
+    
+    #[test]
+    fn test_map_with_gaps() {
+        let index = create_test_index();
+        let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+        
+        // chr1 blocks: 100-200, 250-350, 400-500
+        let result = mapper.map_with_gaps("chr1", 50, 450, Strand::Plus);
+        assert_eq!(result.mapped.len(), 3);
+        assert_eq!(result.gaps, vec![(50, 100), (200, 250), (350, 400)]);
+        
+        // Gaps + mapped source ranges cover exactly the query
+        let mut covered: u64 = result.gaps.iter().map(|(s, e)| e - s).sum();
+        covered += result.mapped.iter().map(|seg| seg.source.end - seg.source.start).sum::<u64>();
+        assert_eq!(covered, 400);
+    }
+    
+    #[test]
+    fn test_map_with_gaps_edge_cases() {
+        let index = create_test_index();
+        let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+        
+        // Fully inside a block
+        let result = mapper.map_with_gaps("chr1", 120, 180, Strand::Plus);
+        assert_eq!(result.mapped.len(), 1);
+        assert!(result.gaps.is_empty());
+        
+        // Fully inside a gap
+        let result = mapper.map_with_gaps("chr1", 210, 240, Strand::Plus);
+        assert!(result.mapped.is_empty());
+        assert_eq!(result.gaps, vec![(210, 240)]);
+        
+        // Unknown chromosome
+        let result = mapper.map_with_gaps("chrNONE", 0, 100, Strand::Plus);
+        assert!(result.mapped.is_empty());
+        assert_eq!(result.gaps, vec![(0, 100)]);
+    }
+    
+    /// Run `f` with a subscriber that records formatted log output
+    fn capture_logs<F: FnOnce()>(f: F) -> String {
+        use std::sync::{Arc, Mutex};
+        
+        #[derive(Clone, Default)]
+        struct Buf(Arc<Mutex<Vec<u8>>>);
+        impl std::io::Write for Buf {
+            fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(data);
+                Ok(data.len())
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+        
+        let buf = Buf::default();
+        let writer = buf.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        tracing::subscriber::with_default(subscriber, f);
+        
+        let bytes = buf.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+    
+    #[test]
+    fn test_map_out_of_bounds_warns() {
+        let mapper = CoordinateMapper::new(create_test_index(), ChromStyle::AsIs);
+        
+        // chr1 source size is 1000
+        let logs = capture_logs(|| {
+            assert_eq!(mapper.map("chr1", 450, 1200, Strand::Plus).unwrap().len(), 1);
+        });
+        assert!(logs.contains("WARN"), "{}", logs);
+        assert!(logs.contains("1200") && logs.contains("1000") && logs.contains("chr1"), "{}", logs);
+        
+        // Later out-of-bounds queries on the same chromosome are only counted
+        let logs = capture_logs(|| {
+            mapper.map("chr1", 450, 1300, Strand::Plus);
+        });
+        assert!(logs.is_empty(), "{}", logs);
+        assert_eq!(mapper.out_of_bounds_count(), 2);
+        
+        // In-bounds queries stay silent
+        let logs = capture_logs(|| {
+            mapper.map("chr1", 100, 1000, Strand::Plus);
+        });
+        assert!(logs.is_empty(), "{}", logs);
+    }
+    
+    #[test]
+    fn test_map_out_of_bounds_strict() {
+        let mut mapper = CoordinateMapper::new(create_test_index(), ChromStyle::AsIs);
+        mapper.set_strict_bounds(true);
+        assert!(mapper.strict_bounds());
+        
+        let err = mapper.try_map("chr1", 450, 1200, Strand::Plus).unwrap_err();
+        assert!(matches!(
+            err,
+            MappingError::OutOfBounds { ref chrom, end: 1200, size: 1000 } if chrom == "chr1"
+        ));
+        assert!(mapper.map("chr1", 450, 1200, Strand::Plus).is_none());
+        
+        // In-bounds queries are unaffected
+        assert_eq!(mapper.try_map("chr1", 100, 200, Strand::Plus).unwrap().unwrap().len(), 1);
+    }
+    
+    #[test]
+    fn test_coverage_fraction() {
+        let mapper = CoordinateMapper::new(create_test_index(), ChromStyle::AsIs);
+        
+        // Fully inside one block
+        let segments = mapper.map("chr1", 120, 140, Strand::Plus).unwrap();
+        assert_eq!(segments[0].coverage_fraction, 1.0);
+        
+        // chr1:150-250 spans the 50 bp gap: 50 bp in each block of a 200 bp query
+        let segments = mapper.map("chr1", 150, 350, Strand::Plus).unwrap();
+        let fractions: Vec<f64> = segments.iter().map(|s| s.coverage_fraction).collect();
+        assert_eq!(fractions, vec![0.25, 0.5]);
+        for seg in &segments {
+            assert_eq!(seg.coverage_fraction, (seg.source.end - seg.source.start) as f64 / 200.0);
+        }
+        
+        // Query running off the start of the chain
+        let segments = mapper.map("chr1", 0, 110, Strand::Plus).unwrap();
+        assert!((segments[0].coverage_fraction - 10.0 / 110.0).abs() < 1e-12);
+    }
+    
+    #[test]
+    fn test_map_batch_matches_map() {
+        let mut mapper = CoordinateMapper::new(create_test_index(), ChromStyle::AsIs);
+        let records = [
+            ("chr1", 120, 130, Strand::Plus),
+            ("chr2", 0, 200, Strand::Minus),
+            ("chr3", 0, 10, Strand::Plus),
+            ("1", 240, 420, Strand::Plus),
+            ("chr1", 0, 50, Strand::Plus),
+            ("chr1", 450, 1200, Strand::Minus),
+            ("chr2", 150, 170, Strand::Plus),
+        ];
+        
+        for strict in [false, true] {
+            mapper.set_strict_bounds(strict);
+            let expected: Vec<_> = records
+                .iter()
+                .map(|&(chrom, start, end, strand)| mapper.map(chrom, start, end, strand))
+                .collect();
+            assert_eq!(mapper.map_batch(&records), expected);
+        }
+        assert!(mapper.map_batch(&records)[2].is_none());
+        assert!(mapper.map_batch(&records)[5].is_none());
+        assert_eq!(mapper.map_batch(&records)[4], Some(vec![]));
+        
+        let owned = vec![(String::from("chr2"), 10u64, 20u64, Strand::Plus)];
+        assert_eq!(mapper.map_batch(&owned)[0].as_ref().unwrap()[0].target.start, 10);
+        assert!(mapper.map_batch::<&str>(&[]).is_empty());
+    }
+    
+    #[test]
+    fn test_map_dedups_identical_targets() {
+        // Two chains whose blocks map chr1:0-100 onto the same chr2 interval
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 100 chr2 1000 + 200 300 1
+100
+
+chain 900 chr1 1000 + 0 100 chr2 1000 + 200 300 2
+100
+";
+        let index = || ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap());
+        let mapper = CoordinateMapper::new(index(), ChromStyle::AsIs);
+
+        let segments = mapper.map("chr1", 10, 50, Strand::Plus).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].target.chrom, "chr2");
+        assert_eq!((segments[0].target.start, segments[0].target.end), (210, 250));
+        assert_eq!(mapper.dedup_count(), 1);
+
+        // Strict mode keeps CrossMap's duplicate segments
+        let strict = CoordinateMapper::with_compat_mode(index(), ChromStyle::AsIs, CompatMode::Strict);
+        assert_eq!(strict.map("chr1", 10, 50, Strand::Plus).unwrap().len(), 2);
+        assert_eq!(strict.dedup_count(), 0);
+    }
+
+    #[test]
+    fn test_dedup_segments_keeps_larger_source() {
+        let seg = |source_end: u64| MappingSegment {
+            source: MapResult { chrom: "chr1".into(), start: 0, end: source_end, strand: Strand::Plus },
+            target: MapResult { chrom: "chr2".into(), start: 0, end: 10, strand: Strand::Plus },
+            coverage_fraction: source_end as f64 / 20.0,
+        };
+        let mut segments = vec![seg(10), seg(20), seg(5)];
+        assert_eq!(dedup_segments(&mut segments), 2);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].source.end, 20);
+    }
+
+    #[test]
+    fn test_source_and_target_sizes() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 100 chrA 3000 + 0 100 1
+100
+
+chain 500 chr2 2000 + 0 100 chrB 4000 - 0 100 2
+100
+";
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap());
+        assert_eq!(index.source_chrom_size("chr1"), Some(1000));
+        assert_eq!(index.source_chrom_size("2"), Some(2000));
+        assert_eq!(index.source_chrom_size("chrA"), None);
+        assert_eq!(index.target_chrom_size("chrB"), Some(4000));
+        
+        let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+        let mut source: Vec<_> = mapper.source_sizes().iter().map(|(c, &s)| (c.as_str(), s)).collect();
+        source.sort();
+        assert_eq!(source, vec![("chr1", 1000), ("chr2", 2000)]);
+        let mut target: Vec<_> = mapper.target_sizes().iter().map(|(c, &s)| (c.as_str(), s)).collect();
+        target.sort();
+        assert_eq!(target, vec![("chrA", 3000), ("chrB", 4000)]);
+    }
+
+    #[test]
+    fn test_prefetch() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 300 chrA 3000 + 0 300 1
+100\t50\t50
+150
+
+chain 500 chr2 2000 + 0 100 chrB 4000 - 0 100 2
+100
+";
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap()), ChromStyle::AsIs);
+        assert_eq!(mapper.prefetch(&["chr1"]), 2);
+        assert_eq!(mapper.prefetch(&["1", "chr2", "chrUn"]), 3);
+        assert_eq!(mapper.prefetch(&[]), 0);
+        assert_eq!(mapper.prefetch_all(), 3);
+        
+        // Queries are unaffected
+        let seg = mapper.map_single("chr1", 200, Strand::Plus).unwrap();
+        assert_eq!(seg.target.start, 200);
+    }
+    
+    #[test]
+    fn test_map_inverse() {
+        let chain_data = b"\
+chain 1000 chr1 1000 + 100 400 chr5 1000 + 500 800 1
+100 50 50
+100
+
+chain 500 chr2 2000 + 0 200 chr7 2000 - 0 200 2
+100 50 50
+50
+";
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap()), ChromStyle::AsIs);
+        
+        // Plus strand: chr1:[120, 130) -> chr5:[520, 530) and back
+        let forward = mapper.map("chr1", 120, 130, Strand::Plus).unwrap();
+        assert_eq!((forward[0].target.chrom.as_str(), forward[0].target.start, forward[0].target.end), ("chr5", 520, 530));
+        let inverse = mapper.map_inverse("chr5", 520, 530, Strand::Plus).unwrap();
+        assert_eq!(inverse.len(), 1);
+        assert_eq!((inverse[0].source.chrom.as_str(), inverse[0].source.start, inverse[0].source.end), ("chr5", 520, 530));
+        assert_eq!((inverse[0].target.chrom.as_str(), inverse[0].target.start, inverse[0].target.end), ("chr1", 120, 130));
+        
+        // Spanning the gap: only the aligned parts come back
+        let inverse = mapper.map_inverse("chr5", 590, 660, Strand::Plus).unwrap();
+        let ranges: Vec<(u64, u64)> = inverse.iter().map(|s| (s.target.start, s.target.end)).collect();
+        assert_eq!(ranges, vec![(190, 200), (250, 260)]);
+        
+        // Minus strand: chr2:[10, 20) -> chr7:[1980, 1990), back with source_end - left_offset - size
+        let forward = mapper.map("chr2", 10, 20, Strand::Plus).unwrap();
+        assert_eq!((forward[0].target.start, forward[0].target.end, forward[0].target.strand), (1980, 1990, Strand::Minus));
+        let inverse = mapper.map_inverse("chr7", 1980, 1990, Strand::Plus).unwrap();
+        assert_eq!((inverse[0].target.chrom.as_str(), inverse[0].target.start, inverse[0].target.end), ("chr2", 10, 20));
+        assert_eq!(inverse[0].target.strand, Strand::Minus);
+        
+        // Source chromosomes are not target chromosomes
+        assert!(mapper.map_inverse("chr1", 120, 130, Strand::Plus).is_none());
+        assert_eq!(mapper.map_inverse("chr5", 0, 100, Strand::Plus), Some(vec![]));
+        assert!(mapper.index().has_target_chrom("7"));
+        
+        // The inverted index maps forward what map_inverse maps back
+        let inverted = CoordinateMapper::new(
+            ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap()).into_inverse(),
+            ChromStyle::AsIs,
+        );
+        assert_eq!(inverted.map("chr7", 1980, 1990, Strand::Plus), mapper.map_inverse("chr7", 1980, 1990, Strand::Plus));
+        assert_eq!(inverted.index().source_chrom_size("chr7"), Some(2000));
+        assert!(inverted.index().has_target_chrom("chr1"));
+    }
 }
@@ -3,26 +3,36 @@
 //! This module contains the chain file parser, interval index,
 //! and coordinate mapping algorithms.
 
+pub mod bgzf;
 mod chain;
 pub mod dna;
 mod error;
+#[cfg(any(feature = "flat-index", test))]
+mod flat_index;
 mod index;
 pub mod io;
 mod mapper;
 
 pub use chain::{
-    parse_chain_file, parse_chain_bytes, parse_chain_reader, 
-    ChainBlock, ChainFile, ChainHeader, CompressionFormat,
+    parse_chain_file, parse_chain_bytes, parse_chain_bytes_parallel, parse_chain_reader,
+    parse_chain_file_with_progress, parse_chain_reader_with_progress, PROGRESS_INTERVAL_LINES,
+    parse_chain_file_unchecked, parse_chain_reader_unchecked,
+    parse_chain_reader_with_cancel, CancellationToken, CANCEL_CHECK_INTERVAL_LINES,
+    ChainBlock, ChainFile, ChainHeader, ChainValidationError, CompressionFormat,
     ChainParseError as ChainFileError, ChainParseErrorKind,
     detect_compression,
 };
 pub use error::{
-    ChainParseError, ChainResult, ConversionError, ConversionResult,
-    FastCrossMapError, MappingError, MappingResult, Result,
+    ChainParseError, ChainResult, ConversionError, ConversionResult, LiftoverFailureReason,
+    FastCrossMapError, MappingError, MappingResult, ParseStrandError, Result,
+};
+pub use index::{
+    ChainIndex, ChainIndexBuilder, ChainIndexOptions, ChainInterval, ChainStatistics, ChainSummary, ChromCoverage, ChromOverlapStats, Distribution,
+    IntervalValue, COVERAGE_HISTOGRAM_BINS, DEFAULT_MERGE_THRESHOLD,
 };
-pub use index::{ChainIndex, ChainInterval, IntervalValue};
 pub use io::{
     ByteLineIterator, IoStrategy, LineIterator, SmartReader,
+    is_stdio, open_input, create_output, create_output_compressed, CompressedOutput, compressed_output_path, create_unmap_output, unmap_path_for, resolve_threads, STDIO_PATH, MAX_AUTO_THREADS,
     DEFAULT_BUFFER_SIZE, LARGE_BUFFER_SIZE, MMAP_THRESHOLD,
 };
-pub use mapper::{ChromStyle, CompatMode, CoordinateMapper, MapResult, MappingSegment, Strand, normalize_chrom, update_chrom_id, chroms_equivalent, intersect_intervals};
+pub use mapper::{ChromStyle, CompatMode, CoordinateMapper, MapResult, MappingSegment, MappingWithGaps, Strand, normalize_chrom, update_chrom_id, chroms_equivalent, intersect_intervals};
@@ -5,7 +5,7 @@
 //!
 //! **Validates: Requirements 10.1, 10.2, 10.3, 10.4, 10.5, 10.6, 10.7**
 
-use crate::core::{CoordinateMapper, Strand};
+use crate::core::{io, CoordinateMapper, Strand};
 use rust_htslib::bam::{self, Read, Record, Header, HeaderView};
 use rust_htslib::bam::header::HeaderRecord;
 use rust_htslib::bam::record::{Cigar, CigarString};
@@ -19,6 +19,7 @@ pub enum BamError {
     IoError(std::io::Error),
     InvalidCigar(String),
     MappingFailed(String),
+    MissingReference,
 }
 
 impl std::fmt::Display for BamError {
@@ -28,6 +29,7 @@ impl std::fmt::Display for BamError {
             BamError::IoError(e) => write!(f, "IO error: {}", e),
             BamError::InvalidCigar(msg) => write!(f, "Invalid CIGAR: {}", msg),
             BamError::MappingFailed(msg) => write!(f, "Mapping failed: {}", msg),
+            BamError::MissingReference => write!(f, "CRAM output requires a reference genome"),
         }
     }
 }
@@ -75,6 +77,90 @@ pub struct ConversionStats {
     pub failed: usize,
     pub paired: usize,
     pub single: usize,
+    /// Originally unmapped reads copied to the passthrough file
+    pub unmapped_passthrough: usize,
+    /// Lifted reads whose proper-pair flag (0x2) was cleared
+    pub improper_pairs: usize,
+}
+
+/// Expected relative orientation of the two mates of a proper pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PairOrientation {
+    /// Leftmost mate forward, rightmost mate reverse (Illumina paired-end)
+    #[default]
+    Fr,
+    /// Leftmost mate reverse, rightmost mate forward (mate-pair libraries)
+    Rf,
+    /// Both mates on the same strand
+    Ff,
+}
+
+/// Default maximum insert size for a proper pair (bp)
+pub const DEFAULT_MAX_INSERT_SIZE: u64 = 1000;
+
+/// Re-evaluate the proper-pair flag (0x2) of a read after liftover
+///
+/// `flag` is the read's FLAG with the strand bits (0x10, 0x20) already
+/// describing the lifted mates; `chrom1`/`pos1` are the read and
+/// `chrom2`/`pos2` its mate in the target assembly. The pair is proper if
+/// both mates are mapped on the same chromosome, their leftmost positions
+/// are at most `max_insert_size` apart, and their strands match
+/// `orientation`.
+pub fn re_evaluate_proper_pair(
+    flag: u16,
+    chrom1: &str,
+    pos1: u64,
+    chrom2: &str,
+    pos2: u64,
+    max_insert_size: u64,
+    orientation: PairOrientation,
+) -> bool {
+    // Paired, read mapped, mate mapped
+    if flag & 0x1 == 0 || flag & 0x4 != 0 || flag & 0x8 != 0 {
+        return false;
+    }
+    if chrom1 != chrom2 || pos1.abs_diff(pos2) > max_insert_size {
+        return false;
+    }
+    
+    let (read_reverse, mate_reverse) = (flag & 0x10 != 0, flag & 0x20 != 0);
+    let (left_reverse, right_reverse) = if pos1 <= pos2 {
+        (read_reverse, mate_reverse)
+    } else {
+        (mate_reverse, read_reverse)
+    };
+    match orientation {
+        PairOrientation::Fr => !left_reverse && right_reverse,
+        PairOrientation::Rf => left_reverse && !right_reverse,
+        PairOrientation::Ff => left_reverse == right_reverse,
+    }
+}
+
+/// Options for BAM/SAM/CRAM conversion
+#[derive(Debug, Clone)]
+pub struct BamConvertOptions {
+    /// Number of threads for htslib I/O
+    pub threads: usize,
+    /// Output format (see `OutputFormat::from_path`)
+    pub output_format: OutputFormat,
+    /// Copy originally unmapped reads to `<output>.unmapped.bam`
+    pub passthrough_unmapped: bool,
+    /// Largest distance between the mates of a proper pair
+    pub max_insert_size: u64,
+    /// Mate orientation of a proper pair
+    pub pair_orientation: PairOrientation,
+}
+
+impl Default for BamConvertOptions {
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            output_format: OutputFormat::default(),
+            passthrough_unmapped: false,
+            max_insert_size: DEFAULT_MAX_INSERT_SIZE,
+            pair_orientation: PairOrientation::default(),
+        }
+    }
 }
 
 /// CIGAR operation types
@@ -301,11 +387,29 @@ fn reverse_qual(qual: &[u8]) -> Vec<u8> {
     qual.iter().rev().cloned().collect()
 }
 
+/// Lift the mate of a paired read from its leftmost base
+///
+/// Returns the target chromosome, tid and position of the mate and whether
+/// its strand was flipped.
+fn lift_mate(
+    record: &Record,
+    input_header: &HeaderView,
+    output_header: &HeaderView,
+    mapper: &CoordinateMapper,
+) -> Option<(String, i32, u64, bool)> {
+    let chrom = get_chrom_name(input_header, record.mtid())?;
+    let strand = if record.is_mate_reverse() { Strand::Minus } else { Strand::Plus };
+    let seg = mapper.map_single(&chrom, record.mpos() as u64, strand)?;
+    let tid = get_tid(output_header, &seg.target.chrom)?;
+    Some((seg.target.chrom, tid, seg.target.start, seg.target.strand != strand))
+}
+
 fn convert_record(
     record: &Record,
     input_header: &HeaderView,
     output_header: &HeaderView,
     mapper: &CoordinateMapper,
+    options: &BamConvertOptions,
 ) -> Option<(Record, AlignmentTag)> {
     if record.is_unmapped() { return None; }
     
@@ -345,18 +449,48 @@ fn convert_record(
     new_record.set_tid(target_tid);
     new_record.set_pos(target_start as i64);
     
-    // Set mate information
-    // CrossMap behavior: for single-end reads, set RNEXT to "*" (tid=-1) and PNEXT to 0
-    // For paired-end reads, this would need to be updated based on mate mapping
-    // SAM format: PNEXT is 1-based, so 0 in internal representation = 1 in SAM output
-    new_record.set_mtid(-1);  // RNEXT = "*"
-    new_record.set_mpos(0);   // PNEXT = 1 (0-based internal, 1-based in SAM)
-    new_record.set_insert_size(0);  // TLEN = 0
-    
     let mut flags = record.flags();
     if need_revcomp { flags ^= 0x10; }
     // CrossMap behavior: set secondary alignment flag (0x100) for multiple mappings
     if is_multiple { flags |= 0x100; }
+    
+    // Set mate information
+    // CrossMap behavior: for single-end reads, set RNEXT to "*" (tid=-1) and PNEXT to 0
+    // SAM format: PNEXT is 1-based, so 0 in internal representation = 1 in SAM output
+    let mate = if record.is_paired() && !record.is_mate_unmapped() {
+        lift_mate(record, input_header, output_header, mapper)
+    } else {
+        None
+    };
+    match mate {
+        Some((mate_chrom, mate_tid, mate_pos, mate_flipped)) => {
+            if mate_flipped { flags ^= 0x20; }
+            new_record.set_mtid(mate_tid);
+            new_record.set_mpos(mate_pos as i64);
+            // TLEN keeps its length, signed by the new mate order
+            let tlen = if mate_tid != target_tid {
+                0
+            } else if target_start <= mate_pos {
+                record.insert_size().abs()
+            } else {
+                -record.insert_size().abs()
+            };
+            new_record.set_insert_size(tlen);
+            
+            let proper = re_evaluate_proper_pair(
+                flags, target_chrom, target_start, &mate_chrom, mate_pos,
+                options.max_insert_size, options.pair_orientation,
+            );
+            if proper { flags |= 0x2; } else { flags &= !0x2; }
+        }
+        None => {
+            new_record.set_mtid(-1);  // RNEXT = "*"
+            new_record.set_mpos(0);   // PNEXT = 1 (0-based internal, 1-based in SAM)
+            new_record.set_insert_size(0);  // TLEN = 0
+            // Without a lifted mate the pair can't be proper
+            flags &= !0x2;
+        }
+    }
     new_record.set_flags(flags);
     // CrossMap behavior: preserve original MAPQ for multiple mappings
     new_record.set_mapq(record.mapq());
@@ -379,35 +513,130 @@ fn convert_record(
     Some((new_record, tag))
 }
 
-/// Determine output format based on file extension
-fn get_output_format(path: &Path) -> bam::Format {
-    match path.extension().and_then(|e| e.to_str()) {
-        Some("sam") => bam::Format::Sam,
-        Some("cram") => bam::Format::Cram,
-        _ => bam::Format::Bam, // Default to BAM
+/// Output alignment format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Bam,
+    Sam,
+    /// CRAM requires the target reference genome
+    Cram,
+}
+
+impl OutputFormat {
+    /// Determine output format based on file extension (default BAM)
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("sam") => OutputFormat::Sam,
+            Some("cram") => OutputFormat::Cram,
+            _ => OutputFormat::Bam,
+        }
+    }
+    
+    fn to_htslib(self) -> bam::Format {
+        match self {
+            OutputFormat::Bam => bam::Format::Bam,
+            OutputFormat::Sam => bam::Format::Sam,
+            OutputFormat::Cram => bam::Format::Cram,
+        }
     }
 }
 
 /// Convert a BAM/SAM/CRAM file
+///
+/// Paired reads get the mate lifted as well, with the proper-pair flag
+/// re-evaluated using the default insert size and orientation (see
+/// `convert_bam_with_options`).
+///
+/// # Arguments
+/// * `input` - Input BAM/SAM/CRAM file path
+/// * `output` - Output alignment file path
+/// * `mapper` - Coordinate mapper
+/// * `threads` - Number of threads for htslib I/O
+/// * `output_format` - Output format (see `OutputFormat::from_path`)
+/// * `ref_genome` - Target reference FASTA; required for CRAM output and
+///   used to decode CRAM input
+/// * `passthrough_unmapped` - Copy originally unmapped reads (FLAG 0x4)
+///   unchanged to `<output>.unmapped.bam` instead of the lifted file
+///   (ignored when writing to stdout)
+///
+/// `input` and `output` may be `-` for stdin/stdout.
 pub fn convert_bam<P: AsRef<Path>>(
     input: P,
     output: P,
     mapper: &CoordinateMapper,
     threads: usize,
+    output_format: OutputFormat,
+    ref_genome: Option<P>,
+    passthrough_unmapped: bool,
 ) -> Result<ConversionStats, BamError> {
-    let mut reader = bam::Reader::from_path(input.as_ref())?;
+    let options = BamConvertOptions {
+        threads,
+        output_format,
+        passthrough_unmapped,
+        ..Default::default()
+    };
+    convert_bam_with_options(input, output, mapper, ref_genome, &options)
+}
+
+/// Convert a BAM/SAM/CRAM file with extended options
+///
+/// A lifted read whose mate lands on another chromosome, further than
+/// `max_insert_size` away, or in the wrong orientation loses the
+/// proper-pair flag (0x2); a read whose mate can't be lifted loses it too.
+pub fn convert_bam_with_options<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    mapper: &CoordinateMapper,
+    ref_genome: Option<P>,
+    options: &BamConvertOptions,
+) -> Result<ConversionStats, BamError> {
+    let threads = options.threads;
+    let output_format = options.output_format;
+    let passthrough_unmapped = options.passthrough_unmapped;
+    if output_format == OutputFormat::Cram && ref_genome.is_none() {
+        return Err(BamError::MissingReference);
+    }
+    
+    let mut reader = if io::is_stdio(&input) {
+        bam::Reader::from_stdin()?
+    } else {
+        bam::Reader::from_path(input.as_ref())?
+    };
     reader.set_threads(threads)?;
+    if let Some(ref_path) = &ref_genome {
+        if OutputFormat::from_path(input.as_ref()) == OutputFormat::Cram {
+            reader.set_reference(ref_path.as_ref())?;
+        }
+    }
     let input_header = reader.header().clone();
     
     let target_sizes = mapper.target_sizes();
     let output_header = build_target_header(&input_header, target_sizes);
     
-    // Determine output format based on file extension
-    let output_format = get_output_format(output.as_ref());
-    let mut writer = bam::Writer::from_path(output.as_ref(), &output_header, output_format)?;
+    let mut writer = if io::is_stdio(&output) {
+        bam::Writer::from_stdout(&output_header, output_format.to_htslib())?
+    } else {
+        bam::Writer::from_path(output.as_ref(), &output_header, output_format.to_htslib())?
+    };
     writer.set_threads(threads)?;
+    if output_format == OutputFormat::Cram {
+        if let Some(ref_path) = &ref_genome {
+            writer.set_reference(ref_path.as_ref())?;
+        }
+    }
     let output_header_view = writer.header().clone();
     
+    // Unmapped reads keep the original header since they aren't modified
+    let mut passthrough_writer = if passthrough_unmapped && !io::is_stdio(&output) {
+        let path = output.as_ref().with_extension("unmapped.bam");
+        let mut w = bam::Writer::from_path(&path, &Header::from_template(&input_header), bam::Format::Bam)?;
+        w.set_threads(threads)?;
+        Some(w)
+    } else {
+        None
+    };
+    
     let mut stats = ConversionStats::default();
     let mut record = Record::new();
     
@@ -418,6 +647,11 @@ pub fn convert_bam<P: AsRef<Path>>(
         // Handle originally unmapped reads - CrossMap outputs them as-is
         if record.is_unmapped() {
             stats.unmapped += 1;
+            if let Some(w) = passthrough_writer.as_mut() {
+                w.write(&record)?;
+                stats.unmapped_passthrough += 1;
+                continue;
+            }
             // Create a new record for unmapped read
             let mut new_record = Record::new();
             new_record.set(record.qname(), None, &record.seq().as_bytes(), &record.qual().to_vec());
@@ -432,8 +666,14 @@ pub fn convert_bam<P: AsRef<Path>>(
             continue;
         }
         
-        match convert_record(&record, &input_header, &output_header_view, mapper) {
-            Some((new_record, _tag)) => { writer.write(&new_record)?; stats.mapped += 1; }
+        match convert_record(&record, &input_header, &output_header_view, mapper, options) {
+            Some((new_record, _tag)) => {
+                if record.is_proper_pair() && new_record.flags() & 0x2 == 0 {
+                    stats.improper_pairs += 1;
+                }
+                writer.write(&new_record)?;
+                stats.mapped += 1;
+            }
             None => {
                 // CrossMap behavior: output failed-to-map reads as unmapped
                 stats.failed += 1;
@@ -521,4 +761,39 @@ mod tests {
         assert_eq!(AlignmentTag::QF.as_str(), "QF");
         assert_eq!(AlignmentTag::MM.as_str(), "MM");
     }
+
+    #[test]
+    fn test_output_format_from_path() {
+        assert_eq!(OutputFormat::from_path(Path::new("out.sam")), OutputFormat::Sam);
+        assert_eq!(OutputFormat::from_path(Path::new("out.cram")), OutputFormat::Cram);
+        assert_eq!(OutputFormat::from_path(Path::new("out.bam")), OutputFormat::Bam);
+        assert_eq!(OutputFormat::from_path(Path::new("out")), OutputFormat::Bam);
+    }
+
+    #[test]
+    fn test_re_evaluate_proper_pair() {
+        use PairOrientation::*;
+        // 0x1 paired, 0x20 mate reverse: read forward at 100, mate reverse at 400
+        let fr = 0x1 | 0x20;
+        assert!(re_evaluate_proper_pair(fr, "chr1", 100, "chr1", 400, 1000, Fr));
+        // Mates lifted to different chromosomes
+        assert!(!re_evaluate_proper_pair(fr, "chr1", 100, "chr2", 400, 1000, Fr));
+        // Insert size over the limit
+        assert!(!re_evaluate_proper_pair(fr, "chr1", 100, "chr1", 1200, 1000, Fr));
+        assert!(re_evaluate_proper_pair(fr, "chr1", 100, "chr1", 1100, 1000, Fr));
+        // Mate order swapped by a negative-strand chain: reverse read now leftmost
+        assert!(!re_evaluate_proper_pair(fr, "chr1", 400, "chr1", 100, 1000, Fr));
+        assert!(re_evaluate_proper_pair(fr, "chr1", 400, "chr1", 100, 1000, Rf));
+        
+        // The mate of the reverse read
+        let rf = 0x1 | 0x10;
+        assert!(re_evaluate_proper_pair(rf, "chr1", 400, "chr1", 100, 1000, Fr));
+        assert!(!re_evaluate_proper_pair(rf, "chr1", 400, "chr1", 100, 1000, Ff));
+        assert!(re_evaluate_proper_pair(0x1 | 0x10 | 0x20, "chr1", 100, "chr1", 400, 1000, Ff));
+        
+        // Unpaired, read unmapped or mate unmapped
+        assert!(!re_evaluate_proper_pair(0x20, "chr1", 100, "chr1", 400, 1000, Fr));
+        assert!(!re_evaluate_proper_pair(fr | 0x4, "chr1", 100, "chr1", 400, 1000, Fr));
+        assert!(!re_evaluate_proper_pair(fr | 0x8, "chr1", 100, "chr1", 400, 1000, Fr));
+    }
 }
@@ -4,12 +4,13 @@
 //!
 //! **Validates: Requirements 4.1, 4.2, 4.3, 4.4, 4.5, 4.6, 4.7**
 
-use crate::core::{CoordinateMapper, MappingSegment, Strand};
+use crate::core::{io, CoordinateMapper, MappingSegment, Strand};
 use memchr::memchr;
 use rayon::prelude::*;
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::Path;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::warn;
 
 /// BED record representation for output
 #[derive(Debug, Clone)]
@@ -129,6 +130,21 @@ impl<'a> BedRecordView<'a> {
         self.field(4)
     }
     
+    /// Get score field (field 4) as an integer, if it is one
+    pub fn score_int(&self) -> Option<i64> {
+        self.score().and_then(|s| s.parse().ok())
+    }
+    
+    /// Get score field (field 4) as a float
+    ///
+    /// Accepts integer and floating point scores (e.g. signal values);
+    /// non-finite values are rejected.
+    pub fn score_float(&self) -> Option<f64> {
+        self.score()
+            .and_then(|s| s.parse::<f64>().ok())
+            .filter(|v| v.is_finite())
+    }
+    
     /// Get strand field (field 5) if present
     pub fn strand(&self) -> Option<Strand> {
         self.field(5).and_then(|s| {
@@ -185,6 +201,22 @@ impl<'a> BedRecordView<'a> {
     pub fn is_bed6(&self) -> bool {
         self.field_count() >= 6
     }
+    
+    /// Check if the interval is empty (`start == end`)
+    pub fn is_zero_length(&self) -> bool {
+        self.start == self.end
+    }
+    
+    /// Check that the interval is non-empty and `start < end`
+    pub fn validate(&self) -> Result<(), BedParseError> {
+        if self.start > self.end {
+            return Err(BedParseError::InvalidRange { start: self.start, end: self.end });
+        }
+        if self.is_zero_length() {
+            return Err(BedParseError::ZeroLength { chrom: self.chrom.to_string(), pos: self.start });
+        }
+        Ok(())
+    }
 }
 
 
@@ -203,17 +235,161 @@ pub enum BedParseError {
     #[error("Invalid number in field {0}: {1}")]
     InvalidNumber(&'static str, String),
     
+    #[error("Score {score} outside the BED range 0-1000 in record: {line}")]
+    ScoreOutOfRange { score: i64, line: String },
+    
+    #[error("Start {start} is greater than end {end}")]
+    InvalidRange { start: u64, end: u64 },
+    
+    #[error("Zero-length interval at {chrom}:{pos}")]
+    ZeroLength { chrom: String, pos: u64 },
+    
+    #[error("{0}")]
+    WithContext(Box<BedParseErrorWithContext>),
+    
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
 
+impl BedParseError {
+    /// Attach the offending line to an error (byte offset 0; set it with
+    /// `BedParseErrorWithContext::at_offset`)
+    pub fn with_line(error: BedParseError, line: String) -> BedParseErrorWithContext {
+        BedParseErrorWithContext { error, line, offset: 0 }
+    }
+}
+
+/// A `BedParseError` together with the input line that caused it
+#[derive(Debug, thiserror::Error)]
+#[error("{error} (byte {offset}): {line}")]
+pub struct BedParseErrorWithContext {
+    pub error: BedParseError,
+    /// The full offending line, without its line terminator
+    pub line: String,
+    /// Byte offset of the start of the line in the input
+    pub offset: u64,
+}
+
+impl BedParseErrorWithContext {
+    /// Set the byte offset of the line in the input
+    pub fn at_offset(mut self, offset: u64) -> Self {
+        self.offset = offset;
+        self
+    }
+}
+
+impl From<BedParseErrorWithContext> for BedParseError {
+    fn from(context: BedParseErrorWithContext) -> Self {
+        BedParseError::WithContext(Box::new(context))
+    }
+}
+
 /// Conversion statistics
 #[derive(Debug, Default, Clone)]
 pub struct ConversionStats {
     pub total: usize,
     pub success: usize,
     pub failed: usize,
+    /// Records that mapped to more than one location (also counted in
+    /// `success`); these are the records written to `multimap_output` when set
     pub multi_map: usize,
+    /// Zero-length records dropped by `ZeroLengthPolicy::Skip`
+    pub skipped: usize,
+    /// Lines that could not be parsed (also counted in `failed`)
+    pub invalid: usize,
+}
+
+/// Handling of zero-length records (`start == end`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZeroLengthPolicy {
+    /// Write the record to the unmap file
+    Fail,
+    /// Log a warning and convert the record as-is
+    #[default]
+    Warn,
+    /// Drop the record (neither output nor unmap)
+    Skip,
+    /// Convert as the 1-bp interval `[start, start + 1)`
+    Expand,
+}
+
+/// Coordinate convention of the start column in BED input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoordinateSystem {
+    /// Standard BED: 0-based, half-open `[start, end)`
+    #[default]
+    ZeroBased,
+    /// 1-based, closed `[start, end]` (as exported by some tools); output
+    /// keeps the same convention
+    OneBased,
+}
+
+/// Options for BED conversion
+#[derive(Debug, Clone)]
+pub struct BedConvertOptions {
+    /// Number of threads for parallel processing (1 = sequential, 0 = auto)
+    pub threads: usize,
+    /// Fail on integer scores outside 0-1000 (float scores are passed through)
+    pub strict_score: bool,
+    /// In parallel mode, write each worker's results to `<output>.part_N`
+    /// files and concatenate them at the end instead of collecting all
+    /// output lines in memory
+    pub per_thread_output: bool,
+    /// What to do with zero-length records
+    pub zero_length_policy: ZeroLengthPolicy,
+    /// Precede each unparseable line in the unmap file with a `#` comment
+    /// giving the error and its byte offset, and wrap fatal errors in
+    /// `BedParseError::WithContext`. Forces sequential conversion, since
+    /// offsets come from a single reader.
+    pub verbose_errors: bool,
+    /// Lines per parallel work item (0 is treated as 1). Smaller chunks
+    /// balance heavy BED12 records better; larger ones cut scheduling
+    /// overhead on simple BED3.
+    pub chunk_size: usize,
+    /// Pad or truncate every converted record to exactly this many columns
+    /// (see [`normalize_bed_fields`]); unmapped records are left as-is
+    pub normalize_field_count: Option<usize>,
+    /// Write a self-contained HTML summary of the conversion to this path
+    /// (see [`report::bed_report_html`](crate::formats::report::bed_report_html))
+    pub generate_report: Option<PathBuf>,
+    /// Write every line of multi-mapped records to this file instead of
+    /// the main output
+    pub multimap_output: Option<PathBuf>,
+    /// In parallel mode, stream chunks through bounded channels (see
+    /// [`convert_bed_channel`]) instead of reading the whole input first.
+    /// Also parallelizes stdin input.
+    pub streaming: bool,
+    /// Coordinate convention of the input start column
+    pub coord_system: CoordinateSystem,
+    /// Warn about records whose name is longer than this many characters
+    /// (the UCSC browser truncates names past 255)
+    pub max_name_length: Option<usize>,
+    /// Also truncate such names to `max_name_length`, ending in `...`
+    pub truncate_names: bool,
+    /// BGZF-compress the main output (unmap and multi-map files stay plain
+    /// text)
+    pub compress: bool,
+}
+
+impl Default for BedConvertOptions {
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            strict_score: false,
+            per_thread_output: false,
+            zero_length_policy: ZeroLengthPolicy::default(),
+            verbose_errors: false,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            normalize_field_count: None,
+            generate_report: None,
+            multimap_output: None,
+            streaming: false,
+            coord_system: CoordinateSystem::default(),
+            max_name_length: None,
+            truncate_names: false,
+            compress: false,
+        }
+    }
 }
 
 /// Result of converting a single BED record
@@ -227,6 +403,8 @@ pub enum ConversionResult {
     Failed(String),
     /// Comment or header line (pass through)
     PassThrough(String),
+    /// Dropped on purpose (zero-length record under `ZeroLengthPolicy::Skip`)
+    Skipped,
 }
 
 /// Represents a single block in BED12 format
@@ -236,6 +414,91 @@ struct Block {
     end: u64,    // Absolute end position
 }
 
+/// Check an integer score against the BED range when `strict` is set
+fn check_score(view: &BedRecordView, line: &str, strict: bool) -> Result<(), BedParseError> {
+    if strict {
+        if let Some(score) = view.score_int() {
+            if !(0..=1000).contains(&score) {
+                return Err(BedParseError::ScoreOutOfRange { score, line: line.to_string() });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Normalize an itemRgb value to decimal `R,G,B`
+///
+/// Accepts `#RRGGBB`, `#RGB` and common color names (case-insensitive).
+/// Values already in decimal form, and anything unrecognized, are returned
+/// unchanged.
+pub fn normalize_itemrgb(value: &str) -> String {
+    let trimmed = value.trim();
+    if let Some(hex) = trimmed.strip_prefix('#') {
+        let channels = match hex.len() {
+            6 => (0..3)
+                .map(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok())
+                .collect::<Option<Vec<u8>>>(),
+            3 => (0..3)
+                .map(|i| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|v| v * 17))
+                .collect::<Option<Vec<u8>>>(),
+            _ => None,
+        };
+        return match channels {
+            Some(c) => format!("{},{},{}", c[0], c[1], c[2]),
+            None => value.to_string(),
+        };
+    }
+
+    let rgb = match trimmed.to_ascii_lowercase().as_str() {
+        "black" => "0,0,0",
+        "white" => "255,255,255",
+        "red" => "255,0,0",
+        "green" => "0,128,0",
+        "blue" => "0,0,255",
+        "yellow" => "255,255,0",
+        "cyan" => "0,255,255",
+        "magenta" => "255,0,255",
+        "orange" => "255,165,0",
+        "purple" => "128,0,128",
+        "pink" => "255,192,203",
+        "brown" => "165,42,42",
+        "gray" | "grey" => "128,128,128",
+        _ => return value.to_string(),
+    };
+    rgb.to_string()
+}
+
+/// Pad or truncate a BED line to exactly `count` tab-separated columns
+/// 
+/// Missing name and strand become `.`; score, thickStart, thickEnd and
+/// itemRgb become `0`. Missing BED12 block fields describe a single block
+/// spanning the whole record, and columns past the twelfth are `.`. A count
+/// below 3 is treated as 3.
+pub fn normalize_bed_fields(line: &str, count: usize) -> String {
+    let count = count.max(3);
+    let mut fields: Vec<&str> = line.split('\t').collect();
+    fields.truncate(count);
+    
+    let mut output = fields.join("\t");
+    let length = || {
+        let start = fields.get(1).and_then(|s| s.parse::<u64>().ok()).unwrap_or(0);
+        let end = fields.get(2).and_then(|s| s.parse::<u64>().ok()).unwrap_or(start);
+        end.saturating_sub(start)
+    };
+    for i in fields.len()..count {
+        output.push('\t');
+        match i {
+            3 | 5 => output.push('.'),
+            4 | 6 | 7 | 8 => output.push('0'),
+            9 => output.push('1'),
+            10 => output.push_str(&format!("{},", length())),
+            11 => output.push_str("0,"),
+            _ => output.push('.'),
+        }
+    }
+    output
+}
+
 /// Parse BED12 blocks from a record view
 fn parse_bed12_blocks(view: &BedRecordView) -> Option<Vec<Block>> {
     let chrom_start = view.start;
@@ -417,7 +680,7 @@ fn convert_bed12_record(
     // item_rgb (field 8)
     if let Some(rgb) = view.item_rgb() {
         output.push('\t');
-        output.push_str(rgb);
+        output.push_str(&normalize_itemrgb(rgb));
     }
     
     // block_count (field 9)
@@ -488,6 +751,118 @@ fn convert_bed_record(
     convert_bed_record_simple(view, mapper, input_strand)
 }
 
+/// Convert a parsed data record, applying the zero-length policy first and
+/// field count normalization last
+fn convert_bed_view(
+    mut view: BedRecordView,
+    line: &str,
+    mapper: &CoordinateMapper,
+    options: &BedConvertOptions,
+) -> ConversionResult {
+    let one_based = options.coord_system == CoordinateSystem::OneBased;
+    if one_based {
+        // 1-based closed [start, end] is 0-based half-open [start - 1, end)
+        if view.start == 0 {
+            return ConversionResult::Failed(format_unmapped_line(&view));
+        }
+        view.start -= 1;
+    }
+
+    if view.is_zero_length() {
+        match options.zero_length_policy {
+            ZeroLengthPolicy::Fail => {
+                // Report the record with its original 1-based start
+                if one_based {
+                    view.start += 1;
+                }
+                return ConversionResult::Failed(format_unmapped_line(&view));
+            }
+            ZeroLengthPolicy::Warn => warn!("Zero-length BED record: {}", line),
+            ZeroLengthPolicy::Skip => return ConversionResult::Skipped,
+            ZeroLengthPolicy::Expand => view.end = view.start + 1,
+        }
+    }
+    
+    let truncate_to = match (options.max_name_length, view.name()) {
+        (Some(max), Some(name)) if name.chars().count() > max => {
+            warn!("BED name longer than {} characters: {}", max, name);
+            options.truncate_names.then_some(max)
+        }
+        _ => None,
+    };
+    
+    let input_strand = view.strand().unwrap_or(Strand::Plus);
+    let result = convert_bed_record(&view, mapper, input_strand);
+    let result = match (truncate_to, result) {
+        (Some(max), ConversionResult::Success(line)) => ConversionResult::Success(truncate_name_column(&line, max)),
+        (Some(max), ConversionResult::MultiMap(lines)) => {
+            ConversionResult::MultiMap(lines.iter().map(|l| truncate_name_column(l, max)).collect())
+        }
+        (_, result) => result,
+    };
+    let result = if one_based {
+        match result {
+            ConversionResult::Success(line) => ConversionResult::Success(shift_start_column(&line)),
+            ConversionResult::MultiMap(lines) => {
+                ConversionResult::MultiMap(lines.iter().map(|l| shift_start_column(l)).collect())
+            }
+            ConversionResult::Failed(_) => {
+                view.start += 1;
+                ConversionResult::Failed(format_unmapped_line(&view))
+            }
+            result => result,
+        }
+    } else {
+        result
+    };
+    
+    match (options.normalize_field_count, result) {
+        (Some(count), ConversionResult::Success(line)) => {
+            ConversionResult::Success(normalize_bed_fields(&line, count))
+        }
+        (Some(count), ConversionResult::MultiMap(lines)) => {
+            ConversionResult::MultiMap(lines.iter().map(|l| normalize_bed_fields(l, count)).collect())
+        }
+        (_, result) => result,
+    }
+}
+
+/// Shorten `name` to at most `max` characters, marking the cut with `...`
+/// 
+/// Limits below 4 leave no room for the marker and cut the name plainly.
+fn truncate_name(name: &str, max: usize) -> String {
+    if name.chars().count() <= max {
+        return name.to_string();
+    }
+    if max < 4 {
+        return name.chars().take(max).collect();
+    }
+    let mut truncated: String = name.chars().take(max - 3).collect();
+    truncated.push_str("...");
+    truncated
+}
+
+/// Apply [`truncate_name`] to the name column of an output line
+fn truncate_name_column(line: &str, max: usize) -> String {
+    let mut fields: Vec<String> = line.split('\t').map(String::from).collect();
+    if let Some(name) = fields.get_mut(3) {
+        *name = truncate_name(name, max);
+    }
+    fields.join("\t")
+}
+
+/// Convert the 0-based start column of an output line back to 1-based
+fn shift_start_column(line: &str) -> String {
+    let mut fields = line.splitn(3, '\t');
+    let (Some(chrom), Some(start), Some(rest)) = (fields.next(), fields.next(), fields.next()) else {
+        return line.to_string();
+    };
+    match start.parse::<u64>() {
+        Ok(start) => format!("{}\t{}\t{}", chrom, start + 1, rest),
+        Err(_) => line.to_string(),
+    }
+}
+
 /// Format output line for a successfully mapped segment
 fn format_output_line(view: &BedRecordView, seg: &MappingSegment) -> String {
     let mut output = String::with_capacity(256);
@@ -526,27 +901,30 @@ fn format_output_line(view: &BedRecordView, seg: &MappingSegment) -> String {
         
         // BED12 fields (6-11) - adjust coordinates relative to new position
         if view.is_bed12() {
+            // A minus-strand target flips the record: offsets from chromStart
+            // become offsets from chromEnd and the block order reverses
+            let flipped = seg.target.strand != view.strand().unwrap_or(Strand::Plus);
+            let span = view.end - view.start;
+            let thick_start = view.thick_start().unwrap_or(view.start).clamp(view.start, view.end) - view.start;
+            let thick_end = view.thick_end().unwrap_or(view.end).clamp(view.start, view.end) - view.start;
+            let (thick_start, thick_end) = if flipped {
+                (span - thick_end, span - thick_start)
+            } else {
+                (thick_start, thick_end)
+            };
+            
             // thick_start (field 6)
-            if let Some(thick_start) = view.thick_start() {
-                output.push('\t');
-                // Adjust thick_start relative to new coordinates
-                let offset = seg.target.start as i64 - view.start as i64;
-                let new_thick_start = (thick_start as i64 + offset).max(seg.target.start as i64) as u64;
-                output.push_str(&new_thick_start.to_string());
-            }
+            output.push('\t');
+            output.push_str(&(seg.target.start + thick_start).min(seg.target.end).to_string());
             
             // thick_end (field 7)
-            if let Some(thick_end) = view.thick_end() {
-                output.push('\t');
-                let offset = seg.target.start as i64 - view.start as i64;
-                let new_thick_end = (thick_end as i64 + offset).min(seg.target.end as i64) as u64;
-                output.push_str(&new_thick_end.to_string());
-            }
+            output.push('\t');
+            output.push_str(&(seg.target.start + thick_end).min(seg.target.end).to_string());
             
-            // item_rgb (field 8) - preserve as-is
+            // item_rgb (field 8) - normalized to decimal R,G,B
             if let Some(rgb) = view.item_rgb() {
                 output.push('\t');
-                output.push_str(rgb);
+                output.push_str(&normalize_itemrgb(rgb));
             }
             
             // block_count (field 9) - preserve as-is
@@ -555,49 +933,24 @@ fn format_output_line(view: &BedRecordView, seg: &MappingSegment) -> String {
                 output.push_str(count);
             }
             
-            // block_sizes (field 10) - preserve as-is
-            if let Some(sizes) = view.block_sizes() {
-                output.push('\t');
-                output.push_str(sizes);
-            }
-            
-            // block_starts (field 11) - recalculate relative to new chromStart
-            // CrossMap behavior: block_starts are relative to chromStart (always starting from 0)
-            // When coordinates change, we need to recalculate block_starts
-            // The first block_start should always be 0, and subsequent ones are adjusted
-            if let Some(starts_str) = view.block_starts() {
-                output.push('\t');
-                // Parse original block_starts and recalculate relative to new position
-                // CrossMap recalculates block_starts so they are relative to the new chromStart
-                // This means the first block_start becomes 0, and others are adjusted accordingly
-                let original_start = view.start;
-                let new_start = seg.target.start;
-                
-                // Calculate the offset between old and new start positions
-                let offset = new_start as i64 - original_start as i64;
-                
-                let new_starts: Vec<String> = starts_str
-                    .split(',')
-                    .filter(|s| !s.is_empty())
-                    .map(|s| {
-                        if let Ok(start_val) = s.parse::<i64>() {
-                            // Original absolute position = original_start + start_val
-                            // New absolute position = original_start + start_val + offset
-                            // New relative position = (original_start + start_val + offset) - new_start
-                            //                       = original_start + start_val + offset - new_start
-                            //                       = original_start + start_val + (new_start - original_start) - new_start
-                            //                       = start_val
-                            // But CrossMap actually recalculates based on the mapped coordinates
-                            // The new relative position should be: start_val + offset
-                            // where offset = new_start - original_start
-                            let new_relative = start_val + offset;
-                            new_relative.to_string()
-                        } else {
-                            s.to_string()
-                        }
-                    })
-                    .collect();
-                output.push_str(&new_starts.join(","));
+            // block_sizes (field 10) and block_starts (field 11)
+            // Block starts are relative to chromStart, so they only change
+            // when the record is flipped; the first one stays 0 either way
+            if let (Some(sizes_str), Some(starts_str)) = (view.block_sizes(), view.block_starts()) {
+                match flip_bed12_blocks(sizes_str, starts_str, span).filter(|_| flipped) {
+                    Some((sizes, starts)) => {
+                        output.push('\t');
+                        output.push_str(&sizes);
+                        output.push('\t');
+                        output.push_str(&starts);
+                    }
+                    None => {
+                        output.push('\t');
+                        output.push_str(sizes_str);
+                        output.push('\t');
+                        output.push_str(starts_str);
+                    }
+                }
             }
         }
         
@@ -613,6 +966,39 @@ fn format_output_line(view: &BedRecordView, seg: &MappingSegment) -> String {
     output
 }
 
+/// Block sizes and starts of a BED12 record of length `span` after flipping
+/// it onto the minus strand
+/// 
+/// A block at `[start, start + size)` moves to `[span - start - size, span - start)`;
+/// both lists are reversed so the starts stay ascending, and each keeps the
+/// input's trailing comma if it had one. `None` if a field is malformed or a
+/// block extends past `span`.
+fn flip_bed12_blocks(sizes_str: &str, starts_str: &str, span: u64) -> Option<(String, String)> {
+    let parse = |list: &str| -> Option<Vec<u64>> {
+        list.split(',').filter(|s| !s.is_empty()).map(|s| s.parse().ok()).collect()
+    };
+    let (sizes, starts) = (parse(sizes_str)?, parse(starts_str)?);
+    if sizes.len() != starts.len() {
+        return None;
+    }
+    
+    let mut flipped = Vec::with_capacity(sizes.len());
+    for (&size, &start) in sizes.iter().zip(&starts).rev() {
+        flipped.push((size, span.checked_sub(start.checked_add(size)?)?));
+    }
+    let join = |values: &mut dyn Iterator<Item = u64>, original: &str| {
+        let mut list = values.map(|v| v.to_string()).collect::<Vec<_>>().join(",");
+        if original.ends_with(',') {
+            list.push(',');
+        }
+        list
+    };
+    Some((
+        join(&mut flipped.iter().map(|&(size, _)| size), sizes_str),
+        join(&mut flipped.iter().map(|&(_, start)| start), starts_str),
+    ))
+}
+
 /// Format unmapped line for failed conversion
 fn format_unmapped_line(view: &BedRecordView) -> String {
     // Reconstruct original line
@@ -657,17 +1043,17 @@ impl Default for ParseBuffer {
     }
 }
 
-/// Chunk size for parallel processing
-const CHUNK_SIZE: usize = 10000;
+/// Default number of lines per parallel work item
+pub const DEFAULT_CHUNK_SIZE: usize = 10000;
 
 /// Convert a BED file using the coordinate mapper (sequential version)
 /// 
 /// # Arguments
-/// * `input` - Input BED file path
-/// * `output` - Output BED file path for successfully mapped records
-/// * `unmap` - Output file path for unmapped records
+/// * `input` - Input BED file path (`-` for stdin)
+/// * `output` - Output BED file path for successfully mapped records (`-` for stdout)
+/// * `unmap` - Output file path for unmapped records (empty path: write them to stderr)
 /// * `mapper` - Coordinate mapper with loaded chain index
-/// * `threads` - Number of threads for parallel processing (1 = sequential)
+/// * `threads` - Number of threads for parallel processing (1 = sequential, forced for stdin)
 /// 
 /// # Returns
 /// Conversion statistics
@@ -678,11 +1064,47 @@ pub fn convert_bed<P: AsRef<Path>>(
     mapper: &CoordinateMapper,
     threads: usize,
 ) -> Result<ConversionStats, BedParseError> {
-    if threads > 1 {
-        convert_bed_parallel(input, output, unmap, mapper, threads)
+    let options = BedConvertOptions {
+        threads,
+        ..Default::default()
+    };
+    convert_bed_with_options(input, output, unmap, mapper, &options)
+}
+
+/// Convert a BED file with extended options
+///
+/// With `strict_score`, the first record whose integer score falls outside
+/// 0-1000 aborts the conversion with `BedParseError::ScoreOutOfRange`.
+/// With `generate_report`, the HTML report is written once conversion has
+/// finished.
+pub fn convert_bed_with_options<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    unmap: P,
+    mapper: &CoordinateMapper,
+    options: &BedConvertOptions,
+) -> Result<ConversionStats, BedParseError> {
+    if options.threads == 0 {
+        let options = BedConvertOptions { threads: io::resolve_threads(0), ..options.clone() };
+        return convert_bed_with_options(input, output, unmap, mapper, &options);
+    }
+    let report_input = input.as_ref().to_path_buf();
+    let stats = if options.threads > 1 && options.streaming && !options.verbose_errors {
+        convert_bed_streaming(input, output, unmap, mapper, options)?
+    } else if options.threads > 1 && !io::is_stdio(&input) && !options.verbose_errors {
+        if options.per_thread_output && !io::is_stdio(&output) {
+            convert_bed_parallel_per_thread(input, output, unmap, mapper, options)?
+        } else {
+            convert_bed_parallel(input, output, unmap, mapper, options)?
+        }
     } else {
-        convert_bed_sequential(input, output, unmap, mapper)
+        convert_bed_sequential(input, output, unmap, mapper, options)?
+    };
+    
+    if let Some(report_path) = &options.generate_report {
+        crate::formats::report::write_bed_report(report_path, &report_input, &stats, mapper)?;
     }
+    Ok(stats)
 }
 
 /// Sequential BED conversion (single-threaded)
@@ -691,18 +1113,21 @@ fn convert_bed_sequential<P: AsRef<Path>>(
     output: P,
     unmap: P,
     mapper: &CoordinateMapper,
+    options: &BedConvertOptions,
 ) -> Result<ConversionStats, BedParseError> {
-    let input_file = std::fs::File::open(input.as_ref())?;
-    let reader = BufReader::with_capacity(128 * 1024, input_file);
+    let reader = io::open_input(input.as_ref(), 128 * 1024)?;
     
     // Use BufWriter to avoid per-line syscalls (critical for performance)
-    let mut output_file = BufWriter::with_capacity(128 * 1024, std::fs::File::create(output.as_ref())?);
-    let mut unmap_file = BufWriter::with_capacity(64 * 1024, std::fs::File::create(unmap.as_ref())?);
+    let mut output_file = io::create_output_compressed(output.as_ref(), 128 * 1024, options.compress)?;
+    let mut unmap_file = io::create_unmap_output(unmap.as_ref(), 64 * 1024)?;
+    let mut multimap_file = create_multimap_output(options)?;
     
     let mut stats = ConversionStats::default();
     let mut line_buf = String::with_capacity(4096);
     
     let mut reader = reader;
+    // Byte offset of the current line, for verbose errors
+    let mut next_offset = 0u64;
     
     loop {
         line_buf.clear();
@@ -710,6 +1135,8 @@ fn convert_bed_sequential<P: AsRef<Path>>(
         if bytes_read == 0 {
             break;
         }
+        let offset = next_offset;
+        next_offset += bytes_read as u64;
         
         // Remove trailing newline
         let line = line_buf.trim_end();
@@ -728,18 +1155,26 @@ fn convert_bed_sequential<P: AsRef<Path>>(
         // Parse the BED record
         match BedRecordView::parse(line.as_bytes()) {
             Ok(view) => {
-                // Get input strand for mapping
-                let input_strand = view.strand().unwrap_or(Strand::Plus);
+                if let Err(e) = check_score(&view, line, options.strict_score) {
+                    if options.verbose_errors {
+                        return Err(BedParseError::with_line(e, line.to_string()).at_offset(offset).into());
+                    }
+                    return Err(e);
+                }
                 
                 // Convert the record
-                match convert_bed_record(&view, mapper, input_strand) {
+                match convert_bed_view(view, line, mapper, options) {
                     ConversionResult::Success(output_line) => {
                         writeln!(output_file, "{}", output_line)?;
                         stats.success += 1;
                     }
                     ConversionResult::MultiMap(output_lines) => {
+                        let writer: &mut dyn Write = match multimap_file.as_mut() {
+                            Some(multimap_file) => multimap_file,
+                            None => &mut output_file,
+                        };
                         for output_line in output_lines {
-                            writeln!(output_file, "{}", output_line)?;
+                            writeln!(writer, "{}", output_line)?;
                         }
                         stats.success += 1;
                         stats.multi_map += 1;
@@ -751,42 +1186,78 @@ fn convert_bed_sequential<P: AsRef<Path>>(
                     ConversionResult::PassThrough(line) => {
                         writeln!(output_file, "{}", line)?;
                     }
+                    ConversionResult::Skipped => {
+                        stats.skipped += 1;
+                    }
                 }
             }
-            Err(_) => {
+            Err(e) => {
                 // Invalid BED line - write to unmap file
+                if options.verbose_errors {
+                    writeln!(unmap_file, "# {}", BedParseError::with_line(e, line.to_string()).at_offset(offset))?;
+                }
                 writeln!(unmap_file, "{}", line)?;
                 stats.failed += 1;
+                stats.invalid += 1;
             }
         }
     }
     
+    output_file.finish()?;
+    unmap_file.flush()?;
+    if let Some(multimap_file) = multimap_file.as_mut() {
+        multimap_file.flush()?;
+    }
     Ok(stats)
 }
 
-/// Parallel BED conversion using rayon
-/// 
-/// Reads all lines into memory, processes in parallel chunks, then writes output.
-/// This trades memory for speed - suitable for files that fit in memory.
-fn convert_bed_parallel<P: AsRef<Path>>(
-    input: P,
-    output: P,
-    unmap: P,
-    mapper: &CoordinateMapper,
-    threads: usize,
-) -> Result<ConversionStats, BedParseError> {
-    // Configure rayon thread pool
-    let pool = rayon::ThreadPoolBuilder::new()
+/// Open `options.multimap_output`, if set
+fn create_multimap_output(options: &BedConvertOptions) -> std::io::Result<Option<Box<dyn Write + Send>>> {
+    options
+        .multimap_output
+        .as_ref()
+        .map(|path| io::create_output(path, 64 * 1024))
+        .transpose()
+}
+
+/// Record counters shared between parallel workers
+#[derive(Default)]
+struct AtomicStats {
+    total: AtomicUsize,
+    success: AtomicUsize,
+    failed: AtomicUsize,
+    multi_map: AtomicUsize,
+    skipped: AtomicUsize,
+    invalid: AtomicUsize,
+}
+
+impl AtomicStats {
+    fn into_stats(self) -> ConversionStats {
+        ConversionStats {
+            total: self.total.into_inner(),
+            success: self.success.into_inner(),
+            failed: self.failed.into_inner(),
+            multi_map: self.multi_map.into_inner(),
+            skipped: self.skipped.into_inner(),
+            invalid: self.invalid.into_inner(),
+        }
+    }
+}
+
+/// Build the rayon pool used by the parallel converters
+fn build_thread_pool(threads: usize) -> Result<rayon::ThreadPool, BedParseError> {
+    rayon::ThreadPoolBuilder::new()
         .num_threads(threads)
         .build()
         .map_err(|e| BedParseError::Io(std::io::Error::new(
             std::io::ErrorKind::Other,
             format!("Failed to create thread pool: {}", e)
-        )))?;
-    
-    // Read all lines
-    let input_file = std::fs::File::open(input.as_ref())?;
-    let reader = BufReader::with_capacity(128 * 1024, input_file);
+        )))
+}
+
+/// Read all lines, separating header lines from data lines
+fn read_bed_lines<P: AsRef<Path>>(input: P) -> Result<(Vec<String>, Vec<String>), BedParseError> {
+    let reader = io::open_input(input.as_ref(), 128 * 1024)?;
     
     let mut header_lines = Vec::new();
     let mut data_lines = Vec::new();
@@ -803,89 +1274,504 @@ fn convert_bed_parallel<P: AsRef<Path>>(
         }
     }
     
-    // Atomic counters for stats
-    let total = AtomicUsize::new(0);
-    let success = AtomicUsize::new(0);
-    let failed = AtomicUsize::new(0);
-    let multi_map = AtomicUsize::new(0);
+    Ok((header_lines, data_lines))
+}
+
+/// Convert one data line in a parallel worker, updating the shared counters
+///
+/// Lines that fail to parse come back as `ConversionResult::Failed`.
+fn convert_line_counted(
+    line: &str,
+    mapper: &CoordinateMapper,
+    options: &BedConvertOptions,
+    counters: &AtomicStats,
+) -> Result<ConversionResult, BedParseError> {
+    counters.total.fetch_add(1, Ordering::Relaxed);
+    
+    let view = match BedRecordView::parse(line.as_bytes()) {
+        Ok(view) => view,
+        Err(_) => {
+            counters.failed.fetch_add(1, Ordering::Relaxed);
+            counters.invalid.fetch_add(1, Ordering::Relaxed);
+            return Ok(ConversionResult::Failed(line.to_string()));
+        }
+    };
+    check_score(&view, line, options.strict_score)?;
+    
+    let result = convert_bed_view(view, line, mapper, options);
+    match &result {
+        ConversionResult::Success(_) => {
+            counters.success.fetch_add(1, Ordering::Relaxed);
+        }
+        ConversionResult::MultiMap(_) => {
+            counters.success.fetch_add(1, Ordering::Relaxed);
+            counters.multi_map.fetch_add(1, Ordering::Relaxed);
+        }
+        ConversionResult::Failed(_) => {
+            counters.failed.fetch_add(1, Ordering::Relaxed);
+        }
+        ConversionResult::Skipped => {
+            counters.skipped.fetch_add(1, Ordering::Relaxed);
+        }
+        ConversionResult::PassThrough(_) => {}
+    }
+    Ok(result)
+}
+
+/// Output, unmap and multi-map lines of one converted chunk
+type ChunkLines = (Vec<String>, Vec<String>, Vec<String>);
+
+/// Convert one chunk of lines in a parallel worker
+///
+/// Header lines (`#`, `track`, `browser`) are passed through to the output
+/// part. Multi-map lines go to the third list when `multimap_output` is set.
+fn convert_chunk<S: AsRef<str>>(
+    chunk: &[S],
+    mapper: &CoordinateMapper,
+    options: &BedConvertOptions,
+    counters: &AtomicStats,
+) -> Result<ChunkLines, BedParseError> {
+    let split_multimap = options.multimap_output.is_some();
+    let mut success_lines = Vec::with_capacity(chunk.len());
+    let mut failed_lines = Vec::new();
+    let mut multimap_lines = Vec::new();
+    
+    for line in chunk {
+        let line = line.as_ref();
+        if line.starts_with('#') || line.starts_with("track") || line.starts_with("browser") {
+            success_lines.push(line.to_string());
+            continue;
+        }
+        match convert_line_counted(line, mapper, options, counters)? {
+            ConversionResult::Success(output_line) | ConversionResult::PassThrough(output_line) => {
+                success_lines.push(output_line);
+            }
+            ConversionResult::MultiMap(output_lines) if split_multimap => {
+                multimap_lines.extend(output_lines);
+            }
+            ConversionResult::MultiMap(output_lines) => {
+                success_lines.extend(output_lines);
+            }
+            ConversionResult::Failed(unmapped_line) => {
+                failed_lines.push(unmapped_line);
+            }
+            ConversionResult::Skipped => {}
+        }
+    }
+    
+    Ok((success_lines, failed_lines, multimap_lines))
+}
+
+/// Parallel BED conversion using rayon
+/// 
+/// Reads all lines into memory, processes in parallel chunks, then writes output.
+/// This trades memory for speed - suitable for files that fit in memory.
+fn convert_bed_parallel<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    unmap: P,
+    mapper: &CoordinateMapper,
+    options: &BedConvertOptions,
+) -> Result<ConversionStats, BedParseError> {
+    let pool = build_thread_pool(options.threads)?;
+    let (header_lines, data_lines) = read_bed_lines(input)?;
+    let counters = AtomicStats::default();
     
     // Process in parallel
-    let results: Vec<(Vec<String>, Vec<String>)> = pool.install(|| {
+    let results: Vec<ChunkLines> = pool.install(|| {
+        data_lines
+            .par_chunks(options.chunk_size.max(1))
+            .map(|chunk| convert_chunk(chunk, mapper, options, &counters))
+            .collect::<Result<_, BedParseError>>()
+    })?;
+    
+    // Write output files with BufWriter for performance
+    let mut output_file = io::create_output_compressed(output.as_ref(), 128 * 1024, options.compress)?;
+    let mut unmap_file = io::create_unmap_output(unmap.as_ref(), 64 * 1024)?;
+    let mut multimap_file = create_multimap_output(options)?;
+    
+    // Write headers first
+    for header in &header_lines {
+        writeln!(output_file, "{}", header)?;
+    }
+    
+    // Write results (maintaining chunk order)
+    for (success_lines, failed_lines, multimap_lines) in results {
+        for line in success_lines {
+            writeln!(output_file, "{}", line)?;
+        }
+        for line in failed_lines {
+            writeln!(unmap_file, "{}", line)?;
+        }
+        if let Some(multimap_file) = multimap_file.as_mut() {
+            for line in multimap_lines {
+                writeln!(multimap_file, "{}", line)?;
+            }
+        }
+    }
+    output_file.finish()?;
+    unmap_file.flush()?;
+    if let Some(multimap_file) = multimap_file.as_mut() {
+        multimap_file.flush()?;
+    }
+    
+    Ok(counters.into_stats())
+}
+
+/// Convert a BED file with a channel-based reader / worker / writer pipeline
+///
+/// A reader thread sends chunks of raw lines into a bounded channel,
+/// `threads` workers convert them, and the calling thread writes the
+/// results back in input order. At most a few chunks per worker are in
+/// flight, so memory stays bounded regardless of input size, and stdin
+/// input is converted in parallel too.
+///
+/// # Arguments
+/// Same as [`convert_bed`]
+pub fn convert_bed_channel<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    unmap: P,
+    mapper: &CoordinateMapper,
+    threads: usize,
+) -> Result<ConversionStats, BedParseError> {
+    let options = BedConvertOptions {
+        threads: io::resolve_threads(threads),
+        streaming: true,
+        ..Default::default()
+    };
+    convert_bed_streaming(input, output, unmap, mapper, &options)
+}
+
+/// Channel pipeline behind [`convert_bed_channel`] and `options.streaming`
+fn convert_bed_streaming<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    unmap: P,
+    mapper: &CoordinateMapper,
+    options: &BedConvertOptions,
+) -> Result<ConversionStats, BedParseError> {
+    let workers = options.threads.max(1);
+    let chunk_size = options.chunk_size.max(1);
+    let reader = io::open_input(input.as_ref(), 128 * 1024)?;
+    let mut output_file = io::create_output_compressed(output.as_ref(), 128 * 1024, options.compress)?;
+    let mut unmap_file = io::create_unmap_output(unmap.as_ref(), 64 * 1024)?;
+    let mut multimap_file = create_multimap_output(options)?;
+    let counters = AtomicStats::default();
+    
+    std::thread::scope(|scope| -> Result<(), BedParseError> {
+        let (chunk_tx, chunk_rx) = crossbeam_channel::bounded::<(usize, Vec<String>)>(workers * 2);
+        let (result_tx, result_rx) = crossbeam_channel::bounded::<(usize, Result<ChunkLines, BedParseError>)>(workers * 2);
+        
+        let reader_thread = scope.spawn(move || -> std::io::Result<()> {
+            let mut seq = 0;
+            let mut chunk = Vec::with_capacity(chunk_size);
+            for line in reader.lines() {
+                let line = line?;
+                if line.is_empty() {
+                    continue;
+                }
+                chunk.push(line);
+                if chunk.len() == chunk_size {
+                    let full = std::mem::replace(&mut chunk, Vec::with_capacity(chunk_size));
+                    // A closed channel means the writer stopped on an error
+                    if chunk_tx.send((seq, full)).is_err() {
+                        return Ok(());
+                    }
+                    seq += 1;
+                }
+            }
+            if !chunk.is_empty() {
+                let _ = chunk_tx.send((seq, chunk));
+            }
+            Ok(())
+        });
+        
+        for _ in 0..workers {
+            let chunk_rx = chunk_rx.clone();
+            let result_tx = result_tx.clone();
+            let counters = &counters;
+            scope.spawn(move || {
+                for (seq, chunk) in chunk_rx {
+                    let result = convert_chunk(&chunk, mapper, options, counters);
+                    if result_tx.send((seq, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(chunk_rx);
+        drop(result_tx);
+        
+        // Chunks finish out of order; hold them until their turn
+        let mut pending = std::collections::HashMap::new();
+        let mut next = 0;
+        for (seq, result) in result_rx {
+            pending.insert(seq, result?);
+            while let Some((success_lines, failed_lines, multimap_lines)) = pending.remove(&next) {
+                for line in success_lines {
+                    writeln!(output_file, "{}", line)?;
+                }
+                for line in failed_lines {
+                    writeln!(unmap_file, "{}", line)?;
+                }
+                if let Some(multimap_file) = multimap_file.as_mut() {
+                    for line in multimap_lines {
+                        writeln!(multimap_file, "{}", line)?;
+                    }
+                }
+                next += 1;
+            }
+        }
+        
+        reader_thread.join().expect("BED reader thread panicked")?;
+        Ok(())
+    })?;
+    
+    output_file.finish()?;
+    unmap_file.flush()?;
+    if let Some(multimap_file) = multimap_file.as_mut() {
+        multimap_file.flush()?;
+    }
+    Ok(counters.into_stats())
+}
+
+/// Output and unmap part files owned by one worker thread
+struct PartFiles {
+    index: usize,
+    output: std::io::BufWriter<std::fs::File>,
+    unmap: std::io::BufWriter<std::fs::File>,
+    /// Only opened when `multimap_output` is set
+    multimap: Option<std::io::BufWriter<std::fs::File>>,
+    output_pos: u64,
+    unmap_pos: u64,
+    multimap_pos: u64,
+}
+
+/// Where one chunk's lines ended up: part index plus byte ranges
+struct ChunkSpan {
+    part: usize,
+    output: (u64, u64),
+    unmap: (u64, u64),
+    multimap: (u64, u64),
+}
+
+/// Path of worker `index`'s output part file
+fn part_path(output: &Path, index: usize) -> std::path::PathBuf {
+    std::path::PathBuf::from(format!("{}.part_{}", output.display(), index))
+}
+
+/// Path of worker `index`'s unmap part file
+fn unmap_part_path(output: &Path, index: usize) -> std::path::PathBuf {
+    std::path::PathBuf::from(format!("{}.part_{}.unmap", output.display(), index))
+}
+
+/// Path of worker `index`'s multi-map part file
+fn multimap_part_path(output: &Path, index: usize) -> std::path::PathBuf {
+    std::path::PathBuf::from(format!("{}.part_{}.multimap", output.display(), index))
+}
+
+/// Write one line and advance the byte position
+fn write_part_line<W: Write>(writer: &mut W, pos: &mut u64, line: &str) -> std::io::Result<()> {
+    writer.write_all(line.as_bytes())?;
+    writer.write_all(b"\n")?;
+    *pos += line.len() as u64 + 1;
+    Ok(())
+}
+
+/// Append `len` bytes starting at `offset` of `part` to `writer`
+fn copy_part_range<W: Write>(part: &mut std::fs::File, (offset, len): (u64, u64), writer: &mut W) -> std::io::Result<()> {
+    use std::io::{Read, Seek, SeekFrom};
+    
+    if len > 0 {
+        part.seek(SeekFrom::Start(offset))?;
+        std::io::copy(&mut Read::by_ref(part).take(len), writer)?;
+    }
+    Ok(())
+}
+
+/// Parallel BED conversion with one output file per worker thread
+/// 
+/// Each rayon thread appends its chunks to `<output>.part_N` (and
+/// `<output>.part_N.unmap`), so converted lines are never collected in a
+/// shared Vec. Only the byte range of each chunk is kept; the parts are
+/// then concatenated in input order and removed.
+fn convert_bed_parallel_per_thread<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    unmap: P,
+    mapper: &CoordinateMapper,
+    options: &BedConvertOptions,
+) -> Result<ConversionStats, BedParseError> {
+    use std::cell::RefCell;
+    use std::fs::File;
+    use std::io::BufWriter;
+    use thread_local::ThreadLocal;
+    
+    let output_path = output.as_ref();
+    let pool = build_thread_pool(options.threads)?;
+    let (header_lines, data_lines) = read_bed_lines(input)?;
+    let counters = AtomicStats::default();
+    let parts: ThreadLocal<RefCell<PartFiles>> = ThreadLocal::new();
+    
+    let spans: Result<Vec<ChunkSpan>, BedParseError> = pool.install(|| {
         data_lines
-            .par_chunks(CHUNK_SIZE)
+            .par_chunks(options.chunk_size.max(1))
             .map(|chunk| {
-                let mut success_lines = Vec::with_capacity(chunk.len());
-                let mut failed_lines = Vec::new();
+                let cell = parts.get_or_try(|| -> std::io::Result<_> {
+                    let index = rayon::current_thread_index().unwrap_or(0);
+                    Ok(RefCell::new(PartFiles {
+                        index,
+                        output: BufWriter::with_capacity(128 * 1024, File::create(part_path(output_path, index))?),
+                        unmap: BufWriter::with_capacity(64 * 1024, File::create(unmap_part_path(output_path, index))?),
+                        multimap: match options.multimap_output {
+                            Some(_) => Some(BufWriter::with_capacity(64 * 1024, File::create(multimap_part_path(output_path, index))?)),
+                            None => None,
+                        },
+                        output_pos: 0,
+                        unmap_pos: 0,
+                        multimap_pos: 0,
+                    }))
+                })?;
+                let mut files = cell.borrow_mut();
+                let files = &mut *files;
+                let output_start = files.output_pos;
+                let unmap_start = files.unmap_pos;
+                let multimap_start = files.multimap_pos;
                 
                 for line in chunk {
-                    total.fetch_add(1, Ordering::Relaxed);
-                    
-                    match BedRecordView::parse(line.as_bytes()) {
-                        Ok(view) => {
-                            let input_strand = view.strand().unwrap_or(Strand::Plus);
-                            
-                            match convert_bed_record(&view, mapper, input_strand) {
-                                ConversionResult::Success(output_line) => {
-                                    success_lines.push(output_line);
-                                    success.fetch_add(1, Ordering::Relaxed);
-                                }
-                                ConversionResult::MultiMap(output_lines) => {
-                                    success_lines.extend(output_lines);
-                                    success.fetch_add(1, Ordering::Relaxed);
-                                    multi_map.fetch_add(1, Ordering::Relaxed);
-                                }
-                                ConversionResult::Failed(unmapped_line) => {
-                                    failed_lines.push(unmapped_line);
-                                    failed.fetch_add(1, Ordering::Relaxed);
-                                }
-                                ConversionResult::PassThrough(pass_line) => {
-                                    success_lines.push(pass_line);
-                                }
+                    match convert_line_counted(line, mapper, options, &counters)? {
+                        ConversionResult::Success(output_line) | ConversionResult::PassThrough(output_line) => {
+                            write_part_line(&mut files.output, &mut files.output_pos, &output_line)?;
+                        }
+                        ConversionResult::MultiMap(output_lines) => {
+                            let (writer, pos) = match files.multimap.as_mut() {
+                                Some(multimap) => (multimap, &mut files.multimap_pos),
+                                None => (&mut files.output, &mut files.output_pos),
+                            };
+                            for output_line in &output_lines {
+                                write_part_line(writer, pos, output_line)?;
                             }
                         }
-                        Err(_) => {
-                            failed_lines.push(line.clone());
-                            failed.fetch_add(1, Ordering::Relaxed);
+                        ConversionResult::Failed(unmapped_line) => {
+                            write_part_line(&mut files.unmap, &mut files.unmap_pos, &unmapped_line)?;
                         }
+                        ConversionResult::Skipped => {}
                     }
                 }
                 
-                (success_lines, failed_lines)
+                Ok(ChunkSpan {
+                    part: files.index,
+                    output: (output_start, files.output_pos - output_start),
+                    unmap: (unmap_start, files.unmap_pos - unmap_start),
+                    multimap: (multimap_start, files.multimap_pos - multimap_start),
+                })
             })
             .collect()
     });
     
-    // Write output files with BufWriter for performance
-    let mut output_file = BufWriter::with_capacity(128 * 1024, std::fs::File::create(output.as_ref())?);
-    let mut unmap_file = BufWriter::with_capacity(64 * 1024, std::fs::File::create(unmap.as_ref())?);
-    
-    // Write headers first
-    for header in &header_lines {
-        writeln!(output_file, "{}", header)?;
+    // Flush every part before reading it back
+    let mut part_indices = Vec::new();
+    let mut flushed: std::io::Result<()> = Ok(());
+    for cell in parts {
+        let mut files = cell.into_inner();
+        part_indices.push(files.index);
+        flushed = flushed.and(files.output.flush()).and(files.unmap.flush());
+        if let Some(multimap) = files.multimap.as_mut() {
+            flushed = flushed.and(multimap.flush());
+        }
     }
     
-    // Write results (maintaining chunk order)
-    for (success_lines, failed_lines) in results {
-        for line in success_lines {
-            writeln!(output_file, "{}", line)?;
+    let merged = spans.and_then(|spans| {
+        flushed?;
+        
+        let mut output_file = io::create_output_compressed(output_path, 128 * 1024, options.compress)?;
+        let mut unmap_file = io::create_unmap_output(unmap.as_ref(), 64 * 1024)?;
+        let mut multimap_file = create_multimap_output(options)?;
+        
+        for header in &header_lines {
+            writeln!(output_file, "{}", header)?;
         }
-        for line in failed_lines {
-            writeln!(unmap_file, "{}", line)?;
+        
+        let mut readers = std::collections::HashMap::new();
+        for &index in &part_indices {
+            let part_multimap = match multimap_file {
+                Some(_) => Some(File::open(multimap_part_path(output_path, index))?),
+                None => None,
+            };
+            readers.insert(index, (File::open(part_path(output_path, index))?, File::open(unmap_part_path(output_path, index))?, part_multimap));
+        }
+        for span in spans {
+            let (part_output, part_unmap, part_multimap) = readers.get_mut(&span.part).expect("span refers to an open part");
+            copy_part_range(part_output, span.output, &mut output_file)?;
+            copy_part_range(part_unmap, span.unmap, &mut unmap_file)?;
+            if let (Some(part_multimap), Some(multimap_file)) = (part_multimap.as_mut(), multimap_file.as_mut()) {
+                copy_part_range(part_multimap, span.multimap, multimap_file)?;
+            }
         }
+        
+        output_file.finish()?;
+        unmap_file.flush()?;
+        if let Some(multimap_file) = multimap_file.as_mut() {
+            multimap_file.flush()?;
+        }
+        Ok(())
+    });
+    
+    for index in part_indices {
+        std::fs::remove_file(part_path(output_path, index)).ok();
+        std::fs::remove_file(unmap_part_path(output_path, index)).ok();
+        std::fs::remove_file(multimap_part_path(output_path, index)).ok();
     }
     
-    Ok(ConversionStats {
-        total: total.load(Ordering::Relaxed),
-        success: success.load(Ordering::Relaxed),
-        failed: failed.load(Ordering::Relaxed),
-        multi_map: multi_map.load(Ordering::Relaxed),
-    })
+    merged?;
+    Ok(counters.into_stats())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     
+    #[test]
+    fn test_validate() {
+        let view = BedRecordView::parse(b"chr1\t100\t200").unwrap();
+        assert!(view.validate().is_ok());
+        assert!(!view.is_zero_length());
+        
+        let view = BedRecordView::parse(b"chr1\t100\t100").unwrap();
+        assert!(view.is_zero_length());
+        assert!(matches!(view.validate(), Err(BedParseError::ZeroLength { ref chrom, pos: 100 }) if chrom == "chr1"));
+        
+        let view = BedRecordView::parse(b"chr1\t200\t100").unwrap();
+        assert!(matches!(view.validate(), Err(BedParseError::InvalidRange { start: 200, end: 100 })));
+    }
+    
+    #[test]
+    fn test_normalize_itemrgb() {
+        assert_eq!(normalize_itemrgb("#FF0000"), "255,0,0");
+        assert_eq!(normalize_itemrgb("#00ff80"), "0,255,128");
+        assert_eq!(normalize_itemrgb("#0F0"), "0,255,0");
+        assert_eq!(normalize_itemrgb("red"), "255,0,0");
+        assert_eq!(normalize_itemrgb("Grey"), "128,128,128");
+        assert_eq!(normalize_itemrgb("255,0,0"), "255,0,0");
+        assert_eq!(normalize_itemrgb("0"), "0");
+        assert_eq!(normalize_itemrgb("#GG0000"), "#GG0000");
+        assert_eq!(normalize_itemrgb("chartreuse"), "chartreuse");
+    }
+    
+    #[test]
+    fn test_normalize_bed_fields() {
+        assert_eq!(normalize_bed_fields("chr1\t100\t200", 6), "chr1\t100\t200\t.\t0\t.");
+        assert_eq!(normalize_bed_fields("chr1\t100\t200\ta\t5\t-", 6), "chr1\t100\t200\ta\t5\t-");
+        assert_eq!(normalize_bed_fields("chr1\t100\t200\ta\t5\t-", 4), "chr1\t100\t200\ta");
+        assert_eq!(
+            normalize_bed_fields("chr1\t100\t200\ta", 12),
+            "chr1\t100\t200\ta\t0\t.\t0\t0\t0\t1\t100,\t0,"
+        );
+        assert_eq!(normalize_bed_fields("chr1\t100\t200", 13), "chr1\t100\t200\t.\t0\t.\t0\t0\t0\t1\t100,\t0,\t.");
+        assert_eq!(normalize_bed_fields("chr1\t100\t200\ta", 1), "chr1\t100\t200");
+    }
+    
     #[test]
     fn test_bed_record_view_bed3() {
         let line = b"chr1\t1000\t2000";
@@ -931,6 +1817,20 @@ mod tests {
         assert!(view.is_bed12());
     }
     
+    #[test]
+    fn test_error_with_line() {
+        let line = "chr1\tabc\t200";
+        let Err(error) = BedRecordView::parse(line.as_bytes()) else { panic!("expected a parse error") };
+        let context = BedParseError::with_line(error, line.to_string()).at_offset(42);
+        assert_eq!(context.offset, 42);
+        assert_eq!(context.line, line);
+        assert_eq!(context.to_string(), "Invalid number in field start: abc (byte 42): chr1\tabc\t200");
+        
+        // Wrapped back into a BedParseError, the display is unchanged
+        let wrapped: BedParseError = context.into();
+        assert_eq!(wrapped.to_string(), "Invalid number in field start: abc (byte 42): chr1\tabc\t200");
+    }
+    
     #[test]
     fn test_bed_record_view_too_few_fields() {
         let line = b"chr1\t1000";
@@ -952,6 +1852,44 @@ mod tests {
         assert!(matches!(result, Err(BedParseError::InvalidNumber(_, _))));
     }
     
+    #[test]
+    fn test_score_int_and_float() {
+        let int = BedRecordView::parse(b"chr1\t0\t10\tname\t500").unwrap();
+        assert_eq!(int.score_int(), Some(500));
+        assert_eq!(int.score_float(), Some(500.0));
+        
+        let float = BedRecordView::parse(b"chr1\t0\t10\tname\t3.75").unwrap();
+        assert_eq!(float.score_int(), None);
+        assert_eq!(float.score_float(), Some(3.75));
+        
+        let dot = BedRecordView::parse(b"chr1\t0\t10\tname\t.").unwrap();
+        assert_eq!(dot.score_int(), None);
+        assert_eq!(dot.score_float(), None);
+        
+        let bed3 = BedRecordView::parse(b"chr1\t0\t10").unwrap();
+        assert_eq!(bed3.score_int(), None);
+        assert_eq!(bed3.score_float(), None);
+    }
+    
+    #[test]
+    fn test_check_score_strict() {
+        let line = "chr1\t0\t10\tname\t1001";
+        let view = BedRecordView::parse(line.as_bytes()).unwrap();
+        assert!(check_score(&view, line, false).is_ok());
+        assert!(matches!(
+            check_score(&view, line, true),
+            Err(BedParseError::ScoreOutOfRange { score: 1001, .. })
+        ));
+        
+        for ok in ["chr1\t0\t10\tn\t0", "chr1\t0\t10\tn\t1000", "chr1\t0\t10\tn\t2.5e6"] {
+            let view = BedRecordView::parse(ok.as_bytes()).unwrap();
+            assert!(check_score(&view, ok, true).is_ok(), "{}", ok);
+        }
+        let negative = "chr1\t0\t10\tn\t-1";
+        let view = BedRecordView::parse(negative.as_bytes()).unwrap();
+        assert!(check_score(&view, negative, true).is_err());
+    }
+    
     #[test]
     fn test_strand_parsing() {
         let plus = b"chr1\t1000\t2000\tname\t0\t+";
@@ -962,4 +1900,51 @@ mod tests {
         assert_eq!(BedRecordView::parse(minus).unwrap().strand(), Some(Strand::Minus));
         assert_eq!(BedRecordView::parse(dot).unwrap().strand(), None);
     }
+    
+    #[test]
+    fn test_simple_bed12_block_starts() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+        
+        let mapper = |target: &str| {
+            let chain = format!("chain 1000 chr1 10000 + 0 10000 chr1 20000 {} 5000 15000 1\n10000\n", target);
+            CoordinateMapper::new(ChainIndex::from_chain_data(parse_chain_bytes(chain.as_bytes()).unwrap()), ChromStyle::AsIs)
+        };
+        let line = b"chr1\t1000\t2000\tname\t0\t+\t1050\t1900\t0\t3\t100,200,300,\t0,400,700,";
+        let view = BedRecordView::parse(line).unwrap();
+        
+        // Plus strand: block starts are relative to chromStart and stay unchanged
+        let ConversionResult::Success(out) = convert_bed_record_simple(&view, &mapper("+"), Strand::Plus) else {
+            panic!("expected a single mapping");
+        };
+        assert_eq!(out, "chr1\t6000\t7000\tname\t0\t+\t6050\t6900\t0\t3\t100,200,300,\t0,400,700,");
+        
+        // Minus strand: [1000, 2000) -> [13000, 14000), blocks flipped and reversed
+        let ConversionResult::Success(out) = convert_bed_record_simple(&view, &mapper("-"), Strand::Plus) else {
+            panic!("expected a single mapping");
+        };
+        assert_eq!(out, "chr1\t13000\t14000\tname\t0\t-\t13100\t13950\t0\t3\t300,200,100,\t0,400,900,");
+        let fields: Vec<&str> = out.split('\t').collect();
+        assert_eq!(fields[11].split(',').next(), Some("0"));
+    }
+    
+    #[test]
+    fn test_truncate_name() {
+        assert_eq!(truncate_name("abcdefghij", 10), "abcdefghij");
+        assert_eq!(truncate_name("abcdefghijk", 10), "abcdefg...");
+        assert_eq!(truncate_name("ééééé", 4), "é...");
+        assert_eq!(truncate_name("abcdef", 3), "abc");
+        assert_eq!(truncate_name_column("chr1\t1\t2\tabcdefghijk\t0\t+", 10), "chr1\t1\t2\tabcdefg...\t0\t+");
+        assert_eq!(truncate_name_column("chr1\t1\t2", 10), "chr1\t1\t2");
+    }
+    
+    #[test]
+    fn test_flip_bed12_blocks() {
+        assert_eq!(flip_bed12_blocks("10,20", "0,80", 100), Some(("20,10".to_string(), "0,90".to_string())));
+        assert_eq!(flip_bed12_blocks("100", "0", 100), Some(("100".to_string(), "0".to_string())));
+        assert_eq!(flip_bed12_blocks("10,20,", "0,80,", 100), Some(("20,10,".to_string(), "0,90,".to_string())));
+        // Block past the record end, count mismatch, malformed
+        assert_eq!(flip_bed12_blocks("10,30", "0,80", 100), None);
+        assert_eq!(flip_bed12_blocks("10", "0,80", 100), None);
+        assert_eq!(flip_bed12_blocks("10,x", "0,80", 100), None);
+    }
 }
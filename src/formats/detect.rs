@@ -0,0 +1,271 @@
+//! Input format detection
+//!
+//! Detects the genomic file format from the file extension and, when the
+//! extension is missing or ambiguous, by sniffing the first few lines.
+
+use crate::core::{io, FastCrossMapError};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+/// Number of non-empty lines inspected when sniffing file content
+const SNIFF_LINES: usize = 20;
+
+/// BigWig magic number (little-endian)
+const BIGWIG_MAGIC: [u8; 4] = [0x26, 0xFC, 0x8F, 0x88];
+
+/// Genomic file formats supported by the converters
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenomicFormat {
+    Bed,
+    Vcf,
+    Gvcf,
+    Gff,
+    Gtf,
+    Wig,
+    BigWig,
+    Maf,
+    Bam,
+}
+
+impl GenomicFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GenomicFormat::Bed => "BED",
+            GenomicFormat::Vcf => "VCF",
+            GenomicFormat::Gvcf => "GVCF",
+            GenomicFormat::Gff => "GFF3",
+            GenomicFormat::Gtf => "GTF",
+            GenomicFormat::Wig => "Wiggle",
+            GenomicFormat::BigWig => "BigWig",
+            GenomicFormat::Maf => "MAF",
+            GenomicFormat::Bam => "BAM",
+        }
+    }
+}
+
+impl std::fmt::Display for GenomicFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Detect format from the file name alone
+///
+/// A trailing `.gz` / `.bgz` is ignored. Returns None for unknown or
+/// ambiguous extensions (e.g. `.txt`).
+fn detect_by_extension(path: &Path) -> Option<GenomicFormat> {
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+    let name = name
+        .strip_suffix(".gz")
+        .or_else(|| name.strip_suffix(".bgz"))
+        .unwrap_or(&name);
+
+    if name.ends_with(".g.vcf") || name.ends_with(".gvcf") {
+        return Some(GenomicFormat::Gvcf);
+    }
+
+    let ext = name.rsplit_once('.')?.1;
+    match ext {
+        "bed" => Some(GenomicFormat::Bed),
+        "vcf" => Some(GenomicFormat::Vcf),
+        "gff" | "gff3" => Some(GenomicFormat::Gff),
+        "gtf" => Some(GenomicFormat::Gtf),
+        "wig" | "bedgraph" | "bdg" => Some(GenomicFormat::Wig),
+        "bw" | "bigwig" => Some(GenomicFormat::BigWig),
+        "maf" => Some(GenomicFormat::Maf),
+        "bam" | "sam" | "cram" => Some(GenomicFormat::Bam),
+        _ => None,
+    }
+}
+
+/// Detect format from the first few lines of text content
+fn detect_by_lines<I: Iterator<Item = String>>(lines: I) -> Option<GenomicFormat> {
+    let mut is_vcf = false;
+
+    for line in lines.filter(|l| !l.trim().is_empty()).take(SNIFF_LINES) {
+        let line = line.trim_end();
+
+        if line.starts_with("##fileformat=VCF") {
+            is_vcf = true;
+            continue;
+        }
+        if is_vcf {
+            if line.starts_with("##GVCFBlock") || line.contains("NON_REF") {
+                return Some(GenomicFormat::Gvcf);
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            return Some(GenomicFormat::Vcf);
+        }
+
+        if line.starts_with("##gff-version") {
+            return Some(GenomicFormat::Gff);
+        }
+        if line.starts_with("#version 2.") || line.starts_with("Hugo_Symbol\t") {
+            return Some(GenomicFormat::Maf);
+        }
+        if line.starts_with("@HD") || line.starts_with("@SQ") {
+            return Some(GenomicFormat::Bam);
+        }
+        if line.starts_with("variableStep") || line.starts_with("fixedStep") {
+            return Some(GenomicFormat::Wig);
+        }
+        if line.starts_with("track") {
+            if line.contains("type=wiggle_0") || line.contains("type=bedGraph") {
+                return Some(GenomicFormat::Wig);
+            }
+            continue;
+        }
+        if line.starts_with('#') || line.starts_with("browser") {
+            continue;
+        }
+
+        // First data line: decide by column layout
+        let fields: Vec<&str> = line.split('\t').collect();
+        let is_int = |s: &str| s.parse::<u64>().is_ok();
+
+        if fields.len() >= 9
+            && is_int(fields[3])
+            && is_int(fields[4])
+            && matches!(fields[6], "+" | "-" | "." | "?")
+        {
+            let attrs = fields[8].trim();
+            let is_gtf = attrs.contains(" \"") || attrs.starts_with("gene_id ");
+            return Some(if is_gtf { GenomicFormat::Gtf } else { GenomicFormat::Gff });
+        }
+        if fields.len() == 4 && is_int(fields[1]) && is_int(fields[2]) && fields[3].parse::<f64>().is_ok() {
+            return Some(GenomicFormat::Wig);
+        }
+        if fields.len() >= 3 && is_int(fields[1]) && is_int(fields[2]) {
+            return Some(GenomicFormat::Bed);
+        }
+        return None;
+    }
+
+    if is_vcf { Some(GenomicFormat::Vcf) } else { None }
+}
+
+/// Detect format by sniffing file content
+fn detect_by_content(path: &Path) -> Result<Option<GenomicFormat>, FastCrossMapError> {
+    let mut file = File::open(path)?;
+    let mut magic = [0u8; 4];
+    let n = file.read(&mut magic)?;
+    drop(file);
+
+    if n == 4 && magic == BIGWIG_MAGIC {
+        return Ok(Some(GenomicFormat::BigWig));
+    }
+
+    let file = File::open(path)?;
+    let reader: Box<dyn BufRead> = if n >= 2 && magic[0] == 0x1f && magic[1] == 0x8b {
+        let mut decoder = flate2::read::MultiGzDecoder::new(file);
+        let mut bam_magic = [0u8; 4];
+        // BGZF-compressed BAM starts with "BAM\1" once decompressed
+        if decoder.read_exact(&mut bam_magic).is_ok() && &bam_magic == b"BAM\x01" {
+            return Ok(Some(GenomicFormat::Bam));
+        }
+        Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(File::open(path)?)))
+    } else {
+        Box::new(BufReader::new(file))
+    };
+
+    Ok(detect_by_lines(reader.lines().map_while(|l| l.ok())))
+}
+
+/// Detect the genomic format of an input file
+///
+/// Known extensions (`.bed`, `.vcf`, `.gff`, `.gff3`, `.gtf`, `.wig`,
+/// `.bw`, `.maf`, `.bam`, optionally followed by `.gz`) decide directly.
+/// Otherwise the first few lines are inspected.
+pub fn detect_format(path: &Path) -> Result<GenomicFormat, FastCrossMapError> {
+    // Sniffing stdin would consume the records it is meant to describe
+    if io::is_stdio(path) {
+        return Err(FastCrossMapError::UnknownFormat(path.to_path_buf()));
+    }
+
+    if let Some(format) = detect_by_extension(path) {
+        return Ok(format);
+    }
+
+    detect_by_content(path)?.ok_or_else(|| FastCrossMapError::UnknownFormat(path.to_path_buf()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn sniff(content: &str) -> Option<GenomicFormat> {
+        detect_by_lines(content.lines().map(String::from))
+    }
+
+    #[test]
+    fn test_detect_by_extension() {
+        let cases = [
+            ("a.bed", GenomicFormat::Bed),
+            ("a.vcf", GenomicFormat::Vcf),
+            ("a.vcf.gz", GenomicFormat::Vcf),
+            ("a.g.vcf.gz", GenomicFormat::Gvcf),
+            ("a.gvcf", GenomicFormat::Gvcf),
+            ("a.gff", GenomicFormat::Gff),
+            ("a.GFF3", GenomicFormat::Gff),
+            ("a.gtf", GenomicFormat::Gtf),
+            ("a.wig", GenomicFormat::Wig),
+            ("a.bedGraph", GenomicFormat::Wig),
+            ("a.bw", GenomicFormat::BigWig),
+            ("a.maf", GenomicFormat::Maf),
+            ("a.bam", GenomicFormat::Bam),
+            ("a.cram", GenomicFormat::Bam),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(detect_by_extension(&PathBuf::from(name)), Some(expected), "{}", name);
+        }
+        assert_eq!(detect_by_extension(&PathBuf::from("a.txt")), None);
+        assert_eq!(detect_by_extension(&PathBuf::from("noext")), None);
+    }
+
+    #[test]
+    fn test_detect_by_content() {
+        assert_eq!(sniff("##fileformat=VCFv4.2\n#CHROM\tPOS\nchr1\t100\t.\tA\tG\n"), Some(GenomicFormat::Vcf));
+        assert_eq!(
+            sniff("##fileformat=VCFv4.2\n##ALT=<ID=NON_REF,Description=\"x\">\n#CHROM\n"),
+            Some(GenomicFormat::Gvcf)
+        );
+        assert_eq!(sniff("##gff-version 3\nchr1\t.\tgene\t1\t100\t.\t+\t.\tID=g1\n"), Some(GenomicFormat::Gff));
+        assert_eq!(sniff("chr1\t.\tgene\t1\t100\t.\t+\t.\tID=g1\n"), Some(GenomicFormat::Gff));
+        assert_eq!(sniff("chr1\thavana\texon\t1\t100\t.\t-\t.\tgene_id \"g1\";\n"), Some(GenomicFormat::Gtf));
+        assert_eq!(sniff("track type=wiggle_0\nvariableStep chrom=chr1\n1\t2\n"), Some(GenomicFormat::Wig));
+        assert_eq!(sniff("fixedStep chrom=chr1 start=1 step=1\n1\n"), Some(GenomicFormat::Wig));
+        assert_eq!(sniff("chr1\t0\t100\t1.5\n"), Some(GenomicFormat::Wig));
+        assert_eq!(sniff("#version 2.4\nHugo_Symbol\tEntrez_Gene_Id\n"), Some(GenomicFormat::Maf));
+        assert_eq!(sniff("Hugo_Symbol\tEntrez_Gene_Id\tCenter\n"), Some(GenomicFormat::Maf));
+        assert_eq!(sniff("@HD\tVN:1.6\n@SQ\tSN:chr1\tLN:1000\n"), Some(GenomicFormat::Bam));
+        assert_eq!(sniff("browser position chr1\ntrack name=x\nchr1\t100\t200\tfoo\n"), Some(GenomicFormat::Bed));
+        assert_eq!(sniff("chr1\t100\t200\n"), Some(GenomicFormat::Bed));
+        assert_eq!(sniff("hello world\n"), None);
+        assert_eq!(sniff(""), None);
+    }
+
+    #[test]
+    fn test_detect_format_file() {
+        let dir = std::env::temp_dir().join("fast_crossmap_detect_test");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let vcf = dir.join("input.txt");
+        std::fs::write(&vcf, "##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\n").unwrap();
+        assert_eq!(detect_format(&vcf).unwrap(), GenomicFormat::Vcf);
+
+        let bw = dir.join("input.dat");
+        std::fs::write(&bw, [0x26, 0xFC, 0x8F, 0x88, 0, 0]).unwrap();
+        assert_eq!(detect_format(&bw).unwrap(), GenomicFormat::BigWig);
+
+        let unknown = dir.join("input.unknown");
+        std::fs::write(&unknown, "not a genomic file\n").unwrap();
+        assert!(matches!(detect_format(&unknown), Err(FastCrossMapError::UnknownFormat(_))));
+        assert!(matches!(detect_format(Path::new("-")), Err(FastCrossMapError::UnknownFormat(_))));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -5,12 +5,17 @@
 //!
 //! **Validates: Requirements 6.1, 6.2, 6.3, 6.4, 6.5, 6.6, 6.7**
 
-use crate::core::{CoordinateMapper, Strand};
+use crate::core::{io, CoordinateMapper, LiftoverFailureReason, MapResult, MappingSegment, Strand};
 use memchr::memchr;
 use rayon::prelude::*;
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::Path;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::collections::HashSet;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use tracing::warn;
 
 /// GFF/GTF parse error
 #[derive(Debug, Clone)]
@@ -20,6 +25,9 @@ pub enum GffParseError {
     InvalidUtf8(&'static str),
     InvalidNumber(&'static str, String),
     InvalidStrand(String),
+    EmptySeqid,
+    InvalidRange { start: u64, end: u64 },
+    InvalidPhase(String),
 }
 
 impl std::fmt::Display for GffParseError {
@@ -34,6 +42,11 @@ impl std::fmt::Display for GffParseError {
                 write!(f, "Invalid number in field {}: {}", field, value)
             }
             GffParseError::InvalidStrand(s) => write!(f, "Invalid strand: {}", s),
+            GffParseError::EmptySeqid => write!(f, "Empty seqid"),
+            GffParseError::InvalidRange { start, end } => {
+                write!(f, "Invalid range: start {} must be >= 1 and <= end {}", start, end)
+            }
+            GffParseError::InvalidPhase(p) => write!(f, "Invalid CDS phase: {}", p),
         }
     }
 }
@@ -137,7 +150,7 @@ impl<'a> GffRecordView<'a> {
         let strand = match strand_char {
             "+" => Some(Strand::Plus),
             "-" => Some(Strand::Minus),
-            "." => None,
+            "." | "?" => None,
             _ => return Err(GffParseError::InvalidStrand(strand_char.to_string())),
         };
         
@@ -162,6 +175,68 @@ impl<'a> GffRecordView<'a> {
     }
 }
 
+/// Check the fields `GffRecordView::parse` accepts but liftover relies on
+///
+/// Requires a non-empty seqid, `1 <= start <= end`, a strand of
+/// `+`, `-`, `.` or `?`, and a phase of `0`, `1`, `2` or `.` on CDS features.
+pub fn validate_gff_record(view: &GffRecordView) -> Result<(), GffParseError> {
+    if view.seqname.is_empty() {
+        return Err(GffParseError::EmptySeqid);
+    }
+    if view.start == 0 || view.start > view.end {
+        return Err(GffParseError::InvalidRange { start: view.start, end: view.end });
+    }
+    if !matches!(view.strand_char, "+" | "-" | "." | "?") {
+        return Err(GffParseError::InvalidStrand(view.strand_char.to_string()));
+    }
+    if view.feature == "CDS" && !matches!(view.frame, "0" | "1" | "2" | ".") {
+        return Err(GffParseError::InvalidPhase(view.frame.to_string()));
+    }
+    Ok(())
+}
+
+/// Bundled Sequence Ontology term names (one per line, `#` comments)
+const SO_TERMS: &str = include_str!("so_terms.txt");
+
+/// Whether `feature` is a Sequence Ontology term name or accession
+/// (`SO:` followed by seven digits)
+pub fn is_so_term(feature: &str) -> bool {
+    static TERMS: OnceLock<HashSet<&'static str>> = OnceLock::new();
+    let terms = TERMS.get_or_init(|| {
+        SO_TERMS
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .collect()
+    });
+    if let Some(id) = feature.strip_prefix("SO:") {
+        return id.len() == 7 && id.bytes().all(|b| b.is_ascii_digit());
+    }
+    terms.contains(feature)
+}
+
+/// Warn once per feature type (column 3) that is not an SO term, with the
+/// number of records using it
+fn warn_non_so_terms(lines: &[String]) {
+    let mut unknown: Vec<(&str, usize)> = Vec::new();
+    for line in lines {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some(feature) = line.split('\t').nth(2) else { continue };
+        if is_so_term(feature) {
+            continue;
+        }
+        match unknown.iter_mut().find(|(f, _)| *f == feature) {
+            Some((_, count)) => *count += 1,
+            None => unknown.push((feature, 1)),
+        }
+    }
+    for (feature, count) in unknown {
+        warn!("Feature type '{}' is not a Sequence Ontology term ({} records)", feature, count);
+    }
+}
+
 
 /// Conversion statistics
 #[derive(Debug, Clone, Default)]
@@ -170,78 +245,392 @@ pub struct ConversionStats {
     pub success: usize,
     pub failed: usize,
     pub comments: usize,
+    /// Features written to `multimap_output` (also counted in `success`)
+    pub multi_map: usize,
 }
 
-/// Convert a single GFF record
-/// Returns None if conversion fails (unmapped, size changed, or multiple mappings)
-fn convert_gff_record(
-    view: &GffRecordView,
-    mapper: &CoordinateMapper,
-) -> Option<String> {
+/// `##gff-version` pragma written to the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GffVersion {
+    /// Keep the input pragma, correcting it when the attributes are GFF3 style
+    #[default]
+    Auto,
+    /// Always write `##gff-version 2`
+    V2,
+    /// Always write `##gff-version 3`
+    V3,
+}
+
+/// Options for GFF/GTF conversion
+#[derive(Debug, Clone)]
+pub struct GffConvertOptions {
+    /// Number of threads (1 = sequential, 0 = auto)
+    pub threads: usize,
+    /// Attribute keys renamed in lifted records (old key -> new key)
+    pub rename_attributes: HashMap<String, String>,
+    /// `##gff-version` pragma handling
+    pub gff_version: GffVersion,
+    /// Validate records with `validate_gff_record` before mapping; invalid
+    /// records go to the unmap file tagged `InvalidRecord`
+    pub strict_input: bool,
+    /// Warn about feature types (column 3) that are not Sequence Ontology
+    /// terms; records are converted either way
+    pub validate_so_terms: bool,
+    /// Lift features that map to more than one target location (one line
+    /// per location) into this file instead of rejecting them
+    pub multimap_output: Option<PathBuf>,
+    /// Write a CDS split by target-side chain gaps as one line per fragment
+    /// with recalculated phase (never in strict mode)
+    pub split_cds: bool,
+}
+
+impl Default for GffConvertOptions {
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            rename_attributes: HashMap::new(),
+            gff_version: GffVersion::default(),
+            strict_input: false,
+            validate_so_terms: false,
+            multimap_output: None,
+            split_cds: false,
+        }
+    }
+}
+
+/// Number of leading lines inspected by `detect_gff_version`
+const DETECT_LINES: usize = 100;
+
+/// Whether an attribute column uses GFF3 `key=value` syntax rather than
+/// GFF2/GTF `key "value"`
+fn is_gff3_attribute_syntax(attrs: &str) -> bool {
+    let attrs = attrs.trim();
+    let is_gtf = attrs.contains(" \"") || attrs.starts_with("gene_id ");
+    !is_gtf && attrs.contains('=')
+}
+
+/// Whether the first data record uses GFF3 `key=value` attributes
+fn uses_gff3_attributes(lines: &[String]) -> bool {
+    lines
+        .iter()
+        .find(|l| !l.is_empty() && !l.starts_with('#'))
+        .and_then(|l| GffRecordView::parse(l.as_bytes()).ok())
+        .map(|view| is_gff3_attribute_syntax(view.attributes))
+        .unwrap_or(false)
+}
+
+/// Detect the input dialect from the first lines of a file
+///
+/// A `##gff-version` pragma decides, except that a version 2 pragma in
+/// front of GFF3 `key=value` attributes is treated as GFF3 (the same
+/// correction `GffVersion::Auto` applies to the output pragma). Without a
+/// pragma the first record with attributes decides: `key=value` is GFF3,
+/// `key "value";` is GFF2/GTF. Returns `V2` or `V3`, never `Auto`; input
+/// with nothing to go on is taken as GFF3.
+pub fn detect_gff_version(first_lines: &[&str]) -> GffVersion {
+    let declared = first_lines
+        .iter()
+        .take_while(|l| l.is_empty() || l.starts_with('#'))
+        .find_map(|l| l.strip_prefix("##gff-version"))
+        .map(str::trim);
+    let gff3_attributes = first_lines
+        .iter()
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|l| GffRecordView::parse(l.as_bytes()).ok())
+        .map(|view| view.attributes.trim())
+        .find(|attrs| !attrs.is_empty() && *attrs != ".")
+        .map(is_gff3_attribute_syntax);
+
+    match (declared, gff3_attributes) {
+        (Some(v), Some(false)) | (Some(v), None) if v.starts_with('2') => GffVersion::V2,
+        (Some(_), _) => GffVersion::V3,
+        (None, Some(false)) => GffVersion::V2,
+        (None, _) => GffVersion::V3,
+    }
+}
+
+/// Pragma to write at the top of the output in place of the input's
+/// `##gff-version` lines, or None to pass the input through unchanged
+fn output_version_pragma(lines: &[String], version: GffVersion) -> Option<&'static str> {
+    match version {
+        GffVersion::V2 => Some("##gff-version 2"),
+        GffVersion::V3 => Some("##gff-version 3"),
+        GffVersion::Auto => {
+            let declared = lines
+                .iter()
+                .take_while(|l| l.is_empty() || l.starts_with('#'))
+                .find_map(|l| l.strip_prefix("##gff-version"))
+                .map(str::trim);
+            if !uses_gff3_attributes(lines) {
+                return None;
+            }
+            match declared {
+                None => Some("##gff-version 3"),
+                Some(v) if v.starts_with('2') => {
+                    warn!("Input declares ##gff-version {} but uses GFF3 attributes; treating it as GFF3", v);
+                    Some("##gff-version 3")
+                }
+                Some(_) => None,
+            }
+        }
+    }
+}
+
+/// Rename attribute keys in a GFF3 (`key=value;...`) or GTF
+/// (`key "value"; ...`) attribute string
+///
+/// Values, separators and spacing are kept as they are; keys not in `map`
+/// are unchanged.
+pub fn rename_gff_attributes(attr_str: &str, map: &HashMap<String, String>) -> String {
+    rename_attributes(attr_str, map, GffVersion::Auto)
+}
+
+/// Attribute renaming with the key/value separator of `version`: `=` for
+/// GFF3, a space for GFF2/GTF, whichever comes first for `Auto`
+fn rename_attributes(attr_str: &str, map: &HashMap<String, String>, version: GffVersion) -> String {
+    attr_str
+        .split(';')
+        .map(|entry| {
+            let rest = entry.trim_start();
+            let lead = &entry[..entry.len() - rest.len()];
+            let key_end = match version {
+                GffVersion::V3 => rest.find('='),
+                GffVersion::V2 => rest.find(' '),
+                GffVersion::Auto => rest.find(['=', ' ']),
+            }
+            .unwrap_or(rest.len());
+            match map.get(&rest[..key_end]) {
+                Some(new_key) => format!("{}{}{}", lead, new_key, &rest[key_end..]),
+                None => entry.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Recalculate the phase of a CDS fragment that follows another fragment
+///
+/// `first_fragment_length` is the length of the preceding (5') fragment and
+/// `original_phase` its phase. Returns the phase of the next fragment.
+pub fn recalculate_phase(first_fragment_length: u64, original_phase: u8) -> u8 {
+    ((3 - (first_fragment_length % 3) as u8) % 3 + original_phase) % 3
+}
+
+/// Format a GFF line for a record lifted onto `target`
+fn format_gff_line(view: &GffRecordView, target: &MapResult, frame: &str, attributes: &str) -> String {
+    // Convert back to 1-based coordinates for GFF output
+    // CrossMap behavior: use the strand from the mapping result
+    // fields[6] = a[1][3] in CrossMap's mapgff.py
+    format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+        target.chrom,
+        view.source,
+        view.feature,
+        target.start + 1,
+        target.end,
+        view.score,
+        target.strand.to_char(),
+        frame,
+        attributes
+    )
+}
+
+/// Convert a CDS split into several fragments by chain gaps
+///
+/// The fragments must come from a single chain: same target chromosome and
+/// strand, disjoint source blocks that together cover the whole CDS (a gap
+/// in the source would drop bases and shift the reading frame). Each
+/// fragment is written on its own line (lines are joined with '\n'), and
+/// the phase of every fragment after the 5'-most one is recalculated from
+/// the preceding fragment's length.
+fn convert_split_cds(view: &GffRecordView, segments: &[MappingSegment], attributes: &str) -> Option<String> {
+    let phase: u8 = match view.frame {
+        "0" => 0,
+        "1" => 1,
+        "2" => 2,
+        _ => return None,
+    };
+    
+    let first = &segments[0].target;
+    if segments.iter().any(|s| s.target.chrom != first.chrom || s.target.strand != first.strand) {
+        return None;
+    }
+    
+    let mut ordered: Vec<&MappingSegment> = segments.iter().collect();
+    ordered.sort_by_key(|s| s.source.start);
+    if ordered.windows(2).any(|w| w[0].source.end > w[1].source.start) {
+        return None;
+    }
+    // Size preservation: every base of the CDS is lifted
+    let mapped_size: u64 = ordered.iter().map(|s| s.target.end - s.target.start).sum();
+    if mapped_size != view.size() {
+        return None;
+    }
+    
+    // Phase is counted from the 5' end of the feature
+    if view.strand == Some(Strand::Minus) {
+        ordered.reverse();
+    }
+    let mut fragments = Vec::with_capacity(ordered.len());
+    let mut fragment_phase = phase;
+    for seg in ordered {
+        fragments.push((&seg.target, fragment_phase));
+        fragment_phase = recalculate_phase(seg.source.end - seg.source.start, fragment_phase);
+    }
+    
+    // Write fragments in target coordinate order
+    fragments.sort_by_key(|(target, _)| target.start);
+    let lines: Vec<String> = fragments
+        .into_iter()
+        .map(|(target, phase)| format_gff_line(view, target, &phase.to_string(), attributes))
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// Map a GFF record's range, converting 1-based closed coordinates to
+/// 0-based half-open ones
+fn map_gff_record(view: &GffRecordView, mapper: &CoordinateMapper) -> Result<Vec<MappingSegment>, LiftoverFailureReason> {
+    // Zero or inverted ranges can't be mapped (validated with `strict_input`)
+    if view.start == 0 || view.start > view.end {
+        return Err(LiftoverFailureReason::ParseError);
+    }
+    
     // Get query strand (use Plus if unstranded)
     let query_strand = view.strand.unwrap_or(Strand::Plus);
     
-    // Convert 1-based GFF coordinates to 0-based for mapping
     // GFF: [start, end] 1-based inclusive
     // Internal: [start, end) 0-based half-open
-    let start_0based = view.start - 1;
-    let end_0based = view.end; // end is exclusive in 0-based
-    
-    // Map coordinates
-    let segments = mapper.map(view.seqname, start_0based, end_0based, query_strand)?;
-    
-    // GFF requires exact match: single segment, no size change
+    let segments = mapper
+        .map(view.seqname, view.start - 1, view.end, query_strand)
+        .ok_or(LiftoverFailureReason::Unmapped)?;
     if segments.is_empty() {
-        return None;
+        return Err(LiftoverFailureReason::Unmapped);
     }
+    Ok(segments)
+}
+
+/// Whether some source bases map through more than one segment, i.e. the
+/// feature has several target locations rather than being split by gaps
+fn segments_overlap_in_source(segments: &[MappingSegment]) -> bool {
+    let mut sources: Vec<(u64, u64)> = segments.iter().map(|s| (s.source.start, s.source.end)).collect();
+    sources.sort_unstable();
+    sources.windows(2).any(|pair| pair[1].0 < pair[0].1)
+}
+
+/// Convert a single GFF record already mapped to `segments`
+/// Returns the failure reason if conversion fails (unmapped, size changed, or
+/// multiple mappings).
+/// With `split_cds` (ignored in strict mode), a CDS split by chain gaps is
+/// written as one line per fragment.
+/// `version` is the input dialect, selecting the attribute parser.
+fn convert_gff_segments(
+    view: &GffRecordView,
+    segments: &[MappingSegment],
+    mapper: &CoordinateMapper,
+    options: &GffConvertOptions,
+    version: GffVersion,
+) -> Result<String, LiftoverFailureReason> {
+    let attributes = if options.rename_attributes.is_empty() {
+        Cow::Borrowed(view.attributes)
+    } else {
+        Cow::Owned(rename_attributes(view.attributes, &options.rename_attributes, version))
+    };
     
-    // Multiple mappings = fail
+    // Several target locations = multiple hits; a feature split by chain
+    // gaps fails, except for a CDS with `split_cds`
     if segments.len() > 1 {
-        return None;
+        if segments_overlap_in_source(segments) {
+            return Err(LiftoverFailureReason::MultipleHits);
+        }
+        if options.split_cds && view.feature == "CDS" && !mapper.compat_mode().is_strict() {
+            return convert_split_cds(view, segments, &attributes).ok_or(LiftoverFailureReason::InvalidBlock);
+        }
+        return Err(LiftoverFailureReason::Unmapped);
     }
     
     let seg = &segments[0];
     
-    // Check size preservation (exact match required)
+    // GFF requires exact match: no size change
     let original_size = view.size();
     let mapped_size = seg.target.end - seg.target.start;
     if mapped_size != original_size {
-        return None;
+        return Err(LiftoverFailureReason::Unmapped);
     }
     
-    // Convert back to 1-based coordinates for GFF output
-    let new_start = seg.target.start + 1;
-    let new_end = seg.target.end;
-    
-    // Determine output strand
-    // CrossMap behavior: use the strand from the mapping result
-    // fields[6] = a[1][3] in CrossMap's mapgff.py
-    let output_strand = seg.target.strand.to_char();
-    
     // Build output line
-    Some(format!(
-        "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
-        seg.target.chrom,
-        view.source,
-        view.feature,
-        new_start,
-        new_end,
-        view.score,
-        output_strand,
-        view.frame,
-        view.attributes
-    ))
+    Ok(format_gff_line(view, &seg.target, view.frame, &attributes))
 }
 
 
+/// Outcome of converting one GFF data line
+enum LineOutcome {
+    /// Lifted record (one or more output lines)
+    Lifted(String),
+    /// Mapped to several target locations (one line each), with `multimap_output`
+    MultiMapped(String),
+    /// Unparseable or unmappable; written to unmap unchanged
+    Unmapped,
+    /// Rejected by `validate_gff_record` (with `strict_input`)
+    Invalid,
+}
+
+/// Parse, optionally validate, and convert one GFF data line
+fn convert_gff_line(
+    line: &str,
+    mapper: &CoordinateMapper,
+    options: &GffConvertOptions,
+    version: GffVersion,
+) -> LineOutcome {
+    let view = match GffRecordView::parse(line.as_bytes()) {
+        Ok(view) => view,
+        Err(_) => return LineOutcome::Unmapped,
+    };
+    if options.strict_input && validate_gff_record(&view).is_err() {
+        return LineOutcome::Invalid;
+    }
+    let segments = match map_gff_record(&view, mapper) {
+        Ok(segments) => segments,
+        Err(_) => return LineOutcome::Unmapped,
+    };
+    match convert_gff_segments(&view, &segments, mapper, options, version) {
+        Ok(converted) => LineOutcome::Lifted(converted),
+        // Only features that truly map to several locations go to the
+        // multimap file; every other failure stays in the unmap file
+        Err(LiftoverFailureReason::MultipleHits) if options.multimap_output.is_some() => {
+            LineOutcome::MultiMapped(convert_gff_multimap(&view, &segments, options, version))
+        }
+        Err(_) => LineOutcome::Unmapped,
+    }
+}
+
+/// Lift a feature with several target locations, writing one line per
+/// location
+fn convert_gff_multimap(
+    view: &GffRecordView,
+    segments: &[MappingSegment],
+    options: &GffConvertOptions,
+    version: GffVersion,
+) -> String {
+    let attributes = if options.rename_attributes.is_empty() {
+        Cow::Borrowed(view.attributes)
+    } else {
+        Cow::Owned(rename_attributes(view.attributes, &options.rename_attributes, version))
+    };
+    let lines: Vec<String> = segments
+        .iter()
+        .map(|seg| format_gff_line(view, &seg.target, view.frame, &attributes))
+        .collect();
+    lines.join("\n")
+}
+
 /// Chunk size for parallel processing
 const CHUNK_SIZE: usize = 10000;
 
 /// Convert a GFF/GTF file
 ///
 /// # Arguments
-/// * `input` - Input GFF/GTF file path
-/// * `output` - Output GFF/GTF file path
+/// * `input` - Input GFF/GTF file path (`-` for stdin)
+/// * `output` - Output GFF/GTF file path (`-` for stdout)
 /// * `mapper` - Coordinate mapper
 /// * `threads` - Number of threads (1 = sequential)
 ///
@@ -253,25 +642,63 @@ pub fn convert_gff<P: AsRef<Path>>(
     mapper: &CoordinateMapper,
     threads: usize,
 ) -> Result<ConversionStats, std::io::Error> {
-    let input_file = std::fs::File::open(input.as_ref())?;
-    let reader = BufReader::with_capacity(128 * 1024, input_file);
+    let options = GffConvertOptions {
+        threads,
+        ..Default::default()
+    };
+    convert_gff_with_options(input, output, mapper, &options)
+}
+
+/// Convert a GFF/GTF file with extended options
+///
+/// Attribute renaming is applied to lifted records only; unmapped records
+/// are written unchanged.
+pub fn convert_gff_with_options<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    mapper: &CoordinateMapper,
+    options: &GffConvertOptions,
+) -> Result<ConversionStats, std::io::Error> {
+    let threads = io::resolve_threads(options.threads);
+    let reader = io::open_input(input.as_ref(), 128 * 1024)?;
     
     // Prepare output files with BufWriter for performance
     let output_path = output.as_ref();
-    let unmap_path = output_path.with_extension("gff.unmap");
+    let unmap_path = io::unmap_path_for(output_path, "gff.unmap");
     
-    let mut output_file = BufWriter::with_capacity(128 * 1024, std::fs::File::create(output_path)?);
-    let mut unmap_file = BufWriter::with_capacity(64 * 1024, std::fs::File::create(&unmap_path)?);
+    let mut output_file = io::create_output(output_path, 128 * 1024)?;
+    let mut unmap_file = io::create_unmap_output(&unmap_path, 64 * 1024)?;
+    let mut multimap_file = options
+        .multimap_output
+        .as_ref()
+        .map(|path| io::create_output(path, 64 * 1024))
+        .transpose()?;
     
     // Atomic counters for parallel processing
     let total = AtomicUsize::new(0);
     let success = AtomicUsize::new(0);
     let failed = AtomicUsize::new(0);
     let comments = AtomicUsize::new(0);
+    let multi_map = AtomicUsize::new(0);
     
     // Collect lines for processing
     let lines: Vec<String> = reader.lines().filter_map(|l| l.ok()).collect();
     
+    // A corrected pragma goes first and replaces the input's version lines
+    let pragma = output_version_pragma(&lines, options.gff_version);
+    if let Some(pragma) = pragma {
+        writeln!(output_file, "{}", pragma)?;
+    }
+    let skip_line = |line: &str| pragma.is_some() && line.starts_with("##gff-version");
+    
+    // Input dialect, deciding how attributes are split for renaming
+    let head: Vec<&str> = lines.iter().take(DETECT_LINES).map(String::as_str).collect();
+    let version = detect_gff_version(&head);
+    
+    if options.validate_so_terms {
+        warn_non_so_terms(&lines);
+    }
+    
     if threads <= 1 {
         // Sequential processing
         for line in &lines {
@@ -282,7 +709,9 @@ pub fn convert_gff<P: AsRef<Path>>(
             
             // Pass through comment lines (starting with #)
             if line.starts_with('#') {
-                writeln!(output_file, "{}", line)?;
+                if !skip_line(line) {
+                    writeln!(output_file, "{}", line)?;
+                }
                 comments.fetch_add(1, Ordering::Relaxed);
                 continue;
             }
@@ -290,21 +719,26 @@ pub fn convert_gff<P: AsRef<Path>>(
             total.fetch_add(1, Ordering::Relaxed);
             
             // Parse and convert
-            match GffRecordView::parse(line.as_bytes()) {
-                Ok(view) => {
-                    if let Some(converted) = convert_gff_record(&view, mapper) {
-                        writeln!(output_file, "{}", converted)?;
-                        success.fetch_add(1, Ordering::Relaxed);
-                    } else {
-                        writeln!(unmap_file, "{}", line)?;
-                        failed.fetch_add(1, Ordering::Relaxed);
+            match convert_gff_line(line, mapper, options, version) {
+                LineOutcome::Lifted(converted) => {
+                    writeln!(output_file, "{}", converted)?;
+                    success.fetch_add(1, Ordering::Relaxed);
+                }
+                LineOutcome::MultiMapped(converted) => {
+                    if let Some(multimap_file) = multimap_file.as_mut() {
+                        writeln!(multimap_file, "{}", converted)?;
                     }
+                    success.fetch_add(1, Ordering::Relaxed);
+                    multi_map.fetch_add(1, Ordering::Relaxed);
                 }
-                Err(_) => {
-                    // Parse error - write to unmap
+                LineOutcome::Unmapped => {
                     writeln!(unmap_file, "{}", line)?;
                     failed.fetch_add(1, Ordering::Relaxed);
                 }
+                LineOutcome::Invalid => {
+                    writeln!(unmap_file, "{}\t{}", line, LiftoverFailureReason::InvalidRecord.to_bed_annotation())?;
+                    failed.fetch_add(1, Ordering::Relaxed);
+                }
             }
         }
     } else {
@@ -323,7 +757,9 @@ pub fn convert_gff<P: AsRef<Path>>(
                     }
                     
                     if line.starts_with('#') {
-                        writeln!(output_file, "{}", line).ok();
+                        if !skip_line(line) {
+                            writeln!(output_file, "{}", line).ok();
+                        }
                         comments.fetch_add(1, Ordering::Relaxed);
                     } else {
                         data_lines.push((idx, line));
@@ -331,14 +767,11 @@ pub fn convert_gff<P: AsRef<Path>>(
                 }
                 
                 // Process data lines in parallel
-                let results: Vec<(usize, Option<String>, &String)> = data_lines
+                let results: Vec<(usize, LineOutcome, &String)> = data_lines
                     .par_chunks(CHUNK_SIZE)
                     .flat_map(|chunk| {
                         chunk.iter().map(|(idx, line)| {
-                            let result = GffRecordView::parse(line.as_bytes())
-                                .ok()
-                                .and_then(|view| convert_gff_record(&view, mapper));
-                            (*idx, result, *line)
+                            (*idx, convert_gff_line(line, mapper, options, version), *line)
                         }).collect::<Vec<_>>()
                     })
                     .collect();
@@ -347,24 +780,40 @@ pub fn convert_gff<P: AsRef<Path>>(
                 for (_idx, result, original) in results {
                     total.fetch_add(1, Ordering::Relaxed);
                     match result {
-                        Some(converted) => {
+                        LineOutcome::Lifted(converted) => {
                             writeln!(output_file, "{}", converted).ok();
                             success.fetch_add(1, Ordering::Relaxed);
                         }
-                        None => {
+                        LineOutcome::MultiMapped(converted) => {
+                            if let Some(multimap_file) = multimap_file.as_mut() {
+                                writeln!(multimap_file, "{}", converted).ok();
+                            }
+                            success.fetch_add(1, Ordering::Relaxed);
+                            multi_map.fetch_add(1, Ordering::Relaxed);
+                        }
+                        LineOutcome::Unmapped => {
                             writeln!(unmap_file, "{}", original).ok();
                             failed.fetch_add(1, Ordering::Relaxed);
                         }
+                        LineOutcome::Invalid => {
+                            writeln!(unmap_file, "{}\t{}", original, LiftoverFailureReason::InvalidRecord.to_bed_annotation()).ok();
+                            failed.fetch_add(1, Ordering::Relaxed);
+                        }
                     }
                 }
             });
     }
     
+    if let Some(multimap_file) = multimap_file.as_mut() {
+        multimap_file.flush()?;
+    }
+    
     Ok(ConversionStats {
         total: total.load(Ordering::Relaxed),
         success: success.load(Ordering::Relaxed),
         failed: failed.load(Ordering::Relaxed),
         comments: comments.load(Ordering::Relaxed),
+        multi_map: multi_map.load(Ordering::Relaxed),
     })
 }
 
@@ -372,6 +821,17 @@ pub fn convert_gff<P: AsRef<Path>>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    
+    /// Map and convert a single GFF record
+    fn convert_gff_record(
+        view: &GffRecordView,
+        mapper: &CoordinateMapper,
+        options: &GffConvertOptions,
+        version: GffVersion,
+    ) -> Result<String, LiftoverFailureReason> {
+        let segments = map_gff_record(view, mapper)?;
+        convert_gff_segments(view, &segments, mapper, options, version)
+    }
 
     #[test]
     fn test_gff_record_view_basic() {
@@ -434,6 +894,233 @@ mod tests {
         assert!(matches!(result, Err(GffParseError::InvalidStrand(_))));
     }
 
+    #[test]
+    fn test_gff_record_view_unknown_strand() {
+        let view = GffRecordView::parse(b"chr1\t.\tgene\t1000\t2000\t.\t?\t.\tID=g1").unwrap();
+        assert_eq!(view.strand, None);
+        assert_eq!(view.strand_char, "?");
+    }
+
+    #[test]
+    fn test_validate_gff_record() {
+        let validate = |line: &str| validate_gff_record(&GffRecordView::parse(line.as_bytes()).unwrap());
+        
+        assert!(validate("chr1\t.\tgene\t1000\t2000\t.\t+\t.\tID=g1").is_ok());
+        assert!(validate("chr1\t.\tgene\t1000\t1000\t.\t?\t.\tID=g1").is_ok());
+        assert!(validate("chr1\t.\tCDS\t1000\t2000\t.\t-\t2\tID=c1").is_ok());
+        // Phase is only checked on CDS
+        assert!(validate("chr1\t.\texon\t1000\t2000\t.\t+\t7\tID=e1").is_ok());
+        
+        assert!(matches!(validate("\t.\tgene\t1000\t2000\t.\t+\t.\tID=g1"), Err(GffParseError::EmptySeqid)));
+        assert!(matches!(
+            validate("chr1\t.\tgene\t2000\t1000\t.\t+\t.\tID=g1"),
+            Err(GffParseError::InvalidRange { start: 2000, end: 1000 })
+        ));
+        assert!(matches!(
+            validate("chr1\t.\tgene\t0\t1000\t.\t+\t.\tID=g1"),
+            Err(GffParseError::InvalidRange { start: 0, .. })
+        ));
+        assert!(matches!(
+            validate("chr1\t.\tCDS\t1000\t2000\t.\t+\t3\tID=c1"),
+            Err(GffParseError::InvalidPhase(ref p)) if p == "3"
+        ));
+        assert!(matches!(
+            validate("chr1\t.\tCDS\t1000\t2000\t.\t+\t\tID=c1"),
+            Err(GffParseError::InvalidPhase(_))
+        ));
+        
+        // Strands outside +/-/./? are rejected at parse time already
+        assert!(matches!(
+            GffRecordView::parse(b"chr1\t.\tgene\t1000\t2000\t.\tX\t.\tID=g1"),
+            Err(GffParseError::InvalidStrand(_))
+        ));
+    }
+
+    #[test]
+    fn test_recalculate_phase() {
+        // Length divisible by 3 keeps the phase
+        assert_eq!(recalculate_phase(300, 0), 0);
+        assert_eq!(recalculate_phase(300, 2), 2);
+        // One leftover base needs two more to finish the codon
+        assert_eq!(recalculate_phase(100, 0), 2);
+        assert_eq!(recalculate_phase(100, 1), 0);
+        // Two leftover bases need one more
+        assert_eq!(recalculate_phase(50, 0), 1);
+        assert_eq!(recalculate_phase(50, 2), 0);
+        assert_eq!(recalculate_phase(1, 0), 2);
+        assert_eq!(recalculate_phase(0, 1), 1);
+    }
+
+    #[test]
+    fn test_convert_split_cds() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+        
+        // A 10 bp insertion in the target after source base 100
+        let chain = parse_chain_bytes(
+            b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15010 1\n100\t0\t10\n9900\n",
+        ).unwrap();
+        let mut mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+        let options = GffConvertOptions { split_cds: true, ..Default::default() };
+        
+        // CDS 51..160 is split into 51..100 (50 bp) and 101..160
+        let plus = GffRecordView::parse(b"chr1\t.\tCDS\t51\t160\t.\t+\t0\tID=cds1").unwrap();
+        assert_eq!(
+            convert_gff_record(&plus, &mapper, &options, GffVersion::V3).unwrap(),
+            "chr1\t.\tCDS\t5051\t5100\t.\t+\t0\tID=cds1\nchr1\t.\tCDS\t5111\t5170\t.\t+\t1\tID=cds1"
+        );
+        
+        // On the minus strand the 5' fragment is the right-hand one
+        let minus = GffRecordView::parse(b"chr1\t.\tCDS\t51\t162\t.\t-\t2\tID=cds2").unwrap();
+        assert_eq!(
+            convert_gff_record(&minus, &mapper, &options, GffVersion::V3).unwrap(),
+            "chr1\t.\tCDS\t5051\t5100\t.\t-\t0\tID=cds2\nchr1\t.\tCDS\t5111\t5172\t.\t-\t2\tID=cds2"
+        );
+        
+        // Other split features still fail
+        let exon = GffRecordView::parse(b"chr1\t.\texon\t51\t160\t.\t+\t.\tID=exon1").unwrap();
+        assert_eq!(convert_gff_record(&exon, &mapper, &options, GffVersion::V3), Err(LiftoverFailureReason::Unmapped));
+        
+        // Off by default and in strict mode
+        assert_eq!(
+            convert_gff_record(&plus, &mapper, &GffConvertOptions::default(), GffVersion::V3),
+            Err(LiftoverFailureReason::Unmapped)
+        );
+        mapper.set_compat_mode(crate::core::CompatMode::Strict);
+        assert_eq!(convert_gff_record(&plus, &mapper, &options, GffVersion::V3), Err(LiftoverFailureReason::Unmapped));
+    }
+
+    #[test]
+    fn test_convert_split_cds_drops_bases() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+        
+        // Source 100-110 is in a chain gap, so a CDS across it loses bases
+        let chain = parse_chain_bytes(
+            b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n100\t10\t10\n9890\n",
+        ).unwrap();
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+        let options = GffConvertOptions { split_cds: true, ..Default::default() };
+        let cds = GffRecordView::parse(b"chr1\t.\tCDS\t51\t160\t.\t+\t0\tID=cds1").unwrap();
+        assert_eq!(convert_gff_record(&cds, &mapper, &options, GffVersion::V3), Err(LiftoverFailureReason::InvalidBlock));
+        
+        // Neither a split CDS that lost bases nor a gap-split feature is
+        // multi-mapped
+        let dir = tempfile::tempdir().unwrap();
+        let multimap_path = dir.path().join("out.multimap.gff");
+        let options = GffConvertOptions { split_cds: true, multimap_output: Some(multimap_path), ..Default::default() };
+        for line in ["chr1\t.\tCDS\t51\t160\t.\t+\t0\tID=cds1", "chr1\t.\texon\t51\t160\t.\t+\t.\tID=exon1"] {
+            assert!(matches!(convert_gff_line(line, &mapper, &options, GffVersion::V3), LineOutcome::Unmapped));
+        }
+    }
+    
+    #[test]
+    fn test_segments_overlap_in_source() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+        
+        // chr1:500-1000 is covered by two chains; 100-110 is a gap in the first
+        let chain = parse_chain_bytes(
+            b"chain 1000 chr1 10000 + 0 1000 chr1 20000 + 5000 6000 1\n100\t10\t10\n890\n\n\
+chain 500 chr1 10000 + 500 1000 chr2 20000 + 0 500 2\n500\n",
+        ).unwrap();
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+        let segments = |start, end| mapper.map("chr1", start, end, Strand::Plus).unwrap();
+        assert!(!segments_overlap_in_source(&segments(50, 160)));
+        assert!(segments_overlap_in_source(&segments(600, 700)));
+    }
+
+    #[test]
+    fn test_rename_gff_attributes() {
+        let map: HashMap<String, String> = [("gene_id", "ID"), ("transcript_id", "Parent")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        
+        // GTF style keeps quoting and spacing
+        assert_eq!(
+            rename_gff_attributes("gene_id \"G1\"; transcript_id \"T1\"; gene_name \"ABC\";", &map),
+            "ID \"G1\"; Parent \"T1\"; gene_name \"ABC\";"
+        );
+        // GFF3 style
+        assert_eq!(
+            rename_gff_attributes("gene_id=G1;Name=gene_id_like;transcript_id=T1", &map),
+            "ID=G1;Name=gene_id_like;Parent=T1"
+        );
+        // Keys are matched exactly, values are never renamed
+        assert_eq!(rename_gff_attributes("gene_idx=gene_id", &map), "gene_idx=gene_id");
+        assert_eq!(rename_gff_attributes(".", &map), ".");
+        assert_eq!(rename_gff_attributes("gene_id=G1", &HashMap::new()), "gene_id=G1");
+    }
+
+    #[test]
+    fn test_detect_gff_version() {
+        let gff3 = "chr1\t.\tgene\t1\t100\t.\t+\t.\tID=g1;Name=A";
+        let gtf = "chr1\t.\texon\t1\t100\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";";
+        let gff2 = "chr1\t.\texon\t1\t100\t.\t+\t.\tGroup \"g1\"; Note \"a=b\"";
+        let bare = "chr1\t.\texon\t1\t100\t.\t+\t.\t.";
+        
+        // Attribute syntax without a pragma
+        assert_eq!(detect_gff_version(&[gff3]), GffVersion::V3);
+        assert_eq!(detect_gff_version(&["#comment", gtf]), GffVersion::V2);
+        assert_eq!(detect_gff_version(&[gff2]), GffVersion::V2);
+        // Records without attributes are skipped
+        assert_eq!(detect_gff_version(&[bare, gtf]), GffVersion::V2);
+        assert_eq!(detect_gff_version(&[bare]), GffVersion::V3);
+        assert_eq!(detect_gff_version(&[]), GffVersion::V3);
+        
+        // The pragma wins
+        assert_eq!(detect_gff_version(&["##gff-version 3", gtf]), GffVersion::V3);
+        assert_eq!(detect_gff_version(&["##gff-version 2", bare]), GffVersion::V2);
+        assert_eq!(detect_gff_version(&["##gff-version 2", gtf]), GffVersion::V2);
+        assert_eq!(detect_gff_version(&["##gff-version 3.1.26"]), GffVersion::V3);
+        // ... unless a version 2 pragma sits on GFF3 attributes
+        assert_eq!(detect_gff_version(&["##gff-version 2", gff3]), GffVersion::V3);
+        // Pragmas after the first record are ignored
+        assert_eq!(detect_gff_version(&[gtf, "##gff-version 3"]), GffVersion::V2);
+    }
+
+    #[test]
+    fn test_rename_attributes_by_version() {
+        let map: HashMap<String, String> = [("Note", "note")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        
+        // A GFF2 value containing '=' does not end the key early
+        assert_eq!(
+            rename_attributes("Note \"a=b\"; Note=x", &map, GffVersion::V2),
+            "note \"a=b\"; Note=x"
+        );
+        // A GFF3 key ends at '=' only
+        assert_eq!(
+            rename_attributes("Note=a b;Note b=c", &map, GffVersion::V3),
+            "note=a b;Note b=c"
+        );
+    }
+
+    #[test]
+    fn test_output_version_pragma() {
+        let lines = |text: &str| -> Vec<String> { text.lines().map(String::from).collect() };
+        let gff3 = "chr1\t.\tgene\t1\t100\t.\t+\t.\tID=g1;Name=A";
+        let gtf = "chr1\t.\texon\t1\t100\t.\t+\t.\tgene_id \"g1\"; transcript_id \"t1\";";
+        
+        // Missing pragma with GFF3 attributes is added
+        assert_eq!(output_version_pragma(&lines(gff3), GffVersion::Auto), Some("##gff-version 3"));
+        // Version 2 declared but GFF3 attributes: corrected
+        let v2 = format!("##gff-version 2\n{}", gff3);
+        assert_eq!(output_version_pragma(&lines(&v2), GffVersion::Auto), Some("##gff-version 3"));
+        // Correct pragma is kept as is
+        let v3 = format!("##gff-version 3.1.26\n{}", gff3);
+        assert_eq!(output_version_pragma(&lines(&v3), GffVersion::Auto), None);
+        // GTF / GFF2 attributes are left alone
+        assert_eq!(output_version_pragma(&lines(gtf), GffVersion::Auto), None);
+        let gtf_v2 = format!("##gff-version 2\n{}", gtf);
+        assert_eq!(output_version_pragma(&lines(&gtf_v2), GffVersion::Auto), None);
+        assert_eq!(output_version_pragma(&[], GffVersion::Auto), None);
+        
+        // Explicit versions always win
+        assert_eq!(output_version_pragma(&lines(gtf), GffVersion::V3), Some("##gff-version 3"));
+        assert_eq!(output_version_pragma(&lines(&v3), GffVersion::V2), Some("##gff-version 2"));
+    }
+
     #[test]
     fn test_gff_record_view_gtf_format() {
         // GTF format with gene_id and transcript_id
@@ -448,4 +1135,14 @@ mod tests {
         assert!(view.attributes.contains("gene_id"));
         assert!(view.attributes.contains("transcript_id"));
     }
+
+    #[test]
+    fn test_is_so_term() {
+        for term in ["gene", "exon", "CDS", "mRNA", "five_prime_UTR", "SO:0000704"] {
+            assert!(is_so_term(term), "{}", term);
+        }
+        for term in ["bogus_feature", "Gene", "", "SO:704", "SO:000070x", "# Sequence Ontology"] {
+            assert!(!is_so_term(term), "{}", term);
+        }
+    }
 }
@@ -5,12 +5,12 @@
 //!
 //! **Validates: Requirements 7.1, 7.2, 7.3, 7.4, 7.5, 7.6, 7.7**
 
-use crate::core::{dna, CoordinateMapper, Strand};
+use crate::core::{chroms_equivalent, dna, io, update_chrom_id, ChromStyle, CoordinateMapper, LiftoverFailureReason, Strand};
 use memchr::memchr;
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::Path;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// GVCF parsing error
@@ -204,6 +204,14 @@ impl<'a> GvcfRecordView<'a> {
         self.end_position().is_some()
     }
     
+    /// Get GQ (genotype quality) of the first sample
+    /// Returns None if FORMAT has no GQ or the value is missing
+    pub fn sample_gq(&self) -> Option<u32> {
+        let gq_index = self.format()?.split(':').position(|k| k == "GQ")?;
+        let sample = self.field(9)?;
+        sample.split(':').nth(gq_index)?.parse().ok()
+    }
+    
     /// Check if ALT is <NON_REF> or <*> (GVCF non-variant marker)
     pub fn is_gvcf_non_ref(&self) -> bool {
         if let Some(alt) = self.alt_alleles() {
@@ -215,73 +223,322 @@ impl<'a> GvcfRecordView<'a> {
 }
 
 
-/// Stub for FASTA reader (reference genome access)
-/// In production, this would use rust-htslib or similar
-pub mod fasta_stub {
-    use std::path::Path;
+/// Indexed FASTA reader for target reference access
+///
+/// Sequences are located through a samtools-style `.fai` index (read from
+/// `<fasta>.fai` when present, otherwise built with one scan of the file),
+/// so a fetch seeks straight to the requested bases instead of holding the
+/// whole genome in memory. `load_chrom` pulls one chromosome into memory;
+/// `fetch_run` does so for a long run of small fetches on one chromosome,
+/// as in a sorted GVCF.
+pub mod fasta {
+    use crate::core::bgzf::{self, BgzfIndex, BgzfReader};
     use std::collections::HashMap;
-    use std::io::{BufRead, BufReader};
+    use std::fs::File;
+    use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+    
+    /// One `.fai` line: sequence length, byte offset of the first base,
+    /// bases per line and bytes per line (including the line terminator)
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct FaiEntry {
+        pub length: u64,
+        pub offset: u64,
+        pub line_bases: u64,
+        pub line_width: u64,
+    }
+    
+    impl FaiEntry {
+        /// Byte offset of base `pos` (0-based) in the FASTA file
+        fn byte_offset(&self, pos: u64) -> u64 {
+            self.offset + (pos / self.line_bases) * self.line_width + pos % self.line_bases
+        }
+    }
+    
+    /// Read a `.fai` index
+    pub fn read_fai<P: AsRef<Path>>(path: P) -> io::Result<HashMap<String, FaiEntry>> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut index = HashMap::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            let number = |i: usize| -> io::Result<u64> {
+                fields.get(i).and_then(|f| f.parse().ok()).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, format!("Invalid .fai line: {}", line))
+                })
+            };
+            let entry = FaiEntry {
+                length: number(1)?,
+                offset: number(2)?,
+                line_bases: number(3)?,
+                line_width: number(4)?,
+            };
+            index.insert(fields[0].to_string(), entry);
+        }
+        Ok(index)
+    }
+    
+    /// Build the `.fai` entries of a FASTA file with one pass over it
+    ///
+    /// Like `samtools faidx`, every line of a sequence but the last must
+    /// have the same length.
+    pub fn build_fai<R: BufRead>(mut reader: R) -> io::Result<HashMap<String, FaiEntry>> {
+        let mut index = HashMap::new();
+        let mut current: Option<(String, FaiEntry)> = None;
+        // Set once a line shorter than `line_bases` has been seen
+        let mut short_line = false;
+        let mut offset = 0u64;
+        let mut line = Vec::new();
+        
+        loop {
+            line.clear();
+            let width = reader.read_until(b'\n', &mut line)? as u64;
+            if width == 0 {
+                break;
+            }
+            offset += width;
+            
+            if line.first() == Some(&b'>') {
+                if let Some((name, entry)) = current.take() {
+                    index.insert(name, entry);
+                }
+                let name = String::from_utf8_lossy(&line[1..])
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or("")
+                    .to_string();
+                let entry = FaiEntry { length: 0, offset, line_bases: 0, line_width: 0 };
+                current = Some((name, entry));
+                short_line = false;
+                continue;
+            }
+            
+            let Some((name, entry)) = current.as_mut() else {
+                continue;
+            };
+            let bases = line.iter().filter(|b| !b.is_ascii_whitespace()).count() as u64;
+            if bases == 0 {
+                continue;
+            }
+            if entry.line_bases == 0 {
+                entry.line_bases = bases;
+                entry.line_width = width;
+            } else if short_line || bases > entry.line_bases {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Different line length in sequence '{}'", name),
+                ));
+            }
+            short_line |= bases < entry.line_bases;
+            entry.length += bases;
+        }
+        
+        if let Some((name, entry)) = current {
+            index.insert(name, entry);
+        }
+        Ok(index)
+    }
+    
+    /// Underlying FASTA file
+    enum Source {
+        Plain(File),
+        /// bgzip-compressed, with the block index translating `.fai`
+        /// offsets (uncompressed) into virtual offsets
+        Bgzf(BgzfReader<BufReader<File>>, BgzfIndex),
+    }
     
-    /// Simple FASTA reader for reference genome
-    /// Loads all sequences into memory at once for fast access
+    /// FASTA reader with `.fai` random access
+    ///
+    /// Plain and BGZF-compressed (`bgzip`, `.fa.gz`) files are supported.
+    /// For BGZF the `.fai` offsets refer to the uncompressed data, as
+    /// written by `samtools faidx`; the block index comes from
+    /// `<path>.gzi` when present and is otherwise built by decompressing
+    /// the file once.
     pub struct FastaReader {
-        /// Chromosome sequences
-        sequences: HashMap<String, Vec<u8>>,
+        file: Mutex<Source>,
+        index: HashMap<String, FaiEntry>,
+        /// Chromosome held in memory by `load_chrom`
+        loaded: Mutex<Option<(String, Vec<u8>)>>,
+        /// Chromosome of the latest `fetch_run` calls and how many in a row
+        run: Mutex<Option<(String, usize)>>,
     }
     
+    /// Consecutive `fetch_run` calls on one chromosome before it is loaded
+    /// into memory
+    pub const LOAD_AFTER_RUN: usize = 64;
+    
     impl FastaReader {
-        /// Open a FASTA file and load all sequences into memory
-        pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
-            let file = std::fs::File::open(path)?;
-            let reader = BufReader::new(file);
-            let mut sequences = HashMap::new();
-            let mut current_name = String::new();
-            let mut current_seq = Vec::new();
+        /// Open a FASTA file, using `<path>.fai` if it exists
+        pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+            let path = path.as_ref();
+            let with_suffix = |suffix: &str| {
+                let mut p = path.as_os_str().to_owned();
+                p.push(suffix);
+                PathBuf::from(p)
+            };
+            let fai_path = with_suffix(".fai");
             
-            for line in reader.lines() {
-                let line = line?;
-                if line.starts_with('>') {
-                    if !current_name.is_empty() {
-                        sequences.insert(current_name.clone(), current_seq.clone());
-                    }
-                    current_name = line[1..].split_whitespace().next().unwrap_or("").to_string();
-                    current_seq.clear();
+            let mut magic = [0u8; 16];
+            let magic_len = File::open(path)?.read(&mut magic)?;
+            let is_bgzf = bgzf::is_bgzf_header(&magic[..magic_len]);
+            if !is_bgzf && magic[..magic_len].starts_with(&[0x1f, 0x8b]) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} is gzip- but not BGZF-compressed; recompress it with bgzip", path.display()),
+                ));
+            }
+            
+            let index = if fai_path.exists() {
+                read_fai(&fai_path)?
+            } else if is_bgzf {
+                build_fai(BgzfReader::new(BufReader::new(File::open(path)?)))?
+            } else {
+                build_fai(BufReader::new(File::open(path)?))?
+            };
+            let source = if is_bgzf {
+                let gzi_path = with_suffix(".gzi");
+                let blocks = if gzi_path.exists() {
+                    BgzfIndex::read_gzi(&gzi_path)?
                 } else {
-                    current_seq.extend(line.trim().bytes());
+                    BgzfIndex::build(BufReader::new(File::open(path)?))?
+                };
+                Source::Bgzf(BgzfReader::new(BufReader::new(File::open(path)?)), blocks)
+            } else {
+                Source::Plain(File::open(path)?)
+            };
+            Ok(Self {
+                file: Mutex::new(source),
+                index,
+                loaded: Mutex::new(None),
+                run: Mutex::new(None),
+            })
+        }
+        
+        /// Index entry of a chromosome, trying with and without chr prefix
+        fn resolve(&self, chrom: &str) -> Option<(&str, &FaiEntry)> {
+            let alias = match chrom.strip_prefix("chr") {
+                Some(bare) => bare.to_string(),
+                None => format!("chr{}", chrom),
+            };
+            self.index
+                .get_key_value(chrom)
+                .or_else(|| self.index.get_key_value(&alias))
+                .map(|(name, entry)| (name.as_str(), entry))
+        }
+        
+        /// Length of a chromosome
+        pub fn chrom_len(&self, chrom: &str) -> Option<u64> {
+            self.resolve(chrom).map(|(_, entry)| entry.length)
+        }
+        
+        /// Read bases `[start, end)` of an indexed sequence from disk
+        fn read_bases(&self, entry: &FaiEntry, start: u64, end: u64) -> io::Result<Vec<u8>> {
+            if start >= end {
+                return Ok(Vec::new());
+            }
+            let from = entry.byte_offset(start);
+            let to = entry.byte_offset(end - 1) + 1;
+            let mut raw = vec![0u8; (to - from) as usize];
+            match &mut *self.file.lock().unwrap_or_else(|e| e.into_inner()) {
+                Source::Plain(file) => {
+                    file.seek(SeekFrom::Start(from))?;
+                    file.read_exact(&mut raw)?;
+                }
+                Source::Bgzf(reader, blocks) => {
+                    reader.seek_virtual(blocks.virtual_offset(from)?)?;
+                    reader.read_exact(&mut raw)?;
                 }
             }
-            
-            if !current_name.is_empty() {
-                sequences.insert(current_name, current_seq);
+            raw.retain(|b| !b.is_ascii_whitespace());
+            Ok(raw)
+        }
+        
+        /// Read a whole chromosome into memory so later fetches on it skip
+        /// the disk
+        ///
+        /// Only one chromosome is held at a time; loading another replaces
+        /// it. Returns `false` if the chromosome is not in the index.
+        pub fn load_chrom(&self, chrom: &str) -> io::Result<bool> {
+            let Some((name, entry)) = self.resolve(chrom) else {
+                return Ok(false);
+            };
+            let mut loaded = self.loaded.lock().unwrap_or_else(|e| e.into_inner());
+            if loaded.as_ref().is_some_and(|(loaded_name, _)| loaded_name == name) {
+                return Ok(true);
             }
-            
-            Ok(Self { sequences })
+            let seq = self.read_bases(entry, 0, entry.length)?;
+            *loaded = Some((name.to_string(), seq));
+            Ok(true)
         }
         
         /// Fetch sequence at given position (0-based, half-open)
+        ///
+        /// `end` is clamped to the chromosome length; None if the chromosome
+        /// is unknown or `start` lies past its end.
         pub fn fetch(&self, chrom: &str, start: u64, end: u64) -> Option<String> {
-            // Try with and without chr prefix
-            let seq = self.sequences.get(chrom)
-                .or_else(|| {
-                    if chrom.starts_with("chr") {
-                        self.sequences.get(&chrom[3..])
-                    } else {
-                        self.sequences.get(&format!("chr{}", chrom))
-                    }
-                })?;
-            
-            let start = start as usize;
-            let end = (end as usize).min(seq.len());
-            
-            if start >= seq.len() {
+            let (name, entry) = self.resolve(chrom)?;
+            if start >= entry.length {
                 return None;
             }
+            let end = end.min(entry.length);
             
-            Some(String::from_utf8_lossy(&seq[start..end]).to_string())
+            {
+                let loaded = self.loaded.lock().unwrap_or_else(|e| e.into_inner());
+                if let Some((_, seq)) = loaded.as_ref().filter(|(loaded_name, _)| loaded_name == name) {
+                    return Some(String::from_utf8_lossy(&seq[start as usize..end as usize]).to_string());
+                }
+            }
+            
+            let seq = self.read_bases(entry, start, end).ok()?;
+            Some(String::from_utf8_lossy(&seq).to_string())
+        }
+        
+        /// [`fetch`](Self::fetch) for a stream of records, loading the
+        /// chromosome once it has been asked for [`LOAD_AFTER_RUN`] times in
+        /// a row
+        ///
+        /// Sorted input stays on one chromosome for long runs and is served
+        /// from memory; unsorted input or chains onto many target
+        /// chromosomes use indexed reads instead of reloading a whole
+        /// chromosome at every switch.
+        pub fn fetch_run(&self, chrom: &str, start: u64, end: u64) -> Option<String> {
+            let (name, _) = self.resolve(chrom)?;
+            let in_memory = {
+                let loaded = self.loaded.lock().unwrap_or_else(|e| e.into_inner());
+                loaded.as_ref().is_some_and(|(loaded_name, _)| loaded_name == name)
+            };
+            if !in_memory {
+                let mut run = self.run.lock().unwrap_or_else(|e| e.into_inner());
+                let count = match run.as_mut() {
+                    Some((run_name, count)) if run_name == name => {
+                        *count += 1;
+                        *count
+                    }
+                    _ => {
+                        *run = Some((name.to_string(), 1));
+                        1
+                    }
+                };
+                drop(run);
+                if count >= LOAD_AFTER_RUN {
+                    self.load_chrom(name).ok()?;
+                }
+            }
+            self.fetch(chrom, start, end)
         }
     }
 }
 
+/// Former name of [`fasta`], kept so existing imports keep compiling
+#[deprecated(note = "renamed to `gvcf::fasta`")]
+pub mod fasta_stub {
+    pub use super::fasta::*;
+}
+
 
 /// Conversion statistics
 #[derive(Debug, Clone, Default)]
@@ -290,6 +547,8 @@ pub struct ConversionStats {
     pub success: usize,
     pub failed: usize,
     pub headers: usize,
+    /// Non-variant blocks dropped by the minimum GQ filter (also counted in failed)
+    pub filtered_gq: usize,
 }
 
 /// Result of converting a single GVCF record
@@ -340,7 +599,7 @@ fn update_info_end(info: &str, new_end: u64) -> String {
 fn convert_gvcf_record(
     view: &GvcfRecordView,
     mapper: &CoordinateMapper,
-    ref_genome: Option<&fasta_stub::FastaReader>,
+    ref_genome: Option<&fasta::FastaReader>,
     no_comp_allele: bool,
 ) -> ConversionResult {
     // Check if this is a non-variant block (has END=)
@@ -382,13 +641,13 @@ fn convert_gvcf_record(
                 ref_allele.to_string()
             } else if let Some(ref_reader) = ref_genome {
                 // Variant record: get REF from target reference
-                match ref_reader.fetch(target_chrom, target_start, target_start + 1) {
+                match ref_reader.fetch_run(target_chrom, target_start, target_start + 1) {
                     Some(seq) if !seq.is_empty() => seq.to_uppercase(),
                     _ => {
                         // CrossMap behavior: fail with KeyError if can't fetch reference
                         return ConversionResult::Failed(
                             reconstruct_line(view),
-                            "Fail(KeyError)".to_string(),
+                            LiftoverFailureReason::ReferenceKeyError.to_vcf_annotation(),
                         );
                     }
                 }
@@ -428,7 +687,7 @@ fn convert_gvcf_record(
                 if !no_comp_allele && alt_joined == new_ref {
                     return ConversionResult::Failed(
                         reconstruct_line(view),
-                        "Fail(REF==ALT)".to_string(),
+                        LiftoverFailureReason::RefEqualsAlt.to_vcf_annotation(),
                     );
                 }
                 alt_joined
@@ -471,21 +730,27 @@ fn convert_gvcf_record(
             ConversionResult::Success(output)
         }
         Some(segments) if segments.is_empty() => {
-            ConversionResult::Failed(reconstruct_line(view), "Fail(Unmapped)".to_string())
+            ConversionResult::Failed(reconstruct_line(view), LiftoverFailureReason::Unmapped.to_vcf_annotation())
         }
         Some(_) => {
             // Multiple mappings
-            ConversionResult::Failed(reconstruct_line(view), "Fail(Multiple)".to_string())
+            ConversionResult::Failed(reconstruct_line(view), LiftoverFailureReason::MultipleHits.to_vcf_annotation())
         }
         None => {
-            ConversionResult::Failed(reconstruct_line(view), "Fail(Unmapped)".to_string())
+            ConversionResult::Failed(reconstruct_line(view), LiftoverFailureReason::Unmapped.to_vcf_annotation())
         }
     }
 }
 
 
 /// Update contig header with target assembly information
-fn update_contig_header(line: &str, mapper: &CoordinateMapper) -> String {
+///
+/// The contig ID is matched against the target chromosomes with
+/// `chroms_equivalent`, so a header using `chr1` finds chain chromosome `1`
+/// and vice versa. With `ChromStyle::AsIs` the ID keeps the header's
+/// naming, as lifted records do; other styles rename the target
+/// chromosome. Contigs missing from the target assembly are unchanged.
+pub fn update_contig_header(line: &str, mapper: &CoordinateMapper) -> String {
     // Parse contig header: ##contig=<ID=chr1,length=248956422>
     if !line.starts_with("##contig=") {
         return line.to_string();
@@ -500,9 +765,17 @@ fn update_contig_header(line: &str, mapper: &CoordinateMapper) -> String {
     if let (Some(start), Some(end)) = (id_start, id_end) {
         let chrom = &line[start..end];
         
-        // Get target size for this chromosome
-        if let Some(size) = mapper.index().target_chrom_size(chrom) {
-            return format!("##contig=<ID={},length={}>", chrom, size);
+        // Get target size for this chromosome, exact name first
+        let target = mapper
+            .target_sizes()
+            .get_key_value(chrom)
+            .or_else(|| mapper.target_sizes().iter().find(|(name, _)| chroms_equivalent(name, chrom)));
+        if let Some((target_chrom, size)) = target {
+            let id = match mapper.chrom_style() {
+                ChromStyle::AsIs => chrom.to_string(),
+                style => update_chrom_id(target_chrom, style),
+            };
+            return format!("##contig=<ID={},length={}>", id, size);
         }
     }
     
@@ -513,15 +786,46 @@ fn update_contig_header(line: &str, mapper: &CoordinateMapper) -> String {
 #[allow(dead_code)]
 const CHUNK_SIZE: usize = 10000;
 
+/// Options for GVCF conversion
+#[derive(Debug, Clone)]
+pub struct GvcfConvertOptions {
+    /// If true, don't filter REF==ALT
+    pub no_comp_allele: bool,
+    /// If set, non-variant blocks with GQ below this go to the unmap file
+    pub min_gq: Option<u32>,
+    /// Number of threads (reserved for future parallel processing)
+    pub threads: usize,
+    /// Write `##liftover*` provenance headers before `#CHROM`
+    pub generate_provenance: bool,
+    /// Chain file recorded in `##liftoverChain` (base name only)
+    pub chain_file: Option<PathBuf>,
+    /// Command line recorded in `##liftoverCommandLine`
+    pub command_line: Option<String>,
+}
+
+impl Default for GvcfConvertOptions {
+    fn default() -> Self {
+        Self {
+            no_comp_allele: false,
+            min_gq: None,
+            threads: 1,
+            generate_provenance: true,
+            chain_file: None,
+            command_line: None,
+        }
+    }
+}
+
 /// Convert a GVCF file
 ///
 /// # Arguments
-/// * `input` - Input GVCF file path
-/// * `output` - Output GVCF file path
+/// * `input` - Input GVCF file path (`-` for stdin)
+/// * `output` - Output GVCF file path (`-` for stdout)
 /// * `mapper` - Coordinate mapper
 /// * `ref_genome` - Optional path to target reference genome (FASTA)
 /// * `no_comp_allele` - If true, don't filter REF==ALT
-/// * `_threads` - Number of threads (reserved for future parallel processing)
+/// * `min_gq` - If set, non-variant blocks with GQ below this go to the unmap file
+/// * `threads` - Number of threads (reserved for future parallel processing)
 ///
 /// # Returns
 /// Conversion statistics
@@ -531,21 +835,39 @@ pub fn convert_gvcf<P: AsRef<Path>>(
     mapper: &CoordinateMapper,
     ref_genome: Option<P>,
     no_comp_allele: bool,
-    _threads: usize,
+    min_gq: Option<u32>,
+    threads: usize,
 ) -> Result<ConversionStats, std::io::Error> {
-    let input_file = std::fs::File::open(input.as_ref())?;
-    let reader = BufReader::with_capacity(128 * 1024, input_file);
+    let options = GvcfConvertOptions {
+        no_comp_allele,
+        min_gq,
+        threads,
+        ..Default::default()
+    };
+    convert_gvcf_with_options(input, output, mapper, ref_genome, &options)
+}
+
+/// Convert a GVCF file with extended options
+pub fn convert_gvcf_with_options<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    mapper: &CoordinateMapper,
+    ref_genome: Option<P>,
+    options: &GvcfConvertOptions,
+) -> Result<ConversionStats, std::io::Error> {
+    let GvcfConvertOptions { no_comp_allele, min_gq, .. } = *options;
+    let reader = io::open_input(input.as_ref(), 128 * 1024)?;
     
     // Prepare output files with BufWriter for performance
     let output_path = output.as_ref();
-    let unmap_path = output_path.with_extension("gvcf.unmap");
+    let unmap_path = io::unmap_path_for(output_path, "gvcf.unmap");
     
-    let mut output_file = BufWriter::with_capacity(128 * 1024, std::fs::File::create(output_path)?);
-    let mut unmap_file = BufWriter::with_capacity(64 * 1024, std::fs::File::create(&unmap_path)?);
+    let mut output_file = io::create_output(output_path, 128 * 1024)?;
+    let mut unmap_file = io::create_unmap_output(&unmap_path, 64 * 1024)?;
     
     // Open reference genome if provided
-    let mut ref_reader = ref_genome
-        .map(|p| fasta_stub::FastaReader::open(p.as_ref()))
+    let ref_reader = ref_genome
+        .map(|p| fasta::FastaReader::open(p.as_ref()))
         .transpose()?;
     
     // Atomic counters
@@ -553,6 +875,7 @@ pub fn convert_gvcf<P: AsRef<Path>>(
     let success = AtomicUsize::new(0);
     let failed = AtomicUsize::new(0);
     let headers = AtomicUsize::new(0);
+    let filtered_gq = AtomicUsize::new(0);
     
     // Collect lines
     let lines: Vec<String> = reader.lines().filter_map(|l| l.ok()).collect();
@@ -616,8 +939,16 @@ pub fn convert_gvcf<P: AsRef<Path>>(
                     }
                 }
                 
-                // Write liftover metadata (CrossMap format)
-                writeln!(output_file, "##liftOverProgram=FastCrossMap")?;
+                // Write liftover metadata: the provenance block, or the
+                // CrossMap-format program line when it is disabled
+                if options.generate_provenance {
+                    let provenance = super::vcf::provenance_headers(options.chain_file.as_deref(), options.command_line.as_deref());
+                    for header in provenance {
+                        writeln!(output_file, "{}", header)?;
+                    }
+                } else {
+                    writeln!(output_file, "{}", super::vcf::LEGACY_PROGRAM_HEADER)?;
+                }
                 
                 // Write column header to both files
                 writeln!(output_file, "{}", line)?;
@@ -635,6 +966,16 @@ pub fn convert_gvcf<P: AsRef<Path>>(
         // Parse and convert
         match GvcfRecordView::parse(line.as_bytes()) {
             Ok(view) => {
+                // Drop low-quality non-variant blocks before liftover
+                if let Some(min) = min_gq {
+                    if view.is_non_variant_block() && view.sample_gq().is_some_and(|gq| gq < min) {
+                        writeln!(unmap_file, "{}\t{}", line, LiftoverFailureReason::LowGenotypeQuality.to_vcf_annotation())?;
+                        filtered_gq.fetch_add(1, Ordering::Relaxed);
+                        failed.fetch_add(1, Ordering::Relaxed);
+                        continue;
+                    }
+                }
+                
                 let result = convert_gvcf_record(&view, mapper, ref_reader.as_ref(), no_comp_allele);
                 match result {
                     ConversionResult::Success(converted) => {
@@ -663,6 +1004,7 @@ pub fn convert_gvcf<P: AsRef<Path>>(
         success: success.load(Ordering::Relaxed),
         failed: failed.load(Ordering::Relaxed),
         headers: headers.load(Ordering::Relaxed),
+        filtered_gq: filtered_gq.load(Ordering::Relaxed),
     })
 }
 
@@ -671,6 +1013,53 @@ pub fn convert_gvcf<P: AsRef<Path>>(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_update_contig_header_naming() {
+        use crate::core::{parse_chain_bytes, ChainIndex};
+        
+        // Chain without "chr" prefixes, header with them
+        let chain = b"chain 1000 1 1000 + 0 1000 1 5000 + 0 1000 1\n1000\n";
+        let index = || ChainIndex::from_chain_data(parse_chain_bytes(chain).unwrap());
+        
+        let mapper = CoordinateMapper::new(index(), ChromStyle::AsIs);
+        assert_eq!(update_contig_header("##contig=<ID=chr1,length=1000>", &mapper), "##contig=<ID=chr1,length=5000>");
+        assert_eq!(update_contig_header("##contig=<ID=1,length=1000>", &mapper), "##contig=<ID=1,length=5000>");
+        assert_eq!(update_contig_header("##contig=<ID=chr2,length=1000>", &mapper), "##contig=<ID=chr2,length=1000>");
+        assert_eq!(update_contig_header("##INFO=<ID=chr1>", &mapper), "##INFO=<ID=chr1>");
+        
+        let mapper = CoordinateMapper::new(index(), ChromStyle::Long);
+        assert_eq!(update_contig_header("##contig=<ID=1>", &mapper), "##contig=<ID=chr1,length=5000>");
+        let mapper = CoordinateMapper::new(index(), ChromStyle::Short);
+        assert_eq!(update_contig_header("##contig=<ID=chr1,length=1000>", &mapper), "##contig=<ID=1,length=5000>");
+    }
+
+    #[test]
+    fn test_convert_gvcf_record_failure_reasons() {
+        use crate::core::{parse_chain_bytes, ChainIndex};
+        
+        // chr1:0-100 -> chr1, and chr1:50-100 also -> chr2
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 100 chr1 1000 + 0 100 1
+100
+
+chain 500 chr1 1000 + 50 100 chr2 1000 + 0 50 2
+50
+";
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap()), ChromStyle::AsIs);
+        let reason = |line: &str| {
+            let view = GvcfRecordView::parse(line.as_bytes()).unwrap();
+            match convert_gvcf_record(&view, &mapper, None, false) {
+                ConversionResult::Failed(_, reason) => Some(reason),
+                _ => None,
+            }
+        };
+        
+        assert_eq!(reason("chr1\t10\t.\tA\tG\t.\t.\t."), None);
+        assert_eq!(reason("chr1\t60\t.\tA\tG\t.\t.\t.").as_deref(), Some("Fail(Multiple_hits)"));
+        assert_eq!(reason("chr1\t500\t.\tA\tG\t.\t.\t.").as_deref(), Some("Fail(Unmap)"));
+        assert_eq!(reason("chrUn\t10\t.\tA\tG\t.\t.\t.").as_deref(), Some("Fail(Unmap)"));
+    }
+    
     #[test]
     fn test_gvcf_record_view_basic() {
         let line = b"chr1\t100\t.\tA\tG\t30\tPASS\tDP=100";
@@ -752,4 +1141,138 @@ mod tests {
         let result = GvcfRecordView::parse(line);
         assert!(matches!(result, Err(GvcfParseError::TooFewFields { .. })));
     }
+
+    #[test]
+    fn test_sample_gq() {
+        let line = b"chr1\t100\t.\tA\t<NON_REF>\t.\t.\tEND=200\tGT:DP:GQ\t0/0:30:42";
+        let view = GvcfRecordView::parse(line).unwrap();
+        assert_eq!(view.sample_gq(), Some(42));
+        
+        // No GQ in FORMAT
+        let line = b"chr1\t100\t.\tA\t<NON_REF>\t.\t.\tEND=200\tGT:DP\t0/0:30";
+        let view = GvcfRecordView::parse(line).unwrap();
+        assert_eq!(view.sample_gq(), None);
+        
+        // Missing GQ value
+        let line = b"chr1\t100\t.\tA\t<NON_REF>\t.\t.\tEND=200\tGT:GQ\t0/0:.";
+        let view = GvcfRecordView::parse(line).unwrap();
+        assert_eq!(view.sample_gq(), None);
+    }
+    
+    const TEST_FASTA: &str = ">chr1 test\nACGTACGTAC\nGGGGGCCCCC\nTTT\n>2\nAAAAA\nC\n";
+
+    #[test]
+    fn test_build_fai() {
+        let index = fasta::build_fai(TEST_FASTA.as_bytes()).unwrap();
+        assert_eq!(index["chr1"], fasta::FaiEntry { length: 23, offset: 11, line_bases: 10, line_width: 11 });
+        assert_eq!(index["2"], fasta::FaiEntry { length: 6, offset: 40, line_bases: 5, line_width: 6 });
+        
+        // A short line that is not the last one cannot be indexed
+        assert!(fasta::build_fai(">x\nACG\nACGT\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_fasta_fetch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ref.fa");
+        std::fs::write(&path, TEST_FASTA).unwrap();
+        
+        // Index built on open, then the same from a .fai file
+        let built = fasta::FastaReader::open(&path).unwrap();
+        std::fs::write(dir.path().join("ref.fa.fai"), "chr1\t23\t11\t10\t11\n2\t6\t40\t5\t6\n").unwrap();
+        let indexed = fasta::FastaReader::open(&path).unwrap();
+        
+        for reader in [&built, &indexed] {
+            assert_eq!(reader.fetch("chr1", 0, 3).as_deref(), Some("ACG"));
+            // Across line breaks
+            assert_eq!(reader.fetch("chr1", 8, 22).as_deref(), Some("ACGGGGGCCCCCTT"));
+            // End clamped, start past the end
+            assert_eq!(reader.fetch("1", 20, 100).as_deref(), Some("TTT"));
+            assert_eq!(reader.fetch("chr1", 23, 24), None);
+            // chr prefix added or removed
+            assert_eq!(reader.fetch("chr2", 4, 6).as_deref(), Some("AC"));
+            assert_eq!(reader.fetch("chr3", 0, 1), None);
+            assert_eq!(reader.chrom_len("chr2"), Some(6));
+        }
+    }
+
+    #[test]
+    fn test_fasta_fetch_bgzf() {
+        use crate::core::bgzf::BgzfWriter;
+        use std::io::Write as _;
+        
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ref.fa.gz");
+        // Small blocks so sequences and fetches span block boundaries
+        let mut writer = BgzfWriter::with_block_size(std::fs::File::create(&path).unwrap(), 7);
+        writer.write_all(TEST_FASTA.as_bytes()).unwrap();
+        writer.finish().unwrap();
+        
+        // Index built on open, then the same from .fai (uncompressed offsets)
+        let built = fasta::FastaReader::open(&path).unwrap();
+        std::fs::write(dir.path().join("ref.fa.gz.fai"), "chr1\t23\t11\t10\t11\n2\t6\t40\t5\t6\n").unwrap();
+        let indexed = fasta::FastaReader::open(&path).unwrap();
+        
+        for reader in [&built, &indexed] {
+            assert_eq!(reader.chrom_len("chr1"), Some(23));
+            assert_eq!(reader.fetch("chr1", 0, 3).as_deref(), Some("ACG"));
+            assert_eq!(reader.fetch("chr1", 8, 22).as_deref(), Some("ACGGGGGCCCCCTT"));
+            assert_eq!(reader.fetch("chr2", 4, 6).as_deref(), Some("AC"));
+            assert!(reader.load_chrom("1").unwrap());
+            assert_eq!(reader.fetch("chr1", 9, 11).as_deref(), Some("CG"));
+        }
+        
+        // Plain gzip cannot be accessed randomly
+        let gz_path = dir.path().join("plain.fa.gz");
+        let mut gz = flate2::write::GzEncoder::new(std::fs::File::create(&gz_path).unwrap(), flate2::Compression::default());
+        gz.write_all(TEST_FASTA.as_bytes()).unwrap();
+        gz.finish().unwrap();
+        assert_eq!(fasta::FastaReader::open(&gz_path).err().unwrap().kind(), std::io::ErrorKind::InvalidData);
+    }
+    
+    #[test]
+    fn test_fasta_load_chrom() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ref.fa");
+        std::fs::write(&path, TEST_FASTA).unwrap();
+        let reader = fasta::FastaReader::open(&path).unwrap();
+        
+        assert!(reader.load_chrom("1").unwrap());
+        assert!(!reader.load_chrom("chrUn").unwrap());
+        assert_eq!(reader.fetch("chr1", 9, 11).as_deref(), Some("CG"));
+        // Other chromosomes are still read from disk
+        assert_eq!(reader.fetch("2", 0, 6).as_deref(), Some("AAAAAC"));
+        assert!(reader.load_chrom("chr2").unwrap());
+        assert_eq!(reader.fetch("chr2", 5, 6).as_deref(), Some("C"));
+        assert_eq!(reader.fetch("chr1", 22, 23).as_deref(), Some("T"));
+    }
+    
+    #[test]
+    fn test_fasta_fetch_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ref.fa");
+        std::fs::write(&path, TEST_FASTA).unwrap();
+        let reader = fasta::FastaReader::open(&path).unwrap();
+        let is_loaded = |chrom: &str| {
+            // A loaded chromosome is served even after the file is gone
+            reader.fetch(chrom, 0, 1).is_some()
+        };
+        
+        // Alternating chromosomes never load either of them
+        for _ in 0..fasta::LOAD_AFTER_RUN {
+            assert_eq!(reader.fetch_run("chr1", 9, 11).as_deref(), Some("CG"));
+            assert_eq!(reader.fetch_run("2", 5, 6).as_deref(), Some("C"));
+        }
+        std::fs::write(&path, "").unwrap();
+        assert!(!is_loaded("chr1") && !is_loaded("2"));
+        
+        // A long enough run on one chromosome loads it
+        std::fs::write(&path, TEST_FASTA).unwrap();
+        for _ in 0..fasta::LOAD_AFTER_RUN {
+            assert_eq!(reader.fetch_run("chr1", 9, 11).as_deref(), Some("CG"));
+        }
+        std::fs::write(&path, "").unwrap();
+        assert!(is_loaded("chr1"));
+        assert!(!is_loaded("2"));
+    }
 }
@@ -3,11 +3,15 @@
 //! Handles MAF format conversion for mutation annotation data.
 //! MAF is a tab-delimited format used by TCGA and other cancer genomics projects.
 //!
+//! Note: UCSC Multiple Alignment Format (also `.maf`, with `a`/`s`/`i`/`e`
+//! block lines and multi-species rows) is a different format and is not
+//! supported; such input is rejected with an `InvalidData` error.
+//!
 //! **Validates: Requirements 8.1, 8.2, 8.3, 8.4, 8.5, 8.6**
 
-use crate::core::{dna, CoordinateMapper, Strand};
+use crate::core::{dna, io, CoordinateMapper, Strand};
 use memchr::memchr;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::io::{BufRead, Write};
 use std::path::Path;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
@@ -373,11 +377,23 @@ fn convert_maf_record(
     Some(output_fields.join("\t"))
 }
 
+/// Check if a line looks like UCSC Multiple Alignment Format
+/// (`##maf` header or an `a`/`s`/`i`/`e`/`q` block line)
+fn is_alignment_maf_line(line: &str) -> bool {
+    if line.starts_with("##maf") {
+        return true;
+    }
+    let mut parts = line.split_whitespace();
+    matches!(parts.next(), Some("a") | Some("s") | Some("i") | Some("e") | Some("q"))
+        && parts.next().is_some()
+        && !line.contains('\t')
+}
+
 /// Convert a MAF file
 ///
 /// # Arguments
-/// * `input` - Input MAF file path
-/// * `output` - Output MAF file path
+/// * `input` - Input MAF file path (`-` for stdin)
+/// * `output` - Output MAF file path (`-` for stdout)
 /// * `mapper` - Coordinate mapper
 /// * `ref_genome` - Optional path to target reference genome (FASTA)
 /// * `target_build` - Target assembly name (e.g., "GRCh38")
@@ -391,15 +407,14 @@ pub fn convert_maf<P: AsRef<Path>>(
     ref_genome: Option<P>,
     target_build: &str,
 ) -> Result<ConversionStats, std::io::Error> {
-    let input_file = std::fs::File::open(input.as_ref())?;
-    let reader = BufReader::with_capacity(128 * 1024, input_file);
+    let reader = io::open_input(input.as_ref(), 128 * 1024)?;
     
     // Prepare output files with BufWriter for performance
     let output_path = output.as_ref();
-    let unmap_path = output_path.with_extension("maf.unmap");
+    let unmap_path = io::unmap_path_for(output_path, "maf.unmap");
     
-    let mut output_file = BufWriter::with_capacity(128 * 1024, std::fs::File::create(output_path)?);
-    let mut unmap_file = BufWriter::with_capacity(64 * 1024, std::fs::File::create(&unmap_path)?);
+    let mut output_file = io::create_output(output_path, 128 * 1024)?;
+    let mut unmap_file = io::create_unmap_output(&unmap_path, 64 * 1024)?;
     
     // Open reference genome if provided
     let ref_reader = ref_genome
@@ -421,6 +436,14 @@ pub fn convert_maf<P: AsRef<Path>>(
             continue;
         }
         
+        // Multiple Alignment Format uses the same extension but isn't supported
+        if column_indices.is_none() && is_alignment_maf_line(&line) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "Input looks like UCSC Multiple Alignment Format; only Mutation Annotation Format is supported",
+            ));
+        }
+        
         // Handle header/comment lines
         if line.starts_with('#') {
             writeln!(output_file, "{}", line)?;
@@ -553,4 +576,17 @@ mod tests {
         let result = MafRecordView::parse(line, &indices);
         assert!(matches!(result, Err(MafParseError::EmptyLine)));
     }
+
+    #[test]
+    fn test_is_alignment_maf_line() {
+        assert!(is_alignment_maf_line("##maf version=1 scoring=tba.v8"));
+        assert!(is_alignment_maf_line("a score=23262.0"));
+        assert!(is_alignment_maf_line("s hg18.chr7    27578828 38 + 158545518 AAA-GGGAATGTTAACCAAATGA"));
+        assert!(is_alignment_maf_line("i panTro1.chr6 N 0 C 0"));
+        
+        // Mutation Annotation Format lines
+        assert!(!is_alignment_maf_line("#version 2.4"));
+        assert!(!is_alignment_maf_line("Hugo_Symbol\tEntrez_Gene_Id\tCenter"));
+        assert!(!is_alignment_maf_line("s\t0\tcenter\tGRCh37\t1"));
+    }
 }
@@ -1,24 +1,29 @@
 //! File format adapters
 //!
-//! Adapters for different genomic file formats (BED, VCF, GVCF, GFF/GTF, MAF, Wiggle/BigWig, BAM/SAM/CRAM, Region).
+//! Adapters for different genomic file formats (BED, VCF, GVCF, GFF/GTF, MAF, Wiggle/BigWig, BAM/SAM/CRAM, Region),
+//! plus HTML conversion reports.
 
 #[cfg(feature = "bam")]
 pub mod bam;
 pub mod bed;
+pub mod detect;
 pub mod gff;
 pub mod gvcf;
 pub mod maf;
 pub mod region;
+pub mod report;
 pub mod vcf;
 pub mod wig;
 
 #[cfg(feature = "bam")]
-pub use bam::{BamError, AlignmentTag, CigarOp, CigarReconstructor, ConversionStats as BamConversionStats, convert_bam};
-pub use bed::{BedRecordView, BedParseError, convert_bed, ConversionStats as BedConversionStats};
-pub use gff::{GffRecordView, GffParseError, convert_gff, ConversionStats as GffConversionStats};
-pub use gvcf::{GvcfRecordView, GvcfParseError, convert_gvcf, ConversionStats as GvcfConversionStats};
+pub use bam::{BamError, AlignmentTag, BamConvertOptions, CigarOp, CigarReconstructor, OutputFormat as BamOutputFormat, ConversionStats as BamConversionStats, PairOrientation, convert_bam, convert_bam_with_options, re_evaluate_proper_pair};
+pub use detect::{GenomicFormat, detect_format};
+pub use bed::{BedRecordView, BedParseError, BedParseErrorWithContext, BedConvertOptions, CoordinateSystem, ZeroLengthPolicy, convert_bed, convert_bed_channel, convert_bed_with_options, normalize_bed_fields, normalize_itemrgb, ConversionStats as BedConversionStats};
+pub use gff::{GffRecordView, GffParseError, GffConvertOptions, GffVersion, convert_gff, convert_gff_with_options, detect_gff_version, is_so_term, rename_gff_attributes, validate_gff_record, ConversionStats as GffConversionStats};
+pub use gvcf::{GvcfRecordView, GvcfParseError, GvcfConvertOptions, convert_gvcf, convert_gvcf_with_options, update_contig_header, ConversionStats as GvcfConversionStats};
 pub use maf::{MafRecordView, MafParseError, MafColumnIndices, convert_maf, ConversionStats as MafConversionStats};
-pub use region::{RegionError, RegionResult, FailureReason, map_region, convert_region, parse_bed_line, ConversionStats as RegionConversionStats};
-pub use vcf::{VcfRecordView, VcfParseError, convert_vcf, ConversionStats as VcfConversionStats};
-pub use wig::{WigReader, WigDeclaration, WigFormat, WigDataPoint, BedGraphRecord, WigParseError, convert_wig, ConversionStats as WigConversionStats};
-pub use wig::bigwig::convert_bigwig;
+pub use report::{bed_report_html, write_bed_report};
+pub use region::{RegionError, RegionResult, RegionConvertOptions, FailureReason, map_region, mapping_ratio, convert_region, convert_region_with_options, parse_bed_line, ConversionStats as RegionConversionStats};
+pub use vcf::{VcfRecordView, VcfRecordViewEager, InfoMap, VcfParseError, VcfConvertOptions, BndUnmapPolicy, convert_vcf, convert_vcf_with_options, ConversionStats as VcfConversionStats};
+pub use wig::{WigReader, WigDeclaration, WigFormat, WigDataPoint, BedGraphRecord, WigParseError, WigAggregation, WigConvertOptions, WigErrorMode, merge_bedgraph_records, convert_wig, convert_wig_with_aggregation, convert_wig_with_options, convert_wig_parallel, ConversionStats as WigConversionStats};
+pub use wig::bigwig::{convert_bigwig, BigWigAggregation};
@@ -6,11 +6,10 @@
 //!
 //! **Validates: Requirements 11.1, 11.2, 11.3, 11.4, 11.5, 11.6**
 
-use crate::core::{CoordinateMapper, Strand};
-use std::collections::HashSet;
-use std::io::{BufRead, BufReader, Write, BufWriter};
-use std::fs::File;
-use std::path::Path;
+use crate::core::{io, CoordinateMapper, LiftoverFailureReason, MappingSegment, Strand};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
 
 /// Region conversion error
 #[derive(Debug)]
@@ -44,7 +43,37 @@ pub struct ConversionStats {
     pub failed: usize,
     pub cross_chrom: usize,
     pub low_ratio: usize,
+    /// Mapped segments covering less than `min_ratio` of their query
+    /// (see `MappingSegment::coverage_fraction`), counted in every record
+    pub low_coverage: usize,
     pub unmapped: usize,
+    /// Fragment records written with `emit_fragments`
+    pub total_fragments: usize,
+}
+
+/// Options for region conversion
+#[derive(Debug, Clone)]
+pub struct RegionConvertOptions {
+    /// Minimum mapping ratio for the merged region
+    pub min_ratio: f64,
+    /// Also write every mapped fragment of a split query as its own record
+    pub emit_fragments: bool,
+    /// Dedicated file for fragment records (defaults to the main output)
+    pub fragments_output: Option<PathBuf>,
+    /// Also append `map_ratio=` to Unmap and CrossChroms records in the
+    /// unmap file (LowRatio records always carry it)
+    pub emit_ratio: bool,
+}
+
+impl Default for RegionConvertOptions {
+    fn default() -> Self {
+        Self {
+            min_ratio: 0.85,
+            emit_fragments: false,
+            fragments_output: None,
+            emit_ratio: false,
+        }
+    }
 }
 
 /// Region mapping result
@@ -54,6 +83,7 @@ pub struct RegionResult {
     pub start: u64,
     pub end: u64,
     pub strand: Strand,
+    /// Fraction of the query bases that mapped (see [`mapping_ratio`])
     pub map_ratio: f64,
 }
 
@@ -67,89 +97,135 @@ pub enum FailureReason {
 }
 
 impl FailureReason {
+    /// Reason column written to the unmap file
     pub fn as_str(&self) -> &'static str {
-        match self {
-            FailureReason::Unmapped => "Unmap",
-            FailureReason::CrossChrom => "CrossChroms",
-            FailureReason::LowRatio => "LowRatio",
-            FailureReason::InvalidFormat => "InvalidFormat",
+        LiftoverFailureReason::from(*self).to_bed_annotation()
+    }
+}
+
+impl From<FailureReason> for LiftoverFailureReason {
+    fn from(reason: FailureReason) -> Self {
+        match reason {
+            FailureReason::Unmapped => LiftoverFailureReason::Unmapped,
+            FailureReason::CrossChrom => LiftoverFailureReason::CrossChromosome,
+            FailureReason::LowRatio => LiftoverFailureReason::LowCoverage,
+            FailureReason::InvalidFormat => LiftoverFailureReason::ParseError,
         }
     }
 }
 
-/// Map a single region with partial mapping support
+/// Fraction of a query of `query_length` bases covered by `segments`
 ///
-/// Returns Ok(RegionResult) if mapping succeeds with ratio >= min_ratio
-/// Returns Err(FailureReason) if mapping fails
-pub fn map_region(
-    mapper: &CoordinateMapper,
-    chrom: &str,
-    start: u64,
-    end: u64,
-    strand: Strand,
-    min_ratio: f64,
-) -> Result<RegionResult, FailureReason> {
-    let total_query_length = end - start;
-    if total_query_length == 0 {
-        return Err(FailureReason::InvalidFormat);
+/// Source bases mapped more than once are counted once. When the segments
+/// land on several target chromosomes, only the chromosome receiving the
+/// most query bases (the largest fragment) counts.
+pub fn mapping_ratio(segments: &[MappingSegment], query_length: u64) -> f64 {
+    if query_length == 0 {
+        return 0.0;
+    }
+    let mut by_target: HashMap<&str, Vec<(u64, u64)>> = HashMap::new();
+    for seg in segments {
+        by_target.entry(seg.target.chrom.as_str()).or_default().push((seg.source.start, seg.source.end));
     }
     
-    // Get all mapping segments
-    let segments = mapper.map(chrom, start, end, strand);
-    
-    if segments.is_none() || segments.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
+    let largest = by_target
+        .into_values()
+        .map(|mut pieces| {
+            pieces.sort_unstable();
+            let mut covered = 0;
+            let mut run: Option<(u64, u64)> = None;
+            for (start, end) in pieces {
+                run = match run {
+                    Some((run_start, run_end)) if start <= run_end => Some((run_start, run_end.max(end))),
+                    Some((run_start, run_end)) => {
+                        covered += run_end - run_start;
+                        Some((start, end))
+                    }
+                    None => Some((start, end)),
+                };
+            }
+            covered + run.map_or(0, |(start, end)| end - start)
+        })
+        .max()
+        .unwrap_or(0);
+    largest as f64 / query_length as f64
+}
+
+impl CoordinateMapper {
+    /// Map a large region, accepting partial matches
+    ///
+    /// The query is mapped with [`CoordinateMapper::map`] and all segments are
+    /// merged into one target region spanning them. The region is rejected
+    /// when no segment maps (`Unmapped`), when segments land on more than one
+    /// target chromosome (`CrossChrom`), or when fewer than `min_ratio` of
+    /// the query bases map (`LowRatio`, see [`mapping_ratio`]).
+    pub fn map_region(
+        &self,
+        chrom: &str,
+        start: u64,
+        end: u64,
+        strand: Strand,
+        min_ratio: f64,
+    ) -> Result<RegionResult, FailureReason> {
+        if end == start {
+            return Err(FailureReason::InvalidFormat);
+        }
+        let segments = self.map(chrom, start, end, strand).unwrap_or_default();
+        merge_segments(&segments, end - start, min_ratio)
+    }
+}
+
+/// Merge the segments of one query of `query_length` bases into a single
+/// target region (see [`CoordinateMapper::map_region`])
+fn merge_segments(segments: &[MappingSegment], query_length: u64, min_ratio: f64) -> Result<RegionResult, FailureReason> {
+    if segments.is_empty() {
         return Err(FailureReason::Unmapped);
     }
-    
-    let segments = segments.unwrap();
-    
-    // Single segment = 100% match
-    if segments.len() == 1 {
-        let seg = &segments[0];
+
+    // Single segment: one piece, possibly covering only part of the query
+    if let [seg] = segments {
+        if seg.coverage_fraction < min_ratio {
+            return Err(FailureReason::LowRatio);
+        }
         return Ok(RegionResult {
             chrom: seg.target.chrom.clone(),
             start: seg.target.start,
             end: seg.target.end,
             strand: seg.target.strand,
-            map_ratio: 1.0,
+            map_ratio: seg.coverage_fraction,
         });
     }
-    
-    // Multiple segments - calculate mapping ratio
-    let mut mapped_bases: u64 = 0;
+    let map_ratio = mapping_ratio(segments, query_length);
+
+    // Multiple segments - merge into one target region
     let mut target_chroms = HashSet::new();
     let mut target_starts = Vec::new();
     let mut target_ends = Vec::new();
     let mut target_strand = Strand::Plus;
-    
-    for seg in &segments {
-        // Count mapped bases from source
-        mapped_bases += seg.source.end - seg.source.start;
-        
+
+    for seg in segments {
         // Collect target info
         target_chroms.insert(seg.target.chrom.clone());
         target_starts.push(seg.target.start);
         target_ends.push(seg.target.end);
         target_strand = seg.target.strand;
     }
-    
-    let map_ratio = mapped_bases as f64 / total_query_length as f64;
-    
+
     // Check if mapping crosses chromosomes
     if target_chroms.len() > 1 {
         return Err(FailureReason::CrossChrom);
     }
-    
+
     // Check if mapping ratio meets threshold
     if map_ratio < min_ratio {
         return Err(FailureReason::LowRatio);
     }
-    
+
     // Merge all target segments into one region
     let target_chrom = target_chroms.into_iter().next().unwrap();
     let merged_start = *target_starts.iter().min().unwrap();
     let merged_end = *target_ends.iter().max().unwrap();
-    
+
     Ok(RegionResult {
         chrom: target_chrom,
         start: merged_start,
@@ -159,6 +235,21 @@ pub fn map_region(
     })
 }
 
+/// Map a single region with partial mapping support
+///
+/// Returns Ok(RegionResult) if mapping succeeds with ratio >= min_ratio
+/// Returns Err(FailureReason) if mapping fails
+pub fn map_region(
+    mapper: &CoordinateMapper,
+    chrom: &str,
+    start: u64,
+    end: u64,
+    strand: Strand,
+    min_ratio: f64,
+) -> Result<RegionResult, FailureReason> {
+    mapper.map_region(chrom, start, end, strand, min_ratio)
+}
+
 /// Parse a BED line and extract region info
 pub fn parse_bed_line(line: &str) -> Result<(String, u64, u64, Strand, Vec<&str>), FailureReason> {
     let fields: Vec<&str> = line.split('\t').collect();
@@ -190,11 +281,28 @@ pub fn parse_bed_line(line: &str) -> Result<(String, u64, u64, Strand, Vec<&str>
     Ok((chrom, start, end, strand, fields))
 }
 
+/// Replace coordinates and strand in the original BED fields
+fn format_mapped_fields(fields: &[&str], chrom: &str, start: u64, end: u64, strand: Strand) -> String {
+    let mut out_fields: Vec<String> = fields.iter().map(|s| s.to_string()).collect();
+    out_fields[0] = chrom.to_string();
+    out_fields[1] = start.to_string();
+    out_fields[2] = end.to_string();
+    
+    // Update strand if present
+    for field in &mut out_fields {
+        if *field == "+" || *field == "-" {
+            *field = strand.to_char().to_string();
+        }
+    }
+    
+    out_fields.join("\t")
+}
+
 /// Convert a region BED file
 ///
 /// # Arguments
-/// * `input` - Input BED file path
-/// * `output` - Output BED file path (mapped regions)
+/// * `input` - Input BED file path (`-` for stdin)
+/// * `output` - Output BED file path (mapped regions, `-` for stdout)
 /// * `mapper` - Coordinate mapper
 /// * `min_ratio` - Minimum mapping ratio (default 0.85)
 ///
@@ -206,16 +314,39 @@ pub fn convert_region<P: AsRef<Path>>(
     mapper: &CoordinateMapper,
     min_ratio: f64,
 ) -> Result<ConversionStats, RegionError> {
-    let input_file = File::open(input.as_ref())?;
-    let reader = BufReader::new(input_file);
-    
-    let output_file = File::create(output.as_ref())?;
-    let mut writer = BufWriter::new(output_file);
+    let options = RegionConvertOptions { min_ratio, ..Default::default() };
+    convert_region_with_options(input, output, mapper, &options)
+}
+
+/// Convert a region BED file with explicit options
+///
+/// With `emit_fragments`, a query that maps to two or more target
+/// fragments additionally gets one record per fragment, ordered by source
+/// position and tagged with a trailing `#fragment=N/M` column. Fragments
+/// are written even when the merged region fails (e.g. CrossChrom), to
+/// `fragments_output` if set and to the main output otherwise.
+pub fn convert_region_with_options<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    mapper: &CoordinateMapper,
+    options: &RegionConvertOptions,
+) -> Result<ConversionStats, RegionError> {
+    let min_ratio = options.min_ratio;
+    let reader = io::open_input(input.as_ref(), 8 * 1024)?;
+    let mut writer = io::create_output(output.as_ref(), 8 * 1024)?;
     
     // Create unmap file
-    let unmap_path = format!("{}.unmap", output.as_ref().display());
-    let unmap_file = File::create(&unmap_path)?;
-    let mut unmap_writer = BufWriter::new(unmap_file);
+    let unmap_path = if io::is_stdio(output.as_ref()) {
+        PathBuf::new()
+    } else {
+        PathBuf::from(format!("{}.unmap", output.as_ref().display()))
+    };
+    let mut unmap_writer = io::create_unmap_output(&unmap_path, 8 * 1024)?;
+    
+    let mut fragment_writer = match (&options.fragments_output, options.emit_fragments) {
+        (Some(path), true) => Some(io::create_output(path, 8 * 1024)?),
+        _ => None,
+    };
     
     let mut stats = ConversionStats::default();
     
@@ -237,55 +368,68 @@ pub fn convert_region<P: AsRef<Path>>(
         // Parse BED line
         let parsed = parse_bed_line(trimmed);
         if parsed.is_err() {
-            writeln!(unmap_writer, "{}\tFail\tInvalidFormat", trimmed)?;
+            writeln!(unmap_writer, "{}\tFail\t{}", trimmed, FailureReason::InvalidFormat.as_str())?;
             stats.failed += 1;
             continue;
         }
         
         let (chrom, start, end, strand, fields) = parsed.unwrap();
         
+        // Map once; the fragments, the merged region and the unmap ratio
+        // all come from these segments
+        let segments = mapper.map(&chrom, start, end, strand).unwrap_or_default();
+        stats.low_coverage += segments.iter().filter(|seg| seg.coverage_fraction < min_ratio).count();
+        
+        if options.emit_fragments {
+            let mut segments = segments.clone();
+            if segments.len() > 1 {
+                segments.sort_by_key(|seg| seg.source.start);
+                let count = segments.len();
+                for (i, seg) in segments.iter().enumerate() {
+                    let line = format_mapped_fields(
+                        &fields, &seg.target.chrom, seg.target.start, seg.target.end, seg.target.strand,
+                    );
+                    let out: &mut dyn Write = match fragment_writer.as_mut() {
+                        Some(w) => w,
+                        None => &mut writer,
+                    };
+                    writeln!(out, "{}\t#fragment={}/{}", line, i + 1, count)?;
+                }
+                stats.total_fragments += count;
+            }
+        }
+        
         // Map the region
-        match map_region(mapper, &chrom, start, end, strand, min_ratio) {
+        let region = if end == start {
+            Err(FailureReason::InvalidFormat)
+        } else {
+            merge_segments(&segments, end - start, min_ratio)
+        };
+        match region {
             Ok(result) => {
                 // Build output line with updated coordinates
-                let mut out_fields: Vec<String> = fields.iter().map(|s| s.to_string()).collect();
-                out_fields[0] = result.chrom;
-                out_fields[1] = result.start.to_string();
-                out_fields[2] = result.end.to_string();
-                
-                // Update strand if present
-                for field in &mut out_fields {
-                    if *field == "+" || *field == "-" {
-                        *field = if result.strand == Strand::Plus { "+".to_string() } else { "-".to_string() };
-                    }
-                }
-                
-                writeln!(writer, "{}\tmap_ratio={:.4}", out_fields.join("\t"), result.map_ratio)?;
+                let line = format_mapped_fields(&fields, &result.chrom, result.start, result.end, result.strand);
+                writeln!(writer, "{}\tmap_ratio={:.4}", line, result.map_ratio)?;
                 stats.success += 1;
             }
             Err(reason) => {
+                let ratio = || mapping_ratio(&segments, end - start);
                 match reason {
-                    FailureReason::Unmapped => {
-                        writeln!(unmap_writer, "{}\tFail\t{}", trimmed, reason.as_str())?;
-                        stats.unmapped += 1;
-                    }
-                    FailureReason::CrossChrom => {
-                        writeln!(unmap_writer, "{}\tFail\t{}", trimmed, reason.as_str())?;
-                        stats.cross_chrom += 1;
-                    }
-                    FailureReason::LowRatio => {
-                        // For low ratio, we still want to show the ratio
-                        // Need to recalculate to get the actual ratio
-                        let total_len = end - start;
-                        if let Some(segments) = mapper.map(&chrom, start, end, strand) {
-                            let mapped: u64 = segments.iter()
-                                .map(|s| s.source.end - s.source.start)
-                                .sum();
-                            let ratio = mapped as f64 / total_len as f64;
-                            writeln!(unmap_writer, "{}\tFail\tmap_ratio={:.4}", trimmed, ratio)?;
+                    FailureReason::Unmapped | FailureReason::CrossChrom => {
+                        if options.emit_ratio {
+                            writeln!(unmap_writer, "{}\tFail\t{}\tmap_ratio={:.4}", trimmed, reason.as_str(), ratio())?;
                         } else {
                             writeln!(unmap_writer, "{}\tFail\t{}", trimmed, reason.as_str())?;
                         }
+                        if reason == FailureReason::Unmapped {
+                            stats.unmapped += 1;
+                        } else {
+                            stats.cross_chrom += 1;
+                        }
+                    }
+                    FailureReason::LowRatio => {
+                        // For low ratio, we still want to show the ratio
+                        writeln!(unmap_writer, "{}\tFail\tmap_ratio={:.4}", trimmed, ratio())?;
                         stats.low_ratio += 1;
                     }
                     FailureReason::InvalidFormat => {
@@ -339,4 +483,90 @@ mod tests {
         assert_eq!(FailureReason::LowRatio.as_str(), "LowRatio");
         assert_eq!(FailureReason::InvalidFormat.as_str(), "InvalidFormat");
     }
+
+    #[test]
+    fn test_failure_reason_to_liftover_reason() {
+        assert_eq!(LiftoverFailureReason::from(FailureReason::Unmapped), LiftoverFailureReason::Unmapped);
+        assert_eq!(LiftoverFailureReason::from(FailureReason::CrossChrom), LiftoverFailureReason::CrossChromosome);
+        assert_eq!(LiftoverFailureReason::from(FailureReason::LowRatio), LiftoverFailureReason::LowCoverage);
+        assert_eq!(LiftoverFailureReason::from(FailureReason::InvalidFormat), LiftoverFailureReason::ParseError);
+    }
+
+    #[test]
+    fn test_map_region_ratio() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+
+        // Source [0,100) -> chr1 [1000,1100), gap, [150,250) -> chr1 [1150,1250),
+        // and source [300,360) -> chr2 [0,60)
+        let chain = parse_chain_bytes(b"\
+chain 1000 chr1 10000 + 0 250 chr1 10000 + 1000 1250 1
+100 50 50
+100
+
+chain 500 chr1 10000 + 300 360 chr2 1000 + 0 60 2
+60
+").unwrap();
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+
+        // Fully mapped
+        let result = map_region(&mapper, "chr1", 10, 50, Strand::Plus, 0.85).unwrap();
+        assert_eq!((result.start, result.end, result.map_ratio), (1010, 1050, 1.0));
+
+        // One segment covering half of the query
+        let result = map_region(&mapper, "chr1", 50, 150, Strand::Plus, 0.4).unwrap();
+        assert_eq!((result.start, result.end, result.map_ratio), (1050, 1100, 0.5));
+        assert_eq!(map_region(&mapper, "chr1", 50, 150, Strand::Plus, 0.85).unwrap_err(), FailureReason::LowRatio);
+
+        // Two segments, 200 of 250 bases
+        let result = map_region(&mapper, "chr1", 0, 250, Strand::Plus, 0.5).unwrap();
+        assert_eq!((result.start, result.end, result.map_ratio), (1000, 1250, 0.8));
+
+        // Cross-chromosome: 50 bases land on chr1, 60 on chr2; the larger counts
+        let segments = mapper.map("chr1", 200, 360, Strand::Plus).unwrap();
+        assert_eq!(mapping_ratio(&segments, 160), 60.0 / 160.0);
+        assert_eq!(map_region(&mapper, "chr1", 200, 360, Strand::Plus, 0.1).unwrap_err(), FailureReason::CrossChrom);
+        assert_eq!(mapping_ratio(&[], 100), 0.0);
+    }
+
+    #[test]
+    fn test_coordinate_mapper_map_region() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+
+        // Source chr1 [0,300) on the minus strand of chr5 (size 1000):
+        // blocks [0,100) and [200,300), with a 100 bp deletion in between
+        let chain = parse_chain_bytes(b"\
+chain 1000 chr1 10000 + 0 300 chr5 1000 - 500 700 1
+100 100 0
+100
+").unwrap();
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+
+        // Full coverage inside one block
+        let result = mapper.map_region("chr1", 10, 90, Strand::Plus, 1.0).unwrap();
+        assert_eq!((result.chrom.as_str(), result.strand, result.map_ratio), ("chr5", Strand::Minus, 1.0));
+        assert_eq!(result.end - result.start, 80);
+
+        // Both blocks, the deleted 100 bp counts against the ratio
+        let result = mapper.map_region("chr1", 0, 300, Strand::Plus, 0.6).unwrap();
+        assert!((result.map_ratio - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!((result.start, result.end), (300, 500));
+        assert_eq!(mapper.map_region("chr1", 0, 300, Strand::Plus, 0.7).unwrap_err(), FailureReason::LowRatio);
+
+        // Ratio exactly at the threshold is accepted
+        assert_eq!(mapper.map_region("chr1", 50, 150, Strand::Plus, 0.5).unwrap().map_ratio, 0.5);
+
+        // Entirely inside the deletion, unknown chromosome, empty query
+        assert_eq!(mapper.map_region("chr1", 120, 180, Strand::Plus, 0.1).unwrap_err(), FailureReason::Unmapped);
+        assert_eq!(mapper.map_region("chrUn", 0, 100, Strand::Plus, 0.1).unwrap_err(), FailureReason::Unmapped);
+        assert_eq!(mapper.map_region("chr1", 50, 50, Strand::Plus, 0.1).unwrap_err(), FailureReason::InvalidFormat);
+
+        // Query on the minus strand flips the result strand
+        let result = mapper.map_region("chr1", 10, 90, Strand::Minus, 0.85).unwrap();
+        assert_eq!(result.strand, Strand::Plus);
+
+        // Free function is a thin wrapper
+        let method = mapper.map_region("chr1", 0, 300, Strand::Plus, 0.6).unwrap();
+        let free = map_region(&mapper, "chr1", 0, 300, Strand::Plus, 0.6).unwrap();
+        assert_eq!((free.chrom, free.start, free.end, free.map_ratio), (method.chrom, method.start, method.end, method.map_ratio));
+    }
 }
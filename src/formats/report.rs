@@ -0,0 +1,122 @@
+//! HTML conversion reports
+//!
+//! Renders a self-contained HTML page (inline CSS, no scripts or external
+//! resources) summarizing a BED conversion: record counts, failure reasons,
+//! chain file metadata and the chain coverage histogram.
+
+use crate::core::{CoordinateMapper, Distribution, COVERAGE_HISTOGRAM_BINS};
+use crate::formats::bed::ConversionStats;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Width of the longest bar in the coverage histogram
+const HISTOGRAM_BAR_WIDTH: usize = 50;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1.5em}\
+th,td{border:1px solid #ccc;padding:4px 10px;text-align:left}\
+td.num{text-align:right}\
+pre{background:#f6f6f6;padding:1em}";
+
+/// Escape `&`, `<`, `>` and `"` for use in HTML text and attributes
+fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Append a two-column table of `(label, value)` rows
+fn push_table(html: &mut String, heading: &str, rows: &[(&str, String)]) {
+    let _ = write!(html, "<h2>{}</h2>\n<table>\n", heading);
+    for (label, value) in rows {
+        let _ = writeln!(html, "<tr><th>{}</th><td class=\"num\">{}</td></tr>", label, escape_html(value));
+    }
+    html.push_str("</table>\n");
+}
+
+/// Share of `count` in `total` as a percentage string
+fn percent(count: usize, total: usize) -> String {
+    if total == 0 {
+        return "0.00%".to_string();
+    }
+    format!("{:.2}%", count as f64 * 100.0 / total as f64)
+}
+
+/// Render the report for one BED conversion
+pub fn bed_report_html(input: &Path, stats: &ConversionStats, mapper: &CoordinateMapper) -> String {
+    let index = mapper.index();
+    let summary = index.statistics();
+    let title = format!("fast-crossmap BED liftover report: {}", input.display());
+
+    let mut html = String::with_capacity(4096);
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n",
+        escape_html(&title), STYLE, escape_html(&title)
+    );
+
+    push_table(&mut html, "Conversion statistics", &[
+        ("Total records", stats.total.to_string()),
+        ("Converted", format!("{} ({})", stats.success, percent(stats.success, stats.total))),
+        ("Multi-mapped", stats.multi_map.to_string()),
+        ("Failed", format!("{} ({})", stats.failed, percent(stats.failed, stats.total))),
+        ("Skipped", stats.skipped.to_string()),
+    ]);
+
+    let unmapped = stats.failed.saturating_sub(stats.invalid);
+    push_table(&mut html, "Failure reasons", &[
+        ("Unmapped", format!("{} ({})", unmapped, percent(unmapped, stats.failed))),
+        ("Invalid record", format!("{} ({})", stats.invalid, percent(stats.invalid, stats.failed))),
+    ]);
+
+    let distribution = |d: &Distribution| format!("min {} / max {} / mean {:.2} / median {:.1}", d.min, d.max, d.mean, d.median);
+    push_table(&mut html, "Chain file", &[
+        ("Chains", summary.chain_count.to_string()),
+        ("Blocks", summary.block_count.to_string()),
+        ("Source chromosomes", summary.source_coverage.len().to_string()),
+        ("Target chromosomes", index.target_sizes.len().to_string()),
+        ("Chain score", distribution(&summary.scores)),
+        ("Block size", distribution(&summary.block_sizes)),
+        ("Duplicate blocks removed", index.removed_duplicates().to_string()),
+    ]);
+
+    html.push_str("<h2>Source chromosome coverage</h2>\n<pre>\n");
+    let bin_width = 100 / COVERAGE_HISTOGRAM_BINS;
+    let max_count = summary.coverage_histogram.iter().copied().max().unwrap_or(0).max(1);
+    for (i, &count) in summary.coverage_histogram.iter().enumerate() {
+        let bar = "#".repeat((count * HISTOGRAM_BAR_WIDTH).div_ceil(max_count));
+        let _ = writeln!(html, "{:>8} | {:<width$} {}", format!("{}-{}%", i * bin_width, (i + 1) * bin_width), bar, count, width = HISTOGRAM_BAR_WIDTH);
+    }
+    html.push_str("</pre>\n</body>\n</html>\n");
+    html
+}
+
+/// Write the BED conversion report to `path`
+pub fn write_bed_report(path: &Path, input: &Path, stats: &ConversionStats, mapper: &CoordinateMapper) -> std::io::Result<()> {
+    std::fs::write(path, bed_report_html(input, stats, mapper))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("a<b>&\"c\""), "a&lt;b&gt;&amp;&quot;c&quot;");
+        assert_eq!(escape_html("plain.bed"), "plain.bed");
+    }
+
+    #[test]
+    fn test_percent() {
+        assert_eq!(percent(1, 4), "25.00%");
+        assert_eq!(percent(0, 0), "0.00%");
+    }
+}
@@ -4,14 +4,16 @@
 //!
 //! **Validates: Requirements 5.1, 5.2, 5.3, 5.4, 5.5, 5.6, 5.7**
 
-use crate::core::{dna, CoordinateMapper, Strand};
+use crate::core::{dna, io, CoordinateMapper, LiftoverFailureReason, Strand};
 use memchr::memchr;
 use rayon::prelude::*;
+use std::borrow::Cow;
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, BufWriter, Write};
-use std::path::Path;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::warn;
 
 /// VCF record representation for output
 #[derive(Debug, Clone)]
@@ -28,8 +30,17 @@ pub struct VcfRecord {
     pub samples: Vec<String>,
 }
 
+/// Parsed INFO column: key -> value (empty string for flags)
+pub type InfoMap = HashMap<String, String>;
+
 /// Zero-copy VCF record view for parsing
 /// Only parses CHROM and POS immediately, other fields are kept as byte slices
+///
+/// The lazily parsed INFO cache uses `Cell`/`RefCell`, so a view is `Send`
+/// but not `Sync`: it cannot be shared between threads by reference (e.g.
+/// in an `Arc` or a structure read by several rayon workers). The converters
+/// create one view per line on the thread that uses it. To share parsed
+/// records, use [`VcfRecordView::parse_info_eager`] instead.
 pub struct VcfRecordView<'a> {
     /// Original line bytes
     line: &'a [u8],
@@ -44,51 +55,75 @@ pub struct VcfRecordView<'a> {
     info_cache: RefCell<Option<HashMap<String, String>>>,
 }
 
-impl<'a> VcfRecordView<'a> {
-    /// Parse a VCF line with minimal allocation
-    /// Only parses CHROM and POS immediately
-    pub fn parse(line: &'a [u8]) -> Result<Self, VcfParseError> {
-        if line.is_empty() {
-            return Err(VcfParseError::EmptyLine);
+/// Split a VCF line into field boundaries and parse CHROM and POS
+fn split_vcf_fields(line: &[u8]) -> Result<VcfRecordViewEager<'_>, VcfParseError> {
+    if line.is_empty() {
+        return Err(VcfParseError::EmptyLine);
+    }
+
+    // Find field boundaries using memchr for tab characters
+    let mut field_bounds = Vec::with_capacity(10);
+    let mut start_pos = 0;
+    let mut pos = 0;
+    
+    while pos < line.len() {
+        if let Some(tab_pos) = memchr(b'\t', &line[pos..]) {
+            let end_pos = pos + tab_pos;
+            field_bounds.push((start_pos, end_pos));
+            start_pos = end_pos + 1;
+            pos = start_pos;
+        } else {
+            // Last field
+            field_bounds.push((start_pos, line.len()));
+            break;
         }
+    }
+    
+    // VCF requires at least 8 fields (CHROM, POS, ID, REF, ALT, QUAL, FILTER, INFO)
+    if field_bounds.len() < 8 {
+        return Err(VcfParseError::TooFewFields {
+            expected: 8,
+            found: field_bounds.len(),
+        });
+    }
+    
+    // Parse CHROM (field 0)
+    let chrom = std::str::from_utf8(&line[field_bounds[0].0..field_bounds[0].1])
+        .map_err(|_| VcfParseError::InvalidUtf8("CHROM"))?;
+    
+    // Parse POS (field 1)
+    let pos_str = std::str::from_utf8(&line[field_bounds[1].0..field_bounds[1].1])
+        .map_err(|_| VcfParseError::InvalidUtf8("POS"))?;
+    let pos: u64 = pos_str
+        .parse()
+        .map_err(|_| VcfParseError::InvalidNumber("POS", pos_str.to_string()))?;
+    
+    Ok(VcfRecordViewEager { line, chrom, pos, field_bounds })
+}
 
-        // Find field boundaries using memchr for tab characters
-        let mut field_bounds = Vec::with_capacity(10);
-        let mut start_pos = 0;
-        let mut pos = 0;
-        
-        while pos < line.len() {
-            if let Some(tab_pos) = memchr(b'\t', &line[pos..]) {
-                let end_pos = pos + tab_pos;
-                field_bounds.push((start_pos, end_pos));
-                start_pos = end_pos + 1;
-                pos = start_pos;
+/// Parse an INFO column into a map (empty for `.`)
+fn parse_info_str(info_str: &str) -> InfoMap {
+    let mut map = HashMap::new();
+    if info_str != "." {
+        for item in info_str.split(';') {
+            if let Some(eq_pos) = item.find('=') {
+                let key = item[..eq_pos].to_string();
+                let value = item[eq_pos + 1..].to_string();
+                map.insert(key, value);
             } else {
-                // Last field
-                field_bounds.push((start_pos, line.len()));
-                break;
+                // Flag without value
+                map.insert(item.to_string(), String::new());
             }
         }
-        
-        // VCF requires at least 8 fields (CHROM, POS, ID, REF, ALT, QUAL, FILTER, INFO)
-        if field_bounds.len() < 8 {
-            return Err(VcfParseError::TooFewFields {
-                expected: 8,
-                found: field_bounds.len(),
-            });
-        }
-        
-        // Parse CHROM (field 0)
-        let chrom = std::str::from_utf8(&line[field_bounds[0].0..field_bounds[0].1])
-            .map_err(|_| VcfParseError::InvalidUtf8("CHROM"))?;
-        
-        // Parse POS (field 1)
-        let pos_str = std::str::from_utf8(&line[field_bounds[1].0..field_bounds[1].1])
-            .map_err(|_| VcfParseError::InvalidUtf8("POS"))?;
-        let pos: u64 = pos_str
-            .parse()
-            .map_err(|_| VcfParseError::InvalidNumber("POS", pos_str.to_string()))?;
-        
+    }
+    map
+}
+
+impl<'a> VcfRecordView<'a> {
+    /// Parse a VCF line with minimal allocation
+    /// Only parses CHROM and POS immediately
+    pub fn parse(line: &'a [u8]) -> Result<Self, VcfParseError> {
+        let VcfRecordViewEager { line, chrom, pos, field_bounds } = split_vcf_fields(line)?;
         Ok(Self {
             line,
             chrom,
@@ -99,6 +134,16 @@ impl<'a> VcfRecordView<'a> {
         })
     }
     
+    /// Parse a VCF line together with its INFO column
+    /// 
+    /// Returns a view without interior mutability, which is `Sync` and can
+    /// be shared between threads, plus the parsed INFO map.
+    pub fn parse_info_eager(line: &'a [u8]) -> Result<(VcfRecordViewEager<'a>, InfoMap), VcfParseError> {
+        let view = split_vcf_fields(line)?;
+        let info = parse_info_str(view.info().unwrap_or("."));
+        Ok((view, info))
+    }
+    
     /// Get the number of fields
     pub fn field_count(&self) -> usize {
         self.field_bounds.len()
@@ -156,22 +201,7 @@ impl<'a> VcfRecordView<'a> {
     /// Parse INFO field lazily (only when needed)
     pub fn parse_info(&self) -> HashMap<String, String> {
         if !self.info_parsed.get() {
-            let info_str = self.info().unwrap_or(".");
-            let mut map = HashMap::new();
-            
-            if info_str != "." {
-                for item in info_str.split(';') {
-                    if let Some(eq_pos) = item.find('=') {
-                        let key = item[..eq_pos].to_string();
-                        let value = item[eq_pos + 1..].to_string();
-                        map.insert(key, value);
-                    } else {
-                        // Flag without value
-                        map.insert(item.to_string(), String::new());
-                    }
-                }
-            }
-            
+            let map = parse_info_str(self.info().unwrap_or("."));
             *self.info_cache.borrow_mut() = Some(map.clone());
             self.info_parsed.set(true);
             map
@@ -199,6 +229,78 @@ impl<'a> VcfRecordView<'a> {
     }
 }
 
+/// Thread-safe VCF record view returned by [`VcfRecordView::parse_info_eager`]
+///
+/// Same zero-copy field access as `VcfRecordView`, without the lazy INFO
+/// cache; the INFO map is parsed up front and returned alongside.
+#[derive(Debug, Clone)]
+pub struct VcfRecordViewEager<'a> {
+    /// Original line bytes
+    line: &'a [u8],
+    /// Chromosome name
+    pub chrom: &'a str,
+    /// Position (1-based)
+    pub pos: u64,
+    /// Field boundaries (start, end)
+    field_bounds: Vec<(usize, usize)>,
+}
+
+impl<'a> VcfRecordViewEager<'a> {
+    /// Get the number of fields
+    pub fn field_count(&self) -> usize {
+        self.field_bounds.len()
+    }
+    
+    /// Get field as string slice
+    pub fn field(&self, index: usize) -> Option<&'a str> {
+        self.field_bounds.get(index).and_then(|(start, end)| {
+            std::str::from_utf8(&self.line[*start..*end]).ok()
+        })
+    }
+    
+    /// Get ID field (field 2)
+    pub fn id(&self) -> Option<&'a str> {
+        self.field(2)
+    }
+    
+    /// Get REF field (field 3)
+    pub fn ref_allele(&self) -> Option<&'a str> {
+        self.field(3)
+    }
+    
+    /// Get ALT field (field 4)
+    pub fn alt_alleles(&self) -> Option<&'a str> {
+        self.field(4)
+    }
+    
+    /// Get QUAL field (field 5)
+    pub fn qual(&self) -> Option<&'a str> {
+        self.field(5)
+    }
+    
+    /// Get FILTER field (field 6)
+    pub fn filter(&self) -> Option<&'a str> {
+        self.field(6)
+    }
+    
+    /// Get INFO field (field 7)
+    pub fn info(&self) -> Option<&'a str> {
+        self.field(7)
+    }
+    
+    /// Get FORMAT field (field 8) if present
+    pub fn format(&self) -> Option<&'a str> {
+        self.field(8)
+    }
+    
+    /// Get sample fields (fields 9+)
+    pub fn samples(&self) -> Vec<&'a str> {
+        (9..self.field_count())
+            .filter_map(|i| self.field(i))
+            .collect()
+    }
+}
+
 /// Variant type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VariantType {
@@ -232,6 +334,16 @@ pub struct ConversionStats {
     pub total: usize,
     pub success: usize,
     pub failed: usize,
+    /// Records sent to the unmap file as `Fail(REF==ALT)` (included in
+    /// `failed`; always 0 with `no_comp_allele`). Strict mode keeps such
+    /// records with an empty ALT like CrossMap, so it only counts in
+    /// improved mode.
+    pub ref_eq_alt: usize,
+}
+
+/// Whether an unmap reason is the REF==ALT filter
+fn is_ref_eq_alt(reason: &str) -> bool {
+    reason == LiftoverFailureReason::RefEqualsAlt.to_vcf_annotation()
 }
 
 /// Result of converting a single VCF record
@@ -254,7 +366,7 @@ fn convert_vcf_record(
     view: &VcfRecordView,
     mapper: &CoordinateMapper,
     ref_genome: Option<&pysam_stub::FastaReader>,
-    no_comp_allele: bool,
+    options: &VcfConvertOptions,
 ) -> ConversionResult {
     // Map the first position of REF allele (VCF is 1-based)
     let start = view.pos - 1; // Convert to 0-based
@@ -270,6 +382,33 @@ fn convert_vcf_record(
             let target_end = seg.target.end;
             let target_strand = seg.target.strand;
             
+            // A broken chain can place the position past the end of the target chromosome
+            if let Some(size) = mapper.index().target_chrom_size(target_chrom) {
+                if target_start + 1 > size {
+                    return ConversionResult::Failed(
+                        reconstruct_line(view),
+                        LiftoverFailureReason::CoordinateOverflow.to_vcf_annotation(),
+                    );
+                }
+            }
+            
+            // An inversion lifted through a negative-strand block is reversed
+            // twice; whether it is still an inversion in the target is unclear
+            if target_strand == Strand::Minus && is_inversion(view.info().unwrap_or(".")) {
+                warn!(
+                    id = view.id().unwrap_or("."),
+                    chrom = view.chrom,
+                    pos = view.pos,
+                    "SVTYPE=INV record maps to a negative-strand chain block, inversion orientation is ambiguous"
+                );
+                if options.inversion_strict {
+                    return ConversionResult::Failed(
+                        reconstruct_line(view),
+                        LiftoverFailureReason::InversionStrand.to_vcf_annotation(),
+                    );
+                }
+            }
+            
             // Get original fields
             let ref_allele = view.ref_allele().unwrap_or("N");
             let alt_alleles_str = view.alt_alleles().unwrap_or(".");
@@ -278,6 +417,29 @@ fn convert_vcf_record(
             // Determine variant type
             let _v_type = view.variant_type();
             
+            // A record spanning more than one base keeps its length; strict
+            // mode sets END to the lifted first base like CrossMap
+            let original_info = view.info().unwrap_or(".");
+            let sv_end = match info_end(original_info) {
+                Some(old_end) if old_end > view.pos + 1 && !mapper.compat_mode().is_strict() => Some(old_end),
+                _ => None,
+            };
+            
+            // On a minus-strand block [POS, END] lands reversed, so the lifted
+            // record starts at the image of END
+            let target_start = match (target_strand, sv_end) {
+                (Strand::Minus, Some(old_end)) => match target_start.checked_sub(old_end - view.pos) {
+                    Some(start) => start,
+                    None => {
+                        return ConversionResult::Failed(
+                            reconstruct_line(view),
+                            LiftoverFailureReason::CoordinateOverflow.to_vcf_annotation(),
+                        );
+                    }
+                },
+                _ => target_start,
+            };
+            
             // Calculate new REF position based on strand and variant type
             let (new_pos, new_ref) = if let Some(ref_reader) = ref_genome {
                 // Get REF from target reference genome
@@ -289,7 +451,7 @@ fn convert_vcf_record(
                     None => {
                         return ConversionResult::Failed(
                             reconstruct_line(view),
-                            "Fail(KeyError)".to_string(),
+                            LiftoverFailureReason::ReferenceKeyError.to_vcf_annotation(),
                         );
                     }
                 }
@@ -301,7 +463,7 @@ fn convert_vcf_record(
             if new_ref.is_empty() {
                 return ConversionResult::Failed(
                     reconstruct_line(view),
-                    "Fail(KeyError)".to_string(),
+                    LiftoverFailureReason::ReferenceKeyError.to_vcf_annotation(),
                 );
             }
             
@@ -345,10 +507,29 @@ fn convert_vcf_record(
                 }
             }
             
-            // Filter out ALT alleles that equal REF (CrossMap: alt_alleles_updated = [i for i in alt_alleles_updated if i != ref_allele])
-            alt_alleles_updated.retain(|alt| alt != &new_ref);
+            // Keep the allele order before filtering for GT remapping
+            let transformed_alts = if target_strand == Strand::Minus {
+                alt_alleles_updated.clone()
+            } else {
+                Vec::new()
+            };
+            
+            // Improved mode: a record whose every ALT became the new REF is a
+            // REF==ALT record rather than one with an empty ALT
+            let all_alts_ref = !alt_alleles_updated.is_empty() && alt_alleles_updated.iter().all(|alt| alt == &new_ref);
+            if all_alts_ref && !mapper.compat_mode().is_strict() {
+                if !options.no_comp_allele {
+                    return ConversionResult::Failed(
+                        reconstruct_line(view),
+                        LiftoverFailureReason::RefEqualsAlt.to_vcf_annotation(),
+                    );
+                }
+            } else {
+                // Filter out ALT alleles that equal REF (CrossMap: alt_alleles_updated = [i for i in alt_alleles_updated if i != ref_allele])
+                alt_alleles_updated.retain(|alt| alt != &new_ref);
+            }
             
-            // CrossMap behavior: when alt_alleles_updated is empty after filtering,
+            // CrossMap behavior (strict mode): when alt_alleles_updated is empty after filtering,
             // it sets fields[4] = "" (empty string), then checks if fields[3] != fields[4].
             // Since REF != "", the record is output with empty ALT.
             // We match this behavior exactly.
@@ -357,13 +538,31 @@ fn convert_vcf_record(
             // CrossMap: if fields[3] != fields[4] (after join)
             // Note: when alt_alleles_updated is empty, join produces "", and REF != "" is true
             let alt_joined = alt_alleles_updated.join(",");
-            if !no_comp_allele && alt_joined == new_ref {
+            if !options.no_comp_allele && alt_joined == new_ref {
                 return ConversionResult::Failed(
                     reconstruct_line(view),
-                    "Fail(REF==ALT)".to_string(),
+                    LiftoverFailureReason::RefEqualsAlt.to_vcf_annotation(),
                 );
             }
             
+            // Remap GT indices if the allele list changed on a strand flip
+            let remapped_samples = if target_strand == Strand::Minus {
+                remap_sample_gts(view, ref_allele, &transformed_alts, &new_ref, &alt_alleles_updated)
+            } else {
+                None
+            };
+            
+            // INFO - update END if present
+            let info = match sv_end {
+                Some(old_end) => update_vcf_info_end(original_info, view.pos, old_end, new_pos),
+                None => update_info_end(original_info, target_end),
+            };
+            let info = if options.update_ci_intervals && target_strand == Strand::Minus {
+                mirror_ci_intervals(&info, sv_end.is_some())
+            } else {
+                info
+            };
+            
             // Build output line
             let output = format_output_line(
                 view,
@@ -371,7 +570,8 @@ fn convert_vcf_record(
                 new_pos,
                 &new_ref,
                 &alt_alleles_updated,
-                target_end,
+                &info,
+                remapped_samples.as_deref(),
             );
             
             ConversionResult::Success(output)
@@ -380,19 +580,38 @@ fn convert_vcf_record(
             // Multiple mappings
             ConversionResult::Failed(
                 reconstruct_line(view),
-                "Fail(Multiple_hits)".to_string(),
+                LiftoverFailureReason::MultipleHits.to_vcf_annotation(),
             )
         }
         _ => {
             // No mapping found
             ConversionResult::Failed(
                 reconstruct_line(view),
-                "Fail(Unmap)".to_string(),
+                LiftoverFailureReason::Unmapped.to_vcf_annotation(),
             )
         }
     }
 }
 
+/// Whether the INFO field declares `SVTYPE=INV`
+fn is_inversion(info: &str) -> bool {
+    info.split(';').any(|entry| entry == "SVTYPE=INV")
+}
+
+/// Value of the `END=` INFO entry, if present and numeric
+fn info_end(info: &str) -> Option<u64> {
+    info.split(';').find_map(|entry| entry.strip_prefix("END=")?.parse().ok())
+}
+
+/// Recompute `END=` for a record spanning `[old_start, old_end]` whose POS
+/// was lifted to `new_start` (1-based VCF positions)
+/// 
+/// The span is kept: `new_end = new_start + (old_end - old_start)`. On a
+/// minus-strand block `new_start` is the lifted image of `old_end`.
+fn update_vcf_info_end(info: &str, old_start: u64, old_end: u64, new_start: u64) -> String {
+    update_info_end(info, new_start + old_end.saturating_sub(old_start))
+}
+
 /// Update INFO field with new END value
 /// CrossMap uses: re.sub(r'END\=\d+', 'END=' + str(target_end), fields[7])
 fn update_info_end(info: &str, new_end: u64) -> String {
@@ -402,8 +621,8 @@ fn update_info_end(info: &str, new_end: u64) -> String {
     let bytes = info.as_bytes();
     
     while i < bytes.len() {
-        // Look for "END=" pattern
-        if i + 4 <= bytes.len() && &bytes[i..i+4] == b"END=" {
+        // Look for "END=" at the start of an entry (not inside CIEND=)
+        if i + 4 <= bytes.len() && &bytes[i..i+4] == b"END=" && (i == 0 || bytes[i - 1] == b';') {
             result.push_str("END=");
             i += 4;
             // Skip the old number
@@ -421,6 +640,33 @@ fn update_info_end(info: &str, new_end: u64) -> String {
     result
 }
 
+/// Mirror the `CIPOS` and `CIEND` intervals for a negative-strand target
+/// 
+/// An offset `x` from the position becomes `-x` once the strand flips, so
+/// `CIPOS=-10,50` turns into `CIPOS=-50,10`. With `swap_ends` the lifted
+/// POS is the image of END (and END of POS), so each interval also moves to
+/// the other key: `CIPOS=-10,50` becomes `CIEND=-50,10`. Entries that are
+/// not a pair of integers are left unchanged.
+fn mirror_ci_intervals(info: &str, swap_ends: bool) -> String {
+    info.split(';')
+        .map(|entry| {
+            let Some((key @ ("CIPOS" | "CIEND"), value)) = entry.split_once('=') else {
+                return entry.to_string();
+            };
+            let key = match (swap_ends, key) {
+                (true, "CIPOS") => "CIEND",
+                (true, _) => "CIPOS",
+                (false, key) => key,
+            };
+            match value.split_once(',').map(|(low, high)| (low.parse::<i64>(), high.parse::<i64>())) {
+                Some((Ok(low), Ok(high))) => format!("{}={},{}", key, -high, -low),
+                _ => entry.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
 /// Format output line for a successfully mapped VCF record
 fn format_output_line(
     view: &VcfRecordView,
@@ -428,7 +674,8 @@ fn format_output_line(
     pos: u64,
     ref_allele: &str,
     alt_alleles: &[String],
-    target_end: u64,
+    info: &str,
+    samples: Option<&[String]>,
 ) -> String {
     let mut output = String::with_capacity(512);
     
@@ -460,25 +707,123 @@ fn format_output_line(
     output.push_str(view.filter().unwrap_or("."));
     output.push('\t');
     
-    // INFO - update END if present (CrossMap behavior)
-    let info = view.info().unwrap_or(".");
-    let updated_info = update_info_end(info, target_end);
-    output.push_str(&updated_info);
+    // INFO (END already updated by the caller)
+    output.push_str(info);
     
     // FORMAT and samples
     if let Some(format) = view.format() {
         output.push('\t');
         output.push_str(format);
         
-        for sample in view.samples() {
-            output.push('\t');
-            output.push_str(sample);
+        match samples {
+            Some(samples) => {
+                for sample in samples {
+                    output.push('\t');
+                    output.push_str(sample);
+                }
+            }
+            None => {
+                for sample in view.samples() {
+                    output.push('\t');
+                    output.push_str(sample);
+                }
+            }
         }
     }
     
     output
 }
 
+/// Remap GT allele indices after the allele list changed
+/// 
+/// `old_alleles` are the original alleles (REF first) expressed on the target
+/// strand, `new_alleles` the alleles written to the output. Each index is
+/// replaced by the position of the same allele in `new_alleles`, or `.` if
+/// the allele no longer exists. Phasing separators are preserved.
+/// 
+/// # Example
+/// `0/1` with old `[T, C]` and new `[C]` (ALT became REF) gives `./0`.
+pub fn normalize_gt_on_strand_flip(gt: &str, old_alleles: &[&str], new_alleles: &[&str]) -> String {
+    if old_alleles == new_alleles {
+        return gt.to_string();
+    }
+    
+    let remap = |token: &str| -> String {
+        match token.parse::<usize>() {
+            Ok(idx) => old_alleles
+                .get(idx)
+                .and_then(|allele| new_alleles.iter().position(|a| a == allele))
+                .map(|i| i.to_string())
+                .unwrap_or_else(|| ".".to_string()),
+            Err(_) => token.to_string(),
+        }
+    };
+    
+    let mut result = String::with_capacity(gt.len());
+    let mut token_start = 0;
+    for (i, c) in gt.char_indices() {
+        if c == '/' || c == '|' {
+            result.push_str(&remap(&gt[token_start..i]));
+            result.push(c);
+            token_start = i + 1;
+        }
+    }
+    result.push_str(&remap(&gt[token_start..]));
+    result
+}
+
+/// Apply `normalize_gt_on_strand_flip` to the GT subfield of every sample
+/// 
+/// Returns None if there is nothing to change.
+fn remap_sample_gts(
+    view: &VcfRecordView,
+    ref_allele: &str,
+    transformed_alts: &[String],
+    new_ref: &str,
+    new_alts: &[String],
+) -> Option<Vec<String>> {
+    let gt_index = view.format()?.split(':').position(|k| k == "GT")?;
+    
+    // Original REF on the target strand; indel REFs are re-anchored on new REF
+    let old_ref = if dna::is_dna(ref_allele) && transformed_alts.iter().all(|a| a.len() == ref_allele.len()) {
+        dna::revcomp(ref_allele)
+    } else {
+        new_ref.to_string()
+    };
+    
+    let old_alleles: Vec<&str> = std::iter::once(old_ref.as_str())
+        .chain(transformed_alts.iter().map(|s| s.as_str()))
+        .collect();
+    let new_alleles: Vec<&str> = std::iter::once(new_ref)
+        .chain(new_alts.iter().map(|s| s.as_str()))
+        .collect();
+    
+    if old_alleles == new_alleles {
+        return None;
+    }
+    
+    let samples = view
+        .samples()
+        .iter()
+        .map(|sample| {
+            sample
+                .split(':')
+                .enumerate()
+                .map(|(i, value)| {
+                    if i == gt_index {
+                        normalize_gt_on_strand_flip(value, &old_alleles, &new_alleles)
+                    } else {
+                        value.to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(":")
+        })
+        .collect();
+    
+    Some(samples)
+}
+
 /// Reconstruct original line from view
 fn reconstruct_line(view: &VcfRecordView) -> String {
     let mut output = String::with_capacity(512);
@@ -495,76 +840,577 @@ fn reconstruct_line(view: &VcfRecordView) -> String {
     output
 }
 
+/// A single-ALT record produced by splitting a multi-allelic record
+#[derive(Debug, Clone, PartialEq)]
+pub struct BiallelicRecord {
+    /// Full VCF line with one ALT allele and adjusted GT/AD/PL
+    pub line: String,
+    /// 1-based index of the ALT allele in the original record
+    pub alt_index: usize,
+}
 
-/// Stub module for FASTA reading (placeholder for pysam-like functionality)
-pub mod pysam_stub {
-    use std::collections::HashMap;
-    use std::io::{BufRead, BufReader};
-    use std::path::Path;
-    
-    /// Simple FASTA reader for reference genome
-    pub struct FastaReader {
-        sequences: HashMap<String, Vec<u8>>,
-        chrom_order: Vec<String>,
+/// Split a multi-allelic record into one record per ALT allele
+/// 
+/// FORMAT fields are adjusted per sample (bcftools norm -m- convention):
+/// - GT: the kept allele becomes 1, other ALT alleles become 0
+/// - AD: REF and the kept ALT depth
+/// - PL/GL: likelihoods for 0/0, 0/k, k/k (or 0, k for haploid calls)
+/// 
+/// Records with a single ALT are returned unchanged.
+pub fn split_multiallelic(record: &VcfRecordView) -> Vec<BiallelicRecord> {
+    let alts: Vec<&str> = record.alt_alleles().unwrap_or(".").split(',').collect();
+    if alts.len() < 2 {
+        return vec![BiallelicRecord {
+            line: reconstruct_line(record),
+            alt_index: 1,
+        }];
     }
     
-    impl FastaReader {
-        /// Open a FASTA file and load all sequences
-        pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
-            let file = std::fs::File::open(path)?;
-            let reader = BufReader::new(file);
-            let mut sequences = HashMap::new();
-            let mut chrom_order = Vec::new();
-            let mut current_name = String::new();
-            let mut current_seq = Vec::new();
+    let format_keys: Vec<&str> = record.format().map(|f| f.split(':').collect()).unwrap_or_default();
+    let samples = record.samples();
+    let n_alleles = alts.len() + 1;
+    
+    alts.iter()
+        .enumerate()
+        .map(|(i, alt)| {
+            let k = i + 1;
+            let mut fields: Vec<String> = (0..record.field_count())
+                .map(|f| record.field(f).unwrap_or("").to_string())
+                .collect();
+            fields[4] = alt.to_string();
             
-            for line in reader.lines() {
-                let line = line?;
-                if line.starts_with('>') {
-                    if !current_name.is_empty() {
-                        chrom_order.push(current_name.clone());
-                        sequences.insert(current_name.clone(), current_seq.clone());
-                    }
-                    current_name = line[1..].split_whitespace().next().unwrap_or("").to_string();
-                    current_seq.clear();
-                } else {
-                    current_seq.extend(line.trim().bytes());
-                }
+            for (s, sample) in samples.iter().enumerate() {
+                fields[9 + s] = split_sample_field(sample, &format_keys, k, n_alleles);
             }
             
-            if !current_name.is_empty() {
-                chrom_order.push(current_name.clone());
-                sequences.insert(current_name, current_seq);
+            BiallelicRecord {
+                line: fields.join("\t"),
+                alt_index: k,
             }
-            
-            Ok(Self { sequences, chrom_order })
-        }
-        
-        /// Fetch a region from the reference (0-based, half-open)
-        pub fn fetch(&self, chrom: &str, start: u64, end: u64) -> Option<String> {
-            // Try with and without chr prefix
-            let seq = self.sequences.get(chrom)
-                .or_else(|| {
-                    if chrom.starts_with("chr") {
-                        self.sequences.get(&chrom[3..])
-                    } else {
-                        self.sequences.get(&format!("chr{}", chrom))
-                    }
-                })?;
-            
-            let start = start as usize;
-            let end = (end as usize).min(seq.len());
-            
-            if start >= seq.len() {
-                return None;
+        })
+        .collect()
+}
+
+/// Adjust one sample column for the ALT allele `k`
+fn split_sample_field(sample: &str, format_keys: &[&str], k: usize, n_alleles: usize) -> String {
+    let values: Vec<String> = sample
+        .split(':')
+        .enumerate()
+        .map(|(i, value)| match format_keys.get(i) {
+            Some(&"GT") => split_gt(value, k),
+            Some(&"AD") => select_values(value, n_alleles, &[0, k]),
+            Some(&"PL") | Some(&"GL") => {
+                let n_genotypes = n_alleles * (n_alleles + 1) / 2;
+                let kk = k * (k + 1) / 2;
+                if value.split(',').count() == n_genotypes {
+                    select_values(value, n_genotypes, &[0, kk, kk + k])
+                } else {
+                    // Haploid: one value per allele
+                    select_values(value, n_alleles, &[0, k])
+                }
             }
-            
-            Some(String::from_utf8_lossy(&seq[start..end]).to_string())
-        }
-        
-        /// Get chromosome names in order
-        pub fn references(&self) -> Vec<&str> {
-            self.chrom_order.iter().map(|s| s.as_str()).collect()
+            _ => value.to_string(),
+        })
+        .collect();
+    values.join(":")
+}
+
+/// Remap GT allele indices: `k` -> 1, other ALT alleles -> 0
+fn split_gt(gt: &str, k: usize) -> String {
+    let mut result = String::with_capacity(gt.len());
+    let mut token = String::new();
+    
+    let flush = |token: &mut String, result: &mut String| {
+        match token.parse::<usize>() {
+            Ok(a) if a == k => result.push('1'),
+            Ok(_) => result.push('0'),
+            Err(_) => result.push_str(token),
+        }
+        token.clear();
+    };
+    
+    for c in gt.chars() {
+        if c == '/' || c == '|' {
+            flush(&mut token, &mut result);
+            result.push(c);
+        } else {
+            token.push(c);
+        }
+    }
+    flush(&mut token, &mut result);
+    result
+}
+
+/// Pick values at `indices` from a comma-separated list of `expected` values
+/// 
+/// Returns the value unchanged if it doesn't have the expected length.
+fn select_values(value: &str, expected: usize, indices: &[usize]) -> String {
+    let items: Vec<&str> = value.split(',').collect();
+    if items.len() != expected {
+        return value.to_string();
+    }
+    indices.iter().map(|&i| items[i]).collect::<Vec<_>>().join(",")
+}
+
+/// Re-merge lifted biallelic records that came from the same source record
+/// 
+/// Succeeds only if every record landed on the same CHROM/POS/REF with a
+/// non-empty ALT. ALT alleles are joined in original order, so the original
+/// FORMAT and sample columns remain valid and are restored as-is.
+pub fn merge_biallelic(original: &VcfRecordView, lifted: &[String]) -> Option<String> {
+    let first: Vec<&str> = lifted.first()?.split('\t').collect();
+    if first.len() < 8 {
+        return None;
+    }
+    
+    let mut alts = Vec::with_capacity(lifted.len());
+    for line in lifted {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 8
+            || fields[0] != first[0]
+            || fields[1] != first[1]
+            || fields[3] != first[3]
+            || fields[4].is_empty()
+        {
+            return None;
+        }
+        alts.push(fields[4]);
+    }
+    
+    let mut output = String::with_capacity(512);
+    output.push_str(&first[..4].join("\t"));
+    output.push('\t');
+    output.push_str(&alts.join(","));
+    output.push('\t');
+    output.push_str(&first[5..8].join("\t"));
+    
+    if let Some(format) = original.format() {
+        output.push('\t');
+        output.push_str(format);
+        for sample in original.samples() {
+            output.push('\t');
+            output.push_str(sample);
+        }
+    }
+    
+    Some(output)
+}
+
+/// Rewrite the FILTER column of a lifted line per `reset_filter` / `add_filter`
+///
+/// The added tag replaces `.` and `PASS` and is otherwise appended with `;`
+/// (once).
+fn update_filter(line: String, options: &VcfConvertOptions) -> String {
+    if !options.reset_filter && options.add_filter.is_none() {
+        return line;
+    }
+    
+    let mut fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() < 8 {
+        return line;
+    }
+    
+    let mut filter = fields[6].to_string();
+    if options.reset_filter && filter != "." {
+        filter = "PASS".to_string();
+    }
+    if let Some(tag) = &options.add_filter {
+        if filter == "." || filter == "PASS" {
+            filter = tag.clone();
+        } else if !filter.split(';').any(|f| f == tag) {
+            filter.push(';');
+            filter.push_str(tag);
+        }
+    }
+    
+    fields[6] = &filter;
+    fields.join("\t")
+}
+
+/// Whether INFO key `key` survives `info_passthrough` / `info_blacklist`
+fn info_key_kept(key: &str, options: &VcfConvertOptions) -> bool {
+    if let Some(keep) = &options.info_passthrough {
+        if !keep.iter().any(|k| k == key) {
+            return false;
+        }
+    }
+    match &options.info_blacklist {
+        Some(drop) => !drop.iter().any(|k| k == key),
+        None => true,
+    }
+}
+
+/// Whether a header line survives INFO filtering (only `##INFO` lines of
+/// dropped keys are removed)
+fn info_header_kept(line: &str, options: &VcfConvertOptions) -> bool {
+    match line.strip_prefix("##INFO=<ID=") {
+        Some(rest) => info_key_kept(rest.split([',', '>']).next().unwrap_or(""), options),
+        None => true,
+    }
+}
+
+/// Drop INFO entries of a lifted line per `info_passthrough` /
+/// `info_blacklist`
+///
+/// An INFO column left empty is written as `.`.
+fn filter_info(line: String, options: &VcfConvertOptions) -> String {
+    if options.info_passthrough.is_none() && options.info_blacklist.is_none() {
+        return line;
+    }
+    
+    let mut fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() < 8 || fields[7] == "." {
+        return line;
+    }
+    
+    let kept: Vec<&str> = fields[7]
+        .split(';')
+        .filter(|entry| info_key_kept(entry.split('=').next().unwrap_or(""), options))
+        .collect();
+    let info = if kept.is_empty() { ".".to_string() } else { kept.join(";") };
+    
+    fields[7] = &info;
+    fields.join("\t")
+}
+
+/// Handling of breakend records whose mate position cannot be lifted
+///
+/// The mate of a breakend ALT (`t[p[`, `t]p]`, `]p]t`, `[p[t`) is lifted
+/// along with POS. When the mate falls outside the chain (or maps to more
+/// than one place) the policy decides what happens to the record.
+/// `KeepOriginalCoords` keeps every breakend in the output but leaves ALT
+/// pointing into the source assembly, so downstream tools pairing mates
+/// must check the `OriginalBndCoords` flag; `FailRecord` keeps the output
+/// consistent at the cost of losing the record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BndUnmapPolicy {
+    /// Send the whole record to the unmap file as `Fail(BndMateUnmap)`
+    #[default]
+    FailRecord,
+    /// Lift POS, keep the source mate coordinates and add the
+    /// `OriginalBndCoords` INFO flag
+    KeepOriginalCoords,
+    /// Lift POS and rewrite the ALT as a single breakend (`t.` / `.t`)
+    EmitUnmapped,
+}
+
+/// INFO flag marking breakend ALTs that still carry source mate coordinates
+const ORIGINAL_BND_COORDS_FLAG: &str = "OriginalBndCoords";
+
+/// A breakend ALT allele split around its mate position
+#[derive(Debug, PartialEq)]
+struct Breakend<'a> {
+    /// Bases before the first bracket (`t` in `t[p[`)
+    prefix: &'a str,
+    bracket: char,
+    chrom: &'a str,
+    pos: u64,
+    /// Bases after the last bracket (`t` in `]p]t`)
+    suffix: &'a str,
+}
+
+impl<'a> Breakend<'a> {
+    /// Parse a breakend ALT; returns None for any other allele
+    fn parse(alt: &'a str) -> Option<Self> {
+        let open = alt.find(['[', ']'])?;
+        let bracket = alt[open..].chars().next()?;
+        let close = alt.rfind(bracket)?;
+        if close == open || alt[open + 1..close].contains(['[', ']']) {
+            return None;
+        }
+        let prefix = &alt[..open];
+        let suffix = &alt[close + 1..];
+        if prefix.is_empty() == suffix.is_empty() {
+            return None;
+        }
+        let (chrom, pos) = alt[open + 1..close].rsplit_once(':')?;
+        let pos = pos.parse().ok().filter(|&p| p > 0)?;
+        Some(Self { prefix, bracket, chrom, pos, suffix })
+    }
+    
+    /// Same join with the mate moved to `chrom:pos`
+    fn with_mate(&self, chrom: &str, pos: u64) -> String {
+        let b = self.bracket;
+        format!("{}{}{}:{}{}{}", self.prefix, b, chrom, pos, b, self.suffix)
+    }
+    
+    /// Single breakend with the same orientation and no mate
+    fn single(&self) -> String {
+        if self.prefix.is_empty() {
+            format!(".{}", self.suffix)
+        } else {
+            format!("{}.", self.prefix)
+        }
+    }
+}
+
+/// Lift the mate positions of breakend ALT alleles in a lifted line
+///
+/// Only the mate coordinates are rewritten; the bracket orientation is kept
+/// even if the mate lands on the reverse strand. Returns None when a mate
+/// cannot be lifted under `BndUnmapPolicy::FailRecord`.
+fn lift_breakend_mates(line: String, mapper: &CoordinateMapper, policy: BndUnmapPolicy) -> Option<String> {
+    let mut fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() < 8 || !fields[4].contains(['[', ']']) {
+        return Some(line);
+    }
+    
+    let mut mate_unmapped = false;
+    let alts: Vec<String> = fields[4]
+        .split(',')
+        .map(|alt| {
+            let Some(bnd) = Breakend::parse(alt) else {
+                return alt.to_string();
+            };
+            match mapper.map(bnd.chrom, bnd.pos - 1, bnd.pos, Strand::Plus) {
+                Some(segments) if segments.len() == 1 => {
+                    let target = &segments[0].target;
+                    bnd.with_mate(&target.chrom, target.start + 1)
+                }
+                _ => {
+                    mate_unmapped = true;
+                    match policy {
+                        BndUnmapPolicy::EmitUnmapped => bnd.single(),
+                        _ => alt.to_string(),
+                    }
+                }
+            }
+        })
+        .collect();
+    
+    if mate_unmapped && policy == BndUnmapPolicy::FailRecord {
+        return None;
+    }
+    
+    let alt = alts.join(",");
+    fields[4] = &alt;
+    let info;
+    if mate_unmapped && policy == BndUnmapPolicy::KeepOriginalCoords {
+        info = add_info_flag(fields[7], ORIGINAL_BND_COORDS_FLAG);
+        fields[7] = &info;
+    }
+    Some(fields.join("\t"))
+}
+
+/// Append a flag to an INFO column unless it is already present
+fn add_info_flag(info: &str, flag: &str) -> String {
+    if info == "." || info.is_empty() {
+        flag.to_string()
+    } else if info.split(';').any(|f| f == flag) {
+        info.to_string()
+    } else {
+        format!("{};{}", info, flag)
+    }
+}
+
+/// Whether breakend mates are lifted (`lift_bnd_mates`, never in strict mode)
+fn lifts_bnd_mates(options: &VcfConvertOptions, mapper: &CoordinateMapper) -> bool {
+    options.lift_bnd_mates && !mapper.compat_mode().is_strict()
+}
+
+/// Convert one VCF data line, applying the optional multi-allelic split,
+/// breakend mate liftover, FILTER rewriting and INFO filtering
+fn convert_vcf_line(
+    line: &str,
+    mapper: &CoordinateMapper,
+    ref_genome: Option<&pysam_stub::FastaReader>,
+    options: &VcfConvertOptions,
+) -> Vec<ConversionResult> {
+    let mut results = lift_vcf_line(line, mapper, ref_genome, options);
+    if lifts_bnd_mates(options, mapper) {
+        let mut mate_unmapped = false;
+        results.retain_mut(|result| {
+            let ConversionResult::Success(lifted) = result else {
+                return true;
+            };
+            match lift_breakend_mates(std::mem::take(lifted), mapper, options.bnd_unmap_policy) {
+                Some(updated) => {
+                    *lifted = updated;
+                    true
+                }
+                None => {
+                    mate_unmapped = true;
+                    false
+                }
+            }
+        });
+        // The input line goes to unmap once, however many split records failed
+        if mate_unmapped {
+            results.push(ConversionResult::Failed(line.to_string(), LiftoverFailureReason::MateUnmapped.to_vcf_annotation()));
+        }
+    }
+    if options.reset_filter || options.add_filter.is_some() {
+        for result in &mut results {
+            if let ConversionResult::Success(lifted) = result {
+                *lifted = update_filter(std::mem::take(lifted), options);
+            }
+        }
+    }
+    if options.info_passthrough.is_some() || options.info_blacklist.is_some() {
+        for result in &mut results {
+            if let ConversionResult::Success(lifted) = result {
+                *lifted = filter_info(std::mem::take(lifted), options);
+            }
+        }
+    }
+    results
+}
+
+/// Minimum number of columns accepted in lenient mode (CHROM to ALT)
+const LENIENT_MIN_FIELDS: usize = 5;
+
+/// Fill missing QUAL, FILTER and INFO columns with `.`
+///
+/// Lines with 5-7 columns are padded to the 8 mandatory VCF columns;
+/// anything else is returned unchanged.
+fn pad_missing_fields(line: &str) -> Cow<'_, str> {
+    let field_count = memchr::memchr_iter(b'\t', line.as_bytes()).count() + 1;
+    if !(LENIENT_MIN_FIELDS..8).contains(&field_count) {
+        return Cow::Borrowed(line);
+    }
+    
+    let mut padded = String::with_capacity(line.len() + 2 * (8 - field_count));
+    padded.push_str(line);
+    for _ in field_count..8 {
+        padded.push_str("\t.");
+    }
+    Cow::Owned(padded)
+}
+
+/// Lift one VCF data line, applying the optional multi-allelic split
+fn lift_vcf_line(
+    line: &str,
+    mapper: &CoordinateMapper,
+    ref_genome: Option<&pysam_stub::FastaReader>,
+    options: &VcfConvertOptions,
+) -> Vec<ConversionResult> {
+    let line = if options.lenient {
+        let padded = pad_missing_fields(line);
+        if let Cow::Owned(_) = padded {
+            warn!("VCF record with missing QUAL/FILTER/INFO columns, filled with '.': {}", line);
+        }
+        padded
+    } else {
+        Cow::Borrowed(line)
+    };
+    let line = line.as_ref();
+    
+    let view = match VcfRecordView::parse(line.as_bytes()) {
+        Ok(view) => view,
+        Err(_) => {
+            return vec![ConversionResult::Failed(line.to_string(), LiftoverFailureReason::ParseError.to_vcf_annotation())];
+        }
+    };
+    
+    if !options.split_multiallelic {
+        return vec![convert_vcf_record(&view, mapper, ref_genome, options)];
+    }
+    
+    let records = split_multiallelic(&view);
+    if records.len() == 1 {
+        return vec![convert_vcf_record(&view, mapper, ref_genome, options)];
+    }
+    
+    let results: Vec<ConversionResult> = records
+        .iter()
+        .map(|record| match VcfRecordView::parse(record.line.as_bytes()) {
+            Ok(split_view) => convert_vcf_record(&split_view, mapper, ref_genome, options),
+            Err(_) => ConversionResult::Failed(record.line.clone(), LiftoverFailureReason::ParseError.to_vcf_annotation()),
+        })
+        .collect();
+    
+    if options.merge_biallelic {
+        let lifted: Vec<String> = results
+            .iter()
+            .filter_map(|r| match r {
+                ConversionResult::Success(l) => Some(l.clone()),
+                _ => None,
+            })
+            .collect();
+        if lifted.len() == results.len() {
+            if let Some(merged) = merge_biallelic(&view, &lifted) {
+                return vec![ConversionResult::Success(merged)];
+            }
+        }
+    }
+    
+    results
+}
+
+
+/// Stub module for FASTA reading (placeholder for pysam-like functionality)
+pub mod pysam_stub {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader};
+    use std::path::Path;
+    
+    /// Simple FASTA reader for reference genome
+    /// 
+    /// All sequences are loaded into memory on open, so a single reader is
+    /// `Send + Sync` and is shared read-only by the rayon workers in the
+    /// parallel converter without locking.
+    pub struct FastaReader {
+        sequences: HashMap<String, Vec<u8>>,
+        chrom_order: Vec<String>,
+    }
+    
+    impl FastaReader {
+        /// Open a FASTA file and load all sequences
+        pub fn open<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+            let file = std::fs::File::open(path)?;
+            let reader = BufReader::new(file);
+            let mut sequences = HashMap::new();
+            let mut chrom_order = Vec::new();
+            let mut current_name = String::new();
+            let mut current_seq = Vec::new();
+            
+            for line in reader.lines() {
+                let line = line?;
+                if line.starts_with('>') {
+                    if !current_name.is_empty() {
+                        chrom_order.push(current_name.clone());
+                        sequences.insert(current_name.clone(), current_seq.clone());
+                    }
+                    current_name = line[1..].split_whitespace().next().unwrap_or("").to_string();
+                    current_seq.clear();
+                } else {
+                    current_seq.extend(line.trim().bytes());
+                }
+            }
+            
+            if !current_name.is_empty() {
+                chrom_order.push(current_name.clone());
+                sequences.insert(current_name, current_seq);
+            }
+            
+            Ok(Self { sequences, chrom_order })
+        }
+        
+        /// Fetch a region from the reference (0-based, half-open)
+        pub fn fetch(&self, chrom: &str, start: u64, end: u64) -> Option<String> {
+            // Try with and without chr prefix
+            let seq = self.sequences.get(chrom)
+                .or_else(|| {
+                    if chrom.starts_with("chr") {
+                        self.sequences.get(&chrom[3..])
+                    } else {
+                        self.sequences.get(&format!("chr{}", chrom))
+                    }
+                })?;
+            
+            let start = start as usize;
+            let end = (end as usize).min(seq.len());
+            
+            if start >= seq.len() {
+                return None;
+            }
+            
+            Some(String::from_utf8_lossy(&seq[start..end]).to_string())
+        }
+        
+        /// Get chromosome names in order
+        pub fn references(&self) -> Vec<&str> {
+            self.chrom_order.iter().map(|s| s.as_str()).collect()
         }
         
         /// Get chromosome lengths in order
@@ -574,20 +1420,181 @@ pub mod pysam_stub {
                 .collect()
         }
     }
+    
+    // The parallel VCF path shares one reader across threads
+    const _: fn() = || {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<FastaReader>();
+    };
 }
 
 /// Chunk size for parallel processing
 const CHUNK_SIZE: usize = 10000;
 
+/// Options for VCF conversion
+#[derive(Debug, Clone)]
+pub struct VcfConvertOptions {
+    /// If true, keep variants where REF==ALT after liftover; otherwise they
+    /// go to the unmap file as `Fail(REF==ALT)` (improved mode) or are
+    /// written with an empty ALT (strict mode)
+    pub no_comp_allele: bool,
+    /// Number of threads for parallel processing (1 = sequential, 0 = auto)
+    pub threads: usize,
+    /// Split multi-allelic records into biallelic records before liftover
+    pub split_multiallelic: bool,
+    /// Re-merge split records that land on the same target CHROM/POS/REF
+    pub merge_biallelic: bool,
+    /// Replace every FILTER value other than `.` with `PASS`
+    pub reset_filter: bool,
+    /// Filter tag appended to the FILTER field of every lifted record
+    pub add_filter: Option<String>,
+    /// Write `##liftover*` provenance headers before `#CHROM`
+    pub generate_provenance_headers: bool,
+    /// Chain file recorded in `##liftoverChain` (base name only)
+    pub chain_file: Option<PathBuf>,
+    /// Command line recorded in `##liftoverCommandLine`
+    pub command_line: Option<String>,
+    /// Accept records with only 5-7 columns, filling missing QUAL, FILTER
+    /// and INFO with `.` (logged as a warning)
+    pub lenient: bool,
+    /// Lift the mate positions in breakend ALTs along with POS (ignored in
+    /// strict mode, where ALT is written unchanged like CrossMap)
+    pub lift_bnd_mates: bool,
+    /// With `lift_bnd_mates`, what to do with breakend records whose mate
+    /// cannot be lifted
+    pub bnd_unmap_policy: BndUnmapPolicy,
+    /// If set, only these INFO keys are written to the lifted output
+    pub info_passthrough: Option<Vec<String>>,
+    /// INFO keys dropped from the lifted output (applied after
+    /// `info_passthrough`)
+    pub info_blacklist: Option<Vec<String>>,
+    /// Send `SVTYPE=INV` records that map to a negative-strand block to the
+    /// unmap file as `Fail(InversionStrand)` instead of only warning
+    pub inversion_strict: bool,
+    /// Keep `CIPOS`/`CIEND` relative to the lifted POS and END: on a
+    /// negative-strand block the interval is mirrored (`-10,50` becomes
+    /// `-50,10`); on the positive strand the values are already correct
+    pub update_ci_intervals: bool,
+    /// BGZF-compress the lifted output (the unmap file stays plain text)
+    pub compress: bool,
+}
+
+impl Default for VcfConvertOptions {
+    fn default() -> Self {
+        Self {
+            no_comp_allele: false,
+            threads: 1,
+            split_multiallelic: false,
+            merge_biallelic: false,
+            reset_filter: false,
+            add_filter: None,
+            generate_provenance_headers: true,
+            chain_file: None,
+            command_line: None,
+            lenient: false,
+            lift_bnd_mates: false,
+            bnd_unmap_policy: BndUnmapPolicy::default(),
+            info_passthrough: None,
+            info_blacklist: None,
+            inversion_strict: false,
+            update_ci_intervals: false,
+            compress: false,
+        }
+    }
+}
+
+/// Current UTC time as ISO 8601 (`YYYY-MM-DDTHH:MM:SSZ`)
+fn iso8601_now() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    iso8601_from_unix(secs)
+}
+
+/// Format seconds since the Unix epoch as ISO 8601 UTC
+fn iso8601_from_unix(secs: u64) -> String {
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    
+    // Civil date from days since 1970-01-01 (H. Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, rem / 3_600, rem % 3_600 / 60, rem % 60
+    )
+}
+
+/// CrossMap-style program line, written when provenance headers are disabled
+pub(crate) const LEGACY_PROGRAM_HEADER: &str = "##liftOverProgram=FastCrossMap";
+
+/// `##liftover*` provenance header lines
+///
+/// The chain file and command line are only written when known.
+pub(crate) fn provenance_headers(chain_file: Option<&Path>, command_line: Option<&str>) -> Vec<String> {
+    let mut headers = vec![
+        "##liftoverProgram=fast-crossmap".to_string(),
+        format!("##liftoverVersion={}", env!("CARGO_PKG_VERSION")),
+        format!("##liftoverDate={}", iso8601_now()),
+    ];
+    if let Some(name) = chain_file.and_then(|p| p.file_name()) {
+        headers.push(format!("##liftoverChain={}", name.to_string_lossy()));
+    }
+    if let Some(command_line) = command_line {
+        headers.push(format!("##liftoverCommandLine={}", command_line));
+    }
+    headers
+}
+
+/// Header lines written just before `#CHROM` in the lifted output
+///
+/// The `##liftover*` provenance block replaces the legacy
+/// `##liftOverProgram=FastCrossMap` line; the legacy line is only written
+/// when provenance headers are disabled.
+fn lifted_extra_headers(options: &VcfConvertOptions, mapper: &CoordinateMapper) -> Vec<String> {
+    let mut headers: Vec<String> = added_filter_header(options).into_iter().collect();
+    if lifts_bnd_mates(options, mapper)
+        && options.bnd_unmap_policy == BndUnmapPolicy::KeepOriginalCoords
+        && info_key_kept(ORIGINAL_BND_COORDS_FLAG, options)
+    {
+        headers.push(format!(
+            "##INFO=<ID={},Number=0,Type=Flag,Description=\"Breakend mate could not be lifted; ALT keeps source coordinates\">",
+            ORIGINAL_BND_COORDS_FLAG
+        ));
+    }
+    if options.generate_provenance_headers {
+        headers.extend(provenance_headers(options.chain_file.as_deref(), options.command_line.as_deref()));
+    } else {
+        headers.push(LEGACY_PROGRAM_HEADER.to_string());
+    }
+    headers
+}
+
+/// `##FILTER` header line declaring the `add_filter` tag, if any
+fn added_filter_header(options: &VcfConvertOptions) -> Option<String> {
+    options.add_filter.as_ref().map(|tag| {
+        format!("##FILTER=<ID={},Description=\"Record lifted over by FastCrossMap\">", tag)
+    })
+}
+
 /// Convert a VCF file using the coordinate mapper
 /// 
 /// # Arguments
-/// * `input` - Input VCF file path
-/// * `output` - Output VCF file path for successfully mapped records
+/// * `input` - Input VCF file path (`-` for stdin)
+/// * `output` - Output VCF file path for successfully mapped records (`-` for stdout)
 /// * `unmap` - Output file path for unmapped records (will be output.unmap)
 /// * `mapper` - Coordinate mapper with loaded chain index
 /// * `ref_genome` - Optional path to target reference genome FASTA
-/// * `no_comp_allele` - If true, keep variants where REF==ALT
+/// * `no_comp_allele` - If true, keep variants where REF==ALT after liftover;
+///   otherwise they are written to the unmap file with `Fail(REF==ALT)`
 /// * `threads` - Number of threads for parallel processing (1 = sequential)
 /// 
 /// # Returns
@@ -600,10 +1607,43 @@ pub fn convert_vcf<P: AsRef<Path>>(
     no_comp_allele: bool,
     threads: usize,
 ) -> Result<ConversionStats, VcfParseError> {
-    if threads > 1 {
-        convert_vcf_parallel(input, output, mapper, ref_genome, no_comp_allele, threads)
+    let options = VcfConvertOptions {
+        no_comp_allele,
+        threads,
+        ..Default::default()
+    };
+    convert_vcf_with_options(input, output, mapper, ref_genome, &options)
+}
+
+/// Convert a VCF file with extended options
+/// 
+/// When `split_multiallelic` is set, each emitted biallelic record is
+/// counted separately in the statistics. Reading from stdin (`-`) always
+/// uses the sequential path.
+pub fn convert_vcf_with_options<P: AsRef<Path>>(
+    input: P,
+    output: P,
+    mapper: &CoordinateMapper,
+    ref_genome: Option<P>,
+    options: &VcfConvertOptions,
+) -> Result<ConversionStats, VcfParseError> {
+    if options.threads == 0 {
+        let options = VcfConvertOptions { threads: io::resolve_threads(0), ..options.clone() };
+        return convert_vcf_with_options(input, output, mapper, ref_genome, &options);
+    }
+    if options.threads > 1 && !io::is_stdio(&input) {
+        convert_vcf_parallel(input, output, mapper, ref_genome, options)
     } else {
-        convert_vcf_sequential(input, output, mapper, ref_genome, no_comp_allele)
+        convert_vcf_sequential(input, output, mapper, ref_genome, options)
+    }
+}
+
+/// Unmap file next to `output`; a compressed `out.vcf.gz` gets `out.vcf.unmap`
+fn vcf_unmap_path(output: &Path, compress: bool) -> PathBuf {
+    if compress && output.extension().is_some_and(|ext| ext == "gz") {
+        output.with_extension("").with_extension("vcf.unmap")
+    } else {
+        io::unmap_path_for(output, "vcf.unmap")
     }
 }
 
@@ -613,17 +1653,16 @@ fn convert_vcf_sequential<P: AsRef<Path>>(
     output: P,
     mapper: &CoordinateMapper,
     ref_genome: Option<P>,
-    no_comp_allele: bool,
+    options: &VcfConvertOptions,
 ) -> Result<ConversionStats, VcfParseError> {
-    let input_file = std::fs::File::open(input.as_ref())?;
-    let reader = BufReader::with_capacity(128 * 1024, input_file);
+    let reader = io::open_input(input.as_ref(), 128 * 1024)?;
     
     let output_path = output.as_ref();
-    let unmap_path = output_path.with_extension("vcf.unmap");
+    let unmap_path = vcf_unmap_path(output_path, options.compress);
     
     // Use BufWriter for performance
-    let mut output_file = BufWriter::with_capacity(128 * 1024, std::fs::File::create(output_path)?);
-    let mut unmap_file = BufWriter::with_capacity(64 * 1024, std::fs::File::create(&unmap_path)?);
+    let mut output_file = io::create_output_compressed(output_path, 128 * 1024, options.compress)?;
+    let mut unmap_file = io::create_unmap_output(&unmap_path, 64 * 1024)?;
     
     // Load reference genome if provided
     let ref_reader = ref_genome
@@ -652,7 +1691,10 @@ fn convert_vcf_sequential<P: AsRef<Path>>(
         
         // Handle header lines
         if line.starts_with('#') {
-            if line.starts_with("##fileformat") 
+            if !info_header_kept(line, options) {
+                // Filtered INFO key: only the unmapped records still carry it
+                writeln!(unmap_file, "{}", line)?;
+            } else if line.starts_with("##fileformat") 
                 || line.starts_with("##INFO")
                 || line.starts_with("##FILTER")
                 || line.starts_with("##FORMAT")
@@ -674,8 +1716,9 @@ fn convert_vcf_sequential<P: AsRef<Path>>(
                         writeln!(output_file, "##contig=<ID={},length={}>", chrom, len)?;
                     }
                 }
-                // Write liftover metadata
-                writeln!(output_file, "##liftOverProgram=FastCrossMap")?;
+                for header in lifted_extra_headers(options, mapper) {
+                    writeln!(output_file, "{}", header)?;
+                }
                 // Write column header to both files
                 writeln!(output_file, "{}", line)?;
                 writeln!(unmap_file, "{}", line)?;
@@ -686,39 +1729,35 @@ fn convert_vcf_sequential<P: AsRef<Path>>(
             continue;
         }
         
-        stats.total += 1;
-        
-        // Parse the VCF record
-        match VcfRecordView::parse(line.as_bytes()) {
-            Ok(view) => {
-                match convert_vcf_record(&view, mapper, ref_reader.as_ref(), no_comp_allele) {
-                    ConversionResult::Success(output_line) => {
-                        writeln!(output_file, "{}", output_line)?;
-                        stats.success += 1;
-                    }
-                    ConversionResult::Failed(original, reason) => {
-                        writeln!(unmap_file, "{}\t{}", original, reason)?;
-                        stats.failed += 1;
-                    }
-                    ConversionResult::Header(h) => {
-                        writeln!(output_file, "{}", h)?;
-                    }
-                    ConversionResult::UnmapHeader(h) => {
-                        writeln!(unmap_file, "{}", h)?;
-                    }
-                    ConversionResult::ContigHeader(_) => {
-                        // Already handled above
+        for result in convert_vcf_line(line, mapper, ref_reader.as_ref(), options) {
+            stats.total += 1;
+            match result {
+                ConversionResult::Success(output_line) => {
+                    writeln!(output_file, "{}", output_line)?;
+                    stats.success += 1;
+                }
+                ConversionResult::Failed(original, reason) => {
+                    if is_ref_eq_alt(&reason) {
+                        stats.ref_eq_alt += 1;
                     }
+                    writeln!(unmap_file, "{}\t{}", original, reason)?;
+                    stats.failed += 1;
+                }
+                ConversionResult::Header(h) => {
+                    writeln!(output_file, "{}", h)?;
+                }
+                ConversionResult::UnmapHeader(h) => {
+                    writeln!(unmap_file, "{}", h)?;
+                }
+                ConversionResult::ContigHeader(_) => {
+                    // Already handled above
                 }
-            }
-            Err(_) => {
-                // Invalid VCF line - write to unmap file
-                writeln!(unmap_file, "{}\tFail(ParseError)", line)?;
-                stats.failed += 1;
             }
         }
     }
     
+    output_file.finish()?;
+    unmap_file.flush()?;
     Ok(stats)
 }
 
@@ -728,12 +1767,11 @@ fn convert_vcf_parallel<P: AsRef<Path>>(
     output: P,
     mapper: &CoordinateMapper,
     ref_genome: Option<P>,
-    no_comp_allele: bool,
-    threads: usize,
+    options: &VcfConvertOptions,
 ) -> Result<ConversionStats, VcfParseError> {
     // Configure rayon thread pool
     let pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(threads)
+        .num_threads(options.threads)
         .build()
         .map_err(|e| VcfParseError::Io(std::io::Error::new(
             std::io::ErrorKind::Other,
@@ -741,8 +1779,7 @@ fn convert_vcf_parallel<P: AsRef<Path>>(
         )))?;
     
     // Read all lines
-    let input_file = std::fs::File::open(input.as_ref())?;
-    let reader = BufReader::with_capacity(128 * 1024, input_file);
+    let reader = io::open_input(input.as_ref(), 128 * 1024)?;
     
     let mut header_lines_output = Vec::new();
     let mut header_lines_unmap = Vec::new();
@@ -760,7 +1797,9 @@ fn convert_vcf_parallel<P: AsRef<Path>>(
         }
         
         if line.starts_with('#') {
-            if line.starts_with("##fileformat") 
+            if !info_header_kept(&line, options) {
+                header_lines_unmap.push(line);
+            } else if line.starts_with("##fileformat") 
                 || line.starts_with("##INFO")
                 || line.starts_with("##FILTER")
                 || line.starts_with("##FORMAT")
@@ -779,7 +1818,7 @@ fn convert_vcf_parallel<P: AsRef<Path>>(
                         header_lines_output.push(format!("##contig=<ID={},length={}>", chrom, len));
                     }
                 }
-                header_lines_output.push("##liftOverProgram=FastCrossMap".to_string());
+                header_lines_output.extend(lifted_extra_headers(options, mapper));
                 header_lines_output.push(line.clone());
                 header_lines_unmap.push(line);
             } else {
@@ -794,6 +1833,7 @@ fn convert_vcf_parallel<P: AsRef<Path>>(
     let total = AtomicUsize::new(0);
     let success = AtomicUsize::new(0);
     let failed = AtomicUsize::new(0);
+    let ref_eq_alt = AtomicUsize::new(0);
     
     // Process in parallel
     let results: Vec<(Vec<String>, Vec<String>)> = pool.install(|| {
@@ -804,25 +1844,21 @@ fn convert_vcf_parallel<P: AsRef<Path>>(
                 let mut failed_lines = Vec::new();
                 
                 for line in chunk {
-                    total.fetch_add(1, Ordering::Relaxed);
-                    
-                    match VcfRecordView::parse(line.as_bytes()) {
-                        Ok(view) => {
-                            match convert_vcf_record(&view, mapper, ref_reader.as_ref(), no_comp_allele) {
-                                ConversionResult::Success(output_line) => {
-                                    success_lines.push(output_line);
-                                    success.fetch_add(1, Ordering::Relaxed);
-                                }
-                                ConversionResult::Failed(original, reason) => {
-                                    failed_lines.push(format!("{}\t{}", original, reason));
-                                    failed.fetch_add(1, Ordering::Relaxed);
+                    for result in convert_vcf_line(line, mapper, ref_reader.as_ref(), options) {
+                        total.fetch_add(1, Ordering::Relaxed);
+                        match result {
+                            ConversionResult::Success(output_line) => {
+                                success_lines.push(output_line);
+                                success.fetch_add(1, Ordering::Relaxed);
+                            }
+                            ConversionResult::Failed(original, reason) => {
+                                if is_ref_eq_alt(&reason) {
+                                    ref_eq_alt.fetch_add(1, Ordering::Relaxed);
                                 }
-                                _ => {}
+                                failed_lines.push(format!("{}\t{}", original, reason));
+                                failed.fetch_add(1, Ordering::Relaxed);
                             }
-                        }
-                        Err(_) => {
-                            failed_lines.push(format!("{}\tFail(ParseError)", line));
-                            failed.fetch_add(1, Ordering::Relaxed);
+                            _ => {}
                         }
                     }
                 }
@@ -834,10 +1870,10 @@ fn convert_vcf_parallel<P: AsRef<Path>>(
     
     // Write output files with BufWriter for performance
     let output_path = output.as_ref();
-    let unmap_path = output_path.with_extension("vcf.unmap");
+    let unmap_path = vcf_unmap_path(output_path, options.compress);
     
-    let mut output_file = BufWriter::with_capacity(128 * 1024, std::fs::File::create(output_path)?);
-    let mut unmap_file = BufWriter::with_capacity(64 * 1024, std::fs::File::create(&unmap_path)?);
+    let mut output_file = io::create_output_compressed(output_path, 128 * 1024, options.compress)?;
+    let mut unmap_file = io::create_unmap_output(&unmap_path, 64 * 1024)?;
     
     // Write headers
     for header in &header_lines_output {
@@ -857,10 +1893,13 @@ fn convert_vcf_parallel<P: AsRef<Path>>(
         }
     }
     
+    output_file.finish()?;
+    unmap_file.flush()?;
     Ok(ConversionStats {
         total: total.load(Ordering::Relaxed),
         success: success.load(Ordering::Relaxed),
         failed: failed.load(Ordering::Relaxed),
+        ref_eq_alt: ref_eq_alt.load(Ordering::Relaxed),
     })
 }
 
@@ -894,6 +1933,99 @@ mod tests {
         assert_eq!(view.samples(), vec!["0/1:30", "1/1:25"]);
     }
     
+    #[test]
+    fn test_mirror_ci_intervals() {
+        assert_eq!(mirror_ci_intervals("SVTYPE=DEL;CIPOS=-10,50;END=500;CIEND=-50,50", false), "SVTYPE=DEL;CIPOS=-50,10;END=500;CIEND=-50,50");
+        assert_eq!(mirror_ci_intervals("CIPOS=0,0;CIEND=5,20", false), "CIPOS=0,0;CIEND=-20,-5");
+        // POS and END swapped: the intervals trade keys
+        assert_eq!(mirror_ci_intervals("CIPOS=-10,50;CIEND=5,20", true), "CIEND=-50,10;CIPOS=-20,-5");
+        // Flags, other keys and malformed values pass through
+        assert_eq!(mirror_ci_intervals("IMPRECISE;MCIPOS=-1,1;CIPOS=.;CIEND=1,x", false), "IMPRECISE;MCIPOS=-1,1;CIPOS=.;CIEND=1,x");
+        assert_eq!(mirror_ci_intervals(".", false), ".");
+    }
+    
+    #[test]
+    fn test_update_vcf_info_end() {
+        assert_eq!(info_end("SVTYPE=DEL;CIEND=-5,5;END=500"), Some(500));
+        assert_eq!(info_end("SVTYPE=DEL;END=."), None);
+        assert_eq!(info_end("."), None);
+        
+        // 400 bp deletion moved from 100 to 1100
+        assert_eq!(update_vcf_info_end("SVTYPE=DEL;END=500;CIEND=-20,5", 100, 500, 1100), "SVTYPE=DEL;END=1500;CIEND=-20,5");
+        assert_eq!(update_vcf_info_end("END=500", 100, 500, 50), "END=450");
+    }
+    
+    #[test]
+    fn test_update_info_end_skips_ciend() {
+        assert_eq!(update_info_end("SVTYPE=DEL;END=500;CIEND=-20,5", 900), "SVTYPE=DEL;END=900;CIEND=-20,5");
+        assert_eq!(update_info_end("CIEND=-20,5;END=500", 900), "CIEND=-20,5;END=900");
+        assert_eq!(update_info_end("END=500", 900), "END=900");
+    }
+    
+    #[test]
+    fn test_vcf_record_view_field_count() {
+        let lines: [&[u8]; 4] = [
+            b"chr1\t100\t.\tA\tG\t.\tPASS\tDP=10",
+            b"chr1\t100\t.\tA\tG\t.\tPASS\tDP=10\tGT",
+            b"chr1\t100\t.\tA\tG\t.\tPASS\tDP=10\tGT:DP\t0/1:30",
+            b"chr1\t100\t.\tA\tG\t.\tPASS\tDP=10\tGT:DP\t0/1:30\t1/1:25",
+        ];
+        for (line, expected) in lines.iter().zip(8..) {
+            let tabs = line.iter().filter(|&&b| b == b'\t').count();
+            let view = VcfRecordView::parse(line).unwrap();
+            assert_eq!(view.field_count(), expected);
+            assert_eq!(view.field_count(), tabs + 1);
+            assert_eq!(view.field(expected - 1).map(str::len), line.rsplit(|&b| b == b'\t').next().map(<[u8]>::len));
+            assert_eq!(view.field(expected), None);
+            
+            let (eager, _) = VcfRecordView::parse_info_eager(line).unwrap();
+            assert_eq!(eager.field_count(), expected);
+        }
+    }
+    
+    #[test]
+    fn test_vcf_record_view_sample_count() {
+        let cases: [(&[u8], usize); 4] = [
+            (b"chr1\t100\t.\tA\tG\t.\t.\t.", 0),
+            (b"chr1\t100\t.\tA\tG\t.\t.\t.\tGT", 0),
+            (b"chr1\t100\t.\tA\tG\t.\t.\t.\tGT\t0/1", 1),
+            (b"chr1\t100\t.\tA\tG\t.\t.\t.\tGT\t0/1\t1/1\t./.", 3),
+        ];
+        for (line, expected) in cases {
+            let view = VcfRecordView::parse(line).unwrap();
+            assert_eq!(view.samples().len(), expected);
+            assert_eq!(view.format().is_some(), view.field_count() > 8);
+        }
+    }
+    
+    #[test]
+    fn test_parse_info_eager() {
+        fn assert_sync<T: Sync + Send>(_: &T) {}
+        
+        let line = b"chr1\t12345\trs123\tA\tG\t30\tPASS\tDP=100;DB;AF=0.5\tGT\t0/1";
+        let (view, info) = VcfRecordView::parse_info_eager(line).unwrap();
+        assert_sync(&view);
+        
+        assert_eq!((view.chrom, view.pos), ("chr1", 12345));
+        assert_eq!(view.field_count(), 10);
+        assert_eq!(view.ref_allele(), Some("A"));
+        assert_eq!(view.samples(), vec!["0/1"]);
+        assert_eq!(info, VcfRecordView::parse(line).unwrap().parse_info());
+        assert_eq!(info.get("DB").map(String::as_str), Some(""));
+        
+        // Shared by reference across threads
+        let shared = &view;
+        let chroms: Vec<&str> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..2).map(|_| s.spawn(move || shared.chrom)).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(chroms, vec!["chr1", "chr1"]);
+        
+        let (_, empty) = VcfRecordView::parse_info_eager(b"chr1\t1\t.\tA\tG\t.\t.\t.").unwrap();
+        assert!(empty.is_empty());
+        assert!(matches!(VcfRecordView::parse_info_eager(b"chr1\t1"), Err(VcfParseError::TooFewFields { .. })));
+    }
+    
     #[test]
     fn test_vcf_record_view_too_few_fields() {
         let line = b"chr1\t12345\trs123";
@@ -944,4 +2076,314 @@ mod tests {
         
         assert_eq!(view.alt_alleles(), Some("G,T,C"));
     }
+    
+    #[test]
+    fn test_split_multiallelic() {
+        let line = b"chr1\t100\trs1\tA\tG,T\t50\tPASS\tDP=30\tGT:AD:PL\t1/2:2,10,8:90,40,60,30,0,70";
+        let view = VcfRecordView::parse(line).unwrap();
+        let records = split_multiallelic(&view);
+        
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].alt_index, 1);
+        assert_eq!(
+            records[0].line,
+            "chr1\t100\trs1\tA\tG\t50\tPASS\tDP=30\tGT:AD:PL\t1/0:2,10:90,40,60"
+        );
+        assert_eq!(records[1].alt_index, 2);
+        assert_eq!(
+            records[1].line,
+            "chr1\t100\trs1\tA\tT\t50\tPASS\tDP=30\tGT:AD:PL\t0/1:2,8:90,30,70"
+        );
+    }
+    
+    #[test]
+    fn test_split_multiallelic_biallelic_unchanged() {
+        let line = b"chr1\t100\t.\tA\tG\t.\t.\t.\tGT\t0|1";
+        let view = VcfRecordView::parse(line).unwrap();
+        let records = split_multiallelic(&view);
+        
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].line, "chr1\t100\t.\tA\tG\t.\t.\t.\tGT\t0|1");
+    }
+    
+    #[test]
+    fn test_split_gt() {
+        assert_eq!(split_gt("1/2", 1), "1/0");
+        assert_eq!(split_gt("1/2", 2), "0/1");
+        assert_eq!(split_gt("2|2", 2), "1|1");
+        assert_eq!(split_gt("./.", 1), "./.");
+        assert_eq!(split_gt("0", 1), "0");
+    }
+    
+    #[test]
+    fn test_pos_overflow() {
+        use crate::core::{ChainBlock, ChainFile, ChainIndex, ChromStyle};
+        
+        // chr1:0-1000 -> chr1:0-1000, but the target chromosome claims to be 500 bp
+        let mut chain = ChainFile::new();
+        chain.blocks.push(ChainBlock {
+            source_chrom: "chr1".to_string(),
+            source_start: 0,
+            source_end: 1000,
+            target_chrom: "chr1".to_string(),
+            target_start: 0,
+            target_end: 1000,
+            target_strand: Strand::Plus,
+            score: 0,
+        });
+        chain.source_chrom_sizes.insert("chr1".to_string(), 1000);
+        chain.target_chrom_sizes.insert("chr1".to_string(), 500);
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+        
+        let convert = |line: &str| {
+            let view = VcfRecordView::parse(line.as_bytes()).unwrap();
+            convert_vcf_record(&view, &mapper, None, &VcfConvertOptions::default())
+        };
+        assert!(matches!(convert("chr1\t500\t.\tA\tG\t.\t.\t."), ConversionResult::Success(_)));
+        match convert("chr1\t501\t.\tA\tG\t.\t.\t.") {
+            ConversionResult::Failed(line, reason) => {
+                assert_eq!(line, "chr1\t501\t.\tA\tG\t.\t.\t.");
+                assert_eq!(reason, "Fail(PosOverflow)");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_failure_reasons() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+        
+        // chr1:0-100 -> chr1, and chr1:50-100 also -> chr2
+        let chain_data = b"\
+chain 1000 chr1 1000 + 0 100 chr1 1000 + 0 100 1
+100
+
+chain 500 chr1 1000 + 50 100 chr2 1000 + 0 50 2
+50
+";
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap()), ChromStyle::AsIs);
+        let reason = |line: &str| {
+            let view = VcfRecordView::parse(line.as_bytes()).unwrap();
+            match convert_vcf_record(&view, &mapper, None, &VcfConvertOptions::default()) {
+                ConversionResult::Failed(_, reason) => Some(reason),
+                _ => None,
+            }
+        };
+        
+        assert_eq!(reason("chr1\t10\t.\tA\tG\t.\t.\t."), None);
+        assert_eq!(reason("chr1\t60\t.\tA\tG\t.\t.\t."), Some(LiftoverFailureReason::MultipleHits.to_vcf_annotation()));
+        assert_eq!(reason("chr1\t500\t.\tA\tG\t.\t.\t."), Some(LiftoverFailureReason::Unmapped.to_vcf_annotation()));
+        assert_eq!(reason("chrUn\t10\t.\tA\tG\t.\t.\t."), Some(LiftoverFailureReason::Unmapped.to_vcf_annotation()));
+    }
+    
+    #[test]
+    fn test_split_and_merge_roundtrip() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+        
+        // chr1:0-1000 -> chr1:100-1100
+        let chain = b"chain 1000 chr1 1000 + 0 1000 chr1 1100 + 100 1100 1\n1000\n";
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(chain).unwrap());
+        let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+        
+        let line = "chr1\t100\trs1\tA\tG,T\t50\tPASS\tDP=30\tGT:AD\t1/2:2,10,8";
+        
+        let split_only = VcfConvertOptions {
+            split_multiallelic: true,
+            ..Default::default()
+        };
+        let results = convert_vcf_line(line, &mapper, None, &split_only);
+        assert_eq!(results.len(), 2);
+        
+        let merged = VcfConvertOptions {
+            split_multiallelic: true,
+            merge_biallelic: true,
+            ..Default::default()
+        };
+        let results = convert_vcf_line(line, &mapper, None, &merged);
+        assert_eq!(results.len(), 1);
+        match &results[0] {
+            ConversionResult::Success(l) => {
+                assert_eq!(l, "chr1\t200\trs1\tA\tG,T\t50\tPASS\tDP=30\tGT:AD\t1/2:2,10,8");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_normalize_gt_on_strand_flip() {
+        // Unchanged allele list
+        assert_eq!(normalize_gt_on_strand_flip("0/1", &["T", "C"], &["T", "C"]), "0/1");
+        
+        // ALT became the new REF: old REF no longer exists
+        assert_eq!(normalize_gt_on_strand_flip("0/1", &["T", "C"], &["C"]), "./0");
+        assert_eq!(normalize_gt_on_strand_flip("1|1", &["T", "C"], &["C"]), "0|0");
+        
+        // One ALT dropped from a multi-allelic record
+        assert_eq!(normalize_gt_on_strand_flip("1/2", &["T", "C", "G"], &["C", "G"]), "0/1");
+        assert_eq!(normalize_gt_on_strand_flip("0/2", &["T", "G", "C"], &["T", "C"]), "0/1");
+        
+        // Missing calls are preserved
+        assert_eq!(normalize_gt_on_strand_flip("./.", &["T", "C"], &["C"]), "./.");
+    }
+    
+    #[test]
+    fn test_update_filter() {
+        let line = |filter: &str| format!("chr1\t100\t.\tA\tG\t50\t{}\tDP=3\tGT\t0/1", filter);
+        let filter_of = |l: String| l.split('\t').nth(6).unwrap().to_string();
+        
+        let reset = VcfConvertOptions { reset_filter: true, ..Default::default() };
+        assert_eq!(filter_of(update_filter(line("LowQual"), &reset)), "PASS");
+        assert_eq!(filter_of(update_filter(line("q10;LowQual"), &reset)), "PASS");
+        assert_eq!(filter_of(update_filter(line("."), &reset)), ".");
+        
+        let add = VcfConvertOptions { add_filter: Some("LiftedOver".to_string()), ..Default::default() };
+        assert_eq!(filter_of(update_filter(line("q10"), &add)), "q10;LiftedOver");
+        assert_eq!(filter_of(update_filter(line("PASS"), &add)), "LiftedOver");
+        assert_eq!(filter_of(update_filter(line("."), &add)), "LiftedOver");
+        assert_eq!(filter_of(update_filter(line("q10;LiftedOver"), &add)), "q10;LiftedOver");
+        
+        let both = VcfConvertOptions { reset_filter: true, ..add.clone() };
+        assert_eq!(filter_of(update_filter(line("LowQual"), &both)), "LiftedOver");
+        
+        // Other columns are untouched
+        assert_eq!(update_filter(line("q10"), &add), line("q10;LiftedOver"));
+        assert_eq!(update_filter(line("q10"), &VcfConvertOptions::default()), line("q10"));
+    }
+
+    #[test]
+    fn test_filter_info() {
+        let line = |info: &str| format!("chr1\t100\t.\tA\tG\t50\tPASS\t{}\tGT\t0/1", info);
+        let keys = |list: &[&str]| Some(list.iter().map(|k| k.to_string()).collect::<Vec<_>>());
+        
+        let pass = VcfConvertOptions { info_passthrough: keys(&["DP", "DB"]), ..Default::default() };
+        assert_eq!(filter_info(line("DP=3;AF=0.5;DB"), &pass), line("DP=3;DB"));
+        assert_eq!(filter_info(line("AF=0.5"), &pass), line("."));
+        assert_eq!(filter_info(line("."), &pass), line("."));
+        
+        let black = VcfConvertOptions { info_blacklist: keys(&["AF"]), ..Default::default() };
+        assert_eq!(filter_info(line("DP=3;AF=0.5;DB"), &black), line("DP=3;DB"));
+        // Keys match exactly, not by prefix
+        assert_eq!(filter_info(line("AFR=1"), &black), line("AFR=1"));
+        
+        let both = VcfConvertOptions { info_blacklist: keys(&["DB"]), ..pass.clone() };
+        assert_eq!(filter_info(line("DP=3;AF=0.5;DB"), &both), line("DP=3"));
+        
+        assert_eq!(filter_info(line("AF=0.5"), &VcfConvertOptions::default()), line("AF=0.5"));
+        
+        assert!(info_header_kept("##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">", &both));
+        assert!(!info_header_kept("##INFO=<ID=AF,Number=A,Type=Float,Description=\"Freq\">", &both));
+        assert!(info_header_kept("##FORMAT=<ID=AF,Number=A,Type=Float,Description=\"Freq\">", &both));
+    }
+
+    #[test]
+    fn test_pad_missing_fields() {
+        assert_eq!(pad_missing_fields("chr1\t100\t.\tA\tG"), "chr1\t100\t.\tA\tG\t.\t.\t.");
+        assert_eq!(pad_missing_fields("chr1\t100\t.\tA\tG\t50"), "chr1\t100\t.\tA\tG\t50\t.\t.");
+        assert_eq!(pad_missing_fields("chr1\t100\t.\tA\tG\t50\tPASS"), "chr1\t100\t.\tA\tG\t50\tPASS\t.");
+        
+        // Complete and hopelessly short lines are left alone
+        let full = "chr1\t100\t.\tA\tG\t50\tPASS\tDP=3";
+        assert!(matches!(pad_missing_fields(full), Cow::Borrowed(l) if l == full));
+        assert!(matches!(pad_missing_fields("chr1\t100\t.\tA"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_breakend_parse() {
+        let bnd = Breakend::parse("G]chr2:321682]").unwrap();
+        assert_eq!(bnd, Breakend { prefix: "G", bracket: ']', chrom: "chr2", pos: 321682, suffix: "" });
+        assert_eq!(bnd.with_mate("chr3", 10), "G]chr3:10]");
+        assert_eq!(bnd.single(), "G.");
+        
+        let bnd = Breakend::parse("[HLA-A*01:01:1000[T").unwrap();
+        assert_eq!((bnd.chrom, bnd.pos, bnd.suffix), ("HLA-A*01:01", 1000, "T"));
+        assert_eq!(bnd.single(), ".T");
+        
+        for alt in ["G", "<DEL>", "G.", "G[chr2:0[", "G[chr2:5]", "G]chr2]", "[chr2:5[", "A[chr2:5[T"] {
+            assert_eq!(Breakend::parse(alt), None, "{}", alt);
+        }
+    }
+
+    #[test]
+    fn test_add_info_flag() {
+        assert_eq!(add_info_flag(".", "OriginalBndCoords"), "OriginalBndCoords");
+        assert_eq!(add_info_flag("SVTYPE=BND", "OriginalBndCoords"), "SVTYPE=BND;OriginalBndCoords");
+        assert_eq!(add_info_flag("SVTYPE=BND;OriginalBndCoords", "OriginalBndCoords"), "SVTYPE=BND;OriginalBndCoords");
+    }
+
+    #[test]
+    fn test_provenance_headers() {
+        let headers = provenance_headers(Some(Path::new("/data/chains/hg19ToHg38.over.chain.gz")), Some("fast-crossmap vcf a b"));
+        assert_eq!(headers[0], "##liftoverProgram=fast-crossmap");
+        assert_eq!(headers[1], format!("##liftoverVersion={}", env!("CARGO_PKG_VERSION")));
+        assert!(headers[2].starts_with("##liftoverDate="));
+        assert_eq!(headers[3], "##liftoverChain=hg19ToHg38.over.chain.gz");
+        assert_eq!(headers[4], "##liftoverCommandLine=fast-crossmap vcf a b");
+        
+        // Unknown chain file and command line are left out
+        assert_eq!(provenance_headers(None, None).len(), 3);
+    }
+
+    #[test]
+    fn test_iso8601_from_unix() {
+        assert_eq!(iso8601_from_unix(0), "1970-01-01T00:00:00Z");
+        assert_eq!(iso8601_from_unix(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(iso8601_from_unix(1_700_000_000), "2023-11-14T22:13:20Z");
+        assert_eq!(iso8601_now().len(), 20);
+    }
+
+    #[test]
+    fn test_convert_minus_strand_remaps_gt() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+        
+        // chr1:0-100 maps to the minus strand of chr1 (size 100)
+        let chain = b"chain 100 chr1 100 + 0 100 chr1 100 - 0 100 1\n100\n";
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(chain).unwrap());
+        let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+        
+        // Without a reference the REF A is kept; ALT G,T become C,A and
+        // A is dropped as REF==ALT, so the remaining ALT C is index 1
+        let line = b"chr1\t11\t.\tA\tG,T\t.\t.\t.\tGT:DP\t1/2:10";
+        let view = VcfRecordView::parse(line).unwrap();
+        match convert_vcf_record(&view, &mapper, None, &VcfConvertOptions::default()) {
+            ConversionResult::Success(l) => {
+                let fields: Vec<&str> = l.split('\t').collect();
+                assert_eq!(fields[4], "C");
+                assert_eq!(fields[9], "1/0:10");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+    
+    #[test]
+    fn test_convert_inversion_on_minus_strand() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+        
+        // chr1:0-100 maps to the minus strand of chr1, chr2 to the plus strand
+        let chain = b"chain 100 chr1 100 + 0 100 chr1 100 - 0 100 1\n100\n\nchain 100 chr2 100 + 0 100 chr2 100 + 0 100 2\n100\n";
+        let index = ChainIndex::from_chain_data(parse_chain_bytes(chain).unwrap());
+        let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+        let strict = VcfConvertOptions { inversion_strict: true, ..Default::default() };
+        
+        let convert = |line: &str, options: &VcfConvertOptions| {
+            let view = VcfRecordView::parse(line.as_bytes()).unwrap();
+            convert_vcf_record(&view, &mapper, None, options)
+        };
+        let inv = "chr1\t11\tinv1\tA\t<INV>\t.\tPASS\tSVTYPE=INV;END=20";
+        assert!(matches!(convert(inv, &VcfConvertOptions::default()), ConversionResult::Success(_)));
+        match convert(inv, &strict) {
+            ConversionResult::Failed(line, reason) => {
+                assert_eq!(line, inv);
+                assert_eq!(reason, "Fail(InversionStrand)");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        
+        // Plus-strand inversions and minus-strand non-inversions are unaffected
+        assert!(matches!(convert("chr2\t11\tinv2\tA\t<INV>\t.\tPASS\tSVTYPE=INV", &strict), ConversionResult::Success(_)));
+        assert!(matches!(convert("chr1\t11\tdel1\tA\t<DEL>\t.\tPASS\tSVTYPE=DEL", &strict), ConversionResult::Success(_)));
+        
+        assert!(is_inversion("END=20;SVTYPE=INV"));
+        assert!(!is_inversion("SVTYPE=INVDUP"));
+        assert!(!is_inversion("."));
+    }
 }
@@ -5,9 +5,11 @@
 //!
 //! **Validates: Requirements 9.1, 9.2, 9.3, 9.4, 9.5, 9.6**
 
-use crate::core::{CoordinateMapper, Strand};
-use std::collections::BTreeMap;
-use std::io::{BufRead, BufReader, BufWriter, Write};
+use crate::core::{io, CoordinateMapper, Strand};
+use rayon::prelude::*;
+use tracing::{debug, warn};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, BufWriter, Write};
 use std::path::Path;
 
 /// Wiggle parsing error
@@ -19,6 +21,8 @@ pub enum WigParseError {
     MissingChrom,
     MissingSpan,
     MissingStart,
+    /// A data point reaches past the end of its chromosome
+    SpanExceedsChromosome { chrom: String, start: u64, end: u64, chrom_size: u64 },
     IoError(String),
 }
 
@@ -31,6 +35,11 @@ impl std::fmt::Display for WigParseError {
             WigParseError::MissingChrom => write!(f, "Missing chrom parameter"),
             WigParseError::MissingSpan => write!(f, "Missing span parameter"),
             WigParseError::MissingStart => write!(f, "Missing start parameter"),
+            WigParseError::SpanExceedsChromosome { chrom, start, end, chrom_size } => write!(
+                f,
+                "Data point {}:{}-{} exceeds the chromosome size {}",
+                chrom, start, end, chrom_size
+            ),
             WigParseError::IoError(msg) => write!(f, "IO error: {}", msg),
         }
     }
@@ -115,6 +124,11 @@ impl WigDeclaration {
             step,
         })
     }
+    
+    /// Dense per-base fixedStep data (step=1, span=1)
+    fn is_dense(&self) -> bool {
+        self.format == WigFormat::FixedStep && self.span == 1 && self.step.unwrap_or(self.span) == 1
+    }
 }
 
 /// A single Wiggle data point
@@ -124,6 +138,8 @@ pub struct WigDataPoint {
     pub start: u64,  // 0-based
     pub end: u64,    // 0-based, exclusive
     pub value: f64,
+    /// Run-length encoded dense fixedStep data: stands for one point per base
+    pub run: bool,
 }
 
 /// bedGraph record for output
@@ -151,12 +167,39 @@ pub struct ConversionStats {
     pub merged: usize,
 }
 
+/// How `WigReader` reports data points that run past the chromosome end
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WigErrorMode {
+    /// Log a warning and keep the point
+    #[default]
+    Warn,
+    /// Yield `WigParseError::SpanExceedsChromosome` instead of the point
+    Error,
+}
+
 /// Parse a Wiggle file and yield data points
+///
+/// Dense fixedStep sections (step=1, span=1) are run-length encoded: each
+/// run of consecutive bases with the same value is yielded as one data point.
+///
+/// With chromosome sizes (`with_chrom_sizes`), every point is checked against
+/// its chromosome end and handled according to `WigErrorMode`. Overlapping
+/// consecutive variableStep points (`start + span` past the next start) are
+/// always logged as warnings.
 pub struct WigReader<R: BufRead> {
     reader: R,
     current_decl: Option<WigDeclaration>,
     current_pos: u64,  // For fixedStep
     line_buffer: String,
+    /// Pending run of dense fixedStep values
+    run: Option<WigDataPoint>,
+    /// Process `line_buffer` again instead of reading a new line
+    replay_line: bool,
+    /// Source chromosome sizes for span validation
+    chrom_sizes: Option<HashMap<String, u64>>,
+    error_mode: WigErrorMode,
+    /// End of the previous point of the current variableStep section
+    last_variable_end: Option<u64>,
 }
 
 impl<R: BufRead> WigReader<R> {
@@ -166,20 +209,109 @@ impl<R: BufRead> WigReader<R> {
             current_decl: None,
             current_pos: 0,
             line_buffer: String::with_capacity(256),
+            run: None,
+            replay_line: false,
+            chrom_sizes: None,
+            error_mode: WigErrorMode::default(),
+            last_variable_end: None,
+        }
+    }
+    
+    /// Validate data points against these chromosome sizes
+    pub fn with_chrom_sizes(mut self, chrom_sizes: HashMap<String, u64>) -> Self {
+        self.chrom_sizes = Some(chrom_sizes);
+        self
+    }
+    
+    /// Set how points past the chromosome end are reported
+    pub fn with_error_mode(mut self, error_mode: WigErrorMode) -> Self {
+        self.error_mode = error_mode;
+        self
+    }
+    
+    /// Size of a chromosome, trying with and without chr prefix
+    fn chrom_size(&self, chrom: &str) -> Option<u64> {
+        let sizes = self.chrom_sizes.as_ref()?;
+        sizes.get(chrom).copied().or_else(|| match chrom.strip_prefix("chr") {
+            Some(bare) => sizes.get(bare).copied(),
+            None => sizes.get(&format!("chr{}", chrom)).copied(),
+        })
+    }
+    
+    /// Check a point against its chromosome end
+    fn check_span(&self, point: WigDataPoint) -> Result<WigDataPoint, WigParseError> {
+        match self.chrom_size(&point.chrom) {
+            Some(chrom_size) if point.end > chrom_size => {
+                let error = WigParseError::SpanExceedsChromosome {
+                    chrom: point.chrom.clone(),
+                    start: point.start,
+                    end: point.end,
+                    chrom_size,
+                };
+                match self.error_mode {
+                    WigErrorMode::Warn => {
+                        warn!("{}", error);
+                        Ok(point)
+                    }
+                    WigErrorMode::Error => Err(error),
+                }
+            }
+            _ => Ok(point),
         }
     }
+    
+    /// Group consecutive data points by chromosome
+    /// 
+    /// Yields `(chrom, points)` once all points of a chromosome run have been
+    /// read. A chromosome that appears again later in the file is yielded as
+    /// a separate group. Parse errors are logged as warnings and skipped.
+    pub fn by_chromosome(self) -> impl Iterator<Item = (String, Vec<WigDataPoint>)> {
+        let mut points = self
+            .filter_map(|result| match result {
+                Ok(point) => Some(point),
+                Err(e) => {
+                    warn!("Skipping invalid Wiggle record: {}", e);
+                    None
+                }
+            })
+            .peekable();
+        
+        std::iter::from_fn(move || {
+            let first = points.next()?;
+            let chrom = first.chrom.clone();
+            let mut group = vec![first];
+            while let Some(point) = points.next_if(|p| p.chrom == chrom) {
+                group.push(point);
+            }
+            Some((chrom, group))
+        })
+    }
 }
 
 impl<R: BufRead> Iterator for WigReader<R> {
     type Item = Result<WigDataPoint, WigParseError>;
     
     fn next(&mut self) -> Option<Self::Item> {
+        match self.next_point()? {
+            Ok(point) if self.chrom_sizes.is_some() => Some(self.check_span(point)),
+            result => Some(result),
+        }
+    }
+}
+
+impl<R: BufRead> WigReader<R> {
+    /// Next parsed data point, before span validation
+    fn next_point(&mut self) -> Option<Result<WigDataPoint, WigParseError>> {
         loop {
-            self.line_buffer.clear();
-            match self.reader.read_line(&mut self.line_buffer) {
-                Ok(0) => return None, // EOF
-                Ok(_) => {}
-                Err(e) => return Some(Err(WigParseError::IoError(e.to_string()))),
+            if self.replay_line {
+                self.replay_line = false;
+            } else {
+                self.line_buffer.clear();
+                match self.reader.read_line(&mut self.line_buffer) {
+                    Ok(0) => return self.run.take().map(Ok), // EOF
+                    Ok(_) => {}
+                    Err(e) => return Some(Err(WigParseError::IoError(e.to_string()))),
+                }
             }
             
             let line = self.line_buffer.trim();
@@ -189,6 +321,36 @@ impl<R: BufRead> Iterator for WigReader<R> {
                 continue;
             }
             
+            // Fast path for dense fixedStep data: extend the current run
+            if let Some(decl) = self.current_decl.as_ref().filter(|d| d.is_dense()) {
+                if let Ok(value) = line.parse::<f64>() {
+                    let start = self.current_pos;
+                    self.current_pos += 1;
+                    
+                    if let Some(run) = self.run.as_mut().filter(|r| r.end == start && r.value == value) {
+                        run.end += 1;
+                        continue;
+                    }
+                    let previous = self.run.replace(WigDataPoint {
+                        chrom: decl.chrom.clone(),
+                        start,
+                        end: start + 1,
+                        value,
+                        run: true,
+                    });
+                    match previous {
+                        Some(run) => return Some(Ok(run)),
+                        None => continue,
+                    }
+                }
+            }
+            
+            // Any other line ends the run; emit it and handle the line next time
+            if let Some(run) = self.run.take() {
+                self.replay_line = true;
+                return Some(Ok(run));
+            }
+            
             // Check for declaration line
             if line.starts_with("variableStep") || line.starts_with("fixedStep") {
                 match WigDeclaration::parse(line) {
@@ -197,6 +359,7 @@ impl<R: BufRead> Iterator for WigReader<R> {
                             self.current_pos = decl.start.unwrap_or(1) - 1; // Convert to 0-based
                         }
                         self.current_decl = Some(decl);
+                        self.last_variable_end = None;
                         continue;
                     }
                     Err(e) => return Some(Err(e)),
@@ -204,21 +367,17 @@ impl<R: BufRead> Iterator for WigReader<R> {
             }
             
             // Parse data line
-            // Check if it's a bedGraph line (4 columns: chrom start end value)
+            // Check if it's a bedGraph line (4 columns: chrom start end value),
+            // which may appear in the middle of a declared section
             let parts: Vec<&str> = line.split_whitespace().collect();
             if parts.len() >= 4 {
-                // Try to parse as bedGraph format
-                if let (Ok(start), Ok(end), Ok(value)) = (
-                    parts[1].parse::<u64>(),
-                    parts[2].parse::<u64>(),
-                    parts[3].parse::<f64>(),
-                ) {
-                    return Some(Ok(WigDataPoint {
-                        chrom: parts[0].to_string(),
-                        start,
-                        end,
-                        value,
-                    }));
+                match parse_bedgraph_line(&parts) {
+                    Ok(point) => return Some(Ok(point)),
+                    // A non-numeric first column is a chromosome name, never a
+                    // position or value: report the bedGraph error instead of
+                    // handing the line to the section parser
+                    Err(e) if parts.len() == 4 && parts[0].parse::<f64>().is_err() => return Some(Err(e)),
+                    Err(_) => {}
                 }
             }
             
@@ -254,11 +413,20 @@ impl<R: BufRead> Iterator for WigReader<R> {
                     let start = pos - 1;
                     let end = start + decl.span;
                     
+                    if let Some(previous_end) = self.last_variable_end.filter(|&e| e > start) {
+                        warn!(
+                            "Overlapping variableStep points on {}: previous point ends at {}, next starts at {}",
+                            decl.chrom, previous_end, start
+                        );
+                    }
+                    self.last_variable_end = Some(end);
+                    
                     return Some(Ok(WigDataPoint {
                         chrom: decl.chrom.clone(),
                         start,
                         end,
                         value,
+                        run: false,
                     }));
                 }
                 WigFormat::FixedStep => {
@@ -279,6 +447,7 @@ impl<R: BufRead> Iterator for WigReader<R> {
                         start,
                         end,
                         value,
+                        run: false,
                     }));
                 }
             }
@@ -286,63 +455,225 @@ impl<R: BufRead> Iterator for WigReader<R> {
     }
 }
 
-/// Merge overlapping bedGraph records with same value
-fn merge_bedgraph_records(records: Vec<BedGraphRecord>) -> Vec<BedGraphRecord> {
+/// Parse the columns of a bedGraph line (`chrom start end value`, 0-based)
+fn parse_bedgraph_line(parts: &[&str]) -> Result<WigDataPoint, WigParseError> {
+    let number = |s: &str| s.parse::<u64>().map_err(|_| WigParseError::InvalidNumber(s.to_string()));
+    let start = number(parts[1])?;
+    let end = number(parts[2])?;
+    let value = parts[3].parse::<f64>().map_err(|_| WigParseError::InvalidNumber(parts[3].to_string()))?;
+    if start > end {
+        return Err(WigParseError::InvalidFormat(format!(
+            "bedGraph start {} is greater than end {}: {}", start, end, parts.join(" ")
+        )));
+    }
+    Ok(WigDataPoint {
+        chrom: parts[0].to_string(),
+        start,
+        end,
+        value,
+        run: false,
+    })
+}
+
+/// How values of overlapping bedGraph records are combined when merging
+///
+/// Several source intervals can lift onto the same target region (e.g.
+/// after duplications in the chain file). `First` keeps the historical
+/// behaviour: only equal-valued runs are joined and overlapping records with
+/// different values are written as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WigAggregation {
+    /// Keep each record's own value (no aggregation)
+    #[default]
+    First,
+    /// Take the value of the last overlapping record
+    Last,
+    /// Mean of overlapping values
+    Mean,
+    /// Maximum of overlapping values
+    Max,
+    /// Minimum of overlapping values
+    Min,
+    /// Sum of overlapping values
+    Sum,
+}
+
+impl WigAggregation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WigAggregation::First => "first",
+            WigAggregation::Last => "last",
+            WigAggregation::Mean => "mean",
+            WigAggregation::Max => "max",
+            WigAggregation::Min => "min",
+            WigAggregation::Sum => "sum",
+        }
+    }
+}
+
+impl std::fmt::Display for WigAggregation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Options for Wiggle conversion
+#[derive(Debug, Clone)]
+pub struct WigConvertOptions {
+    /// Number of threads (chromosomes are processed in parallel when > 1)
+    pub threads: usize,
+    /// How overlapping lifted records are combined
+    pub aggregation: WigAggregation,
+    /// Write a UCSC `track` line as the first line of the output
+    pub write_track_header: bool,
+    /// Points past the end of their source chromosome (sizes from the
+    /// chain file): warn and convert them, or count them as failed
+    pub error_mode: WigErrorMode,
+}
+
+impl Default for WigConvertOptions {
+    fn default() -> Self {
+        Self {
+            threads: 1,
+            aggregation: WigAggregation::default(),
+            write_track_header: false,
+            error_mode: WigErrorMode::default(),
+        }
+    }
+}
+
+/// UCSC track line for a converted file
+///
+/// The output is variableStep Wiggle, so the track type is `wiggle_0`.
+/// The name is the output prefix's file name and the description names
+/// the source file.
+fn track_header(input: &Path, output_prefix: &Path) -> String {
+    let base_name = |p: &Path| {
+        if io::is_stdio(p) {
+            "stdin".to_string()
+        } else {
+            p.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+        }
+    };
+    let name = if io::is_stdio(output_prefix) { "lifted".to_string() } else { base_name(output_prefix) };
+    format!(
+        "track type=wiggle_0 name=\"{}\" description=\"Lifted from {}\"",
+        name,
+        base_name(input)
+    )
+}
+
+/// Merge overlapping bedGraph records
+/// 
+/// Records are grouped by chromosome (output in chromosome name order) and
+/// merged in a single pass. Converted WIG data is usually already sorted by
+/// start, so the sort is skipped unless a chromosome is out of order.
+///
+/// With `First`, adjacent or overlapping records with the same value are
+/// joined and other records are written as they are. Any other
+/// `aggregation` splits the records at every interval boundary and gives
+/// each piece covered by several records their combined value (see
+/// `WigAggregation`); pieces covered by one record keep its value, and
+/// adjacent pieces with the same value are joined.
+pub fn merge_bedgraph_records(
+    records: Vec<BedGraphRecord>,
+    aggregation: WigAggregation,
+) -> Vec<BedGraphRecord> {
     if records.is_empty() {
         return records;
     }
     
-    // Group by chromosome
+    // Group by chromosome (only clone the name for new chromosomes)
     let mut by_chrom: BTreeMap<String, Vec<BedGraphRecord>> = BTreeMap::new();
     for rec in records {
-        by_chrom.entry(rec.chrom.clone()).or_default().push(rec);
+        match by_chrom.get_mut(&rec.chrom) {
+            Some(recs) => recs.push(rec),
+            None => {
+                by_chrom.insert(rec.chrom.clone(), vec![rec]);
+            }
+        }
     }
     
     let mut merged = Vec::new();
     
-    for (chrom, mut recs) in by_chrom {
-        // Sort by start position
-        recs.sort_by_key(|r| r.start);
+    for (_, mut recs) in by_chrom {
+        // Sort by start position only if needed
+        if !recs.windows(2).all(|w| w[0].start <= w[1].start) {
+            recs.sort_by_key(|r| r.start);
+        }
         
-        let mut current: Option<BedGraphRecord> = None;
+        if aggregation != WigAggregation::First {
+            aggregate_at_breakpoints(recs, aggregation, &mut merged);
+            continue;
+        }
         
-        for rec in recs {
-            match current.take() {
-                None => {
-                    current = Some(rec);
-                }
-                Some(mut curr) => {
-                    // Check if can merge (adjacent or overlapping with same value)
-                    if rec.chrom == curr.chrom 
-                        && rec.start <= curr.end 
-                        && (rec.value - curr.value).abs() < 1e-10 
-                    {
-                        // Merge: extend end
-                        curr.end = curr.end.max(rec.end);
-                        current = Some(curr);
-                    } else {
-                        // Cannot merge, output current and start new
-                        merged.push(BedGraphRecord {
-                            chrom: chrom.clone(),
-                            ..curr
-                        });
-                        current = Some(rec);
-                    }
-                }
+        let mut iter = recs.into_iter();
+        let mut current = match iter.next() {
+            Some(rec) => rec,
+            None => continue,
+        };
+        
+        for rec in iter {
+            let same_value = (rec.value - current.value).abs() < 1e-10;
+            if rec.start <= current.end && same_value {
+                // Merge: extend end
+                current.end = current.end.max(rec.end);
+            } else {
+                // Cannot merge, output current and start new
+                merged.push(std::mem::replace(&mut current, rec));
             }
         }
         
-        if let Some(curr) = current {
-            merged.push(BedGraphRecord {
-                chrom,
-                ..curr
-            });
-        }
+        merged.push(current);
     }
     
     merged
 }
 
+/// Split one chromosome's records (sorted by start) at every interval
+/// boundary and write each covered piece with the `aggregation` of the
+/// records overlapping it. Adjacent pieces with the same value are joined.
+fn aggregate_at_breakpoints(recs: Vec<BedGraphRecord>, aggregation: WigAggregation, merged: &mut Vec<BedGraphRecord>) {
+    let mut breakpoints: Vec<u64> = recs.iter().flat_map(|r| [r.start, r.end]).collect();
+    breakpoints.sort_unstable();
+    breakpoints.dedup();
+    
+    let first_out = merged.len();
+    let mut next = 0;
+    // Indices of the records covering the current piece, in input order
+    let mut active: Vec<usize> = Vec::new();
+    for piece in breakpoints.windows(2) {
+        let (start, end) = (piece[0], piece[1]);
+        active.retain(|&i| recs[i].end > start);
+        while next < recs.len() && recs[next].start <= start {
+            if recs[next].end > start {
+                active.push(next);
+            }
+            next += 1;
+        }
+        if active.is_empty() {
+            continue;
+        }
+        
+        let values = active.iter().map(|&i| recs[i].value);
+        let value = match aggregation {
+            WigAggregation::First => recs[active[0]].value,
+            WigAggregation::Last => recs[active[active.len() - 1]].value,
+            WigAggregation::Max => values.fold(f64::NEG_INFINITY, f64::max),
+            WigAggregation::Min => values.fold(f64::INFINITY, f64::min),
+            WigAggregation::Sum => values.sum(),
+            WigAggregation::Mean => values.sum::<f64>() / active.len() as f64,
+        };
+        
+        match merged[first_out..].last_mut() {
+            Some(last) if last.end == start && (last.value - value).abs() < 1e-10 => {
+                last.end = end;
+            }
+            _ => merged.push(BedGraphRecord { chrom: recs[active[0]].chrom.clone(), start, end, value }),
+        }
+    }
+}
+
 /// Convert a single Wiggle data point
 fn convert_wig_point(
     point: &WigDataPoint,
@@ -366,11 +697,71 @@ fn convert_wig_point(
     })
 }
 
+/// Lift a data point into `converted`, or record it in `unmapped`
+///
+/// A dense fixedStep run is lifted in one piece when a single segment
+/// covers all of it. Otherwise (chain gap, block boundary, unmapped) it is
+/// lifted base by base like the per-base points it stands for. Returns the
+/// number of converted and failed points, counting a run once per base.
+fn lift_wig_point(
+    point: WigDataPoint,
+    mapper: &CoordinateMapper,
+    converted: &mut Vec<BedGraphRecord>,
+    unmapped: &mut Vec<BedGraphRecord>,
+) -> (usize, usize) {
+    if !point.run {
+        match convert_wig_point(&point, mapper) {
+            Some(rec) => {
+                converted.push(rec);
+                return (1, 0);
+            }
+            None => {
+                debug!("Unmapped Wiggle record {}:{}-{}", point.chrom, point.start, point.end);
+                unmapped.push(BedGraphRecord { chrom: point.chrom, start: point.start, end: point.end, value: point.value });
+                return (0, 1);
+            }
+        }
+    }
+    
+    if let Some(segments) = mapper.map(&point.chrom, point.start, point.end, Strand::Plus) {
+        if let [seg] = segments.as_slice() {
+            if seg.source.start == point.start && seg.source.end == point.end {
+                converted.push(BedGraphRecord {
+                    chrom: seg.target.chrom.clone(),
+                    start: seg.target.start,
+                    end: seg.target.end,
+                    value: point.value,
+                });
+                return ((point.end - point.start) as usize, 0);
+            }
+        }
+    }
+    
+    let (mut success, mut failed) = (0, 0);
+    for start in point.start..point.end {
+        let base = WigDataPoint { start, end: start + 1, run: false, ..point.clone() };
+        match convert_wig_point(&base, mapper) {
+            Some(rec) => {
+                converted.push(rec);
+                success += 1;
+            }
+            None => {
+                unmapped.push(BedGraphRecord { chrom: base.chrom, start, end: start + 1, value: base.value });
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        debug!("Unmapped {} bases of Wiggle run {}:{}-{}", failed, point.chrom, point.start, point.end);
+    }
+    (success, failed)
+}
+
 /// Convert a Wiggle file to Wiggle format (variableStep)
 ///
 /// # Arguments
-/// * `input` - Input Wiggle file path
-/// * `output_prefix` - Output file prefix (will create .wig file)
+/// * `input` - Input Wiggle file path (`-` for stdin)
+/// * `output_prefix` - Output file prefix (will create .wig file, `-` for stdout)
 /// * `mapper` - Coordinate mapper
 ///
 /// # Returns
@@ -380,64 +771,227 @@ pub fn convert_wig<P: AsRef<Path>>(
     output_prefix: P,
     mapper: &CoordinateMapper,
 ) -> Result<ConversionStats, std::io::Error> {
-    let input_file = std::fs::File::open(input.as_ref())?;
-    let reader = BufReader::with_capacity(128 * 1024, input_file);
+    convert_wig_with_aggregation(input, output_prefix, mapper, WigAggregation::default())
+}
+
+/// Convert a Wiggle file, combining overlapping output records with `aggregation`
+///
+/// # Arguments
+/// * `input` - Input Wiggle file path (`-` for stdin)
+/// * `output_prefix` - Output file prefix (will create .wig file, `-` for stdout)
+/// * `mapper` - Coordinate mapper
+/// * `aggregation` - How overlapping lifted records are combined
+pub fn convert_wig_with_aggregation<P: AsRef<Path>>(
+    input: P,
+    output_prefix: P,
+    mapper: &CoordinateMapper,
+    aggregation: WigAggregation,
+) -> Result<ConversionStats, std::io::Error> {
+    let options = WigConvertOptions { aggregation, ..Default::default() };
+    convert_wig_with_options(input, output_prefix, mapper, &options)
+}
+
+/// Convert a Wiggle file with explicit options
+///
+/// Uses `convert_wig_parallel`'s per-chromosome strategy when
+/// `options.threads > 1` and the input is a file.
+pub fn convert_wig_with_options<P: AsRef<Path>>(
+    input: P,
+    output_prefix: P,
+    mapper: &CoordinateMapper,
+    options: &WigConvertOptions,
+) -> Result<ConversionStats, std::io::Error> {
+    let header = options
+        .write_track_header
+        .then(|| track_header(input.as_ref(), output_prefix.as_ref()));
+    
+    if options.threads > 1 && !io::is_stdio(&input) {
+        convert_wig_parallel_inner(input, output_prefix, mapper, options, header.as_deref())
+    } else {
+        convert_wig_sequential(input, output_prefix, mapper, options, header.as_deref())
+    }
+}
+
+/// Sequential Wiggle conversion
+fn convert_wig_sequential<P: AsRef<Path>>(
+    input: P,
+    output_prefix: P,
+    mapper: &CoordinateMapper,
+    options: &WigConvertOptions,
+    track_header: Option<&str>,
+) -> Result<ConversionStats, std::io::Error> {
+    let reader = io::open_input(input.as_ref(), 128 * 1024)?;
     
     // Output files - use .wig extension for Wiggle format
-    let output_path = format!("{}.wig", output_prefix.as_ref().display());
-    let unmap_path = format!("{}.unmap.wig", output_prefix.as_ref().display());
+    let (output_path, unmap_path) = wig_output_paths(output_prefix.as_ref());
     
     let mut stats = ConversionStats::default();
     let mut converted_records = Vec::new();
     let mut unmapped_records = Vec::new();
     
     // Parse and convert
-    let wig_reader = WigReader::new(reader);
+    let wig_reader = WigReader::new(reader)
+        .with_chrom_sizes(mapper.source_sizes().clone())
+        .with_error_mode(options.error_mode);
     
     for result in wig_reader {
         match result {
             Ok(point) => {
-                stats.total += 1;
-                
-                if let Some(converted) = convert_wig_point(&point, mapper) {
-                    converted_records.push(converted);
-                    stats.success += 1;
-                } else {
-                    unmapped_records.push(BedGraphRecord {
-                        chrom: point.chrom,
-                        start: point.start,
-                        end: point.end,
-                        value: point.value,
-                    });
-                    stats.failed += 1;
-                }
+                let (success, failed) = lift_wig_point(point, mapper, &mut converted_records, &mut unmapped_records);
+                stats.total += success + failed;
+                stats.success += success;
+                stats.failed += failed;
             }
             Err(e) => {
-                eprintln!("Warning: {}", e);
+                warn!("Skipping invalid Wiggle record: {}", e);
                 stats.failed += 1;
             }
         }
     }
     
+    write_converted_records(
+        &output_path, &unmap_path, converted_records, &unmapped_records, options.aggregation, track_header, &mut stats,
+    )?;
+    
+    Ok(stats)
+}
+
+/// Convert a Wiggle file with chromosomes distributed across threads
+///
+/// The input is grouped by chromosome with `WigReader::by_chromosome` and
+/// each group is lifted on a rayon worker. Output is identical to
+/// `convert_wig`, except that unparseable lines are not counted in `failed`.
+///
+/// # Arguments
+/// * `input` - Input Wiggle file path (`-` for stdin)
+/// * `output_prefix` - Output file prefix (will create .wig file, `-` for stdout)
+/// * `mapper` - Coordinate mapper
+/// * `threads` - Number of threads
+/// * `aggregation` - How overlapping lifted records are combined
+pub fn convert_wig_parallel<P: AsRef<Path>>(
+    input: P,
+    output_prefix: P,
+    mapper: &CoordinateMapper,
+    threads: usize,
+    aggregation: WigAggregation,
+) -> Result<ConversionStats, std::io::Error> {
+    let options = WigConvertOptions { threads, aggregation, ..Default::default() };
+    if io::is_stdio(&input) {
+        return convert_wig_sequential(input, output_prefix, mapper, &options, None);
+    }
+    convert_wig_parallel_inner(input, output_prefix, mapper, &options, None)
+}
+
+/// Per-chromosome parallel conversion behind `convert_wig_parallel`
+fn convert_wig_parallel_inner<P: AsRef<Path>>(
+    input: P,
+    output_prefix: P,
+    mapper: &CoordinateMapper,
+    options: &WigConvertOptions,
+    track_header: Option<&str>,
+) -> Result<ConversionStats, std::io::Error> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.threads)
+        .build()
+        .map_err(|e| std::io::Error::new(
+            std::io::ErrorKind::Other,
+            format!("Failed to create thread pool: {}", e)
+        ))?;
+    
+    let reader = io::open_input(input.as_ref(), 128 * 1024)?;
+    let (output_path, unmap_path) = wig_output_paths(output_prefix.as_ref());
+    
+    let groups: Vec<(String, Vec<WigDataPoint>)> = WigReader::new(reader)
+        .with_chrom_sizes(mapper.source_sizes().clone())
+        .with_error_mode(options.error_mode)
+        .by_chromosome()
+        .collect();
+    
+    // Convert each chromosome independently (results keep input order)
+    let results: Vec<(Vec<BedGraphRecord>, Vec<BedGraphRecord>, usize, usize)> = pool.install(|| {
+        groups
+            .into_par_iter()
+            .map(|(_, points)| {
+                let mut converted = Vec::with_capacity(points.len());
+                let mut unmapped = Vec::new();
+                let (mut success, mut failed) = (0, 0);
+                for point in points {
+                    let (s, f) = lift_wig_point(point, mapper, &mut converted, &mut unmapped);
+                    success += s;
+                    failed += f;
+                }
+                (converted, unmapped, success, failed)
+            })
+            .collect()
+    });
+    
+    let mut stats = ConversionStats::default();
+    let mut converted_records = Vec::new();
+    let mut unmapped_records = Vec::new();
+    for (converted, unmapped, success, failed) in results {
+        stats.success += success;
+        stats.failed += failed;
+        converted_records.extend(converted);
+        unmapped_records.extend(unmapped);
+    }
+    stats.total = stats.success + stats.failed;
+    
+    write_converted_records(
+        &output_path, &unmap_path, converted_records, &unmapped_records, options.aggregation, track_header, &mut stats,
+    )?;
+    
+    Ok(stats)
+}
+
+/// Output and unmap Wiggle file names for an output prefix
+///
+/// A `-` prefix writes the output to stdout; unmapped records then go to stderr.
+fn wig_output_paths(output_prefix: &Path) -> (String, String) {
+    if io::is_stdio(output_prefix) {
+        (io::STDIO_PATH.to_string(), String::new())
+    } else {
+        (
+            format!("{}.wig", output_prefix.display()),
+            format!("{}.unmap.wig", output_prefix.display()),
+        )
+    }
+}
+
+/// Merge converted records and write the output and unmap Wiggle files
+fn write_converted_records(
+    output_path: &str,
+    unmap_path: &str,
+    converted_records: Vec<BedGraphRecord>,
+    unmapped_records: &[BedGraphRecord],
+    aggregation: WigAggregation,
+    track_header: Option<&str>,
+    stats: &mut ConversionStats,
+) -> Result<(), std::io::Error> {
     // Merge overlapping records
     let original_count = converted_records.len();
-    let merged_records = merge_bedgraph_records(converted_records);
-    stats.merged = original_count - merged_records.len();
+    let merged_records = merge_bedgraph_records(converted_records, aggregation);
+    stats.merged = original_count.saturating_sub(merged_records.len());
     
     // Write output in Wiggle variableStep format
-    write_wiggle_file(&output_path, &merged_records)?;
+    let mut output_file = io::create_output(output_path, 128 * 1024)?;
+    write_wiggle(&mut output_file, &merged_records, track_header)?;
+    output_file.flush()?;
     
     // Write unmapped in Wiggle format
     if !unmapped_records.is_empty() {
-        write_wiggle_file(&unmap_path, &unmapped_records)?;
+        let mut unmap_file = io::create_unmap_output(unmap_path, 64 * 1024)?;
+        write_wiggle(&mut unmap_file, unmapped_records, None)?;
+        unmap_file.flush()?;
     }
     
-    Ok(stats)
+    Ok(())
 }
 
-/// Write records to a Wiggle file in variableStep format
-fn write_wiggle_file(path: &str, records: &[BedGraphRecord]) -> Result<(), std::io::Error> {
-    let mut output_file = BufWriter::with_capacity(128 * 1024, std::fs::File::create(path)?);
+/// Write records to a Wiggle file in variableStep format, after an optional track line
+fn write_wiggle(output_file: &mut dyn Write, records: &[BedGraphRecord], track_header: Option<&str>) -> Result<(), std::io::Error> {
+    if let Some(header) = track_header {
+        writeln!(output_file, "{}", header)?;
+    }
     
     // Group records by chromosome
     let mut by_chrom: BTreeMap<String, Vec<&BedGraphRecord>> = BTreeMap::new();
@@ -470,12 +1024,43 @@ fn write_wiggle_file(path: &str, records: &[BedGraphRecord]) -> Result<(), std::
     Ok(())
 }
 
+/// Lift data points and merge the converted records with `aggregation`
+///
+/// Returns the merged converted records and the unmapped records.
+fn lift_and_merge_points(
+    points: Vec<WigDataPoint>,
+    mapper: &CoordinateMapper,
+    aggregation: WigAggregation,
+    stats: &mut ConversionStats,
+) -> (Vec<BedGraphRecord>, Vec<BedGraphRecord>) {
+    let mut converted_records = Vec::new();
+    let mut unmapped_records = Vec::new();
+    
+    for point in points {
+        let (success, failed) = lift_wig_point(point, mapper, &mut converted_records, &mut unmapped_records);
+        stats.total += success + failed;
+        stats.success += success;
+        stats.failed += failed;
+    }
+    
+    // Merge overlapping records
+    let original_count = converted_records.len();
+    let merged_records = merge_bedgraph_records(converted_records, aggregation);
+    stats.merged = original_count.saturating_sub(merged_records.len());
+    
+    (merged_records, unmapped_records)
+}
+
 /// BigWig support module
 pub mod bigwig {
     use super::*;
     use bigtools::BigWigRead;
     use std::collections::HashMap;
     
+    /// How values lifted onto the same target interval are combined (only
+    /// where the lifted intervals actually overlap)
+    pub type BigWigAggregation = WigAggregation;
+    
     /// Read intervals from a BigWig file
     pub fn read_bigwig_intervals<P: AsRef<Path>>(
         path: P,
@@ -502,6 +1087,7 @@ pub mod bigwig {
                     start: interval.start as u64,
                     end: interval.end as u64,
                     value: interval.value as f64,
+                    run: false,
                 });
             }
         }
@@ -571,6 +1157,8 @@ pub mod bigwig {
     /// * `input` - Input BigWig file path
     /// * `output_prefix` - Output file prefix (will create .bw file)
     /// * `mapper` - Coordinate mapper
+    /// * `aggregation` - How values of intervals lifted onto overlapping
+    ///   target regions (e.g. a compressed source region) are combined
     ///
     /// # Returns
     /// Conversion statistics
@@ -578,37 +1166,24 @@ pub mod bigwig {
         input: P,
         output_prefix: P,
         mapper: &CoordinateMapper,
+        aggregation: BigWigAggregation,
     ) -> Result<ConversionStats, std::io::Error> {
+        // BigWig is a random-access binary format
+        if io::is_stdio(&input) || io::is_stdio(&output_prefix) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "BigWig conversion does not support stdin/stdout ('-')",
+            ));
+        }
+        
         // Read BigWig intervals
         let points = read_bigwig_intervals(&input)
             .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string()))?;
         
+        // Convert each interval and merge at target resolution
         let mut stats = ConversionStats::default();
-        let mut converted_records = Vec::new();
-        let mut unmapped_records = Vec::new();
-        
-        // Convert each interval
-        for point in points {
-            stats.total += 1;
-            
-            if let Some(converted) = convert_wig_point(&point, mapper) {
-                converted_records.push(converted);
-                stats.success += 1;
-            } else {
-                unmapped_records.push(BedGraphRecord {
-                    chrom: point.chrom,
-                    start: point.start,
-                    end: point.end,
-                    value: point.value,
-                });
-                stats.failed += 1;
-            }
-        }
-        
-        // Merge overlapping records
-        let original_count = converted_records.len();
-        let merged_records = merge_bedgraph_records(converted_records);
-        stats.merged = original_count - merged_records.len();
+        let (merged_records, unmapped_records) =
+            lift_and_merge_points(points, mapper, aggregation, &mut stats);
         
         // Write BigWig output directly
         let bw_path = format!("{}.bw", output_prefix.as_ref().display());
@@ -637,6 +1212,39 @@ mod tests {
     use super::*;
     use std::io::Cursor;
 
+    #[test]
+    fn test_span_exceeds_chromosome() {
+        let data = "fixedStep chrom=chr1 start=991 step=5 span=10\n1\n2\nvariableStep chrom=chr2\n50\t3\n";
+        let sizes: HashMap<String, u64> = [("1".to_string(), 1000), ("chr2".to_string(), 40)].into_iter().collect();
+        let read = |mode: WigErrorMode| -> Vec<Result<WigDataPoint, WigParseError>> {
+            WigReader::new(Cursor::new(data)).with_chrom_sizes(sizes.clone()).with_error_mode(mode).collect()
+        };
+        
+        // Warn: every point is kept
+        let points = read(WigErrorMode::Warn);
+        assert_eq!(points.len(), 3);
+        assert!(points.iter().all(|p| p.is_ok()));
+        
+        // Error: points past the end (chr1 via the "1" entry) are reported
+        let points = read(WigErrorMode::Error);
+        assert_eq!(points[0].as_ref().unwrap().end, 1000);
+        assert!(matches!(
+            &points[1],
+            Err(WigParseError::SpanExceedsChromosome { chrom, start: 995, end: 1005, chrom_size: 1000 }) if chrom == "chr1"
+        ));
+        assert!(matches!(&points[2], Err(WigParseError::SpanExceedsChromosome { start: 49, end: 50, chrom_size: 40, .. })));
+        assert_eq!(points[2].as_ref().unwrap_err().to_string(), "Data point chr2:49-50 exceeds the chromosome size 40");
+        
+        // Unknown chromosomes and readers without sizes are not checked
+        let unchecked: Vec<_> = WigReader::new(Cursor::new(data)).with_error_mode(WigErrorMode::Error).collect();
+        assert!(unchecked.iter().all(|p| p.is_ok()));
+        let other: Vec<_> = WigReader::new(Cursor::new(data))
+            .with_chrom_sizes([("chr3".to_string(), 10)].into_iter().collect())
+            .with_error_mode(WigErrorMode::Error)
+            .collect();
+        assert!(other.iter().all(|p| p.is_ok()));
+    }
+
     #[test]
     fn test_variable_step_declaration() {
         let line = "variableStep chrom=chr1 span=10";
@@ -711,6 +1319,50 @@ variableStep chrom=chr1 span=10
         assert_eq!(p2.start, 2999);
         assert_eq!(p2.end, 3009);
     }
+    
+    #[test]
+    fn test_wig_reader_bedgraph_lines_in_sections() {
+        let wig_content = "\
+variableStep chrom=chr1 span=10
+1000 1.5
+chr2\t100\t200\t4.5
+2000 2.5
+fixedStep chrom=chr3 start=11 step=1
+7
+chrX 5 6 -1
+8
+1\t50\t60\t0.5
+";
+        let points: Vec<WigDataPoint> = WigReader::new(Cursor::new(wig_content))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let summary: Vec<(&str, u64, u64, f64)> = points.iter().map(|p| (p.chrom.as_str(), p.start, p.end, p.value)).collect();
+        assert_eq!(summary, vec![
+            ("chr1", 999, 1009, 1.5),
+            ("chr2", 100, 200, 4.5),
+            ("chr1", 1999, 2009, 2.5),
+            ("chr3", 10, 11, 7.0),
+            ("chrX", 5, 6, -1.0),
+            ("chr3", 11, 12, 8.0),
+            ("1", 50, 60, 0.5),
+        ]);
+    }
+    
+    #[test]
+    fn test_wig_reader_invalid_bedgraph_line() {
+        // The error names the bad bedGraph column, not the chromosome
+        for (line, expected) in [
+            ("chr2 100 2x0 4.5", "Invalid number: 2x0"),
+            ("chr2 100 200 high", "Invalid number: high"),
+            ("chr2 300 200 1", "Invalid format: bedGraph start 300 is greater than end 200: chr2 300 200 1"),
+        ] {
+            let wig_content = format!("variableStep chrom=chr1\n{}\n10 1\n", line);
+            let points: Vec<_> = WigReader::new(Cursor::new(wig_content)).collect();
+            assert_eq!(points.len(), 2);
+            assert_eq!(points[0].as_ref().unwrap_err().to_string(), expected);
+            assert_eq!(points[1].as_ref().unwrap().start, 9);
+        }
+    }
 
     #[test]
     fn test_wig_reader_fixed_step() {
@@ -805,7 +1457,7 @@ variableStep chrom=chr2 span=20
             BedGraphRecord { chrom: "chr1".to_string(), start: 200, end: 300, value: 1.0 },
         ];
         
-        let merged = merge_bedgraph_records(records);
+        let merged = merge_bedgraph_records(records, WigAggregation::First);
         
         assert_eq!(merged.len(), 1);
         assert_eq!(merged[0].start, 0);
@@ -820,7 +1472,7 @@ variableStep chrom=chr2 span=20
             BedGraphRecord { chrom: "chr1".to_string(), start: 100, end: 200, value: 2.0 },
         ];
         
-        let merged = merge_bedgraph_records(records);
+        let merged = merge_bedgraph_records(records, WigAggregation::First);
         
         assert_eq!(merged.len(), 2);
     }
@@ -832,7 +1484,7 @@ variableStep chrom=chr2 span=20
             BedGraphRecord { chrom: "chr2".to_string(), start: 0, end: 100, value: 1.0 },
         ];
         
-        let merged = merge_bedgraph_records(records);
+        let merged = merge_bedgraph_records(records, WigAggregation::First);
         
         assert_eq!(merged.len(), 2);
     }
@@ -844,10 +1496,219 @@ variableStep chrom=chr2 span=20
             BedGraphRecord { chrom: "chr1".to_string(), start: 100, end: 200, value: 1.0 },
         ];
         
-        let merged = merge_bedgraph_records(records);
+        let merged = merge_bedgraph_records(records, WigAggregation::First);
         
         assert_eq!(merged.len(), 1);
         assert_eq!(merged[0].start, 0);
         assert_eq!(merged[0].end, 200);
     }
+
+    /// Expected merged records as `(start, end, value)`
+    type Pieces = &'static [(u64, u64, f64)];
+
+    fn overlapping_pair() -> Vec<BedGraphRecord> {
+        vec![
+            BedGraphRecord { chrom: "chr1".to_string(), start: 0, end: 100, value: 1.0 },
+            BedGraphRecord { chrom: "chr1".to_string(), start: 50, end: 350, value: 3.0 },
+        ]
+    }
+
+    #[test]
+    fn test_merge_bedgraph_aggregation_first() {
+        let merged = merge_bedgraph_records(overlapping_pair(), WigAggregation::First);
+        
+        // Default keeps overlapping records with different values unchanged
+        assert_eq!(merged.len(), 2);
+        assert_eq!((merged[0].start, merged[0].end, merged[0].value), (0, 100, 1.0));
+        assert_eq!((merged[1].start, merged[1].end, merged[1].value), (50, 350, 3.0));
+        assert_eq!(WigAggregation::default(), WigAggregation::First);
+    }
+
+    #[test]
+    fn test_merge_bedgraph_aggregation_modes() {
+        // Only 50-100 is covered by both records
+        let cases: [(WigAggregation, Pieces); 5] = [
+            (WigAggregation::Last, &[(0, 50, 1.0), (50, 350, 3.0)]),
+            (WigAggregation::Max, &[(0, 50, 1.0), (50, 350, 3.0)]),
+            (WigAggregation::Min, &[(0, 100, 1.0), (100, 350, 3.0)]),
+            (WigAggregation::Sum, &[(0, 50, 1.0), (50, 100, 4.0), (100, 350, 3.0)]),
+            (WigAggregation::Mean, &[(0, 50, 1.0), (50, 100, 2.0), (100, 350, 3.0)]),
+        ];
+        for (aggregation, expected) in cases {
+            let merged = merge_bedgraph_records(overlapping_pair(), aggregation);
+            let actual: Vec<(u64, u64, f64)> = merged.iter().map(|r| (r.start, r.end, r.value)).collect();
+            assert_eq!(actual, expected, "{}", aggregation);
+        }
+    }
+
+    #[test]
+    fn test_merge_bedgraph_aggregation_nested_and_gaps() {
+        let records = vec![
+            BedGraphRecord { chrom: "chr1".to_string(), start: 0, end: 300, value: 1.0 },
+            BedGraphRecord { chrom: "chr1".to_string(), start: 100, end: 200, value: 2.0 },
+            BedGraphRecord { chrom: "chr1".to_string(), start: 500, end: 600, value: 1.0 },
+            BedGraphRecord { chrom: "chr2".to_string(), start: 0, end: 10, value: 5.0 },
+        ];
+        let merged = merge_bedgraph_records(records, WigAggregation::Sum);
+        let actual: Vec<(&str, u64, u64, f64)> = merged.iter().map(|r| (r.chrom.as_str(), r.start, r.end, r.value)).collect();
+        assert_eq!(actual, vec![
+            ("chr1", 0, 100, 1.0),
+            ("chr1", 100, 200, 3.0),
+            ("chr1", 200, 300, 1.0),
+            ("chr1", 500, 600, 1.0),
+            ("chr2", 0, 10, 5.0),
+        ]);
+    }
+
+    #[test]
+    fn test_merge_bedgraph_aggregation_adjacent_not_combined() {
+        // Touching intervals do not overlap, so no mode combines their values
+        let records = vec![
+            BedGraphRecord { chrom: "chr1".to_string(), start: 0, end: 100, value: 1.0 },
+            BedGraphRecord { chrom: "chr1".to_string(), start: 100, end: 200, value: 2.0 },
+            BedGraphRecord { chrom: "chr1".to_string(), start: 200, end: 300, value: 2.0 },
+        ];
+        let merged = merge_bedgraph_records(records, WigAggregation::Sum);
+        
+        assert_eq!(merged.len(), 2);
+        assert_eq!((merged[0].start, merged[0].end, merged[0].value), (0, 100, 1.0));
+        assert_eq!((merged[1].start, merged[1].end, merged[1].value), (100, 300, 2.0));
+    }
+
+    #[test]
+    fn test_wig_reader_dense_fixed_step_runs() {
+        let data = "fixedStep chrom=chr1 start=11 step=1\n1\n1\n1\n2\n2\n1\nvariableStep chrom=chr2\n5\t3.0\n";
+        let reader = WigReader::new(std::io::Cursor::new(data));
+        let points: Vec<WigDataPoint> = reader.map(|r| r.unwrap()).collect();
+        
+        let coords: Vec<(&str, u64, u64, f64)> = points
+            .iter()
+            .map(|p| (p.chrom.as_str(), p.start, p.end, p.value))
+            .collect();
+        assert_eq!(coords, vec![
+            ("chr1", 10, 13, 1.0),
+            ("chr1", 13, 15, 2.0),
+            ("chr1", 15, 16, 1.0),
+            ("chr2", 4, 5, 3.0),
+        ]);
+    }
+
+    #[test]
+    fn test_wig_reader_dense_run_ends_at_declaration() {
+        // A new fixedStep section restarts position even with the same value
+        let data = "fixedStep chrom=chr1 start=1 step=1 span=1\n5\n5\nfixedStep chrom=chr1 start=3 step=1\n5\n";
+        let reader = WigReader::new(std::io::Cursor::new(data));
+        let points: Vec<WigDataPoint> = reader.map(|r| r.unwrap()).collect();
+        
+        assert_eq!(points.len(), 2);
+        assert_eq!((points[0].start, points[0].end), (0, 2));
+        assert_eq!((points[1].start, points[1].end), (2, 3));
+        
+        // Sparse fixedStep data is not run-length encoded
+        let sparse = "fixedStep chrom=chr1 start=1 step=2\n5\n5\n";
+        assert_eq!(WigReader::new(std::io::Cursor::new(sparse)).count(), 2);
+    }
+
+    #[test]
+    fn test_lift_and_merge_compressed_region() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+        
+        // Two chains compress chr1:0-200 onto chr1:0-150; target 50-100 is hit twice
+        let chain = parse_chain_bytes(
+            b"chain 1000 chr1 1000 + 0 100 chr1 1000 + 0 100 1\n100\n\n\
+              chain 900 chr1 1000 + 100 200 chr1 1000 + 50 150 2\n100\n",
+        ).unwrap();
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+        let points = || vec![
+            WigDataPoint { chrom: "chr1".to_string(), start: 0, end: 100, value: 2.0, run: false },
+            WigDataPoint { chrom: "chr1".to_string(), start: 100, end: 200, value: 4.0, run: false },
+        ];
+        
+        let mut stats = ConversionStats::default();
+        let (merged, unmapped) = lift_and_merge_points(points(), &mapper, WigAggregation::First, &mut stats);
+        assert_eq!(merged.len(), 2);
+        assert!(unmapped.is_empty());
+        assert_eq!((stats.total, stats.success, stats.merged), (2, 2, 0));
+        
+        // Only target 50-100 combines both values
+        let cases: [(WigAggregation, Pieces); 4] = [
+            (WigAggregation::Mean, &[(0, 50, 2.0), (50, 100, 3.0), (100, 150, 4.0)]),
+            (WigAggregation::Max, &[(0, 50, 2.0), (50, 150, 4.0)]),
+            (WigAggregation::Min, &[(0, 100, 2.0), (100, 150, 4.0)]),
+            (WigAggregation::Sum, &[(0, 50, 2.0), (50, 100, 6.0), (100, 150, 4.0)]),
+        ];
+        for (aggregation, expected) in cases {
+            let mut stats = ConversionStats::default();
+            let (merged, _) = lift_and_merge_points(points(), &mapper, aggregation, &mut stats);
+            let actual: Vec<(u64, u64, f64)> = merged.iter().map(|r| (r.start, r.end, r.value)).collect();
+            assert_eq!(actual, expected, "{}", aggregation);
+            assert_eq!(stats.merged, 0);
+        }
+    }
+
+    #[test]
+    fn test_dense_run_across_chain_gap() {
+        use crate::core::{parse_chain_bytes, ChainIndex, ChromStyle};
+        
+        // Source 50-60 falls in a chain gap
+        let chain = parse_chain_bytes(b"chain 1000 chr1 1000 + 0 100 chr1 1000 + 0 100 1\n50\t10\t10\n40\n").unwrap();
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+        // One run of 30 bases (40-70) with the same value, then a base after the last block
+        let data = format!("fixedStep chrom=chr1 start=41 step=1\n{}", "5\n".repeat(30));
+        let data = format!("{}fixedStep chrom=chr1 start=100 step=1\n7\n7\n", data);
+        let points: Vec<WigDataPoint> = WigReader::new(std::io::Cursor::new(data)).map(|p| p.unwrap()).collect();
+        assert_eq!(points.len(), 2);
+        
+        let mut stats = ConversionStats::default();
+        let (merged, unmapped) = lift_and_merge_points(points, &mapper, WigAggregation::First, &mut stats);
+        assert_eq!((stats.total, stats.success, stats.failed), (32, 21, 11));
+        let lifted: Vec<(u64, u64, f64)> = merged.iter().map(|r| (r.start, r.end, r.value)).collect();
+        assert_eq!(lifted, vec![(40, 50, 5.0), (60, 70, 5.0), (99, 100, 7.0)]);
+        // Unmapped bases are reported one per base, as without run encoding
+        let failed: Vec<(u64, u64)> = unmapped.iter().map(|r| (r.start, r.end)).collect();
+        let mut expected: Vec<(u64, u64)> = (50..60).map(|b| (b, b + 1)).collect();
+        expected.push((100, 101));
+        assert_eq!(failed, expected);
+    }
+
+    #[test]
+    fn test_wig_reader_by_chromosome() {
+        let data = "variableStep chrom=chr1\n100\t1.0\n200\t2.0\nvariableStep chrom=chr2\n300\t3.0\n";
+        let reader = WigReader::new(std::io::Cursor::new(data));
+        let groups: Vec<(String, Vec<WigDataPoint>)> = reader.by_chromosome().collect();
+        
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].0, "chr1");
+        assert_eq!(groups[0].1.len(), 2);
+        assert_eq!(groups[1].0, "chr2");
+        assert_eq!(groups[1].1.len(), 1);
+        assert_eq!(groups[1].1[0].start, 299);
+    }
+
+    #[test]
+    fn test_merge_bedgraph_negative_values() {
+        let records = vec![
+            BedGraphRecord { chrom: "chr1".to_string(), start: 0, end: 10, value: -1.5 },
+            BedGraphRecord { chrom: "chr1".to_string(), start: 10, end: 20, value: -1.5 },
+            BedGraphRecord { chrom: "chr1".to_string(), start: 20, end: 30, value: 1.5 },
+        ];
+        let merged = merge_bedgraph_records(records, WigAggregation::First);
+        
+        assert_eq!(merged.len(), 2);
+        assert_eq!((merged[0].start, merged[0].end, merged[0].value), (0, 20, -1.5));
+        assert_eq!((merged[1].start, merged[1].end, merged[1].value), (20, 30, 1.5));
+    }
+    
+    #[test]
+    fn test_merge_bedgraph_unsorted_input() {
+        let records = vec![
+            BedGraphRecord { chrom: "chr1".to_string(), start: 20, end: 30, value: 2.0 },
+            BedGraphRecord { chrom: "chr1".to_string(), start: 0, end: 10, value: 2.0 },
+            BedGraphRecord { chrom: "chr1".to_string(), start: 10, end: 20, value: 2.0 },
+        ];
+        let merged = merge_bedgraph_records(records, WigAggregation::First);
+        
+        assert_eq!(merged.len(), 1);
+        assert_eq!((merged[0].start, merged[0].end), (0, 30));
+    }
 }
@@ -24,6 +24,7 @@
 //! let result = mapper.map("chr1", 1000, 2000, Strand::Plus);
 //! ```
 
+pub mod batch;
 pub mod core;
 pub mod formats;
 
@@ -3,10 +3,17 @@
 //! High-performance genome coordinate liftover tool compatible with CrossMap.
 
 use clap::{Parser, Subcommand, ValueEnum};
-use fast_crossmap::core::{ChainIndex, CoordinateMapper, ChromStyle, CompatMode};
-use fast_crossmap::formats;
-use std::path::PathBuf;
+use fast_crossmap::core::{
+    compressed_output_path, create_output, is_stdio, unmap_path_for, parse_chain_file_unchecked, parse_chain_file_with_progress, resolve_threads, ChainIndex, CoordinateMapper, ChromStyle, CompatMode,
+    COVERAGE_HISTOGRAM_BINS,
+};
+use fast_crossmap::batch::BatchConfig;
+use fast_crossmap::formats::{self, GenomicFormat};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
+use tracing::{debug, info, warn};
+use tracing_subscriber::EnvFilter;
 
 /// Compatibility mode for CrossMap behavior (CLI enum)
 #[derive(Clone, Copy, Debug, Default, ValueEnum)]
@@ -39,10 +46,34 @@ struct Cli {
     #[arg(long = "compat-mode", global = true, default_value = "improved")]
     compat_mode: CompatModeArg,
     
+    /// Log format for diagnostic messages (filter with RUST_LOG, e.g. fast_crossmap=debug)
+    #[arg(long = "log-format", global = true, default_value = "text")]
+    log_format: LogFormatArg,
+    
+    /// Swap source and target of every chain, mapping target coordinates back to the source assembly
+    #[arg(long, global = true)]
+    inverse: bool,
+    
+    /// Ignore chain blocks whose chain score is below this value
+    #[arg(long = "min-chain-score", global = true, value_name = "SCORE")]
+    min_chain_score: Option<u64>,
+    
     #[command(subcommand)]
     command: Commands,
 }
 
+/// Diagnostic log output format (CLI enum)
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum LogFormatArg {
+    /// Human-readable lines on stderr
+    #[default]
+    #[value(name = "text")]
+    Text,
+    /// One JSON object per event on stderr
+    #[value(name = "json")]
+    Json,
+}
+
 #[derive(Clone, Copy, ValueEnum)]
 enum ChromStyleArg {
     /// Keep chromosome names as-is
@@ -66,19 +97,251 @@ impl From<ChromStyleArg> for ChromStyle {
     }
 }
 
+/// Aggregation of overlapping Wiggle records (CLI enum)
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum WigAggregationArg {
+    /// Keep overlapping records with different values as they are
+    #[default]
+    #[value(name = "first")]
+    First,
+    /// Value of the last overlapping record
+    #[value(name = "last")]
+    Last,
+    /// Length-weighted mean of overlapping values
+    #[value(name = "mean")]
+    Mean,
+    /// Maximum of overlapping values
+    #[value(name = "max")]
+    Max,
+    /// Minimum of overlapping values
+    #[value(name = "min")]
+    Min,
+    /// Sum of overlapping values
+    #[value(name = "sum")]
+    Sum,
+}
+
+/// Handling of Wiggle points past the chromosome end (CLI enum)
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum WigErrorModeArg {
+    /// Warn and convert the point
+    #[default]
+    #[value(name = "warn")]
+    Warn,
+    /// Count the point as failed
+    #[value(name = "error")]
+    Error,
+}
+
+impl From<WigErrorModeArg> for formats::WigErrorMode {
+    fn from(arg: WigErrorModeArg) -> Self {
+        match arg {
+            WigErrorModeArg::Warn => formats::WigErrorMode::Warn,
+            WigErrorModeArg::Error => formats::WigErrorMode::Error,
+        }
+    }
+}
+
+impl From<WigAggregationArg> for formats::WigAggregation {
+    fn from(arg: WigAggregationArg) -> Self {
+        match arg {
+            WigAggregationArg::First => formats::WigAggregation::First,
+            WigAggregationArg::Last => formats::WigAggregation::Last,
+            WigAggregationArg::Mean => formats::WigAggregation::Mean,
+            WigAggregationArg::Max => formats::WigAggregation::Max,
+            WigAggregationArg::Min => formats::WigAggregation::Min,
+            WigAggregationArg::Sum => formats::WigAggregation::Sum,
+        }
+    }
+}
+
+/// GFF version pragma handling (CLI enum)
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum GffVersionArg {
+    /// Write ##gff-version 2
+    #[value(name = "2")]
+    V2,
+    /// Write ##gff-version 3
+    #[value(name = "3")]
+    V3,
+    /// Keep the input pragma, correcting it for GFF3-style attributes
+    #[default]
+    #[value(name = "auto")]
+    Auto,
+}
+
+impl From<GffVersionArg> for formats::GffVersion {
+    fn from(arg: GffVersionArg) -> Self {
+        match arg {
+            GffVersionArg::V2 => formats::GffVersion::V2,
+            GffVersionArg::V3 => formats::GffVersion::V3,
+            GffVersionArg::Auto => formats::GffVersion::Auto,
+        }
+    }
+}
+
+/// Zero-length BED records (CLI enum)
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum ZeroLengthPolicyArg {
+    /// Write the record to the unmap file
+    #[value(name = "fail")]
+    Fail,
+    /// Log a warning and convert the record as-is
+    #[default]
+    #[value(name = "warn")]
+    Warn,
+    /// Drop the record
+    #[value(name = "skip")]
+    Skip,
+    /// Convert as a 1-bp interval
+    #[value(name = "expand")]
+    Expand,
+}
+
+impl From<ZeroLengthPolicyArg> for formats::ZeroLengthPolicy {
+    fn from(arg: ZeroLengthPolicyArg) -> Self {
+        match arg {
+            ZeroLengthPolicyArg::Fail => formats::ZeroLengthPolicy::Fail,
+            ZeroLengthPolicyArg::Warn => formats::ZeroLengthPolicy::Warn,
+            ZeroLengthPolicyArg::Skip => formats::ZeroLengthPolicy::Skip,
+            ZeroLengthPolicyArg::Expand => formats::ZeroLengthPolicy::Expand,
+        }
+    }
+}
+
+/// Breakend records with an unliftable mate (CLI enum)
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum BndUnmapPolicyArg {
+    /// Write the record to the unmap file
+    #[default]
+    #[value(name = "fail")]
+    Fail,
+    /// Lift POS, keep source mate coordinates and flag INFO with OriginalBndCoords
+    #[value(name = "keep")]
+    Keep,
+    /// Lift POS and rewrite ALT as a single breakend without mate
+    #[value(name = "single")]
+    Single,
+}
+
+/// Mate orientation of a proper pair (CLI enum)
+#[cfg(feature = "bam")]
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum PairOrientationArg {
+    /// Leftmost mate forward, rightmost mate reverse
+    #[default]
+    #[value(name = "fr")]
+    Fr,
+    /// Leftmost mate reverse, rightmost mate forward
+    #[value(name = "rf")]
+    Rf,
+    /// Both mates on the same strand
+    #[value(name = "ff")]
+    Ff,
+}
+
+#[cfg(feature = "bam")]
+impl From<PairOrientationArg> for formats::PairOrientation {
+    fn from(arg: PairOrientationArg) -> Self {
+        match arg {
+            PairOrientationArg::Fr => formats::PairOrientation::Fr,
+            PairOrientationArg::Rf => formats::PairOrientation::Rf,
+            PairOrientationArg::Ff => formats::PairOrientation::Ff,
+        }
+    }
+}
+
+/// Coordinate convention of BED input (CLI enum)
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+enum CoordinateSystemArg {
+    /// 0-based, half-open (standard BED)
+    #[default]
+    #[value(name = "0")]
+    ZeroBased,
+    /// 1-based, closed
+    #[value(name = "1")]
+    OneBased,
+}
+
+impl From<CoordinateSystemArg> for formats::CoordinateSystem {
+    fn from(arg: CoordinateSystemArg) -> Self {
+        match arg {
+            CoordinateSystemArg::ZeroBased => formats::CoordinateSystem::ZeroBased,
+            CoordinateSystemArg::OneBased => formats::CoordinateSystem::OneBased,
+        }
+    }
+}
+
+impl From<BndUnmapPolicyArg> for formats::BndUnmapPolicy {
+    fn from(arg: BndUnmapPolicyArg) -> Self {
+        match arg {
+            BndUnmapPolicyArg::Fail => formats::BndUnmapPolicy::FailRecord,
+            BndUnmapPolicyArg::Keep => formats::BndUnmapPolicy::KeepOriginalCoords,
+            BndUnmapPolicyArg::Single => formats::BndUnmapPolicy::EmitUnmapped,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Convert BED format file
     Bed {
         /// Chain file for coordinate conversion
         chain: PathBuf,
-        /// Input BED file
-        input: PathBuf,
-        /// Output file (optional, stdout if not specified)
-        output: Option<PathBuf>,
-        /// Number of threads (default: number of CPUs)
-        #[arg(short = 't', long, default_value = "1")]
+        /// Input BED file(s) ('-' for stdin), then the output file ('-' for stdout) unless --output-dir is given
+        #[arg(required = true, value_name = "INPUT... [OUTPUT]")]
+        paths: Vec<PathBuf>,
+        /// Write one output per input into this directory as <input_stem>.bed
+        #[arg(long = "output-dir")]
+        output_dir: Option<PathBuf>,
+        /// Number of threads (0 = number of CPUs, up to 16)
+        #[arg(short = 't', long, default_value = "0")]
         threads: usize,
+        /// Fail on integer scores outside 0-1000 (float scores are passed through)
+        #[arg(long = "strict-score")]
+        strict_score: bool,
+        /// With --threads > 1, write per-thread part files and concatenate them at the end
+        /// (lower peak memory for very large inputs)
+        #[arg(long = "per-thread-output")]
+        per_thread_output: bool,
+        /// With --threads > 1, stream chunks through bounded channels instead of reading the whole
+        /// input first (bounded memory, also parallelizes stdin)
+        #[arg(long)]
+        streaming: bool,
+        /// With --threads > 1, number of lines per parallel work item
+        #[arg(long = "chunk-size", default_value_t = formats::bed::DEFAULT_CHUNK_SIZE)]
+        chunk_size: usize,
+        /// Zero-length records (start == end): fail, warn, skip, expand (to 1 bp)
+        #[arg(long = "zero-length", value_enum, default_value = "warn")]
+        zero_length: ZeroLengthPolicyArg,
+        /// Coordinate convention of the input start column: 0 (BED, half-open) or 1 (1-based,
+        /// closed); output uses the same convention
+        #[arg(long = "coord-system", value_enum, default_value = "0")]
+        coord_system: CoordinateSystemArg,
+        /// Warn about records whose name is longer than N characters (the UCSC browser limit is 255)
+        #[arg(long = "max-name-length", value_name = "N")]
+        max_name_length: Option<usize>,
+        /// With --max-name-length, also truncate such names to N characters ending in '...'
+        #[arg(long = "truncate-names", requires = "max_name_length")]
+        truncate_names: bool,
+        /// Write the parse error and byte offset as a '#' comment above each invalid line in the
+        /// unmap file (runs single-threaded)
+        #[arg(long = "verbose-errors")]
+        verbose_errors: bool,
+        /// Pad or truncate every converted record to exactly N columns (missing name/strand
+        /// become '.', score/thickStart/thickEnd/itemRgb '0', BED12 blocks one full-length block)
+        #[arg(long = "normalize-fields", value_name = "N")]
+        normalize_fields: Option<usize>,
+        /// Write a self-contained HTML report (statistics, failure reasons, chain metadata)
+        #[arg(long, value_name = "PATH", conflicts_with = "output_dir")]
+        report: Option<PathBuf>,
+        /// Write records that map to more than one location to this file instead of the main output
+        #[arg(long = "multimap-output", value_name = "PATH", conflicts_with = "output_dir")]
+        multimap_output: Option<PathBuf>,
+        /// BGZF-compress the output (appends .gz to the output name) and index it with tabix if
+        /// tabix is on PATH
+        #[arg(long = "output-compress")]
+        output_compress: bool,
         /// Chromosome ID style: a(as-is), s(short), l(long)
         #[arg(long = "chromid", default_value = "a")]
         chrom_style: ChromStyleArg,
@@ -87,18 +350,62 @@ enum Commands {
     Vcf {
         /// Chain file for coordinate conversion
         chain: PathBuf,
-        /// Input VCF file
-        input: PathBuf,
-        /// Target reference genome FASTA file (required for proper REF allele update)
-        refgenome: PathBuf,
-        /// Output file (optional, stdout if not specified)
-        output: Option<PathBuf>,
-        /// Number of threads (default: number of CPUs)
-        #[arg(short = 't', long, default_value = "1")]
+        /// Input VCF file(s) ('-' for stdin), the target reference genome FASTA (required for proper
+        /// REF allele update), then the output file ('-' for stdout) unless --output-dir is given
+        #[arg(required = true, value_name = "INPUT... REFGENOME [OUTPUT]")]
+        paths: Vec<PathBuf>,
+        /// Write one output per input into this directory as <input_stem>.vcf
+        #[arg(long = "output-dir")]
+        output_dir: Option<PathBuf>,
+        /// Number of threads (0 = number of CPUs, up to 16)
+        #[arg(short = 't', long, default_value = "0")]
         threads: usize,
-        /// Don't filter variants where REF==ALT after liftover
+        /// Don't filter variants where REF==ALT after liftover (by default they go to the unmap
+        /// file as Fail(REF==ALT); strict mode writes them with an empty ALT like CrossMap)
         #[arg(long = "no-comp-allele")]
         no_comp_allele: bool,
+        /// Split multi-allelic records into biallelic records before liftover
+        #[arg(long = "split-multiallelic")]
+        split_multiallelic: bool,
+        /// Re-merge split records that map to the same position (requires --split-multiallelic)
+        #[arg(long = "merge-biallelic", requires = "split_multiallelic")]
+        merge_biallelic: bool,
+        /// Replace every FILTER value except '.' with PASS
+        #[arg(long = "reset-filter")]
+        reset_filter: bool,
+        /// Append this filter tag (e.g. LiftedOver) to the FILTER field of every lifted record
+        #[arg(long = "add-filter", value_name = "NAME")]
+        add_filter: Option<String>,
+        /// Don't write ##liftover* provenance headers
+        #[arg(long = "no-provenance-headers")]
+        no_provenance_headers: bool,
+        /// Accept records with only 5-7 columns, filling missing QUAL/FILTER/INFO with '.'
+        #[arg(long)]
+        lenient: bool,
+        /// Lift the mate positions in breakend ALTs along with POS (ignored with --compat-mode
+        /// strict)
+        #[arg(long = "lift-bnd-mates")]
+        lift_bnd_mates: bool,
+        /// With --lift-bnd-mates, breakend records whose mate cannot be lifted: fail, keep, single
+        #[arg(long = "bnd-unmap-policy", value_enum, default_value = "fail")]
+        bnd_unmap_policy: BndUnmapPolicyArg,
+        /// Only write these INFO keys to lifted records (comma-separated)
+        #[arg(long = "info-passthrough", value_name = "KEYS", value_delimiter = ',')]
+        info_passthrough: Option<Vec<String>>,
+        /// Drop these INFO keys from lifted records (comma-separated)
+        #[arg(long = "info-blacklist", value_name = "KEYS", value_delimiter = ',')]
+        info_blacklist: Option<Vec<String>>,
+        /// Send SVTYPE=INV records that map to a negative-strand block to the unmap file
+        #[arg(long = "inversion-strict")]
+        inversion_strict: bool,
+        /// Mirror CIPOS/CIEND for records lifted to a negative-strand block so they stay relative
+        /// to the new POS and END
+        #[arg(long = "update-ci-intervals")]
+        update_ci_intervals: bool,
+        /// BGZF-compress the output (appends .gz to the output name) and index it with tabix if
+        /// tabix is on PATH
+        #[arg(long = "output-compress")]
+        output_compress: bool,
         /// Chromosome ID style: a(as-is), s(short), l(long)
         #[arg(long = "chromid", default_value = "a")]
         chrom_style: ChromStyleArg,
@@ -107,13 +414,36 @@ enum Commands {
     Gff {
         /// Chain file for coordinate conversion
         chain: PathBuf,
-        /// Input GFF/GTF file
-        input: PathBuf,
-        /// Output file (optional, stdout if not specified)
-        output: Option<PathBuf>,
-        /// Number of threads (default: number of CPUs)
-        #[arg(short = 't', long, default_value = "1")]
+        /// Input GFF/GTF file(s) ('-' for stdin), then the output file ('-' for stdout) unless --output-dir is given
+        #[arg(required = true, value_name = "INPUT... [OUTPUT]")]
+        paths: Vec<PathBuf>,
+        /// Write one output per input into this directory as <input_stem>.gff
+        #[arg(long = "output-dir")]
+        output_dir: Option<PathBuf>,
+        /// Number of threads (0 = number of CPUs, up to 16)
+        #[arg(short = 't', long, default_value = "0")]
         threads: usize,
+        /// Rename an attribute key in lifted records, e.g. gene_id=ID (repeatable)
+        #[arg(long = "rename-attr", value_name = "OLD=NEW", value_parser = parse_rename_attr)]
+        rename_attr: Vec<(String, String)>,
+        /// ##gff-version pragma in the output: 2, 3, or auto (fix missing/wrong GFF3 pragmas)
+        #[arg(long = "gff-version", value_enum, default_value = "auto")]
+        gff_version: GffVersionArg,
+        /// Reject records with an empty seqid, start > end, a bad strand, or a bad CDS phase
+        /// (written to the unmap file tagged InvalidRecord)
+        #[arg(long = "strict-input")]
+        strict_input: bool,
+        /// Warn about feature types that are not Sequence Ontology terms
+        #[arg(long = "validate-so-terms")]
+        validate_so_terms: bool,
+        /// Lift features that map to more than one location into this file (one line per location)
+        /// instead of the unmap file
+        #[arg(long = "multimap-output", value_name = "PATH", conflicts_with = "output_dir")]
+        multimap_output: Option<PathBuf>,
+        /// Write a CDS split by insertions in the target as one line per fragment, with
+        /// recalculated phase (ignored with --compat-mode strict)
+        #[arg(long = "split-cds")]
+        split_cds: bool,
         /// Chromosome ID style: a(as-is), s(short), l(long)
         #[arg(long = "chromid", default_value = "a")]
         chrom_style: ChromStyleArg,
@@ -122,17 +452,24 @@ enum Commands {
     Gvcf {
         /// Chain file for coordinate conversion
         chain: PathBuf,
-        /// Input GVCF file
-        input: PathBuf,
-        /// Target reference genome FASTA file (required for proper REF allele update)
-        refgenome: PathBuf,
-        /// Output file (optional, stdout if not specified)
-        output: Option<PathBuf>,
+        /// Input GVCF file(s) ('-' for stdin), the target reference genome FASTA (plain or bgzip, required
+        /// for proper REF allele update), then the output file ('-' for stdout) unless --output-dir is given
+        #[arg(required = true, value_name = "INPUT... REFGENOME [OUTPUT]")]
+        paths: Vec<PathBuf>,
+        /// Write one output per input into this directory as <input_stem>.gvcf
+        #[arg(long = "output-dir")]
+        output_dir: Option<PathBuf>,
         /// Don't filter variants where REF==ALT after liftover
         #[arg(long = "no-comp-allele")]
         no_comp_allele: bool,
-        /// Number of threads (default: number of CPUs)
-        #[arg(short = 't', long, default_value = "1")]
+        /// Send non-variant blocks with GQ below this value to the unmap file
+        #[arg(long = "min-gq")]
+        min_gq: Option<u32>,
+        /// Don't write ##liftover* provenance headers
+        #[arg(long = "no-provenance")]
+        no_provenance: bool,
+        /// Number of threads (0 = number of CPUs, up to 16)
+        #[arg(short = 't', long, default_value = "0")]
         threads: usize,
         /// Chromosome ID style: a(as-is), s(short), l(long)
         #[arg(long = "chromid", default_value = "a")]
@@ -142,15 +479,16 @@ enum Commands {
     Maf {
         /// Chain file for coordinate conversion
         chain: PathBuf,
-        /// Input MAF file
-        input: PathBuf,
-        /// Target reference genome FASTA file (required for proper REF allele update)
-        refgenome: PathBuf,
+        /// Input MAF file(s) ('-' for stdin), the target reference genome FASTA (required for proper
+        /// REF allele update), then the output file ('-' for stdout) unless --output-dir is given
+        #[arg(required = true, value_name = "INPUT... REFGENOME [OUTPUT]")]
+        paths: Vec<PathBuf>,
+        /// Write one output per input into this directory as <input_stem>.maf
+        #[arg(long = "output-dir")]
+        output_dir: Option<PathBuf>,
         /// Target genome build name (e.g., GRCh38)
         #[arg(short = 'b', long)]
         build: String,
-        /// Output file (optional, stdout if not specified)
-        output: Option<PathBuf>,
         /// Chromosome ID style: a(as-is), s(short), l(long)
         #[arg(long = "chromid", default_value = "a")]
         chrom_style: ChromStyleArg,
@@ -159,10 +497,24 @@ enum Commands {
     Wig {
         /// Chain file for coordinate conversion
         chain: PathBuf,
-        /// Input Wiggle/bedGraph file
-        input: PathBuf,
-        /// Output file (optional, stdout if not specified)
-        output: Option<PathBuf>,
+        /// Input Wiggle/bedGraph file(s) ('-' for stdin), then the output file ('-' for stdout) unless --output-dir is given
+        #[arg(required = true, value_name = "INPUT... [OUTPUT]")]
+        paths: Vec<PathBuf>,
+        /// Write one output per input into this directory as <input_stem>.wig
+        #[arg(long = "output-dir")]
+        output_dir: Option<PathBuf>,
+        /// Number of threads (chromosomes are processed in parallel)
+        #[arg(short = 't', long, default_value = "1")]
+        threads: usize,
+        /// How values of records lifted onto overlapping regions are combined
+        #[arg(long, value_enum, default_value = "first")]
+        aggregation: WigAggregationArg,
+        /// Start the output with a UCSC track line naming the output and source file
+        #[arg(long = "add-track-header")]
+        add_track_header: bool,
+        /// Data points reaching past the source chromosome end: warn, error (count as failed)
+        #[arg(long = "error-mode", value_enum, default_value = "warn")]
+        error_mode: WigErrorModeArg,
         /// Chromosome ID style: a(as-is), s(short), l(long)
         #[arg(long = "chromid", default_value = "a")]
         chrom_style: ChromStyleArg,
@@ -172,13 +524,31 @@ enum Commands {
     Bam {
         /// Chain file for coordinate conversion
         chain: PathBuf,
-        /// Input BAM/SAM/CRAM file
-        input: PathBuf,
-        /// Output BAM file
-        output: PathBuf,
+        /// Input BAM/SAM/CRAM file(s) ('-' for stdin), then the output BAM file ('-' for stdout)
+        /// unless --output-dir is given
+        #[arg(required = true, value_name = "INPUT... [OUTPUT]")]
+        paths: Vec<PathBuf>,
+        /// Write one output per input into this directory as <input_stem>.bam
+        #[arg(long = "output-dir")]
+        output_dir: Option<PathBuf>,
         /// Number of threads for parallel I/O
         #[arg(short = 't', long, default_value = "1")]
         threads: usize,
+        /// Write CRAM output (regardless of output file extension)
+        #[arg(long = "output-cram", requires = "refgenome")]
+        output_cram: bool,
+        /// Target reference genome FASTA (required for CRAM output)
+        #[arg(long = "refgenome")]
+        refgenome: Option<PathBuf>,
+        /// Copy unmapped reads unchanged to <output>.unmapped.bam
+        #[arg(long = "passthrough-unmapped")]
+        passthrough_unmapped: bool,
+        /// Largest mate distance of a proper pair after liftover (bp)
+        #[arg(long = "max-insert-size", default_value_t = formats::bam::DEFAULT_MAX_INSERT_SIZE)]
+        max_insert_size: u64,
+        /// Mate orientation of a proper pair: fr, rf, ff
+        #[arg(long = "pair-orientation", value_enum, default_value = "fr")]
+        pair_orientation: PairOrientationArg,
         /// Chromosome ID style: a(as-is), s(short), l(long)
         #[arg(long = "chromid", default_value = "a")]
         chrom_style: ChromStyleArg,
@@ -187,13 +557,25 @@ enum Commands {
     Region {
         /// Chain file for coordinate conversion
         chain: PathBuf,
-        /// Input BED file with regions
-        input: PathBuf,
-        /// Output file (optional, stdout if not specified)
-        output: Option<PathBuf>,
+        /// Input BED file with regions(s) ('-' for stdin), then the output file ('-' for stdout) unless --output-dir is given
+        #[arg(required = true, value_name = "INPUT... [OUTPUT]")]
+        paths: Vec<PathBuf>,
+        /// Write one output per input into this directory as <input_stem>.bed
+        #[arg(long = "output-dir")]
+        output_dir: Option<PathBuf>,
         /// Minimum mapping ratio (default: 0.85)
         #[arg(short = 'r', long, default_value = "0.85")]
         ratio: f64,
+        /// Also write each fragment of a split region as its own record, tagged #fragment=N/M
+        #[arg(long = "emit-fragments")]
+        emit_fragments: bool,
+        /// Write fragment records to this file instead of the main output
+        #[arg(long = "fragments-output", requires = "emit_fragments")]
+        fragments_output: Option<PathBuf>,
+        /// Also write map_ratio= for Unmap and CrossChroms records in the unmap file (CrossChroms
+        /// use the largest fragment); mapped and LowRatio records always include it
+        #[arg(long = "emit-ratio")]
+        emit_ratio: bool,
         /// Chromosome ID style: a(as-is), s(short), l(long)
         #[arg(long = "chromid", default_value = "a")]
         chrom_style: ChromStyleArg,
@@ -202,187 +584,853 @@ enum Commands {
     Bigwig {
         /// Chain file for coordinate conversion
         chain: PathBuf,
-        /// Input BigWig file
+        /// Input BigWig file(s), then the output file prefix (will create .bgr file) unless
+        /// --output-dir is given
+        #[arg(required = true, value_name = "INPUT... [OUTPUT]")]
+        paths: Vec<PathBuf>,
+        /// Write one output per input into this directory, prefixed with <input_stem>
+        #[arg(long = "output-dir")]
+        output_dir: Option<PathBuf>,
+        /// How values of intervals lifted onto overlapping target regions are combined
+        #[arg(long, value_enum, default_value = "first")]
+        aggregation: WigAggregationArg,
+        /// Chromosome ID style: a(as-is), s(short), l(long)
+        #[arg(long = "chromid", default_value = "a")]
+        chrom_style: ChromStyleArg,
+    },
+    /// Convert a file, detecting its format from the extension or content
+    Convert {
+        /// Chain file for coordinate conversion
+        chain: PathBuf,
+        /// Input file (BED, VCF, GVCF, GFF/GTF, Wiggle, BigWig, MAF, BAM)
         input: PathBuf,
-        /// Output file prefix (will create .bgr file)
+        /// Output file (optional, a format-specific default if not specified)
         output: Option<PathBuf>,
+        /// Target reference genome FASTA file (required for VCF, GVCF and MAF)
+        #[arg(long = "refgenome")]
+        refgenome: Option<PathBuf>,
+        /// Target genome build name (required for MAF)
+        #[arg(short = 'b', long)]
+        build: Option<String>,
+        /// Number of threads (0 = number of CPUs, up to 16)
+        #[arg(short = 't', long, default_value = "0")]
+        threads: usize,
+        /// Chromosome ID style: a(as-is), s(short), l(long)
+        #[arg(long = "chromid", default_value = "a")]
+        chrom_style: ChromStyleArg,
+    },
+    /// Run the conversions listed in a TOML config with one chain load
+    Batch {
+        /// Chain file for coordinate conversion
+        chain: PathBuf,
+        /// TOML file with [[convert]] entries (type, input, output, optional refgenome)
+        config: PathBuf,
+        /// Run the conversions concurrently
+        #[arg(long)]
+        parallel: bool,
         /// Chromosome ID style: a(as-is), s(short), l(long)
         #[arg(long = "chromid", default_value = "a")]
         chrom_style: ChromStyleArg,
     },
+    /// Show chain file statistics
+    #[command(alias = "chain-stats")]
+    Stats {
+        /// Chain file to inspect
+        chain: PathBuf,
+        /// Print per-chromosome coverage statistics as a TSV table
+        #[arg(long = "per-chrom")]
+        per_chrom: bool,
+        /// Print the summary as a JSON object
+        #[arg(long, conflicts_with = "per_chrom")]
+        json: bool,
+        /// Print chain score and block size histograms with this many bins
+        #[arg(long, num_args = 0..=1, default_missing_value = "10", conflicts_with = "json")]
+        histogram: Option<usize>,
+    },
+    /// Summarize a chain file: blocks, chromosomes, covered bases and scores
+    Summary {
+        /// Chain file to summarize
+        chain: PathBuf,
+        /// Print the summary as a JSON object
+        #[arg(long)]
+        json: bool,
+    },
+    /// Draw chain block coverage of a chromosome as an ASCII bar
+    Visualize {
+        /// Chain file to inspect
+        chain: PathBuf,
+        /// Chromosome to draw
+        chrom: String,
+        /// Number of bins (characters) in the bar
+        #[arg(short = 'w', long, default_value = "80")]
+        width: usize,
+        /// Treat the chromosome as a target (new assembly) chromosome
+        #[arg(long)]
+        target: bool,
+    },
+    /// Write the chain blocks of a chromosome as BED3 records
+    ChainToBed {
+        /// Chain file to inspect
+        chain: PathBuf,
+        /// Chromosome whose blocks to write
+        chrom: String,
+        /// Treat the chromosome as a target (new assembly) chromosome
+        #[arg(long)]
+        target: bool,
+        /// Output BED file ("-" for stdout)
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+    /// Check a chain file for structural problems and list all of them with line numbers
+    ValidateChain {
+        /// Chain file to check
+        chain: PathBuf,
+    },
 }
 
 
-fn load_chain(chain_path: &PathBuf, chrom_style: ChromStyleArg, compat_mode: CompatModeArg) -> anyhow::Result<CoordinateMapper> {
+/// Global options that affect how every subcommand loads its chain file
+#[derive(Clone, Copy)]
+struct ChainLoadArgs {
+    compat_mode: CompatModeArg,
+    inverse: bool,
+    min_chain_score: Option<u64>,
+}
+
+impl ChainLoadArgs {
+    /// Wrap the loaded index in a mapper, first dropping blocks below
+    /// `--min-chain-score` and inverting it when `--inverse` is set
+    fn mapper(self, index: ChainIndex, chrom_style: ChromStyleArg) -> CoordinateMapper {
+        let index = match self.min_chain_score {
+            Some(min_score) => {
+                let filtered = index.filter_by_score(min_score);
+                info!(
+                    "Kept {} of {} alignment blocks with chain score >= {}",
+                    filtered.total_intervals(), index.total_intervals(), min_score
+                );
+                filtered
+            }
+            None => index,
+        };
+        let index = if self.inverse { index.into_inverse() } else { index };
+        CoordinateMapper::with_compat_mode(index, chrom_style.into(), self.compat_mode.into())
+    }
+}
+
+fn load_chain(chain_path: &PathBuf, chrom_style: ChromStyleArg, load_args: ChainLoadArgs) -> anyhow::Result<CoordinateMapper> {
     let start = Instant::now();
-    eprintln!("Loading chain file: {:?}", chain_path);
     
-    let index = ChainIndex::from_chain_file(chain_path)
+    #[cfg(feature = "serde")]
+    if let Some(index) = load_chain_cache(chain_path) {
+        info!("Loaded {} alignment blocks from cache in {:.2}s", index.total_intervals(), start.elapsed().as_secs_f64());
+        return Ok(load_args.mapper(index, chrom_style));
+    }
+    
+    info!("Loading chain file: {:?}", chain_path);
+    
+    let chain_file = parse_chain_file_with_progress(chain_path, |lines| debug!("Read {} chain lines", lines))
         .map_err(|e| anyhow::anyhow!("Failed to load chain file: {}", e))?;
+    let block_count = chain_file.blocks.len();
+    let chrom_count = chain_file.source_chrom_sizes.len();
+    let index = ChainIndex::from_chain_data(chain_file);
     
-    let mapper = CoordinateMapper::with_compat_mode(index, chrom_style.into(), compat_mode.into());
-    eprintln!("Chain file loaded in {:.2}s", start.elapsed().as_secs_f64());
+    let mapper = load_args.mapper(index, chrom_style);
+    if load_args.inverse {
+        info!("Inverted chain file: mapping target coordinates to the source assembly");
+    }
+    info!(
+        "Loaded {} alignment blocks from {} chromosomes in {:.2}s",
+        block_count,
+        chrom_count,
+        start.elapsed().as_secs_f64()
+    );
     
     Ok(mapper)
 }
 
+/// Load the `<chain>.cache` sidecar written by `ChainIndex::save_to_file`
+/// 
+/// Ignored when missing, older than the chain file, or unreadable.
+#[cfg(feature = "serde")]
+fn load_chain_cache(chain_path: &Path) -> Option<ChainIndex> {
+    let mut cache_path = chain_path.as_os_str().to_owned();
+    cache_path.push(".cache");
+    let cache_path = PathBuf::from(cache_path);
+    
+    let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    let cache_time = modified(&cache_path)?;
+    if modified(chain_path).is_some_and(|chain_time| chain_time > cache_time) {
+        tracing::warn!("Ignoring chain cache {:?}: older than the chain file", cache_path);
+        return None;
+    }
+    
+    match ChainIndex::load_from_file(&cache_path) {
+        Ok(index) => {
+            eprintln!("Using chain index cache {}", cache_path.display());
+            Some(index)
+        }
+        Err(e) => {
+            tracing::warn!("Ignoring chain cache {:?}: {}", cache_path, e);
+            None
+        }
+    }
+}
+
+/// Parse an `OLD=NEW` attribute rename for `--rename-attr`
+fn parse_rename_attr(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((old, new)) if !old.is_empty() && !new.is_empty() => Ok((old.to_string(), new.to_string())),
+        _ => Err(format!("expected OLD=NEW, got '{}'", s)),
+    }
+}
+
+/// Output name for an input converted into `--output-dir`
+///
+/// Uses the input file stem (ignoring a `.gz` suffix) plus `ext`; an empty
+/// `ext` gives a bare prefix for converters that add their own extension.
+fn batch_output_name(input: &Path, ext: &str) -> PathBuf {
+    let name = input.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let name = name.strip_suffix(".gz").unwrap_or(&name);
+    let stem = Path::new(name).file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    
+    if ext.is_empty() {
+        PathBuf::from(stem)
+    } else {
+        PathBuf::from(format!("{}.{}", stem, ext))
+    }
+}
+
+/// Turn positional paths into (input, output) pairs
+///
+/// Without `--output-dir` the positionals are `<input> [output]`; with it,
+/// every positional is an input written to `<output_dir>/<input_stem>.<ext>`.
+fn plan_jobs(
+    mut paths: Vec<PathBuf>,
+    output_dir: Option<PathBuf>,
+    ext: &str,
+    default_output: &str,
+) -> anyhow::Result<Vec<(PathBuf, PathBuf)>> {
+    if let Some(dir) = output_dir {
+        std::fs::create_dir_all(&dir)?;
+        return Ok(paths
+            .into_iter()
+            .map(|input| {
+                let output = dir.join(batch_output_name(&input, ext));
+                (input, output)
+            })
+            .collect());
+    }
+    
+    match paths.len() {
+        1 => Ok(vec![(paths.remove(0), PathBuf::from(default_output))]),
+        2 => {
+            let output = paths.pop().unwrap();
+            Ok(vec![(paths.remove(0), output)])
+        }
+        _ => anyhow::bail!("Multiple input files require --output-dir"),
+    }
+}
+
+/// Remove the reference genome from `<input>... <refgenome> [output]` positionals
+fn take_refgenome(paths: &mut Vec<PathBuf>, batch: bool) -> anyhow::Result<PathBuf> {
+    if paths.len() < 2 {
+        anyhow::bail!("Missing reference genome FASTA argument");
+    }
+    Ok(if batch { paths.pop().unwrap() } else { paths.remove(1) })
+}
+
+/// Per-file record counts for a batch run
+#[derive(Default)]
+struct BatchSummary {
+    files: Vec<(PathBuf, usize, usize, usize)>,
+}
+
+impl BatchSummary {
+    fn add(&mut self, input: &Path, total: usize, success: usize, failed: usize) {
+        self.files.push((input.to_path_buf(), total, success, failed));
+    }
+    
+    /// Log per-file and combined counts (only when more than one file was converted)
+    fn report(&self) {
+        if self.files.len() < 2 {
+            return;
+        }
+        
+        info!("=== Batch Statistics ===");
+        let (mut total, mut success, mut failed) = (0, 0, 0);
+        for (input, t, s, f) in &self.files {
+            info!("{:?}: total={} success={} failed={}", input, t, s, f);
+            total += t;
+            success += s;
+            failed += f;
+        }
+        info!("Files:           {}", self.files.len());
+        info!("Total records:   {}", total);
+        info!("Successful:      {}", success);
+        info!("Failed:          {}", failed);
+    }
+}
+
+/// Map an auto-detected input format onto the matching format-specific subcommand
+fn resolve_convert(
+    chain: PathBuf,
+    input: PathBuf,
+    output: Option<PathBuf>,
+    refgenome: Option<PathBuf>,
+    build: Option<String>,
+    threads: usize,
+    chrom_style: ChromStyleArg,
+) -> anyhow::Result<Commands> {
+    let format = formats::detect_format(&input)?;
+    info!("Detected input format: {}", format);
+    
+    let require_ref = |refgenome: Option<PathBuf>| {
+        refgenome.ok_or_else(|| anyhow::anyhow!("{} input requires --refgenome", format))
+    };
+    // Positional paths in the order the format-specific subcommand expects
+    let paths = |refgenome: Option<PathBuf>| -> Vec<PathBuf> {
+        std::iter::once(input.clone()).chain(refgenome).chain(output.clone()).collect()
+    };
+    
+    let command = match format {
+        GenomicFormat::Bed => Commands::Bed {
+            chain, paths: paths(None), output_dir: None, threads,
+            strict_score: false, per_thread_output: false, streaming: false, chunk_size: formats::bed::DEFAULT_CHUNK_SIZE,
+            zero_length: ZeroLengthPolicyArg::default(), coord_system: CoordinateSystemArg::default(),
+            max_name_length: None, truncate_names: false, verbose_errors: false, normalize_fields: None,
+            report: None, multimap_output: None, output_compress: false, chrom_style,
+        },
+        GenomicFormat::Vcf => Commands::Vcf {
+            chain, paths: paths(Some(require_ref(refgenome)?)), output_dir: None, threads,
+            no_comp_allele: false, split_multiallelic: false, merge_biallelic: false,
+            reset_filter: false, add_filter: None, no_provenance_headers: false, lenient: false,
+            lift_bnd_mates: false, bnd_unmap_policy: BndUnmapPolicyArg::default(), info_passthrough: None, info_blacklist: None,
+            inversion_strict: false, update_ci_intervals: false, output_compress: false, chrom_style,
+        },
+        GenomicFormat::Gvcf => Commands::Gvcf {
+            chain, paths: paths(Some(require_ref(refgenome)?)), output_dir: None,
+            no_comp_allele: false, min_gq: None, no_provenance: false, threads, chrom_style,
+        },
+        GenomicFormat::Gff | GenomicFormat::Gtf => Commands::Gff {
+            chain, paths: paths(None), output_dir: None, threads, rename_attr: Vec::new(),
+            gff_version: GffVersionArg::default(), strict_input: false, validate_so_terms: false,
+            multimap_output: None, split_cds: false, chrom_style,
+        },
+        GenomicFormat::Wig => Commands::Wig {
+            chain, paths: paths(None), output_dir: None, threads,
+            aggregation: WigAggregationArg::default(), add_track_header: false,
+            error_mode: WigErrorModeArg::default(), chrom_style,
+        },
+        GenomicFormat::BigWig => Commands::Bigwig { chain, paths: paths(None), output_dir: None, aggregation: WigAggregationArg::default(), chrom_style },
+        GenomicFormat::Maf => Commands::Maf {
+            chain, paths: paths(Some(require_ref(refgenome)?)), output_dir: None,
+            build: build.ok_or_else(|| anyhow::anyhow!("MAF input requires --build"))?,
+            chrom_style,
+        },
+        #[cfg(feature = "bam")]
+        GenomicFormat::Bam => Commands::Bam {
+            chain, paths: paths(None), output_dir: None, threads,
+            output_cram: false, refgenome, passthrough_unmapped: false,
+            max_insert_size: formats::bam::DEFAULT_MAX_INSERT_SIZE, pair_orientation: PairOrientationArg::default(),
+            chrom_style,
+        },
+        #[cfg(not(feature = "bam"))]
+        GenomicFormat::Bam => anyhow::bail!("BAM support is not enabled in this build"),
+    };
+    
+    Ok(command)
+}
+
+/// Index a BGZF output with `tabix -p <preset>`, warning if tabix is missing
+/// or fails (e.g. on unsorted output)
+fn tabix_index(path: &Path, preset: &str) {
+    if is_stdio(path) {
+        return;
+    }
+    match std::process::Command::new("tabix").args(["-f", "-p", preset]).arg(path).status() {
+        Ok(status) if status.success() => info!("Indexed {:?} with tabix", path),
+        Ok(status) => warn!("tabix failed on {:?} ({}); output is not indexed", path, status),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            warn!("tabix not found on PATH; {:?} is not indexed", path)
+        }
+        Err(e) => warn!("Could not run tabix on {:?}: {}", path, e),
+    }
+}
+
+/// Install the tracing subscriber (INFO by default, overridable via RUST_LOG)
+fn init_tracing(log_format: LogFormatArg) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr);
+    
+    match log_format {
+        LogFormatArg::Text => builder.without_time().with_target(false).init(),
+        LogFormatArg::Json => builder.json().init(),
+    }
+}
+
 fn main() -> anyhow::Result<()> {
-    env_logger::init();
     let cli = Cli::parse();
+    init_tracing(cli.log_format);
     let start = Instant::now();
     
     // Log compatibility mode
     match cli.compat_mode {
-        CompatModeArg::Strict => eprintln!("Compatibility mode: strict (CrossMap-identical)"),
+        CompatModeArg::Strict => info!("Compatibility mode: strict (CrossMap-identical)"),
         CompatModeArg::Improved => {} // Don't log for default mode
     }
 
-    match cli.command {
-        Commands::Bed { chain, input, output, threads, chrom_style } => {
-            let mapper = load_chain(&chain, chrom_style, cli.compat_mode)?;
-            let output_path = output.unwrap_or_else(|| PathBuf::from("output.bed"));
-            let unmap_path = output_path.with_extension("bed.unmap");
-            
-            eprintln!("Converting BED file: {:?} -> {:?}", input, output_path);
-            let stats = formats::convert_bed(&input, &output_path, &unmap_path, &mapper, threads)?;
+    run(cli.command, ChainLoadArgs { compat_mode: cli.compat_mode, inverse: cli.inverse, min_chain_score: cli.min_chain_score }, start)
+}
+
+fn run(command: Commands, load_args: ChainLoadArgs, start: Instant) -> anyhow::Result<()> {
+    let mut batch = BatchSummary::default();
+    
+    match command {
+        Commands::Bed {
+            chain, paths, output_dir, threads, strict_score, per_thread_output, streaming, chunk_size, zero_length,
+            coord_system, max_name_length, truncate_names, verbose_errors, normalize_fields, report, multimap_output,
+            output_compress, chrom_style,
+        } => {
+            let jobs = plan_jobs(paths, output_dir, "bed", "output.bed")?;
+            let mapper = load_chain(&chain, chrom_style, load_args)?;
+            let options = formats::BedConvertOptions {
+                threads,
+                strict_score,
+                per_thread_output,
+                zero_length_policy: zero_length.into(),
+                verbose_errors,
+                chunk_size,
+                normalize_field_count: normalize_fields,
+                generate_report: report,
+                multimap_output,
+                streaming,
+                coord_system: coord_system.into(),
+                max_name_length,
+                truncate_names,
+                compress: output_compress,
+            };
             
-            eprintln!("\n=== Conversion Statistics ===");
-            eprintln!("Total records:   {}", stats.total);
-            eprintln!("Successful:      {}", stats.success);
-            eprintln!("Failed:          {}", stats.failed);
-            eprintln!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+            for (input, output_path) in jobs {
+                let unmap_path = unmap_path_for(&output_path, "bed.unmap");
+                let output_path = if output_compress { compressed_output_path(&output_path) } else { output_path };
+                
+                info!("Converting BED file: {:?} -> {:?}", input, output_path);
+                let stats = formats::convert_bed_with_options(&input, &output_path, &unmap_path, &mapper, &options)?;
+                if output_compress {
+                    tabix_index(&output_path, "bed");
+                }
+                
+                info!("=== Conversion Statistics ===");
+                info!("Total records:   {}", stats.total);
+                info!("Successful:      {}", stats.success);
+                info!("Failed:          {}", stats.failed);
+                if stats.skipped > 0 {
+                    info!("Skipped:         {}", stats.skipped);
+                }
+                info!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+                batch.add(&input, stats.total, stats.success, stats.failed);
+            }
         }
         
-        Commands::Vcf { chain, input, refgenome, output, threads, no_comp_allele, chrom_style } => {
-            let mapper = load_chain(&chain, chrom_style, cli.compat_mode)?;
-            let output_path = output.unwrap_or_else(|| PathBuf::from("output.vcf"));
+        Commands::Vcf {
+            chain, mut paths, output_dir, threads, no_comp_allele, split_multiallelic, merge_biallelic,
+            reset_filter, add_filter, no_provenance_headers, lenient, lift_bnd_mates, bnd_unmap_policy, info_passthrough,
+            info_blacklist, inversion_strict, update_ci_intervals, output_compress, chrom_style,
+        } => {
+            let refgenome = take_refgenome(&mut paths, output_dir.is_some())?;
+            let jobs = plan_jobs(paths, output_dir, "vcf", "output.vcf")?;
+            let mapper = load_chain(&chain, chrom_style, load_args)?;
+            let options = formats::VcfConvertOptions {
+                no_comp_allele,
+                threads,
+                split_multiallelic,
+                merge_biallelic,
+                reset_filter,
+                add_filter,
+                generate_provenance_headers: !no_provenance_headers,
+                chain_file: Some(chain.clone()),
+                command_line: Some(std::env::args().collect::<Vec<_>>().join(" ")),
+                lenient,
+                lift_bnd_mates,
+                bnd_unmap_policy: bnd_unmap_policy.into(),
+                info_passthrough,
+                info_blacklist,
+                inversion_strict,
+                update_ci_intervals,
+                compress: output_compress,
+            };
             
-            eprintln!("Converting VCF file: {:?} -> {:?}", input, output_path);
-            eprintln!("Reference genome: {:?}", refgenome);
-            let stats = formats::convert_vcf(&input, &output_path, &mapper, Some(&refgenome), no_comp_allele, threads)?;
-            
-            eprintln!("\n=== Conversion Statistics ===");
-            eprintln!("Total records:   {}", stats.total);
-            eprintln!("Successful:      {}", stats.success);
-            eprintln!("Failed:          {}", stats.failed);
-            eprintln!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+            for (input, output_path) in jobs {
+                let output_path = if output_compress { compressed_output_path(&output_path) } else { output_path };
+                info!("Converting VCF file: {:?} -> {:?}", input, output_path);
+                info!("Reference genome: {:?}", refgenome);
+                let stats = formats::convert_vcf_with_options(&input, &output_path, &mapper, Some(&refgenome), &options)?;
+                if output_compress {
+                    tabix_index(&output_path, "vcf");
+                }
+                
+                info!("=== Conversion Statistics ===");
+                info!("Total records:   {}", stats.total);
+                info!("Successful:      {}", stats.success);
+                info!("Failed:          {}", stats.failed);
+                if stats.ref_eq_alt > 0 {
+                    info!("  - REF==ALT:    {}", stats.ref_eq_alt);
+                }
+                info!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+                batch.add(&input, stats.total, stats.success, stats.failed);
+            }
         }
         
-        Commands::Gff { chain, input, output, threads, chrom_style } => {
-            let mapper = load_chain(&chain, chrom_style, cli.compat_mode)?;
-            let output_path = output.unwrap_or_else(|| PathBuf::from("output.gff"));
+        Commands::Gff {
+            chain, paths, output_dir, threads, rename_attr, gff_version, strict_input, validate_so_terms, multimap_output,
+            split_cds, chrom_style,
+        } => {
+            let jobs = plan_jobs(paths, output_dir, "gff", "output.gff")?;
+            let mapper = load_chain(&chain, chrom_style, load_args)?;
+            let options = formats::GffConvertOptions {
+                threads,
+                rename_attributes: rename_attr.into_iter().collect(),
+                gff_version: gff_version.into(),
+                strict_input,
+                validate_so_terms,
+                multimap_output,
+                split_cds,
+            };
             
-            eprintln!("Converting GFF file: {:?} -> {:?}", input, output_path);
-            let stats = formats::convert_gff(&input, &output_path, &mapper, threads)?;
+            for (input, output_path) in jobs {
+                info!("Converting GFF file: {:?} -> {:?}", input, output_path);
+                let stats = formats::convert_gff_with_options(&input, &output_path, &mapper, &options)?;
+                
+                info!("=== Conversion Statistics ===");
+                info!("Total records:   {}", stats.total);
+                info!("Successful:      {}", stats.success);
+                if options.multimap_output.is_some() {
+                    info!("  - Multi-mapped: {}", stats.multi_map);
+                }
+                info!("Failed:          {}", stats.failed);
+                info!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+                batch.add(&input, stats.total, stats.success, stats.failed);
+            }
+        }
+        
+        Commands::Gvcf { chain, mut paths, output_dir, no_comp_allele, min_gq, no_provenance, threads, chrom_style } => {
+            let refgenome = take_refgenome(&mut paths, output_dir.is_some())?;
+            let jobs = plan_jobs(paths, output_dir, "gvcf", "output.gvcf")?;
+            let mapper = load_chain(&chain, chrom_style, load_args)?;
+            let options = formats::GvcfConvertOptions {
+                no_comp_allele,
+                min_gq,
+                threads,
+                generate_provenance: !no_provenance,
+                chain_file: Some(chain.clone()),
+                command_line: Some(std::env::args().collect::<Vec<_>>().join(" ")),
+            };
             
-            eprintln!("\n=== Conversion Statistics ===");
-            eprintln!("Total records:   {}", stats.total);
-            eprintln!("Successful:      {}", stats.success);
-            eprintln!("Failed:          {}", stats.failed);
-            eprintln!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+            for (input, output_path) in jobs {
+                info!("Converting GVCF file: {:?} -> {:?}", input, output_path);
+                info!("Reference genome: {:?}", refgenome);
+                let stats = formats::convert_gvcf_with_options(&input, &output_path, &mapper, Some(&refgenome), &options)?;
+                
+                info!("=== Conversion Statistics ===");
+                info!("Total records:   {}", stats.total);
+                info!("Successful:      {}", stats.success);
+                info!("Failed:          {}", stats.failed);
+                if min_gq.is_some() {
+                    info!("  - LowGQ:       {}", stats.filtered_gq);
+                }
+                info!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+                batch.add(&input, stats.total, stats.success, stats.failed);
+            }
         }
         
-        Commands::Gvcf { chain, input, refgenome, output, no_comp_allele, threads, chrom_style } => {
-            let mapper = load_chain(&chain, chrom_style, cli.compat_mode)?;
-            let output_path = output.unwrap_or_else(|| PathBuf::from("output.gvcf"));
+        Commands::Maf { chain, mut paths, output_dir, build, chrom_style } => {
+            let refgenome = take_refgenome(&mut paths, output_dir.is_some())?;
+            let jobs = plan_jobs(paths, output_dir, "maf", "output.maf")?;
+            let mapper = load_chain(&chain, chrom_style, load_args)?;
             
-            eprintln!("Converting GVCF file: {:?} -> {:?}", input, output_path);
-            eprintln!("Reference genome: {:?}", refgenome);
-            let stats = formats::convert_gvcf(
-                &input, &output_path, &mapper, 
-                Some(&refgenome), no_comp_allele, threads
-            )?;
+            for (input, output_path) in jobs {
+                info!("Converting MAF file: {:?} -> {:?}", input, output_path);
+                info!("Reference genome: {:?}", refgenome);
+                info!("Target build: {}", build);
+                let stats = formats::convert_maf(
+                    &input, &output_path, &mapper, 
+                    Some(&refgenome), &build
+                )?;
+                
+                info!("=== Conversion Statistics ===");
+                info!("Total records:   {}", stats.total);
+                info!("Successful:      {}", stats.success);
+                info!("Failed:          {}", stats.failed);
+                info!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+                batch.add(&input, stats.total, stats.success, stats.failed);
+            }
+        }
+        
+        Commands::Wig { chain, paths, output_dir, threads, aggregation, add_track_header, error_mode, chrom_style } => {
+            let jobs = plan_jobs(paths, output_dir, "", "output.bedGraph")?;
+            let mapper = load_chain(&chain, chrom_style, load_args)?;
+            let options = formats::WigConvertOptions {
+                threads,
+                aggregation: aggregation.into(),
+                write_track_header: add_track_header,
+                error_mode: error_mode.into(),
+            };
             
-            eprintln!("\n=== Conversion Statistics ===");
-            eprintln!("Total records:   {}", stats.total);
-            eprintln!("Successful:      {}", stats.success);
-            eprintln!("Failed:          {}", stats.failed);
-            eprintln!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+            for (input, output_path) in jobs {
+                info!("Converting Wiggle file: {:?} -> {:?}", input, output_path);
+                let stats = formats::convert_wig_with_options(&input, &output_path, &mapper, &options)?;
+                
+                info!("=== Conversion Statistics ===");
+                info!("Total records:   {}", stats.total);
+                info!("Successful:      {}", stats.success);
+                info!("Failed:          {}", stats.failed);
+                info!("Merged:          {}", stats.merged);
+                info!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+                batch.add(&input, stats.total, stats.success, stats.failed);
+            }
         }
         
-        Commands::Maf { chain, input, refgenome, build, output, chrom_style } => {
-            let mapper = load_chain(&chain, chrom_style, cli.compat_mode)?;
-            let output_path = output.unwrap_or_else(|| PathBuf::from("output.maf"));
+        #[cfg(feature = "bam")]
+        Commands::Bam {
+            chain, paths, output_dir, threads, output_cram, refgenome, passthrough_unmapped, max_insert_size,
+            pair_orientation, chrom_style,
+        } => {
+            let ext = if output_cram { "cram" } else { "bam" };
+            let jobs = plan_jobs(paths, output_dir, ext, "output.bam")?;
+            let mapper = load_chain(&chain, chrom_style, load_args)?;
             
-            eprintln!("Converting MAF file: {:?} -> {:?}", input, output_path);
-            eprintln!("Reference genome: {:?}", refgenome);
-            eprintln!("Target build: {}", build);
-            let stats = formats::convert_maf(
-                &input, &output_path, &mapper, 
-                Some(&refgenome), &build
-            )?;
+            for (input, output) in jobs {
+                let output_format = if output_cram {
+                    formats::BamOutputFormat::Cram
+                } else {
+                    formats::BamOutputFormat::from_path(&output)
+                };
+                
+                info!("Converting BAM file: {:?} -> {:?}", input, output);
+                let options = formats::BamConvertOptions {
+                    threads,
+                    output_format,
+                    passthrough_unmapped,
+                    max_insert_size,
+                    pair_orientation: pair_orientation.into(),
+                };
+                let stats = formats::convert_bam_with_options(&input, &output, &mapper, refgenome.as_ref(), &options)?;
+                
+                info!("=== Conversion Statistics ===");
+                info!("Total records:   {}", stats.total);
+                info!("Mapped:          {}", stats.mapped);
+                info!("Unmapped:        {}", stats.unmapped);
+                if passthrough_unmapped {
+                    info!("  - Passthrough: {}", stats.unmapped_passthrough);
+                }
+                info!("Failed:          {}", stats.failed);
+                info!("Paired:          {}", stats.paired);
+                info!("  - No longer proper: {}", stats.improper_pairs);
+                info!("Single:          {}", stats.single);
+                info!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+                batch.add(&input, stats.total, stats.mapped, stats.unmapped + stats.failed);
+            }
+        }
+        
+        Commands::Region { chain, paths, output_dir, ratio, emit_fragments, fragments_output, emit_ratio, chrom_style } => {
+            let jobs = plan_jobs(paths, output_dir, "bed", "output.bed")?;
+            let mapper = load_chain(&chain, chrom_style, load_args)?;
+            let options = formats::RegionConvertOptions {
+                min_ratio: ratio,
+                emit_fragments,
+                fragments_output,
+                emit_ratio,
+            };
             
-            eprintln!("\n=== Conversion Statistics ===");
-            eprintln!("Total records:   {}", stats.total);
-            eprintln!("Successful:      {}", stats.success);
-            eprintln!("Failed:          {}", stats.failed);
-            eprintln!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+            for (input, output_path) in jobs {
+                info!("Converting Region file: {:?} -> {:?} (min_ratio={})", input, output_path, ratio);
+                let stats = formats::convert_region_with_options(&input, &output_path, &mapper, &options)?;
+                
+                info!("=== Conversion Statistics ===");
+                info!("Total records:   {}", stats.total);
+                info!("Successful:      {}", stats.success);
+                info!("Failed:          {}", stats.failed);
+                info!("  - Unmapped:    {}", stats.unmapped);
+                info!("  - CrossChrom:  {}", stats.cross_chrom);
+                info!("  - LowRatio:    {}", stats.low_ratio);
+                info!("Low-coverage segments: {}", stats.low_coverage);
+                if emit_fragments {
+                    info!("Fragments:       {}", stats.total_fragments);
+                }
+                info!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+                batch.add(&input, stats.total, stats.success, stats.failed);
+            }
         }
         
-        Commands::Wig { chain, input, output, chrom_style } => {
-            let mapper = load_chain(&chain, chrom_style, cli.compat_mode)?;
-            let output_path = output.unwrap_or_else(|| PathBuf::from("output.bedGraph"));
+        Commands::Bigwig { chain, paths, output_dir, aggregation, chrom_style } => {
+            let jobs = plan_jobs(paths, output_dir, "", "output")?;
+            let mapper = load_chain(&chain, chrom_style, load_args)?;
             
-            eprintln!("Converting Wiggle file: {:?} -> {:?}", input, output_path);
-            let stats = formats::convert_wig(&input, &output_path, &mapper)?;
+            for (input, output_path) in jobs {
+                info!("Converting BigWig file: {:?} -> {:?}", input, output_path);
+                let stats = formats::convert_bigwig(&input, &output_path, &mapper, aggregation.into())?;
+                
+                info!("=== Conversion Statistics ===");
+                info!("Total records:   {}", stats.total);
+                info!("Successful:      {}", stats.success);
+                info!("Failed:          {}", stats.failed);
+                info!("Merged:          {}", stats.merged);
+                info!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+                batch.add(&input, stats.total, stats.success, stats.failed);
+            }
+        }
+        
+        Commands::Convert { chain, input, output, refgenome, build, threads, chrom_style } => {
+            // Resolve auto-detection here; not every format accepts 0
+            let threads = resolve_threads(threads);
+            let command = resolve_convert(chain, input, output, refgenome, build, threads, chrom_style)?;
+            return run(command, load_args, start);
+        }
+        
+        Commands::Batch { chain, config, parallel, chrom_style } => {
+            let tasks = BatchConfig::from_toml(&config)?;
+            let mapper = load_chain(&chain, chrom_style, load_args)?;
+            info!("Running {} conversions from {:?}", tasks.len(), config);
             
-            eprintln!("\n=== Conversion Statistics ===");
-            eprintln!("Total records:   {}", stats.total);
-            eprintln!("Successful:      {}", stats.success);
-            eprintln!("Failed:          {}", stats.failed);
-            eprintln!("Merged:          {}", stats.merged);
-            eprintln!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+            let results = fast_crossmap::batch::run_batch(&tasks, &mapper, parallel);
+            for (task, result) in tasks.iter().zip(results) {
+                let stats = result?;
+                info!(
+                    "{} {:?} -> {:?}: total={} success={} failed={}",
+                    task.format, task.input, task.output, stats.total, stats.success, stats.failed
+                );
+                batch.add(&task.input, stats.total, stats.success, stats.failed);
+            }
+            info!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
         }
         
-        #[cfg(feature = "bam")]
-        Commands::Bam { chain, input, output, threads, chrom_style } => {
-            let mapper = load_chain(&chain, chrom_style, cli.compat_mode)?;
+        Commands::Stats { chain, per_chrom, json, histogram } => {
+            let mapper = load_chain(&chain, ChromStyleArg::AsIs, load_args)?;
+            let index = mapper.index();
+            let summary = index.statistics();
+            
+            if json {
+                println!("{}", summary.to_json());
+                return Ok(());
+            }
+            
+            let mut chroms: Vec<&str> = index.source_chroms().collect();
+            chroms.sort_unstable();
+            
+            println!("Source chromosomes: {}", chroms.len());
+            println!("Target chromosomes: {}", index.target_sizes.len());
+            println!("Total chains:       {}", summary.chain_count);
+            println!("Total blocks:       {}", summary.block_count);
+            for (name, d) in [("Chain score", &summary.scores), ("Block size", &summary.block_sizes)] {
+                println!(
+                    "{:<19} min {} / max {} / mean {:.2} / median {:.1}",
+                    format!("{}:", name), d.min, d.max, d.mean, d.median
+                );
+            }
+            
+            println!();
+            println!("Coverage (source chromosomes by covered fraction):");
+            let bin_width = 100 / COVERAGE_HISTOGRAM_BINS;
+            for (i, count) in summary.coverage_histogram.iter().enumerate() {
+                println!("  {:>8} {}", format!("{}-{}%", i * bin_width, (i + 1) * bin_width), count);
+            }
             
-            eprintln!("Converting BAM file: {:?} -> {:?}", input, output);
-            let stats = formats::convert_bam(&input, &output, &mapper, threads)?;
+            if let Some(bins) = histogram {
+                for (name, hist) in [
+                    ("Chain score", index.score_histogram(bins)),
+                    ("Block size", index.block_size_histogram(bins)),
+                ] {
+                    println!();
+                    println!("{} histogram (bucket lower bound, block count):", name);
+                    for (threshold, count) in hist {
+                        println!("  >= {:<12} {}", threshold, count);
+                    }
+                }
+            }
             
-            eprintln!("\n=== Conversion Statistics ===");
-            eprintln!("Total records:   {}", stats.total);
-            eprintln!("Mapped:          {}", stats.mapped);
-            eprintln!("Unmapped:        {}", stats.unmapped);
-            eprintln!("Failed:          {}", stats.failed);
-            eprintln!("Paired:          {}", stats.paired);
-            eprintln!("Single:          {}", stats.single);
-            eprintln!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+            println!();
+            println!("side\tchrom\tsize\tcovered_bases");
+            for (side, coverage) in [("source", &summary.source_coverage), ("target", &summary.target_coverage)] {
+                for c in coverage.iter() {
+                    println!("{}\t{}\t{}\t{}", side, c.chrom, c.size, c.covered_bases);
+                }
+            }
+            
+            if per_chrom {
+                println!();
+                println!("chrom\ttotal_bases\tcovered_bases\tcoverage_fraction\tmax_block_size\tmean_block_size\tblock_count");
+                for chrom in chroms {
+                    if let Some(s) = index.overlap_stats(chrom) {
+                        println!(
+                            "{}\t{}\t{}\t{:.6}\t{}\t{:.2}\t{}",
+                            chrom, s.total_bases, s.covered_bases, s.coverage_fraction,
+                            s.max_block_size, s.mean_block_size, s.block_count
+                        );
+                    }
+                }
+            }
         }
         
-        Commands::Region { chain, input, output, ratio, chrom_style } => {
-            let mapper = load_chain(&chain, chrom_style, cli.compat_mode)?;
-            let output_path = output.unwrap_or_else(|| PathBuf::from("output.bed"));
+        Commands::Summary { chain, json } => {
+            let mapper = load_chain(&chain, ChromStyleArg::AsIs, load_args)?;
+            let summary = mapper.index().summary();
             
-            eprintln!("Converting Region file: {:?} -> {:?} (min_ratio={})", input, output_path, ratio);
-            let stats = formats::convert_region(&input, &output_path, &mapper, ratio)?;
+            if json {
+                println!("{}", summary.to_json());
+                return Ok(());
+            }
             
-            eprintln!("\n=== Conversion Statistics ===");
-            eprintln!("Total records:   {}", stats.total);
-            eprintln!("Successful:      {}", stats.success);
-            eprintln!("Failed:          {}", stats.failed);
-            eprintln!("  - Unmapped:    {}", stats.unmapped);
-            eprintln!("  - CrossChrom:  {}", stats.cross_chrom);
-            eprintln!("  - LowRatio:    {}", stats.low_ratio);
-            eprintln!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+            println!("Blocks:                {}", summary.block_count);
+            println!("Source chromosomes:    {} ({})", summary.source_chroms.len(), summary.source_chroms.join(", "));
+            println!("Target chromosomes:    {} ({})", summary.target_chroms.len(), summary.target_chroms.join(", "));
+            println!("Source bases covered:  {}", summary.total_source_covered);
+            println!("Target bases covered:  {}", summary.total_target_covered);
+            println!(
+                "Block score:           min {} / max {} / mean {:.2}",
+                summary.min_score, summary.max_score, summary.mean_score
+            );
         }
         
-        Commands::Bigwig { chain, input, output, chrom_style } => {
-            let mapper = load_chain(&chain, chrom_style, cli.compat_mode)?;
-            let output_path = output.unwrap_or_else(|| PathBuf::from("output"));
+        Commands::Visualize { chain, chrom, width, target } => {
+            let mapper = load_chain(&chain, ChromStyleArg::AsIs, load_args)?;
+            let index = mapper.index();
             
-            eprintln!("Converting BigWig file: {:?} -> {:?}", input, output_path);
-            let stats = formats::convert_bigwig(&input, &output_path, &mapper)?;
+            let bar = if target {
+                index.visualize_target(&chrom, width)
+            } else {
+                index.visualize_chrom(&chrom, width)
+            };
+            println!("{}", bar);
+        }
+        
+        Commands::ChainToBed { chain, chrom, target, output } => {
+            let mapper = load_chain(&chain, ChromStyleArg::AsIs, load_args)?;
+            let index = mapper.index();
             
-            eprintln!("\n=== Conversion Statistics ===");
-            eprintln!("Total records:   {}", stats.total);
-            eprintln!("Successful:      {}", stats.success);
-            eprintln!("Failed:          {}", stats.failed);
-            eprintln!("Merged:          {}", stats.merged);
-            eprintln!("Time elapsed:    {:.2}s", start.elapsed().as_secs_f64());
+            let mut writer = create_output(&output, 64 * 1024)?;
+            if target {
+                index.to_target_bed(&chrom, &mut writer)?;
+            } else {
+                index.to_bed(&chrom, &mut writer)?;
+            }
+            writer.flush()?;
+        }
+        Commands::ValidateChain { chain } => {
+            let chain_file = parse_chain_file_unchecked(&chain)
+                .map_err(|e| anyhow::anyhow!("Failed to load chain file: {}", e))?;
+            let errors = chain_file.validate();
+            for error in &errors {
+                println!("{}", error);
+            }
+            if !errors.is_empty() {
+                anyhow::bail!("{} problem(s) in {:?}", errors.len(), chain);
+            }
+            info!("{:?}: {} blocks, no problems found", chain, chain_file.blocks.len());
         }
     }
-
+    
+    batch.report();
     Ok(())
 }
@@ -235,3 +235,149 @@ fn test_cigar_single_op_merge() {
     assert_eq!(merged.len(), 1);
     assert_eq!(merged[0], CigarOp::Match(10));
 }
+
+// ============================================================================
+// Conversion Tests
+// ============================================================================
+
+/// Build a small identity-chain test fixture: (mapper, SAM input, reference FASTA)
+fn write_alignment_fixture(
+    dir: &std::path::Path,
+) -> (fast_crossmap::core::CoordinateMapper, std::path::PathBuf, std::path::PathBuf) {
+    use fast_crossmap::core::{parse_chain_bytes, ChainIndex, ChromStyle, CoordinateMapper};
+    
+    let chain = b"chain 1000 chr1 1000 + 0 1000 chr1 1000 + 0 1000 1\n1000\n";
+    let index = ChainIndex::from_chain_data(parse_chain_bytes(chain).unwrap());
+    let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+    
+    let ref_seq = "ACGTTGCA".repeat(125);
+    let fasta_path = dir.join("ref.fa");
+    std::fs::write(&fasta_path, format!(">chr1\n{}\n", ref_seq)).unwrap();
+    
+    let mut sam = String::from("@HD\tVN:1.6\tSO:coordinate\n@SQ\tSN:chr1\tLN:1000\n");
+    for (i, pos) in [100usize, 200, 300].iter().enumerate() {
+        let seq = &ref_seq[pos - 1..pos - 1 + 10];
+        sam.push_str(&format!("read{}\t0\tchr1\t{}\t60\t10M\t*\t0\t0\t{}\tIIIIIIIIII\n", i, pos, seq));
+    }
+    sam.push_str("read3\t4\t*\t0\t0\t*\t*\t0\t0\tACGTACGTAC\tIIIIIIIIII\n");
+    let sam_path = dir.join("input.sam");
+    std::fs::write(&sam_path, sam).unwrap();
+    
+    (mapper, sam_path, fasta_path)
+}
+
+/// Test BAM -> lifted CRAM -> BAM round trip keeps all reads
+#[test]
+fn test_cram_output_round_trip() {
+    use fast_crossmap::formats::bam::{convert_bam, OutputFormat};
+    use rust_htslib::bam::{self, Read};
+    
+    let dir = tempfile::tempdir().unwrap();
+    let (mapper, sam_path, fasta_path) = write_alignment_fixture(dir.path());
+    
+    let cram_path = dir.path().join("lifted.cram");
+    let stats = convert_bam(&sam_path, &cram_path, &mapper, 1, OutputFormat::Cram, Some(&fasta_path), false).unwrap();
+    assert_eq!(stats.total, 4);
+    assert_eq!(stats.mapped, 3);
+    
+    let bam_path = dir.path().join("roundtrip.bam");
+    let stats_back = convert_bam(&cram_path, &bam_path, &mapper, 1, OutputFormat::Bam, Some(&fasta_path), false).unwrap();
+    assert_eq!(stats_back.total, stats.total);
+    assert_eq!(stats_back.mapped, stats.mapped);
+    
+    let mut reader = bam::Reader::from_path(&bam_path).unwrap();
+    assert_eq!(reader.records().count(), 4);
+}
+
+/// Test that CRAM output without a reference genome is rejected
+#[test]
+fn test_cram_output_requires_reference() {
+    use fast_crossmap::formats::bam::{convert_bam, BamError, OutputFormat};
+    
+    let dir = tempfile::tempdir().unwrap();
+    let (mapper, sam_path, _) = write_alignment_fixture(dir.path());
+    
+    let cram_path = dir.path().join("lifted.cram");
+    let result = convert_bam(&sam_path, &cram_path, &mapper, 1, OutputFormat::Cram, None, false);
+    assert!(matches!(result, Err(BamError::MissingReference)));
+}
+
+/// Test unmapped reads go to the passthrough file and not the lifted file
+#[test]
+fn test_unmapped_passthrough() {
+    use fast_crossmap::formats::bam::{convert_bam, OutputFormat};
+    use rust_htslib::bam::{self, Read};
+    
+    let dir = tempfile::tempdir().unwrap();
+    let (mapper, sam_path, _) = write_alignment_fixture(dir.path());
+    
+    let bam_path = dir.path().join("lifted.bam");
+    let stats = convert_bam(&sam_path, &bam_path, &mapper, 1, OutputFormat::Bam, None, true).unwrap();
+    assert_eq!(stats.total, 4);
+    assert_eq!(stats.unmapped, 1);
+    assert_eq!(stats.unmapped_passthrough, 1);
+    
+    let mut lifted = bam::Reader::from_path(&bam_path).unwrap();
+    let lifted_names: Vec<Vec<u8>> = lifted.records().map(|r| r.unwrap().qname().to_vec()).collect();
+    assert_eq!(lifted_names.len(), 3);
+    assert!(!lifted_names.contains(&b"read3".to_vec()));
+    
+    let mut passthrough = bam::Reader::from_path(dir.path().join("lifted.unmapped.bam")).unwrap();
+    let passthrough_names: Vec<Vec<u8>> = passthrough.records().map(|r| r.unwrap().qname().to_vec()).collect();
+    assert_eq!(passthrough_names, vec![b"read3".to_vec()]);
+}
+
+/// Test pairs split across chromosomes by the chain lose the proper-pair flag
+#[test]
+fn test_proper_pair_re_evaluated() {
+    use fast_crossmap::core::{parse_chain_bytes, ChainIndex, ChromStyle, CoordinateMapper};
+    use fast_crossmap::formats::bam::{convert_bam_with_options, BamConvertOptions};
+    use rust_htslib::bam::{self, Read};
+    
+    // chr1:0-500 -> chrA, chr1:500-1000 -> chrB
+    let chain = b"\
+chain 1000 chr1 1000 + 0 500 chrA 500 + 0 500 1
+500
+
+chain 1000 chr1 1000 + 500 1000 chrB 500 + 0 500 2
+500
+";
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(parse_chain_bytes(chain).unwrap()), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let mut sam = String::from("@HD\tVN:1.6\tSO:unsorted\n@SQ\tSN:chr1\tLN:1000\n");
+    // Flags 99/147: paired, proper, FR orientation
+    for (name, pos1, pos2) in [("same", 101, 301), ("split", 401, 601)] {
+        let tlen = pos2 + 9 - pos1 + 1;
+        sam.push_str(&format!("{}\t99\tchr1\t{}\t60\t10M\t=\t{}\t{}\tACGTACGTAC\tIIIIIIIIII\n", name, pos1, pos2, tlen));
+        sam.push_str(&format!("{}\t147\tchr1\t{}\t60\t10M\t=\t{}\t{}\tACGTACGTAC\tIIIIIIIIII\n", name, pos2, pos1, -tlen));
+    }
+    let sam_path = dir.path().join("pairs.sam");
+    std::fs::write(&sam_path, sam).unwrap();
+    
+    let bam_path = dir.path().join("lifted.bam");
+    let stats = convert_bam_with_options(&sam_path, &bam_path, &mapper, None, &BamConvertOptions::default()).unwrap();
+    assert_eq!((stats.mapped, stats.improper_pairs), (4, 2));
+    
+    let mut reader = bam::Reader::from_path(&bam_path).unwrap();
+    let header = reader.header().clone();
+    for record in reader.records() {
+        let record = record.unwrap();
+        let chrom = std::str::from_utf8(header.tid2name(record.tid() as u32)).unwrap().to_string();
+        let mate_chrom = std::str::from_utf8(header.tid2name(record.mtid() as u32)).unwrap().to_string();
+        if record.qname() == b"same" {
+            assert!(record.is_proper_pair());
+            assert_eq!((chrom.as_str(), mate_chrom.as_str()), ("chrA", "chrA"));
+            assert_eq!(record.insert_size().abs(), 210);
+        } else {
+            assert!(!record.is_proper_pair());
+            assert_ne!(chrom, mate_chrom);
+            assert_eq!(record.insert_size(), 0);
+        }
+    }
+    
+    // A tighter insert size limit also breaks the first pair
+    let options = BamConvertOptions { max_insert_size: 100, ..Default::default() };
+    let stats = convert_bam_with_options(&sam_path, &bam_path, &mapper, None, &options).unwrap();
+    assert_eq!(stats.improper_pairs, 4);
+}
@@ -0,0 +1,78 @@
+//! Batch conversion tests
+//!
+//! Runs BED and VCF conversions from one TOML config against a shared mapper.
+
+use fast_crossmap::batch::{run_batch, BatchConfig, TaskSummary};
+use fast_crossmap::core::{ChainIndex, ChromStyle, CoordinateMapper};
+
+fn write_inputs(dir: &std::path::Path) {
+    std::fs::write(dir.join("in.bed"), "chr1\t100\t200\ta\nchr2\t100\t200\tb\n").unwrap();
+    std::fs::write(dir.join("in.vcf"), "\
+##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t500\trs1\tA\tG\t.\tPASS\t.
+").unwrap();
+    std::fs::write(dir.join("batch.toml"), r#"
+[[convert]]
+type = "bed"
+input = "in.bed"
+output = "out.bed"
+
+[[convert]]
+type = "vcf"
+input = "in.vcf"
+output = "out.vcf"
+"#).unwrap();
+}
+
+#[test]
+fn test_batch_bed_and_vcf() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+
+    for parallel in [false, true] {
+        let dir = tempfile::tempdir().unwrap();
+        write_inputs(dir.path());
+
+        let tasks = BatchConfig::from_toml(dir.path().join("batch.toml")).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].input, dir.path().join("in.bed"));
+
+        let results: Vec<TaskSummary> = run_batch(&tasks, &mapper, parallel)
+            .into_iter()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(results, vec![
+            TaskSummary { total: 2, success: 1, failed: 1 },
+            TaskSummary { total: 1, success: 1, failed: 0 },
+        ]);
+
+        let bed = std::fs::read_to_string(dir.path().join("out.bed")).unwrap();
+        assert_eq!(bed, "chr1\t5100\t5200\ta\n");
+        let unmap = std::fs::read_to_string(dir.path().join("out.bed.unmap")).unwrap();
+        assert_eq!(unmap, "chr2\t100\t200\tb\n");
+
+        let vcf = std::fs::read_to_string(dir.path().join("out.vcf")).unwrap();
+        assert!(vcf.lines().any(|l| l == "chr1\t5500\trs1\tA\tG\t.\tPASS\t."));
+    }
+}
+
+#[test]
+fn test_batch_missing_input_reports_task() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+
+    let dir = tempfile::tempdir().unwrap();
+    write_inputs(dir.path());
+    std::fs::remove_file(dir.path().join("in.vcf")).unwrap();
+
+    let tasks = BatchConfig::from_toml(dir.path().join("batch.toml")).unwrap();
+    let results = run_batch(&tasks, &mapper, false);
+    assert!(results[0].is_ok());
+    let err = results[1].as_ref().unwrap_err().to_string();
+    assert!(err.contains("in.vcf"), "{}", err);
+}
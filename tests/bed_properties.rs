@@ -4,7 +4,7 @@
 //! **Validates: Requirements 4.2, 4.3**
 
 use fast_crossmap::core::{ChainIndex, CoordinateMapper, ChromStyle, Strand};
-use fast_crossmap::formats::bed::{BedRecordView, convert_bed};
+use fast_crossmap::formats::bed::{BedRecordView, BedConvertOptions, BedParseError, convert_bed, convert_bed_channel, convert_bed_with_options};
 use proptest::prelude::*;
 use std::path::PathBuf;
 
@@ -37,7 +37,7 @@ fn arb_strand_char() -> impl Strategy<Value = String> {
 }
 
 /// Generate a BED3 line
-fn arb_bed3_line() -> impl Strategy<Value = String> {
+fn arb_bed3() -> impl Strategy<Value = String> {
     (arb_chrom_name(), 1000u64..100000, 100u64..1000)
         .prop_map(|(chrom, start, size)| {
             format!("{}\t{}\t{}", chrom, start, start + size)
@@ -45,7 +45,7 @@ fn arb_bed3_line() -> impl Strategy<Value = String> {
 }
 
 /// Generate a BED6 line
-fn arb_bed6_line() -> impl Strategy<Value = String> {
+fn arb_bed6() -> impl Strategy<Value = String> {
     (
         arb_chrom_name(),
         1000u64..100000,
@@ -60,6 +60,60 @@ fn arb_bed6_line() -> impl Strategy<Value = String> {
         })
 }
 
+/// Generate an itemRgb field ("0" or "r,g,b")
+fn arb_item_rgb() -> impl Strategy<Value = String> {
+    prop_oneof![
+        Just("0".to_string()),
+        (0u8..=255, 0u8..=255, 0u8..=255).prop_map(|(r, g, b)| format!("{},{},{}", r, g, b)),
+    ]
+}
+
+/// Generate a BED12 line
+///
+/// Blocks are laid out from (size, gap) pairs: the first block starts at
+/// chromStart, the last one ends at chromEnd, and thickStart/thickEnd lie
+/// within the record.
+fn arb_bed12() -> impl Strategy<Value = String> {
+    (
+        arb_chrom_name(),
+        1000u64..100000,
+        prop::collection::vec((1u64..500, 1u64..500), 1..10),
+        arb_bed_name(),
+        arb_score(),
+        arb_strand_char(),
+        arb_item_rgb(),
+        (0u64..=100, 0u64..=100),
+    )
+        .prop_map(|(chrom, start, blocks, name, score, strand, rgb, (thick_a, thick_b))| {
+            let mut sizes = Vec::with_capacity(blocks.len());
+            let mut starts = Vec::with_capacity(blocks.len());
+            let mut offset = 0u64;
+            for (i, &(size, gap)) in blocks.iter().enumerate() {
+                starts.push(offset.to_string());
+                sizes.push(size.to_string());
+                offset += size;
+                if i + 1 < blocks.len() {
+                    offset += gap;
+                }
+            }
+            let end = start + offset;
+            // Thick region as percentages of the record
+            let (lo, hi) = (thick_a.min(thick_b), thick_a.max(thick_b));
+            let thick_start = start + offset * lo / 100;
+            let thick_end = start + offset * hi / 100;
+            format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{},\t{},",
+                chrom, start, end, name, score, strand, thick_start, thick_end, rgb,
+                blocks.len(), sizes.join(","), starts.join(",")
+            )
+        })
+}
+
+/// Any of the generated BED flavours
+fn arb_bed_any() -> impl Strategy<Value = String> {
+    prop_oneof![arb_bed3(), arb_bed6(), arb_bed12()]
+}
+
 
 proptest! {
     #![proptest_config(ProptestConfig::with_cases(100))]
@@ -71,7 +125,7 @@ proptest! {
     ///
     /// **Validates: Requirements 4.2, 4.3**
     #[test]
-    fn prop_bed_field_preservation(line in arb_bed6_line()) {
+    fn prop_bed_field_preservation(line in arb_bed6()) {
         // Parse the original line
         let original = BedRecordView::parse(line.as_bytes()).unwrap();
         
@@ -96,7 +150,7 @@ proptest! {
     
     /// Property: BED3 parsing extracts correct coordinates
     #[test]
-    fn prop_bed3_coordinate_parsing(line in arb_bed3_line()) {
+    fn prop_bed3_coordinate_parsing(line in arb_bed3()) {
         let view = BedRecordView::parse(line.as_bytes()).unwrap();
         
         let fields: Vec<&str> = line.split('\t').collect();
@@ -111,7 +165,7 @@ proptest! {
     
     /// Property: BED6 parsing extracts all fields correctly
     #[test]
-    fn prop_bed6_field_parsing(line in arb_bed6_line()) {
+    fn prop_bed6_field_parsing(line in arb_bed6()) {
         let view = BedRecordView::parse(line.as_bytes()).unwrap();
         
         let fields: Vec<&str> = line.split('\t').collect();
@@ -122,6 +176,47 @@ proptest! {
         prop_assert_eq!(view.strand_char(), Some(fields[5]));
         prop_assert!(view.is_bed6());
     }
+    
+    /// Property: every generated record parses and validates
+    #[test]
+    fn prop_generated_bed_parses(line in arb_bed_any()) {
+        let view = BedRecordView::parse(line.as_bytes());
+        prop_assert!(view.is_ok(), "failed to parse: {}", line);
+        prop_assert!(view.unwrap().validate().is_ok(), "invalid record: {}", line);
+    }
+    
+    /// Property: field_count() equals the number of tab-separated fields
+    #[test]
+    fn prop_bed_field_count(line in arb_bed_any()) {
+        let view = BedRecordView::parse(line.as_bytes()).unwrap();
+        prop_assert_eq!(view.field_count(), line.split('\t').count());
+    }
+    
+    /// Property: BED12 blocks lie within chromStart/chromEnd and match blockCount
+    #[test]
+    fn prop_bed12_blocks_within_record(line in arb_bed12()) {
+        let view = BedRecordView::parse(line.as_bytes()).unwrap();
+        prop_assert!(view.is_bed12());
+        
+        let parse_list = |s: &str| -> Vec<u64> {
+            s.split(',').filter(|v| !v.is_empty()).map(|v| v.parse().unwrap()).collect()
+        };
+        let sizes = parse_list(view.block_sizes().unwrap());
+        let starts = parse_list(view.block_starts().unwrap());
+        let count = view.block_count().unwrap() as usize;
+        prop_assert_eq!(sizes.len(), count);
+        prop_assert_eq!(starts.len(), count);
+        
+        prop_assert_eq!(starts[0], 0);
+        for (&block_start, &size) in starts.iter().zip(&sizes) {
+            prop_assert!(view.start + block_start + size <= view.end);
+        }
+        prop_assert_eq!(view.start + starts[count - 1] + sizes[count - 1], view.end);
+        prop_assert!(starts.windows(2).all(|w| w[0] < w[1]));
+        
+        let (thick_start, thick_end) = (view.thick_start().unwrap(), view.thick_end().unwrap());
+        prop_assert!(view.start <= thick_start && thick_start <= thick_end && thick_end <= view.end);
+    }
 }
 
 /// Integration test: BED conversion with real chain file
@@ -493,3 +588,680 @@ chr22\t20000\t20100\tregion8\t800\t-
         assert!(match_rate > 0.9, "Match rate should be > 90%, got {:.2}%", match_rate * 100.0);
     }
 }
+
+/// `fast-crossmap bed chain.gz - -` reads BED from stdin and writes to stdout
+#[test]
+fn test_bed_stdin_stdout_pipe() {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+    
+    let dir = tempfile::tempdir().unwrap();
+    let chain_path = dir.path().join("chain.gz");
+    let mut encoder = GzEncoder::new(std::fs::File::create(&chain_path).unwrap(), Compression::default());
+    encoder.write_all(b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n\n").unwrap();
+    encoder.finish().unwrap();
+    
+    let mut child = Command::new(env!("CARGO_BIN_EXE_fast-crossmap"))
+        .current_dir(dir.path())
+        .args(["bed", chain_path.to_str().unwrap(), "-", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(b"chr1\t100\t200\tfeat1\nchr1\t300\t400\tfeat2\nchr2\t1\t2\tlost\n").unwrap();
+    let output = child.wait_with_output().unwrap();
+    
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "chr1\t5100\t5200\tfeat1\nchr1\t5300\t5400\tfeat2\n"
+    );
+    // Nothing is written next to a stdout output; unmapped records go to stderr
+    assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    assert!(String::from_utf8(output.stderr).unwrap().contains("chr2\t1\t2\tlost"));
+}
+
+/// Several inputs with --output-dir produce one output per input
+#[test]
+fn test_bed_batch_output_dir() {
+    use std::process::Command;
+    
+    let dir = tempfile::tempdir().unwrap();
+    let chain_path = dir.path().join("test.chain");
+    std::fs::write(&chain_path, "chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n\n").unwrap();
+    let first = dir.path().join("first.bed");
+    let second = dir.path().join("second.bed");
+    std::fs::write(&first, "chr1\t100\t200\ta\n").unwrap();
+    std::fs::write(&second, "chr1\t300\t400\tb\nchr2\t1\t2\tlost\n").unwrap();
+    let out_dir = dir.path().join("lifted");
+    
+    let status = Command::new(env!("CARGO_BIN_EXE_fast-crossmap"))
+        .arg("bed")
+        .arg(&chain_path)
+        .arg(&first)
+        .arg(&second)
+        .arg("--output-dir")
+        .arg(&out_dir)
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    
+    assert!(status.success());
+    assert_eq!(std::fs::read_to_string(out_dir.join("first.bed")).unwrap(), "chr1\t5100\t5200\ta\n");
+    assert_eq!(std::fs::read_to_string(out_dir.join("second.bed")).unwrap(), "chr1\t5300\t5400\tb\n");
+    assert!(out_dir.join("second.bed.unmap").exists());
+}
+
+/// Float scores pass through; --strict-score rejects out-of-range integers
+#[test]
+fn test_bed_strict_score() {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let input_path = dir.path().join("scores.bed");
+    let output_path = dir.path().join("out.bed");
+    let unmap_path = dir.path().join("out.bed.unmap");
+    std::fs::write(&input_path, "chr1\t100\t200\tsignal\t0.8125\t+\nchr1\t300\t400\tpeak\t2500\t+\n").unwrap();
+    
+    // Lenient (default): both records are converted with the score untouched
+    for threads in [1, 4] {
+        let options = BedConvertOptions { threads, strict_score: false, ..Default::default() };
+        let stats = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+        assert_eq!(stats.success, 2);
+        assert_eq!(
+            std::fs::read_to_string(&output_path).unwrap(),
+            "chr1\t5100\t5200\tsignal\t0.8125\t+\nchr1\t5300\t5400\tpeak\t2500\t+\n"
+        );
+    }
+    
+    // Strict: the integer score 2500 is an error, in both code paths
+    for threads in [1, 4] {
+        let options = BedConvertOptions { threads, strict_score: true, ..Default::default() };
+        let result = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options);
+        assert!(matches!(result, Err(BedParseError::ScoreOutOfRange { score: 2500, .. })));
+    }
+}
+
+/// Per-thread part files give the same output as the collecting parallel path
+#[test]
+fn test_bed_per_thread_output_matches() {
+    use std::fmt::Write as _;
+    
+    let dir = tempfile::tempdir().unwrap();
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 100000 + 0 100000 chr1 200000 + 5000 105000 1\n50000 10 10\n49990\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    // Several chunks' worth of records; some fall into the chain gap or an unknown chromosome
+    let mut input = String::from("track name=test\n");
+    for i in 0..35_000u64 {
+        let start = (i * 7) % 99_000;
+        let chrom = if i % 997 == 0 { "chr2" } else { "chr1" };
+        writeln!(input, "{}\t{}\t{}\tr{}", chrom, start, start + 20, i).unwrap();
+    }
+    let input_path = dir.path().join("many.bed");
+    std::fs::write(&input_path, input).unwrap();
+    
+    let run = |per_thread_output: bool, name: &str| {
+        let output_path = dir.path().join(name);
+        let unmap_path = dir.path().join(format!("{}.unmap", name));
+        let options = BedConvertOptions { threads: 4, per_thread_output, ..Default::default() };
+        let stats = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+        (stats, std::fs::read_to_string(&output_path).unwrap(), std::fs::read_to_string(&unmap_path).unwrap())
+    };
+    
+    let (collected_stats, collected_out, collected_unmap) = run(false, "collected.bed");
+    let (part_stats, part_out, part_unmap) = run(true, "parts.bed");
+    
+    assert_eq!(part_stats.total, 35_000);
+    assert_eq!(part_stats.success, collected_stats.success);
+    assert_eq!(part_stats.failed, collected_stats.failed);
+    assert!(part_stats.failed > 0);
+    assert_eq!(part_out, collected_out);
+    assert_eq!(part_unmap, collected_unmap);
+    
+    // Part files are cleaned up
+    let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().contains(".part_"))
+        .collect();
+    assert!(leftovers.is_empty());
+}
+
+/// Output does not depend on the parallel chunk size
+#[test]
+fn test_bed_chunk_size_output_matches() {
+    use std::fmt::Write as _;
+    
+    let dir = tempfile::tempdir().unwrap();
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let mut input = String::new();
+    for i in 0..500u64 {
+        let chrom = if i % 7 == 0 { "chr2" } else { "chr1" };
+        writeln!(input, "{}\t{}\t{}\tr{}", chrom, i * 10, i * 10 + 5, i).unwrap();
+    }
+    let input_path = dir.path().join("in.bed");
+    std::fs::write(&input_path, input).unwrap();
+    
+    let run = |chunk_size: usize, per_thread_output: bool| {
+        let output_path = dir.path().join(format!("out_{}_{}.bed", chunk_size, per_thread_output));
+        let unmap_path = dir.path().join(format!("out_{}_{}.bed.unmap", chunk_size, per_thread_output));
+        let options = BedConvertOptions { threads: 4, chunk_size, per_thread_output, ..Default::default() };
+        let stats = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+        assert_eq!((stats.total, stats.failed), (500, 72));
+        (std::fs::read_to_string(&output_path).unwrap(), std::fs::read_to_string(&unmap_path).unwrap())
+    };
+    
+    let expected = run(fast_crossmap::formats::bed::DEFAULT_CHUNK_SIZE, false);
+    for chunk_size in [0, 1, 3, 64, 499] {
+        for per_thread_output in [false, true] {
+            assert_eq!(run(chunk_size, per_thread_output), expected, "chunk size {}", chunk_size);
+        }
+    }
+}
+
+/// Zero-length records follow `zero_length_policy`; Warn keeps the old pass-through behavior
+#[test]
+fn test_bed_zero_length_policy() {
+    use fast_crossmap::formats::bed::ZeroLengthPolicy;
+    
+    let dir = tempfile::tempdir().unwrap();
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let input_path = dir.path().join("zero.bed");
+    std::fs::write(&input_path, "chr1\t100\t200\ta\nchr1\t300\t300\tb\n").unwrap();
+    
+    for threads in [1, 2] {
+        let run = |policy: ZeroLengthPolicy| {
+            let output_path = dir.path().join(format!("{:?}_{}.bed", policy, threads));
+            let unmap_path = dir.path().join(format!("{:?}_{}.bed.unmap", policy, threads));
+            let options = BedConvertOptions { threads, zero_length_policy: policy, ..Default::default() };
+            let stats = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+            let output = std::fs::read_to_string(&output_path).unwrap();
+            let unmap = std::fs::read_to_string(&unmap_path).unwrap();
+            (stats, output, unmap)
+        };
+        
+        let (stats, output, unmap) = run(ZeroLengthPolicy::Warn);
+        assert_eq!(ZeroLengthPolicy::default(), ZeroLengthPolicy::Warn);
+        assert_eq!((stats.total, stats.success, stats.failed, stats.skipped), (2, 2, 0, 0));
+        assert_eq!(output, "chr1\t5100\t5200\ta\nchr1\t5300\t5300\tb\n");
+        assert_eq!(unmap, "");
+        
+        let (stats, output, unmap) = run(ZeroLengthPolicy::Fail);
+        assert_eq!((stats.success, stats.failed, stats.skipped), (1, 1, 0));
+        assert_eq!(output, "chr1\t5100\t5200\ta\n");
+        assert_eq!(unmap, "chr1\t300\t300\tb\n");
+        
+        let (stats, output, unmap) = run(ZeroLengthPolicy::Skip);
+        assert_eq!((stats.success, stats.failed, stats.skipped), (1, 0, 1));
+        assert_eq!(output, "chr1\t5100\t5200\ta\n");
+        assert_eq!(unmap, "");
+        
+        let (stats, output, _) = run(ZeroLengthPolicy::Expand);
+        assert_eq!((stats.success, stats.failed, stats.skipped), (2, 0, 0));
+        assert_eq!(output, "chr1\t5100\t5200\ta\nchr1\t5300\t5301\tb\n");
+    }
+}
+
+/// 1-based records map to the same intervals as their 0-based equivalents
+#[test]
+fn test_bed_one_based_matches_zero_based() {
+    use fast_crossmap::formats::bed::{CoordinateSystem, ZeroLengthPolicy};
+    
+    let dir = tempfile::tempdir().unwrap();
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let zero_path = dir.path().join("zero.bed");
+    let one_path = dir.path().join("one.bed");
+    std::fs::write(&zero_path, "chr1\t99\t200\ta\t0\t+\nchr1\t0\t1\tb\nchr1\t9999\t10000\tc\nchr2\t99\t200\td\n").unwrap();
+    std::fs::write(&one_path, "chr1\t100\t200\ta\t0\t+\nchr1\t1\t1\tb\nchr1\t10000\t10000\tc\nchr2\t100\t200\td\n").unwrap();
+    
+    for threads in [1, 2] {
+        let run = |input: &PathBuf, coord_system: CoordinateSystem| {
+            let output_path = dir.path().join(format!("{:?}_{}.bed", coord_system, threads));
+            let unmap_path = dir.path().join(format!("{:?}_{}.bed.unmap", coord_system, threads));
+            let options = BedConvertOptions { threads, coord_system, ..Default::default() };
+            let stats = convert_bed_with_options(input, &output_path, &unmap_path, &mapper, &options).unwrap();
+            let output = std::fs::read_to_string(&output_path).unwrap();
+            let unmap = std::fs::read_to_string(&unmap_path).unwrap();
+            (stats, output, unmap)
+        };
+        
+        let (zero_stats, zero_output, zero_unmap) = run(&zero_path, CoordinateSystem::ZeroBased);
+        let (one_stats, one_output, one_unmap) = run(&one_path, CoordinateSystem::OneBased);
+        assert_eq!((zero_stats.success, zero_stats.failed), (one_stats.success, one_stats.failed));
+        assert_eq!(zero_output, "chr1\t5099\t5200\ta\t0\t+\nchr1\t5000\t5001\tb\nchr1\t14999\t15000\tc\n");
+        assert_eq!(one_output, "chr1\t5100\t5200\ta\t0\t+\nchr1\t5001\t5001\tb\nchr1\t15000\t15000\tc\n");
+        assert_eq!(zero_unmap, "chr2\t99\t200\td\n");
+        // Unmapped records keep their original 1-based coordinates
+        assert_eq!(one_unmap, "chr2\t100\t200\td\n");
+    }
+    
+    // Position 0 does not exist in 1-based coordinates
+    std::fs::write(&one_path, "chr1\t0\t10\ta\n").unwrap();
+    let output_path = dir.path().join("invalid.bed");
+    let unmap_path = dir.path().join("invalid.bed.unmap");
+    let options = BedConvertOptions { coord_system: CoordinateSystem::OneBased, ..Default::default() };
+    let stats = convert_bed_with_options(&one_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+    assert_eq!((stats.success, stats.failed), (0, 1));
+    assert_eq!(std::fs::read_to_string(&unmap_path).unwrap(), "chr1\t0\t10\ta\n");
+    
+    // A zero-length 1-based record rejected by ZeroLengthPolicy::Fail keeps its 1-based start
+    std::fs::write(&one_path, "chr1\t100\t200\ta\nchr1\t301\t300\tb\n").unwrap();
+    let options = BedConvertOptions {
+        coord_system: CoordinateSystem::OneBased,
+        zero_length_policy: ZeroLengthPolicy::Fail,
+        ..Default::default()
+    };
+    let stats = convert_bed_with_options(&one_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+    assert_eq!((stats.success, stats.failed), (1, 1));
+    assert_eq!(std::fs::read_to_string(&output_path).unwrap(), "chr1\t5100\t5200\ta\n");
+    assert_eq!(std::fs::read_to_string(&unmap_path).unwrap(), "chr1\t301\t300\tb\n");
+}
+
+/// Run `f`, collecting the messages of the WARN tracing events it emits
+fn capture_warnings<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    
+    struct WarnCollector(Arc<Mutex<Vec<String>>>);
+    
+    struct EventVisitor(String);
+    
+    impl Visit for EventVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0.push_str(&format!("{:?}", value));
+            } else {
+                self.0.push_str(&format!(" {}={:?}", field.name(), value));
+            }
+        }
+    }
+    
+    impl<S: Subscriber> Layer<S> for WarnCollector {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            if *event.metadata().level() == Level::WARN {
+                let mut visitor = EventVisitor(String::new());
+                event.record(&mut visitor);
+                self.0.lock().unwrap().push(visitor.0);
+            }
+        }
+    }
+    
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = tracing_subscriber::registry().with(WarnCollector(warnings.clone()));
+    let result = tracing::subscriber::with_default(subscriber, f);
+    let warnings = warnings.lock().unwrap().clone();
+    (result, warnings)
+}
+
+/// Names longer than max_name_length warn; truncate_names also shortens them
+#[test]
+fn test_bed_max_name_length() {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let input_path = dir.path().join("names.bed");
+    std::fs::write(&input_path, "chr1\t100\t200\tabcdefghij\t0\t+\nchr1\t300\t400\tabcdefghijk\t0\t-\nchr2\t1\t2\tabcdefghijklmn\n").unwrap();
+    
+    let run = |max_name_length: Option<usize>, truncate_names: bool| {
+        let output_path = dir.path().join(format!("{:?}_{}.bed", max_name_length, truncate_names));
+        let unmap_path = dir.path().join(format!("{:?}_{}.bed.unmap", max_name_length, truncate_names));
+        let options = BedConvertOptions { threads: 1, max_name_length, truncate_names, ..Default::default() };
+        let (stats, warnings) = capture_warnings(|| {
+            convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap()
+        });
+        assert_eq!((stats.success, stats.failed), (2, 1));
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        let unmap = std::fs::read_to_string(&unmap_path).unwrap();
+        (output, unmap, warnings)
+    };
+    
+    let (output, _, warnings) = run(None, false);
+    assert!(output.contains("\tabcdefghijk\t"));
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    
+    // Warn only: the name at the limit is silent, longer ones are reported and kept
+    let (output, unmap, warnings) = run(Some(10), false);
+    assert_eq!(output, "chr1\t5100\t5200\tabcdefghij\t0\t+\nchr1\t5300\t5400\tabcdefghijk\t0\t-\n");
+    assert_eq!(unmap, "chr2\t1\t2\tabcdefghijklmn\n");
+    assert_eq!(warnings.len(), 2, "{:?}", warnings);
+    assert!(warnings[0].contains("abcdefghijk"));
+    
+    // Truncate: converted records are cut to 10 characters, unmapped ones keep the original
+    let (output, unmap, warnings) = run(Some(10), true);
+    assert_eq!(output, "chr1\t5100\t5200\tabcdefghij\t0\t+\nchr1\t5300\t5400\tabcdefg...\t0\t-\n");
+    assert_eq!(unmap, "chr2\t1\t2\tabcdefghijklmn\n");
+    assert_eq!(warnings.len(), 2, "{:?}", warnings);
+}
+
+#[test]
+fn test_bed_verbose_errors() {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let input_path = dir.path().join("bad.bed");
+    std::fs::write(&input_path, "# header\nchr1\t100\t200\ta\nchr1\t1x0\t200\tb\nchr1\t100\n").unwrap();
+    let output_path = dir.path().join("out.bed");
+    let unmap_path = dir.path().join("out.bed.unmap");
+    
+    // Parallel settings are overridden so offsets can be reported
+    let options = BedConvertOptions { threads: 4, verbose_errors: true, ..Default::default() };
+    let stats = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+    assert_eq!((stats.total, stats.success, stats.failed), (3, 1, 2));
+    let unmap = std::fs::read_to_string(&unmap_path).unwrap();
+    assert_eq!(unmap, "\
+# Invalid number in field start: 1x0 (byte 24): chr1\t1x0\t200\tb
+chr1\t1x0\t200\tb
+# Too few fields: expected at least 3, found 2 (byte 39): chr1\t100
+chr1\t100
+");
+    
+    // Without the flag only the lines are written
+    let options = BedConvertOptions::default();
+    convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+    let unmap = std::fs::read_to_string(&unmap_path).unwrap();
+    assert_eq!(unmap, "chr1\t1x0\t200\tb\nchr1\t100\n");
+    
+    // Fatal errors carry the line and offset
+    std::fs::write(&input_path, "chr1\t100\t200\ta\t5000\n").unwrap();
+    let options = BedConvertOptions { strict_score: true, verbose_errors: true, ..Default::default() };
+    match convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options) {
+        Err(BedParseError::WithContext(context)) => {
+            assert!(matches!(context.error, BedParseError::ScoreOutOfRange { score: 5000, .. }));
+            assert_eq!(context.line, "chr1\t100\t200\ta\t5000");
+            assert_eq!(context.offset, 0);
+        }
+        other => panic!("expected an error with context, got {:?}", other),
+    }
+}
+
+/// Mixed BED3/BED6 input comes out with a uniform column count
+#[test]
+fn test_bed_normalize_field_count() {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let input_path = dir.path().join("mixed.bed");
+    let output_path = dir.path().join("out.bed");
+    let unmap_path = dir.path().join("out.bed.unmap");
+    std::fs::write(&input_path, "chr1\t100\t200\nchr1\t300\t400\tpeak\t500\t-\nchr2\t1\t2\n").unwrap();
+    
+    for threads in [1, 4] {
+        let options = BedConvertOptions { threads, normalize_field_count: Some(6), ..Default::default() };
+        let stats = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+        assert_eq!((stats.success, stats.failed), (2, 1));
+        assert_eq!(
+            std::fs::read_to_string(&output_path).unwrap(),
+            "chr1\t5100\t5200\t.\t0\t.\nchr1\t5300\t5400\tpeak\t500\t-\n"
+        );
+        // Unmapped records keep their original columns
+        assert_eq!(std::fs::read_to_string(&unmap_path).unwrap(), "chr2\t1\t2\n");
+    }
+}
+
+/// Multi-mapped records go only to `multimap_output` when it is set
+#[test]
+fn test_bed_multimap_output() {
+    let dir = tempfile::tempdir().unwrap();
+    // chr1:500-1000 is covered by both chains
+    let chain = fast_crossmap::core::parse_chain_bytes(b"\
+chain 1000 chr1 10000 + 0 1000 chr1 20000 + 5000 6000 1
+1000
+
+chain 500 chr1 10000 + 500 1000 chr2 20000 + 0 500 2
+500
+").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let input_path = dir.path().join("in.bed");
+    let output_path = dir.path().join("out.bed");
+    let unmap_path = dir.path().join("out.bed.unmap");
+    let multimap_path = dir.path().join("out.multimap.bed");
+    std::fs::write(&input_path, "chr1\t100\t200\tsingle\nchr1\t600\t700\tmulti\nchr3\t1\t2\tunmapped\n").unwrap();
+    
+    for (threads, per_thread_output) in [(1, false), (4, false), (4, true)] {
+        // Without the option both hits stay in the main output
+        let options = BedConvertOptions { threads, per_thread_output, ..Default::default() };
+        let stats = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+        assert_eq!((stats.success, stats.multi_map, stats.failed), (2, 1, 1));
+        assert_eq!(std::fs::read_to_string(&output_path).unwrap().matches("multi").count(), 2);
+        
+        let options = BedConvertOptions {
+            threads,
+            per_thread_output,
+            multimap_output: Some(multimap_path.clone()),
+            ..Default::default()
+        };
+        let stats = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+        assert_eq!((stats.success, stats.multi_map, stats.failed), (2, 1, 1));
+        assert_eq!(std::fs::read_to_string(&output_path).unwrap(), "chr1\t5100\t5200\tsingle\n");
+        let multimap = std::fs::read_to_string(&multimap_path).unwrap();
+        let mut multimap_lines: Vec<&str> = multimap.lines().collect();
+        multimap_lines.sort_unstable();
+        assert_eq!(multimap_lines, vec!["chr1\t5600\t5700\tmulti", "chr2\t100\t200\tmulti"]);
+        assert_eq!(std::fs::read_to_string(&unmap_path).unwrap(), "chr3\t1\t2\tunmapped\n");
+        
+        // No part files are left behind
+        let leftovers = std::fs::read_dir(dir.path()).unwrap().filter(|e| {
+            e.as_ref().unwrap().file_name().to_string_lossy().contains(".part_")
+        });
+        assert_eq!(leftovers.count(), 0);
+    }
+}
+
+#[test]
+fn test_bed_channel_matches_sequential() {
+    use std::fmt::Write as _;
+    
+    let dir = tempfile::tempdir().unwrap();
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    // Mapped, unmapped and invalid records with a header in the middle
+    let mut input = String::from("track name=test\n");
+    for i in 0..500u64 {
+        match i % 50 {
+            13 => writeln!(input, "chr2\t{}\t{}\tunmapped{}", i, i + 10, i).unwrap(),
+            27 => writeln!(input, "chr1\tx\t{}\tinvalid{}", i, i).unwrap(),
+            49 => writeln!(input, "# comment {}", i).unwrap(),
+            _ => writeln!(input, "chr1\t{}\t{}\tr{}\t0\t+", i * 10, i * 10 + 5, i).unwrap(),
+        }
+    }
+    let input_path = dir.path().join("in.bed");
+    std::fs::write(&input_path, &input).unwrap();
+    
+    let run = |options: &BedConvertOptions| {
+        let output_path = dir.path().join("out.bed");
+        let unmap_path = dir.path().join("out.bed.unmap");
+        let stats = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, options).unwrap();
+        (stats, std::fs::read_to_string(&output_path).unwrap(), std::fs::read_to_string(&unmap_path).unwrap())
+    };
+    let (expected_stats, expected_output, expected_unmap) = run(&BedConvertOptions::default());
+    assert_eq!((expected_stats.total, expected_stats.failed, expected_stats.invalid), (490, 20, 10));
+    
+    for chunk_size in [1, 7, 10_000] {
+        let (stats, output, unmap) = run(&BedConvertOptions { threads: 4, streaming: true, chunk_size, ..Default::default() });
+        assert_eq!(output, expected_output, "chunk_size {}", chunk_size);
+        assert_eq!(unmap, expected_unmap, "chunk_size {}", chunk_size);
+        assert_eq!((stats.total, stats.success, stats.failed, stats.invalid), 
+                   (expected_stats.total, expected_stats.success, expected_stats.failed, expected_stats.invalid));
+    }
+    
+    let output_path = dir.path().join("channel.bed");
+    let unmap_path = dir.path().join("channel.bed.unmap");
+    let stats = convert_bed_channel(&input_path, &output_path, &unmap_path, &mapper, 3).unwrap();
+    assert_eq!(stats.success, expected_stats.success);
+    assert_eq!(std::fs::read_to_string(&output_path).unwrap(), expected_output);
+    
+    // Errors from a worker stop the pipeline
+    std::fs::write(&input_path, "chr1\t1\t2\ta\t5000\n".repeat(100)).unwrap();
+    let options = BedConvertOptions { threads: 4, streaming: true, strict_score: true, chunk_size: 3, ..Default::default() };
+    let err = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap_err();
+    assert!(matches!(err, BedParseError::ScoreOutOfRange { .. }), "{:?}", err);
+}
+
+/// Check that every non-void HTML element is closed in order
+fn assert_balanced_tags(html: &str) {
+    let mut stack: Vec<String> = Vec::new();
+    let mut rest = html;
+    while let Some(open) = rest.find('<') {
+        let close = rest[open..].find('>').expect("unterminated tag") + open;
+        let tag = &rest[open + 1..close];
+        rest = &rest[close + 1..];
+        if tag.starts_with('!') {
+            continue;
+        }
+        let name: String = tag.trim_start_matches('/').chars().take_while(|c| c.is_ascii_alphanumeric()).collect();
+        if name == "meta" {
+            continue;
+        }
+        if tag.starts_with('/') {
+            assert_eq!(stack.pop().as_deref(), Some(name.as_str()), "unexpected </{}>", name);
+        } else {
+            stack.push(name);
+        }
+    }
+    assert!(stack.is_empty(), "unclosed tags: {:?}", stack);
+}
+
+#[test]
+fn test_bed_html_report() {
+    let dir = tempfile::tempdir().unwrap();
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let input_path = dir.path().join("in.bed");
+    let output_path = dir.path().join("out.bed");
+    let unmap_path = dir.path().join("out.bed.unmap");
+    let report_path = dir.path().join("report.html");
+    std::fs::write(&input_path, "chr1\t100\t200\ta\nchr1\t300\t400\tb\nchr2\t1\t2\tc\nchr1\tx\t2\td\n").unwrap();
+    
+    for threads in [1, 4] {
+        let options = BedConvertOptions { threads, generate_report: Some(report_path.clone()), ..Default::default() };
+        let stats = convert_bed_with_options(&input_path, &output_path, &unmap_path, &mapper, &options).unwrap();
+        assert_eq!((stats.total, stats.success, stats.failed, stats.invalid), (4, 2, 2, 1));
+        
+        let html = std::fs::read_to_string(&report_path).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<tr><th>Total records</th><td class=\"num\">4</td></tr>"));
+        assert!(html.contains("<tr><th>Converted</th><td class=\"num\">2 (50.00%)</td></tr>"));
+        assert!(html.contains("<tr><th>Unmapped</th><td class=\"num\">1 (50.00%)</td></tr>"));
+        assert!(html.contains("<tr><th>Invalid record</th><td class=\"num\">1 (50.00%)</td></tr>"));
+        assert!(html.contains("<tr><th>Chains</th><td class=\"num\">1</td></tr>"));
+        assert!(html.contains("<pre>"));
+        assert!(!html.contains("http"), "report must not reference external resources");
+        assert_balanced_tags(&html);
+    }
+}
+
+/// bgzip-compressed input converts like the plain-text file
+#[test]
+fn test_bed_bgzf_input() {
+    use fast_crossmap::core::bgzf::BgzfWriter;
+    use std::io::Write;
+    
+    let chain = fast_crossmap::core::parse_chain_bytes(b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let mut bed = String::from("track name=test\n");
+    for i in 0..5000u64 {
+        let chrom = if i % 7 == 0 { "chr9" } else { "chr1" };
+        bed.push_str(&format!("{}\t{}\t{}\tr{}\t0\t+\n", chrom, i * 2, i * 2 + 10, i));
+    }
+    
+    let dir = tempfile::tempdir().unwrap();
+    let plain = dir.path().join("in.bed");
+    let compressed = dir.path().join("in.bed.gz");
+    std::fs::write(&plain, &bed).unwrap();
+    let mut writer = BgzfWriter::new(std::fs::File::create(&compressed).unwrap());
+    writer.write_all(bed.as_bytes()).unwrap();
+    writer.finish().unwrap();
+    
+    for threads in [1, 4] {
+        let out = |name: &str| dir.path().join(format!("{}_{}.bed", name, threads));
+        let expected = convert_bed(&plain, &out("plain"), &out("plain_unmap"), &mapper, threads).unwrap();
+        let stats = convert_bed(&compressed, &out("bgzf"), &out("bgzf_unmap"), &mapper, threads).unwrap();
+        
+        assert_eq!(
+            (stats.total, stats.success, stats.failed, stats.skipped),
+            (expected.total, expected.success, expected.failed, expected.skipped)
+        );
+        assert_eq!(stats.total, 5000);
+        assert_eq!(std::fs::read_to_string(out("bgzf")).unwrap(), std::fs::read_to_string(out("plain")).unwrap());
+    }
+}
+
+/// Compressed output decompresses to the plain output on every write path
+#[test]
+fn test_bed_compressed_output_round_trip() {
+    use fast_crossmap::core::bgzf::BgzfReader;
+    use fast_crossmap::formats::{convert_bed_with_options, BedConvertOptions};
+    use std::io::Read;
+    
+    let chain = fast_crossmap::core::parse_chain_bytes(b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let mut bed = String::from("track name=test\n");
+    for i in 0..5000u64 {
+        let chrom = if i % 7 == 0 { "chr9" } else { "chr1" };
+        bed.push_str(&format!("{}\t{}\t{}\tr{}\t0\t+\n", chrom, i * 2, i * 2 + 10, i));
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in.bed");
+    std::fs::write(&input, &bed).unwrap();
+    
+    let modes = [
+        BedConvertOptions::default(),
+        BedConvertOptions { threads: 4, chunk_size: 100, ..Default::default() },
+        BedConvertOptions { threads: 4, chunk_size: 100, streaming: true, ..Default::default() },
+        BedConvertOptions { threads: 4, chunk_size: 100, per_thread_output: true, ..Default::default() },
+    ];
+    for (i, options) in modes.into_iter().enumerate() {
+        let out = |name: &str| dir.path().join(format!("{}_{}.bed", name, i));
+        let bgzf_out = dir.path().join(format!("bgzf_{}.bed.gz", i));
+        let expected = convert_bed_with_options(&input, &out("plain"), &out("plain_unmap"), &mapper, &options).unwrap();
+        let compress = BedConvertOptions { compress: true, ..options };
+        let stats = convert_bed_with_options(&input, &bgzf_out, &out("bgzf_unmap"), &mapper, &compress).unwrap();
+        assert_eq!((stats.total, stats.success, stats.failed), (expected.total, expected.success, expected.failed));
+        
+        let mut text = String::new();
+        BgzfReader::new(std::fs::File::open(&bgzf_out).unwrap()).read_to_string(&mut text).unwrap();
+        assert_eq!(text, std::fs::read_to_string(out("plain")).unwrap(), "mode {}", i);
+        assert_eq!(std::fs::read_to_string(out("bgzf_unmap")).unwrap(), std::fs::read_to_string(out("plain_unmap")).unwrap());
+    }
+}
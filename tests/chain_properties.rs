@@ -404,6 +404,60 @@ proptest! {
 }
 
 
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(100))]
+    
+    /// **Property: 块偏移公式**
+    /// 
+    /// For any block B and position P in `[B.source_start, B.source_end)`,
+    /// mapping `[P, P + 1)` lands on `B.target_start + (P - B.source_start)`
+    /// for positive-strand blocks and `B.target_end - (P - B.source_start) - 1`
+    /// for negative-strand blocks (target coordinates already flipped).
+    #[test]
+    fn prop_block_offset_formula(
+        header in arb_chain_header().prop_map(|mut h| {
+            // Reference (source) side of UCSC chains is always +
+            h.source_strand = Strand::Plus;
+            h
+        }),
+        pick in any::<prop::sample::Index>(),
+    ) {
+        use fast_crossmap::core::{chroms_equivalent, ChainIndex, ChromStyle, CoordinateMapper};
+        
+        let (data_lines, expected_blocks) = generate_data_lines(&header);
+        if expected_blocks.is_empty() {
+            return Ok(());
+        }
+        let mut content = header.to_header_line();
+        content.push('\n');
+        for line in &data_lines {
+            content.push_str(line);
+            content.push('\n');
+        }
+        let chain_file = parse_chain_bytes(content.as_bytes()).unwrap();
+        let blocks = chain_file.blocks.clone();
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain_file), ChromStyle::AsIs);
+        
+        for block in &blocks {
+            let offset = pick.index((block.source_end - block.source_start) as usize) as u64;
+            let pos = block.source_start + offset;
+            let expected = match block.target_strand {
+                Strand::Plus => block.target_start + offset,
+                Strand::Minus => block.target_end - offset - 1,
+            };
+            
+            let segments = mapper.map(&block.source_chrom, pos, pos + 1, Strand::Plus).unwrap();
+            prop_assert_eq!(segments.len(), 1);
+            let target = &segments[0].target;
+            // AsIs follows the query's chr prefix style
+            prop_assert!(chroms_equivalent(&target.chrom, &block.target_chrom));
+            prop_assert_eq!(target.strand, block.target_strand);
+            prop_assert_eq!((target.start, target.end), (expected, expected + 1));
+        }
+    }
+}
+
+
 /// **Property 12: 压缩文件透明处理**
 /// 
 /// For any chain file, parsing the plain text version and the gzip/bzip2
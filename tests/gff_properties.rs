@@ -4,7 +4,7 @@
 //! **Validates: Requirements 6.1, 6.2, 6.3, 6.4, 6.5, 6.6, 6.7**
 
 use fast_crossmap::core::{ChainIndex, CoordinateMapper, ChromStyle, Strand};
-use fast_crossmap::formats::gff::{GffRecordView, convert_gff};
+use fast_crossmap::formats::gff::{GffRecordView, GffConvertOptions, GffVersion, convert_gff, convert_gff_with_options};
 use proptest::prelude::*;
 use std::path::PathBuf;
 
@@ -322,3 +322,266 @@ chr2\thavana\tCDS\t200001\t200100\t.\t-\t0\tID=cds1
     let _ = std::fs::remove_file(fast_output.with_extension("gff.unmap"));
     let _ = std::fs::remove_file(cross_output.with_extension("gff.unmap"));
 }
+
+/// --rename-attr renames attribute keys in lifted records only
+#[test]
+fn test_gff_rename_attributes() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("genes.gtf");
+    let output_path = dir.path().join("lifted.gtf");
+    std::fs::write(&input_path, "\
+chr1\thavana\texon\t101\t200\t.\t+\t.\tgene_id \"G1\"; transcript_id \"T1\";
+chr2\thavana\texon\t101\t200\t.\t+\t.\tgene_id \"G2\"; transcript_id \"T2\";
+").unwrap();
+    
+    let options = GffConvertOptions {
+        rename_attributes: [("gene_id", "ID"), ("transcript_id", "Parent")]
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect(),
+        ..Default::default()
+    };
+    let stats = convert_gff_with_options(&input_path, &output_path, &mapper, &options).unwrap();
+    assert_eq!((stats.success, stats.failed), (1, 1));
+    
+    assert_eq!(
+        std::fs::read_to_string(&output_path).unwrap(),
+        "chr1\thavana\texon\t5101\t5200\t.\t+\t.\tID \"G1\"; Parent \"T1\";\n"
+    );
+    let unmap = std::fs::read_to_string(output_path.with_extension("gff.unmap")).unwrap();
+    assert!(unmap.contains("gene_id \"G2\""));
+}
+
+/// A GFF3 file declaring version 2 gets a corrected pragma in the output
+#[test]
+fn test_gff_version_pragma_correction() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("genes.gff");
+    std::fs::write(&input_path, "##gff-version 2\n#comment\nchr1\t.\tgene\t101\t200\t.\t+\t.\tID=g1\n").unwrap();
+    
+    for threads in [1, 4] {
+        let output_path = dir.path().join(format!("auto_{}.gff", threads));
+        let options = GffConvertOptions { threads, ..Default::default() };
+        convert_gff_with_options(&input_path, &output_path, &mapper, &options).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&output_path).unwrap(),
+            "##gff-version 3\n#comment\nchr1\t.\tgene\t5101\t5200\t.\t+\t.\tID=g1\n"
+        );
+    }
+    
+    let output_path = dir.path().join("v2.gff");
+    let options = GffConvertOptions { gff_version: GffVersion::V2, ..Default::default() };
+    convert_gff_with_options(&input_path, &output_path, &mapper, &options).unwrap();
+    assert!(std::fs::read_to_string(&output_path).unwrap().starts_with("##gff-version 2\n#comment\n"));
+}
+
+/// --strict-input sends invalid records to unmap tagged InvalidRecord
+#[test]
+fn test_gff_strict_input() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("genes.gff");
+    std::fs::write(&input_path, "\
+##gff-version 3
+chr1\t.\tgene\t101\t200\t.\t+\t.\tID=ok
+chr1\t.\tCDS\t101\t200\t.\t+\t5\tID=bad_phase
+chr1\t.\tgene\t300\t250\t.\t+\t.\tID=bad_range
+\t.\tgene\t101\t200\t.\t+\t.\tID=no_seqid
+chr1\t.\tgene\t401\t500\t.\t?\t.\tID=unknown_strand
+").unwrap();
+    
+    for threads in [1, 4] {
+        // Lenient (default): the bad phase is lifted as-is
+        let output_path = dir.path().join(format!("lenient_{}.gff", threads));
+        let options = GffConvertOptions { threads, ..Default::default() };
+        let stats = fast_crossmap::formats::gff::convert_gff_with_options(&input_path, &output_path, &mapper, &options).unwrap();
+        assert_eq!((stats.success, stats.failed), (3, 2));
+        
+        let output_path = dir.path().join(format!("strict_{}.gff", threads));
+        let options = GffConvertOptions { threads, strict_input: true, ..Default::default() };
+        let stats = fast_crossmap::formats::gff::convert_gff_with_options(&input_path, &output_path, &mapper, &options).unwrap();
+        assert_eq!((stats.success, stats.failed), (2, 3));
+        
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        assert!(output.contains("ID=ok") && output.contains("ID=unknown_strand"));
+        
+        let unmap = std::fs::read_to_string(output_path.with_extension("gff.unmap")).unwrap();
+        let invalid: Vec<&str> = unmap.lines().filter(|l| l.ends_with("\tInvalidRecord")).collect();
+        assert_eq!(invalid.len(), 3);
+        assert!(invalid.iter().any(|l| l.contains("ID=bad_phase")));
+        assert!(invalid.iter().any(|l| l.contains("ID=bad_range")));
+        assert!(invalid.iter().any(|l| l.contains("ID=no_seqid")));
+    }
+}
+
+/// --multimap-output lifts multi-mapped features into their own file
+#[test]
+fn test_gff_multimap_output() {
+    // chr1:500-1000 is covered by both chains
+    let chain = fast_crossmap::core::parse_chain_bytes(b"\
+chain 1000 chr1 10000 + 0 1000 chr1 20000 + 5000 6000 1
+1000
+
+chain 500 chr1 10000 + 500 1000 chr2 20000 + 0 500 2
+500
+").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("genes.gff");
+    std::fs::write(&input_path, "\
+##gff-version 3
+chr1\t.\tgene\t101\t200\t.\t+\t.\tID=single
+chr1\t.\tgene\t601\t700\t.\t+\t.\tID=multi
+chr3\t.\tgene\t101\t200\t.\t+\t.\tID=unmapped
+").unwrap();
+    
+    for threads in [1, 4] {
+        // Without the option the multi-mapped feature is rejected
+        let output_path = dir.path().join(format!("plain_{}.gff", threads));
+        let options = GffConvertOptions { threads, ..Default::default() };
+        let stats = convert_gff_with_options(&input_path, &output_path, &mapper, &options).unwrap();
+        assert_eq!((stats.success, stats.failed, stats.multi_map), (1, 2, 0));
+        
+        let output_path = dir.path().join(format!("split_{}.gff", threads));
+        let multimap_path = dir.path().join(format!("split_{}.multimap.gff", threads));
+        let options = GffConvertOptions { threads, multimap_output: Some(multimap_path.clone()), ..Default::default() };
+        let stats = convert_gff_with_options(&input_path, &output_path, &mapper, &options).unwrap();
+        assert_eq!((stats.success, stats.failed, stats.multi_map), (2, 1, 1));
+        
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        assert!(output.contains("ID=single") && !output.contains("ID=multi"));
+        let multimap = std::fs::read_to_string(&multimap_path).unwrap();
+        let mut multimap_lines: Vec<&str> = multimap.lines().collect();
+        multimap_lines.sort_unstable();
+        assert_eq!(multimap_lines, vec![
+            "chr1\t.\tgene\t5601\t5700\t.\t+\t.\tID=multi",
+            "chr2\t.\tgene\t101\t200\t.\t+\t.\tID=multi",
+        ]);
+        let unmap = std::fs::read_to_string(output_path.with_extension("gff.unmap")).unwrap();
+        assert!(unmap.contains("ID=unmapped") && !unmap.contains("ID=multi"));
+    }
+}
+
+/// Run `f`, collecting the messages of the WARN tracing events it emits
+fn capture_warnings<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    
+    struct WarnCollector(Arc<Mutex<Vec<String>>>);
+    
+    struct MessageVisitor(String);
+    
+    impl Visit for MessageVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0 = format!("{:?}", value);
+            }
+        }
+    }
+    
+    impl<S: Subscriber> Layer<S> for WarnCollector {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            if *event.metadata().level() == Level::WARN {
+                let mut visitor = MessageVisitor(String::new());
+                event.record(&mut visitor);
+                self.0.lock().unwrap().push(visitor.0);
+            }
+        }
+    }
+    
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = tracing_subscriber::registry().with(WarnCollector(warnings.clone()));
+    let result = tracing::subscriber::with_default(subscriber, f);
+    let warnings = warnings.lock().unwrap().clone();
+    (result, warnings)
+}
+
+/// --validate-so-terms warns once per non-SO feature type but still lifts it
+#[test]
+fn test_gff_validate_so_terms() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("genes.gff");
+    std::fs::write(&input_path, "\
+##gff-version 3
+chr1\t.\tgene\t101\t200\t.\t+\t.\tID=g1
+chr1\t.\texon\t101\t150\t.\t+\t.\tParent=g1
+chr1\t.\tCDS\t101\t150\t.\t+\t0\tParent=g1
+chr1\t.\tbogus_feature\t301\t400\t.\t+\t.\tID=b1
+chr1\t.\tbogus_feature\t501\t600\t.\t+\t.\tID=b2
+").unwrap();
+    
+    for threads in [1, 4] {
+        let output_path = dir.path().join(format!("out_{}.gff", threads));
+        let options = GffConvertOptions { threads, validate_so_terms: true, ..Default::default() };
+        let (stats, warnings) = capture_warnings(|| {
+            convert_gff_with_options(&input_path, &output_path, &mapper, &options).unwrap()
+        });
+        assert_eq!((stats.success, stats.failed), (5, 0));
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert!(warnings[0].contains("'bogus_feature'") && warnings[0].contains("2 records"), "{}", warnings[0]);
+        
+        // Off by default
+        let options = GffConvertOptions { threads, ..Default::default() };
+        let (_, warnings) = capture_warnings(|| {
+            convert_gff_with_options(&input_path, &output_path, &mapper, &options).unwrap()
+        });
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+}
+
+/// bgzip-compressed input converts like the plain-text file
+#[test]
+fn test_gff_bgzf_input() {
+    use fast_crossmap::core::bgzf::BgzfWriter;
+    use std::io::Write;
+    
+    let chain = fast_crossmap::core::parse_chain_bytes(b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let mut gff = String::from("##gff-version 3\n");
+    for i in 0..3000u64 {
+        let chrom = if i % 5 == 0 { "chr9" } else { "chr1" };
+        gff.push_str(&format!("{}\tsrc\tgene\t{}\t{}\t.\t+\t.\tID=g{}\n", chrom, i * 3 + 1, i * 3 + 50, i));
+    }
+    
+    let dir = tempfile::tempdir().unwrap();
+    let plain = dir.path().join("in.gff3");
+    let compressed = dir.path().join("in.gff3.gz");
+    std::fs::write(&plain, &gff).unwrap();
+    let mut writer = BgzfWriter::new(std::fs::File::create(&compressed).unwrap());
+    writer.write_all(gff.as_bytes()).unwrap();
+    writer.finish().unwrap();
+    
+    for threads in [1, 4] {
+        let plain_out = dir.path().join(format!("plain_{}.gff3", threads));
+        let bgzf_out = dir.path().join(format!("bgzf_{}.gff3", threads));
+        let expected = convert_gff(&plain, &plain_out, &mapper, threads).unwrap();
+        let stats = convert_gff(&compressed, &bgzf_out, &mapper, threads).unwrap();
+        
+        assert_eq!((stats.total, stats.success, stats.failed), (expected.total, expected.success, expected.failed));
+        assert_eq!((stats.total, stats.failed), (3000, 600));
+        assert_eq!(std::fs::read_to_string(&bgzf_out).unwrap(), std::fs::read_to_string(&plain_out).unwrap());
+    }
+}
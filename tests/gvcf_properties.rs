@@ -137,7 +137,7 @@ chr2\t50501\t.\tA\tG\t40\tPASS\tDP=80\tGT\t1/1
     std::fs::write(&input_path, test_gvcf).unwrap();
     
     // Convert (without reference genome)
-    let stats = convert_gvcf(&input_path, &output_path, &mapper, None::<&PathBuf>, false, 1).unwrap();
+    let stats = convert_gvcf(&input_path, &output_path, &mapper, None::<&PathBuf>, false, None, 1).unwrap();
     
     eprintln!("GVCF conversion stats: total={}, success={}, failed={}, headers={}", 
               stats.total, stats.success, stats.failed, stats.headers);
@@ -165,6 +165,51 @@ chr2\t50501\t.\tA\tG\t40\tPASS\tDP=80\tGT\t1/1
     let _ = std::fs::remove_file(&unmap_path);
 }
 
+/// Test that non-variant blocks below the minimum GQ are routed to the unmap file
+#[test]
+fn test_gvcf_min_gq_filter() {
+    use fast_crossmap::core::parse_chain_bytes;
+    
+    // chr1:0-10000 -> chr1:0-10000 (identity)
+    let chain_data = b"chain 10000 chr1 10000 + 0 10000 chr1 10000 + 0 10000 1\n10000\n";
+    let index = ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap());
+    let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+    
+    let test_gvcf = "\
+##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tSAMPLE1
+chr1\t100\t.\tA\t<NON_REF>\t.\t.\tEND=200\tGT:DP:GQ\t0/0:30:50
+chr1\t201\t.\tC\t<NON_REF>\t.\t.\tEND=300\tGT:DP:GQ\t0/0:5:10
+chr1\t301\t.\tG\tT\t30\tPASS\tDP=20\tGT:DP:GQ\t0/1:20:5
+chr1\t302\t.\tA\t<NON_REF>\t.\t.\tEND=400\tGT:DP\t0/0:8
+";
+    
+    let temp_dir = std::env::temp_dir();
+    let input_path = temp_dir.join("gvcf_min_gq_input.gvcf");
+    let output_path = temp_dir.join("gvcf_min_gq_output.gvcf");
+    let unmap_path = output_path.with_extension("gvcf.unmap");
+    std::fs::write(&input_path, test_gvcf).unwrap();
+    
+    let stats = convert_gvcf(&input_path, &output_path, &mapper, None::<&PathBuf>, false, Some(20), 1).unwrap();
+    
+    assert_eq!(stats.total, 4);
+    assert_eq!(stats.filtered_gq, 1, "Only the GQ=10 block should be filtered");
+    assert_eq!(stats.success, 3, "Variants and blocks without GQ are kept");
+    assert_eq!(stats.failed, 1);
+    
+    let unmap = std::fs::read_to_string(&unmap_path).unwrap();
+    assert!(unmap.contains("chr1\t201\t.\tC\t<NON_REF>\t.\t.\tEND=300\tGT:DP:GQ\t0/0:5:10\tFail(LowGQ)"));
+    
+    // Without a threshold nothing is filtered
+    let stats = convert_gvcf(&input_path, &output_path, &mapper, None::<&PathBuf>, false, None, 1).unwrap();
+    assert_eq!(stats.filtered_gq, 0);
+    assert_eq!(stats.success, 4);
+    
+    let _ = std::fs::remove_file(&input_path);
+    let _ = std::fs::remove_file(&output_path);
+    let _ = std::fs::remove_file(&unmap_path);
+}
+
 /// Test GVCF vs CrossMap comparison
 #[test]
 fn test_gvcf_vs_crossmap() {
@@ -208,7 +253,7 @@ chr2\t50000\t.\tG\t<NON_REF>\t.\t.\tEND=50500
     // Run FastCrossMap
     let index = ChainIndex::from_chain_file(&chain_path).expect("Failed to load chain file");
     let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
-    let stats = convert_gvcf(&input_path, &fast_output, &mapper, None::<&PathBuf>, false, 1).unwrap();
+    let stats = convert_gvcf(&input_path, &fast_output, &mapper, None::<&PathBuf>, false, None, 1).unwrap();
     
     eprintln!("FastCrossMap GVCF: total={}, success={}, failed={}", stats.total, stats.success, stats.failed);
     
@@ -235,3 +280,60 @@ chr2\t50000\t.\tG\t<NON_REF>\t.\t.\tEND=50500
     let _ = std::fs::remove_file(&fast_output);
     let _ = std::fs::remove_file(fast_output.with_extension("gvcf.unmap"));
 }
+
+/// Lifted GVCF carries ##liftover* provenance headers before #CHROM unless disabled
+#[test]
+fn test_gvcf_provenance_headers() {
+    use std::process::Command;
+    
+    let dir = tempfile::tempdir().unwrap();
+    let chain_path = dir.path().join("test.chain");
+    std::fs::write(&chain_path, "chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n\n").unwrap();
+    let ref_path = dir.path().join("ref.fa");
+    std::fs::write(&ref_path, format!(">chr1\n{}\n", "A".repeat(20000))).unwrap();
+    let input_path = dir.path().join("in.gvcf");
+    std::fs::write(&input_path, "\
+##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\tFORMAT\tSAMPLE1
+chr1\t100\t.\tA\t<NON_REF>\t.\t.\tEND=200\tGT\t0/0
+").unwrap();
+    
+    let run = |output: &std::path::Path, extra: &[&str]| {
+        let status = Command::new(env!("CARGO_BIN_EXE_fast-crossmap"))
+            .arg("gvcf")
+            .arg(&chain_path)
+            .arg(&input_path)
+            .arg(&ref_path)
+            .arg(output)
+            .args(extra)
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::fs::read_to_string(output).unwrap()
+    };
+    
+    let output = run(&dir.path().join("out.gvcf"), &[]);
+    let headers: Vec<&str> = output.lines().take_while(|l| !l.starts_with("#CHROM")).collect();
+    let liftover: Vec<(&str, &str)> = headers
+        .iter()
+        .filter(|h| h.starts_with("##liftover"))
+        .map(|h| h.split_once('=').unwrap())
+        .collect();
+    let keys: Vec<&str> = liftover.iter().map(|(k, _)| *k).collect();
+    assert_eq!(keys, vec![
+        "##liftoverProgram", "##liftoverVersion", "##liftoverDate", "##liftoverChain", "##liftoverCommandLine",
+    ]);
+    assert!(liftover.iter().all(|(_, v)| !v.is_empty()), "{:?}", liftover);
+    assert!(headers.contains(&"##liftoverProgram=fast-crossmap"));
+    assert!(headers.contains(&format!("##liftoverVersion={}", env!("CARGO_PKG_VERSION")).as_str()));
+    assert!(headers.contains(&"##liftoverChain=test.chain"));
+    assert!(!output.contains("##liftOverProgram"));
+    // The data line still follows the column header
+    assert!(output.lines().skip_while(|l| !l.starts_with("#CHROM")).nth(1).unwrap().starts_with("chr1\t5100\t"));
+    
+    let output = run(&dir.path().join("plain.gvcf"), &["--no-provenance"]);
+    assert!(!output.contains("##liftover"));
+    assert_eq!(output.matches("##liftOverProgram=FastCrossMap").count(), 1);
+    assert!(output.contains("#CHROM"));
+}
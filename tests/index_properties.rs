@@ -68,6 +68,7 @@ fn create_chain_file(intervals_by_chrom: HashMap<String, Vec<(u64, u64)>>) -> Ch
                 target_start: start,
                 target_end: end,
                 target_strand: Strand::Plus,
+                score: 0,
             });
         }
     }
@@ -76,6 +77,9 @@ fn create_chain_file(intervals_by_chrom: HashMap<String, Vec<(u64, u64)>>) -> Ch
         blocks,
         target_chrom_sizes,
         source_chrom_sizes,
+        chain_scores: Vec::new(),
+        removed_duplicates: 0,
+        block_lines: Vec::new(),
     }
 }
 
@@ -158,6 +162,31 @@ proptest! {
         }
     }
     
+    /// Property: query_intervals returns intervals sorted by (start, stop)
+    #[test]
+    fn prop_query_intervals_sorted(
+        // Unsorted and overlapping, as with several chains on one chromosome
+        raw in prop::collection::vec((0u64..10000, 1u64..2000), 1..30),
+        query_start in 0u64..10000,
+        query_len in 1u64..10000,
+    ) {
+        let query_end = query_start + query_len;
+        let intervals: Vec<(u64, u64)> = raw.into_iter().map(|(start, len)| (start, start + len)).collect();
+        
+        let mut intervals_map = HashMap::new();
+        intervals_map.insert("chr1".to_string(), intervals);
+        let index = ChainIndex::from_chain_data(create_chain_file(intervals_map));
+        
+        let results = index.query_intervals("chr1", query_start, query_end);
+        for pair in results.windows(2) {
+            prop_assert!(
+                (pair[0].start, pair[0].stop) <= (pair[1].start, pair[1].stop),
+                "[{}, {}) returned before [{}, {})",
+                pair[0].start, pair[0].stop, pair[1].start, pair[1].stop
+            );
+        }
+    }
+    
     /// Property: Query beyond all intervals returns empty results
     #[test]
     fn prop_query_beyond_intervals_returns_empty(
@@ -36,9 +36,13 @@ fn create_single_block_chain(
             target_start,
             target_end,
             target_strand,
+            score: 0,
         }],
         target_chrom_sizes,
         source_chrom_sizes,
+        chain_scores: Vec::new(),
+        removed_duplicates: 0,
+        block_lines: Vec::new(),
     }
 }
 
@@ -331,4 +335,70 @@ proptest! {
             prop_assert!(results.is_empty(), "No intersection means no results");
         }
     }
+    
+    /// Property: map_batch returns what map returns for each record
+    #[test]
+    fn prop_map_batch_matches_map(
+        t_start in 0u64..100000,
+        minus in any::<bool>(),
+        queries in prop::collection::vec((0u64..3, 0u64..20000, 0u64..500), 0..50),
+    ) {
+        let strand = if minus { Strand::Minus } else { Strand::Plus };
+        let chain_file = create_single_block_chain(
+            "chr1", 5000, 15000,
+            "chr2", t_start, t_start + 10000,
+            strand,
+        );
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain_file), ChromStyle::AsIs);
+        
+        let records: Vec<(String, u64, u64, Strand)> = queries
+            .iter()
+            .map(|&(chrom, start, len)| {
+                let chrom = ["chr1", "1", "chr9"][chrom as usize].to_string();
+                (chrom, start, start + len, if len % 2 == 0 { Strand::Plus } else { Strand::Minus })
+            })
+            .collect();
+        let batch = mapper.map_batch(&records);
+        prop_assert_eq!(batch.len(), records.len());
+        for (record, result) in records.iter().zip(&batch) {
+            prop_assert_eq!(result, &mapper.map(&record.0, record.1, record.2, record.3));
+        }
+    }
+    
+    /// Property: map_inverse lifts a mapped segment back onto the query
+    #[test]
+    fn prop_map_inverse_round_trip(
+        s_start in 0u64..100000,
+        block_size in 100u64..10000,
+        t_start in 0u64..100000,
+        query_offset in 0u64..10000,
+        query_len in 1u64..200,
+        minus in any::<bool>(),
+    ) {
+        let s_end = s_start + block_size;
+        let t_end = t_start + block_size;
+        let q_start = s_start + query_offset % block_size;
+        let q_end = q_start + query_len;
+        let strand = if minus { Strand::Minus } else { Strand::Plus };
+        
+        let chain_file = create_single_block_chain(
+            "chr1", s_start, s_end,
+            "chr2", t_start, t_end,
+            strand,
+        );
+        let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain_file), ChromStyle::AsIs);
+        
+        let results = mapper.map("chr1", q_start, q_end, Strand::Plus).unwrap();
+        prop_assert_eq!(results.len(), 1);
+        let seg = &results[0];
+        
+        let inverse = mapper.map_inverse(&seg.target.chrom, seg.target.start, seg.target.end, Strand::Plus).unwrap();
+        prop_assert_eq!(inverse.len(), 1);
+        let back = &inverse[0].target;
+        prop_assert_eq!(back.chrom.as_str(), "chr1");
+        prop_assert!(intersect_intervals(back.start, back.end, q_start, q_end).is_some());
+        // Inside the block the round trip is exact
+        prop_assert_eq!((back.start, back.end), (seg.source.start, seg.source.end));
+        prop_assert_eq!(back.strand, strand);
+    }
 }
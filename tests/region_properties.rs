@@ -243,3 +243,88 @@ fn test_region_conversion_with_crossmap() {
                  crossmap_lines.len(), fastcm_lines.len());
     }
 }
+
+#[test]
+fn test_region_emit_fragments() {
+    use fast_crossmap::core::{parse_chain_bytes, ChainIndex, ChromStyle, CoordinateMapper};
+    use fast_crossmap::formats::region::{convert_region_with_options, RegionConvertOptions};
+    use tempfile::tempdir;
+    
+    // Source [0,100) -> [1000,1100), gap, source [150,250) -> [1150,1250)
+    let chain = parse_chain_bytes(b"chain 1000 chr1 10000 + 0 250 chr1 10000 + 1000 1250 1\n100 50 50\n100\n").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.bed");
+    std::fs::write(&input, "chr1\t0\t250\tsplit\t0\t+\nchr1\t10\t50\twhole\t0\t+\n").unwrap();
+    
+    // Dedicated fragments file; the split region fails the 0.85 ratio (200/250)
+    let output = dir.path().join("out.bed");
+    let fragments = dir.path().join("fragments.bed");
+    let options = RegionConvertOptions {
+        emit_fragments: true,
+        fragments_output: Some(fragments.clone()),
+        ..Default::default()
+    };
+    let stats = convert_region_with_options(&input, &output, &mapper, &options).unwrap();
+    
+    assert_eq!(stats.total_fragments, 2);
+    assert_eq!(stats.success, 1);
+    assert_eq!(stats.low_ratio, 1);
+    // Both fragments of the split record cover 100 of 250 bases
+    assert_eq!(stats.low_coverage, 2);
+    assert_eq!(
+        std::fs::read_to_string(&fragments).unwrap(),
+        "chr1\t1000\t1100\tsplit\t0\t+\t#fragment=1/2\nchr1\t1150\t1250\tsplit\t0\t+\t#fragment=2/2\n"
+    );
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "chr1\t1010\t1050\twhole\t0\t+\tmap_ratio=1.0000\n");
+    
+    // Without a dedicated file, fragments go to the main output
+    let options = RegionConvertOptions { emit_fragments: true, ..Default::default() };
+    let output = dir.path().join("out2.bed");
+    let stats = convert_region_with_options(&input, &output, &mapper, &options).unwrap();
+    assert_eq!(stats.total_fragments, 2);
+    let content = std::fs::read_to_string(&output).unwrap();
+    assert_eq!(content.lines().filter(|l| l.contains("#fragment=")).count(), 2);
+    assert_eq!(content.lines().count(), 3);
+}
+
+#[test]
+fn test_region_emit_ratio() {
+    use fast_crossmap::core::{parse_chain_bytes, ChainIndex, ChromStyle, CoordinateMapper};
+    use fast_crossmap::formats::region::{convert_region_with_options, RegionConvertOptions};
+    use tempfile::tempdir;
+    
+    // Source [0,100) -> chr1 [1000,1100), source [100,160) -> chr2 [0,60)
+    let chain = parse_chain_bytes(b"\
+chain 1000 chr1 10000 + 0 100 chr1 10000 + 1000 1100 1
+100
+
+chain 500 chr1 10000 + 100 160 chr2 1000 + 0 60 2
+60
+").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempdir().unwrap();
+    let input = dir.path().join("in.bed");
+    std::fs::write(&input, "chr1\t0\t100\tfull\nchr1\t50\t150\tpartial\nchr1\t0\t160\tcross\nchr1\t500\t600\tnone\n").unwrap();
+    let output = dir.path().join("out.bed");
+    let unmap = dir.path().join("out.bed.unmap");
+    
+    let options = RegionConvertOptions { min_ratio: 0.4, emit_ratio: true, ..Default::default() };
+    let stats = convert_region_with_options(&input, &output, &mapper, &options).unwrap();
+    assert_eq!((stats.success, stats.cross_chrom, stats.unmapped), (1, 2, 1));
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "chr1\t1000\t1100\tfull\tmap_ratio=1.0000\n");
+    assert_eq!(
+        std::fs::read_to_string(&unmap).unwrap(),
+        // partial: 50 bases on chr1 and 50 on chr2, so the largest fragment is half of the query
+        "chr1\t50\t150\tpartial\tFail\tCrossChroms\tmap_ratio=0.5000\n\
+chr1\t0\t160\tcross\tFail\tCrossChroms\tmap_ratio=0.6250\n\
+chr1\t500\t600\tnone\tFail\tUnmap\tmap_ratio=0.0000\n"
+    );
+    
+    // Without the option, failed records keep the plain reason column
+    let options = RegionConvertOptions { min_ratio: 0.4, ..Default::default() };
+    convert_region_with_options(&input, &output, &mapper, &options).unwrap();
+    assert!(std::fs::read_to_string(&unmap).unwrap().lines().all(|l| !l.contains("map_ratio")));
+}
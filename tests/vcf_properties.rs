@@ -4,7 +4,7 @@
 //! **Validates: Requirements 5.3**
 
 use fast_crossmap::core::{ChainIndex, CoordinateMapper, ChromStyle};
-use fast_crossmap::formats::vcf::{VcfRecordView, convert_vcf, VariantType};
+use fast_crossmap::formats::vcf::{VcfRecordView, VcfConvertOptions, BndUnmapPolicy, convert_vcf, convert_vcf_with_options, VariantType};
 use proptest::prelude::*;
 use std::path::PathBuf;
 
@@ -366,6 +366,63 @@ fn test_vcf_parallel_determinism() {
     eprintln!("VCF parallel determinism test passed: {} records processed", stats_1.total);
 }
 
+/// Test parallel VCF conversion with a reference genome matches sequential
+#[test]
+fn test_vcf_parallel_with_ref_genome() {
+    use fast_crossmap::core::parse_chain_bytes;
+    
+    // chr1:0-1000 -> chr1:100-1100 (shift by 100)
+    let chain_data = b"chain 1000 chr1 1000 + 0 1000 chr1 1100 + 100 1100 1\n1000\n";
+    let index = ChainIndex::from_chain_data(parse_chain_bytes(chain_data).unwrap());
+    let mapper = CoordinateMapper::new(index, ChromStyle::AsIs);
+    
+    let temp_dir = std::env::temp_dir();
+    
+    // Target reference: repeating ACGT pattern
+    let ref_seq: String = "ACGT".repeat(275);
+    let fasta_path = temp_dir.join("vcf_parallel_ref_genome.fa");
+    std::fs::write(&fasta_path, format!(">chr1\n{}\n", ref_seq)).unwrap();
+    
+    let mut test_vcf = String::from("\
+##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+");
+    for i in 0..200u64 {
+        let pos = 1 + i * 4;
+        test_vcf.push_str(&format!("chr1\t{}\trs{}\tN\tT\t30\tPASS\tDP={}\n", pos, i, i));
+    }
+    
+    let input_path = temp_dir.join("vcf_parallel_ref_input.vcf");
+    std::fs::write(&input_path, &test_vcf).unwrap();
+    
+    let output_1 = temp_dir.join("vcf_parallel_ref_output_1.vcf");
+    let output_4 = temp_dir.join("vcf_parallel_ref_output_4.vcf");
+    let stats_1 = convert_vcf(&input_path, &output_1, &mapper, Some(&fasta_path), false, 1).unwrap();
+    let stats_4 = convert_vcf(&input_path, &output_4, &mapper, Some(&fasta_path), false, 4).unwrap();
+    
+    assert_eq!(stats_1.total, stats_4.total);
+    assert_eq!(stats_1.success, stats_4.success);
+    assert_eq!(stats_1.failed, stats_4.failed);
+    assert!(stats_1.success > 0);
+    
+    let content_1 = std::fs::read_to_string(&output_1).unwrap();
+    let content_4 = std::fs::read_to_string(&output_4).unwrap();
+    assert_eq!(content_1, content_4, "Parallel output with reference genome should match sequential");
+    
+    // REF should have been taken from the target reference
+    let first = content_1.lines().find(|l| !l.starts_with('#')).unwrap();
+    let fields: Vec<&str> = first.split('\t').collect();
+    assert_eq!(fields[1], "101");
+    assert_eq!(fields[3], "A");
+    
+    // Cleanup
+    let _ = std::fs::remove_file(&fasta_path);
+    let _ = std::fs::remove_file(&input_path);
+    let _ = std::fs::remove_file(&output_1);
+    let _ = std::fs::remove_file(&output_4);
+    let _ = std::fs::remove_file(output_1.with_extension("vcf.unmap"));
+    let _ = std::fs::remove_file(output_4.with_extension("vcf.unmap"));
+}
 
 /// Test negative strand handling for SNPs
 #[test]
@@ -609,3 +666,624 @@ fn test_vcf_vs_crossmap() {
     let _ = std::fs::remove_file(&vcf_output);
     let _ = std::fs::remove_file(vcf_output.with_extension("vcf.unmap"));
 }
+
+/// --reset-filter / --add-filter rewrite FILTER on lifted records only
+#[test]
+fn test_vcf_filter_reset_and_add() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("filters.vcf");
+    std::fs::write(&input_path, "\
+##fileformat=VCFv4.2
+##FILTER=<ID=LowQual,Description=\"Low quality\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t100\trs1\tA\tG\t10\tLowQual\t.
+chr1\t200\trs2\tA\tG\t50\tPASS\t.
+chr1\t300\trs3\tA\tG\t50\t.\t.
+chr2\t100\trs4\tA\tG\t10\tLowQual\t.
+").unwrap();
+    
+    for threads in [1, 4] {
+        let output_path = dir.path().join(format!("out_{}.vcf", threads));
+        let options = VcfConvertOptions {
+            threads,
+            reset_filter: true,
+            add_filter: Some("LiftedOver".to_string()),
+            ..Default::default()
+        };
+        let stats = convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap();
+        assert_eq!((stats.success, stats.failed), (3, 1));
+        
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        assert!(output.contains("##FILTER=<ID=LiftedOver,"));
+        let filters: Vec<&str> = output
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .map(|l| l.split('\t').nth(6).unwrap())
+            .collect();
+        assert_eq!(filters, vec!["LiftedOver", "LiftedOver", "LiftedOver"]);
+        
+        // Unmapped records keep their original FILTER
+        let unmap = std::fs::read_to_string(output_path.with_extension("vcf.unmap")).unwrap();
+        assert!(unmap.contains("rs4\tA\tG\t10\tLowQual"));
+    }
+    
+    // Without --add-filter, reset only touches non-missing values
+    let output_path = dir.path().join("reset.vcf");
+    let options = VcfConvertOptions { reset_filter: true, ..Default::default() };
+    convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap();
+    let filters: Vec<String> = std::fs::read_to_string(&output_path)
+        .unwrap()
+        .lines()
+        .filter(|l| !l.starts_with('#'))
+        .map(|l| l.split('\t').nth(6).unwrap().to_string())
+        .collect();
+    assert_eq!(filters, vec!["PASS", "PASS", "."]);
+}
+
+/// --info-passthrough / --info-blacklist drop INFO keys from lifted records
+#[test]
+fn test_vcf_info_filtering() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("info.vcf");
+    std::fs::write(&input_path, "\
+##fileformat=VCFv4.2
+##INFO=<ID=DP,Number=1,Type=Integer,Description=\"Depth\">
+##INFO=<ID=AF,Number=A,Type=Float,Description=\"Allele frequency\">
+##INFO=<ID=DB,Number=0,Type=Flag,Description=\"dbSNP\">
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t100\trs1\tA\tG\t50\tPASS\tDP=10;AF=0.5;DB
+chr1\t200\trs2\tA\tG\t50\tPASS\tAF=0.1
+chr2\t100\trs3\tA\tG\t50\tPASS\tDP=7;AF=0.2
+").unwrap();
+    let keys = |list: &[&str]| Some(list.iter().map(|k| k.to_string()).collect::<Vec<_>>());
+    
+    let cases = [
+        (VcfConvertOptions { info_passthrough: keys(&["DP", "DB"]), ..Default::default() }, ["DP=10;DB", "."], vec!["DP", "DB"]),
+        (VcfConvertOptions { info_blacklist: keys(&["AF"]), ..Default::default() }, ["DP=10;DB", "."], vec!["DP", "DB"]),
+        (
+            VcfConvertOptions { info_passthrough: keys(&["DP", "AF"]), info_blacklist: keys(&["DP"]), ..Default::default() },
+            ["AF=0.5", "AF=0.1"],
+            vec!["AF"],
+        ),
+    ];
+    
+    for (i, (options, expected_info, expected_headers)) in cases.into_iter().enumerate() {
+        for threads in [1, 4] {
+            let output_path = dir.path().join(format!("out_{}_{}.vcf", i, threads));
+            let options = VcfConvertOptions { threads, ..options.clone() };
+            convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap();
+            
+            let output = std::fs::read_to_string(&output_path).unwrap();
+            let info: Vec<&str> = output
+                .lines()
+                .filter(|l| !l.starts_with('#'))
+                .map(|l| l.split('\t').nth(7).unwrap())
+                .collect();
+            assert_eq!(info, expected_info, "case {} threads {}", i, threads);
+            
+            let headers: Vec<&str> = output
+                .lines()
+                .filter_map(|l| l.strip_prefix("##INFO=<ID="))
+                .map(|l| l.split(',').next().unwrap())
+                .collect();
+            assert_eq!(headers, expected_headers, "case {} threads {}", i, threads);
+            
+            // Unmapped records keep all their INFO entries
+            let unmap = std::fs::read_to_string(output_path.with_extension("vcf.unmap")).unwrap();
+            assert!(unmap.contains("rs3\tA\tG\t50\tPASS\tDP=7;AF=0.2"));
+            assert!(unmap.contains("##INFO=<ID=AF,"));
+        }
+    }
+}
+
+/// Lifted VCF carries ##liftover* provenance headers unless disabled
+#[test]
+fn test_vcf_provenance_headers() {
+    use std::process::Command;
+    
+    let dir = tempfile::tempdir().unwrap();
+    let chain_path = dir.path().join("test.chain");
+    std::fs::write(&chain_path, "chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n\n").unwrap();
+    let ref_path = dir.path().join("ref.fa");
+    std::fs::write(&ref_path, format!(">chr1\n{}\n", "A".repeat(20000))).unwrap();
+    let input_path = dir.path().join("in.vcf");
+    std::fs::write(&input_path, "##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\nchr1\t100\t.\tA\tG\t.\t.\t.\n").unwrap();
+    
+    let run = |output: &std::path::Path, extra: &[&str]| {
+        let status = Command::new(env!("CARGO_BIN_EXE_fast-crossmap"))
+            .arg("vcf")
+            .arg(&chain_path)
+            .arg(&input_path)
+            .arg(&ref_path)
+            .arg(output)
+            .args(extra)
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::fs::read_to_string(output).unwrap()
+    };
+    
+    let output = run(&dir.path().join("out.vcf"), &[]);
+    let headers: Vec<&str> = output.lines().take_while(|l| !l.starts_with("#CHROM")).collect();
+    let version = format!("##liftoverVersion={}", env!("CARGO_PKG_VERSION"));
+    assert!(headers.contains(&"##liftoverProgram=fast-crossmap"));
+    assert!(headers.contains(&version.as_str()));
+    assert!(headers.contains(&"##liftoverChain=test.chain"));
+    assert!(headers.iter().any(|h| h.starts_with("##liftoverDate=")));
+    assert!(headers.iter().any(|h| h.starts_with("##liftoverCommandLine=") && h.contains(" vcf ")));
+    // One provenance block: the legacy program line is replaced
+    assert!(!output.contains("##liftOverProgram"));
+    
+    let output = run(&dir.path().join("plain.vcf"), &["--no-provenance-headers"]);
+    assert!(!output.contains("##liftover"));
+    assert_eq!(output.matches("##liftOverProgram=FastCrossMap").count(), 1);
+}
+
+/// --lenient accepts records with 5-7 columns that strict mode rejects
+#[test]
+fn test_vcf_lenient_short_records() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("short.vcf");
+    std::fs::write(&input_path, "\
+##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t100\trs1\tA\tG
+chr1\t200\trs2\tA\tG\t50
+chr1\t300\trs3\tA\tG\t50\tPASS\tDP=3
+chr1\t400\trs4\tA
+").unwrap();
+    
+    for threads in [1, 4] {
+        // Strict (default): only the complete record is lifted
+        let output_path = dir.path().join(format!("strict_{}.vcf", threads));
+        let options = VcfConvertOptions { threads, ..Default::default() };
+        let stats = convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap();
+        assert_eq!((stats.success, stats.failed), (1, 3));
+        
+        // Lenient: missing columns become '.', the 4-column record still fails
+        let output_path = dir.path().join(format!("lenient_{}.vcf", threads));
+        let options = VcfConvertOptions { threads, lenient: true, ..Default::default() };
+        let stats = convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap();
+        assert_eq!((stats.success, stats.failed), (3, 1));
+        
+        let records: Vec<String> = std::fs::read_to_string(&output_path)
+            .unwrap()
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .map(String::from)
+            .collect();
+        assert_eq!(records, vec![
+            "chr1\t5100\trs1\tA\tG\t.\t.\t.",
+            "chr1\t5200\trs2\tA\tG\t50\t.\t.",
+            "chr1\t5300\trs3\tA\tG\t50\tPASS\tDP=3",
+        ]);
+    }
+}
+
+/// With `lift_bnd_mates`, breakend mates are lifted with POS; unliftable
+/// mates follow the policy
+#[test]
+fn test_vcf_bnd_unmap_policy() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("bnd.vcf");
+    std::fs::write(&input_path, "\
+##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t100\tbnd1\tA\tA[chr1:2000[\t.\tPASS\tSVTYPE=BND
+chr1\t200\tbnd2\tC\t]chr9:500]C\t.\tPASS\tSVTYPE=BND
+chr1\t300\tsnv\tA\tG\t.\tPASS\t.
+").unwrap();
+    
+    let lift = |policy: BndUnmapPolicy, threads: usize| {
+        let output_path = dir.path().join(format!("{:?}_{}.vcf", policy, threads));
+        let options = VcfConvertOptions { threads, lift_bnd_mates: true, bnd_unmap_policy: policy, ..Default::default() };
+        let stats = convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap();
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        let unmap = std::fs::read_to_string(format!("{}.unmap", output_path.display())).unwrap();
+        (stats, output, unmap)
+    };
+    let records = |output: &str| -> Vec<String> {
+        output.lines().filter(|l| !l.starts_with('#')).map(String::from).collect()
+    };
+    
+    for threads in [1, 4] {
+        // FailRecord (default): the breakend with an unliftable mate is rejected
+        let (stats, output, unmap) = lift(BndUnmapPolicy::FailRecord, threads);
+        assert_eq!((stats.success, stats.failed), (2, 1));
+        assert_eq!(records(&output), vec![
+            "chr1\t5100\tbnd1\tA\tA[chr1:7000[\t.\tPASS\tSVTYPE=BND",
+            "chr1\t5300\tsnv\tA\tG\t.\tPASS\t.",
+        ]);
+        assert!(unmap.contains("chr1\t200\tbnd2\tC\t]chr9:500]C\t.\tPASS\tSVTYPE=BND\tFail(BndMateUnmap)"));
+        assert!(!output.contains("OriginalBndCoords"));
+        
+        // KeepOriginalCoords: POS is lifted, the mate is left in source coordinates and flagged
+        let (stats, output, _) = lift(BndUnmapPolicy::KeepOriginalCoords, threads);
+        assert_eq!((stats.success, stats.failed), (3, 0));
+        assert_eq!(records(&output)[1], "chr1\t5200\tbnd2\tC\t]chr9:500]C\t.\tPASS\tSVTYPE=BND;OriginalBndCoords");
+        assert!(output.contains("##INFO=<ID=OriginalBndCoords,Number=0,Type=Flag,"));
+        
+        // EmitUnmapped: the breakend loses its mate and becomes a single breakend
+        let (stats, output, _) = lift(BndUnmapPolicy::EmitUnmapped, threads);
+        assert_eq!((stats.success, stats.failed), (3, 0));
+        assert_eq!(records(&output)[1], "chr1\t5200\tbnd2\tC\t.C\t.\tPASS\tSVTYPE=BND");
+        assert!(!output.contains("OriginalBndCoords"));
+    }
+    
+    // Off by default and in strict mode: ALT is written unchanged
+    let unchanged = vec![
+        "chr1\t5100\tbnd1\tA\tA[chr1:2000[\t.\tPASS\tSVTYPE=BND",
+        "chr1\t5200\tbnd2\tC\t]chr9:500]C\t.\tPASS\tSVTYPE=BND",
+        "chr1\t5300\tsnv\tA\tG\t.\tPASS\t.",
+    ];
+    let output_path = dir.path().join("default.vcf");
+    let stats = convert_vcf(&input_path, &output_path, &mapper, None, false, 1).unwrap();
+    assert_eq!((stats.success, stats.failed), (3, 0));
+    assert_eq!(records(&std::fs::read_to_string(&output_path).unwrap()), unchanged);
+    
+    let mut mapper = mapper;
+    mapper.set_compat_mode(fast_crossmap::core::CompatMode::Strict);
+    let output_path = dir.path().join("strict.vcf");
+    let options = VcfConvertOptions { lift_bnd_mates: true, ..Default::default() };
+    let stats = convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap();
+    assert_eq!((stats.success, stats.failed), (3, 0));
+    assert_eq!(records(&std::fs::read_to_string(&output_path).unwrap()), unchanged);
+}
+
+/// A split multi-allelic breakend line whose mates cannot be lifted is
+/// written to the unmap file once
+#[test]
+fn test_vcf_bnd_mate_unmap_split_once() {
+    let chain = fast_crossmap::core::parse_chain_bytes(
+        b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n",
+    ).unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("bnd.vcf");
+    let line = "chr1\t200\tbnd\tC\t]chr9:500]C,C[chr9:900[\t.\tPASS\tSVTYPE=BND";
+    std::fs::write(&input_path, format!("##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n{}\n", line)).unwrap();
+    
+    let output_path = dir.path().join("out.vcf");
+    let options = VcfConvertOptions { lift_bnd_mates: true, split_multiallelic: true, ..Default::default() };
+    let stats = convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap();
+    assert_eq!((stats.success, stats.failed), (0, 1));
+    let unmap = std::fs::read_to_string(dir.path().join("out.vcf.unmap")).unwrap();
+    let failed: Vec<&str> = unmap.lines().filter(|l| !l.starts_with('#')).collect();
+    assert_eq!(failed, vec![format!("{}\tFail(BndMateUnmap)", line)]);
+}
+
+/// Run `f`, collecting the messages of the WARN tracing events it emits
+fn capture_warnings<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    use std::sync::{Arc, Mutex};
+    use tracing::field::{Field, Visit};
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    
+    struct WarnCollector(Arc<Mutex<Vec<String>>>);
+    
+    struct EventVisitor(String);
+    
+    impl Visit for EventVisitor {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if field.name() == "message" {
+                self.0.push_str(&format!("{:?}", value));
+            } else {
+                self.0.push_str(&format!(" {}={:?}", field.name(), value));
+            }
+        }
+    }
+    
+    impl<S: Subscriber> Layer<S> for WarnCollector {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            if *event.metadata().level() == Level::WARN {
+                let mut visitor = EventVisitor(String::new());
+                event.record(&mut visitor);
+                self.0.lock().unwrap().push(visitor.0);
+            }
+        }
+    }
+    
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = tracing_subscriber::registry().with(WarnCollector(warnings.clone()));
+    let result = tracing::subscriber::with_default(subscriber, f);
+    let warnings = warnings.lock().unwrap().clone();
+    (result, warnings)
+}
+
+/// SVTYPE=INV on a negative-strand block warns; --inversion-strict rejects it
+#[test]
+fn test_vcf_inversion_strand_warning() {
+    // chr1 maps to the minus strand of chr1, chr2 to the plus strand
+    let chain = fast_crossmap::core::parse_chain_bytes(b"\
+chain 1000 chr1 10000 + 0 10000 chr1 10000 - 0 10000 1
+10000
+
+chain 1000 chr2 10000 + 0 10000 chr2 10000 + 0 10000 2
+10000
+").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("inv.vcf");
+    std::fs::write(&input_path, "\
+##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t100\tinv_minus\tA\t<INV>\t.\tPASS\tSVTYPE=INV;END=500
+chr2\t100\tinv_plus\tA\t<INV>\t.\tPASS\tSVTYPE=INV;END=500
+chr1\t300\tsnv\tA\tG\t.\tPASS\t.
+").unwrap();
+    
+    let lift = |inversion_strict: bool| {
+        let output_path = dir.path().join(format!("strict_{}.vcf", inversion_strict));
+        let options = VcfConvertOptions { threads: 1, inversion_strict, ..Default::default() };
+        let (stats, warnings) = capture_warnings(|| {
+            convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap()
+        });
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        let unmap = std::fs::read_to_string(format!("{}.unmap", output_path.display())).unwrap();
+        (stats, warnings, output, unmap)
+    };
+    
+    // Default: the record is lifted, with one warning naming the variant
+    let (stats, warnings, output, _) = lift(false);
+    assert_eq!((stats.success, stats.failed), (3, 0));
+    assert!(output.contains("\tinv_minus\t"));
+    let inversion_warnings: Vec<&String> = warnings.iter().filter(|w| w.contains("SVTYPE=INV")).collect();
+    assert_eq!(inversion_warnings.len(), 1, "{:?}", warnings);
+    assert!(inversion_warnings[0].contains("inv_minus") && inversion_warnings[0].contains("pos=100"), "{:?}", warnings);
+    
+    // Strict: the record goes to the unmap file, the plus-strand inversion is kept
+    let (stats, _, output, unmap) = lift(true);
+    assert_eq!((stats.success, stats.failed), (2, 1));
+    assert!(!output.contains("inv_minus"));
+    assert!(output.contains("\tinv_plus\t"));
+    assert!(unmap.contains("chr1\t100\tinv_minus\tA\t<INV>\t.\tPASS\tSVTYPE=INV;END=500\tFail(InversionStrand)"));
+}
+
+/// --update-ci-intervals mirrors CIPOS/CIEND on negative-strand blocks only
+#[test]
+fn test_vcf_update_ci_intervals() {
+    // chr1 maps to the minus strand of chr1, chr2 to the plus strand (+1000)
+    let chain = fast_crossmap::core::parse_chain_bytes(b"\
+chain 1000 chr1 10000 + 0 10000 chr1 10000 - 0 10000 1
+10000
+
+chain 1000 chr2 10000 + 0 9000 chr2 10000 + 1000 10000 2
+9000
+").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("sv.vcf");
+    std::fs::write(&input_path, "\
+##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t100\tdel_minus\tA\t<DEL>\t.\tPASS\tSVTYPE=DEL;END=500;CIPOS=-10,50;CIEND=-20,5
+chr2\t100\tdel_plus\tA\t<DEL>\t.\tPASS\tSVTYPE=DEL;END=500;CIPOS=-10,50;CIEND=-20,5
+").unwrap();
+    
+    let lift = |update_ci_intervals: bool| {
+        let output_path = dir.path().join(format!("ci_{}.vcf", update_ci_intervals));
+        let options = VcfConvertOptions { threads: 1, update_ci_intervals, ..Default::default() };
+        convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap();
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        let records: Vec<Vec<String>> = output
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .map(|l| l.split('\t').map(String::from).collect())
+            .collect();
+        records
+    };
+    let info = |records: &[Vec<String>], id: &str| -> String {
+        records.iter().find(|r| r[2] == id).map(|r| r[7].clone()).unwrap()
+    };
+    
+    // Default: passed through unchanged
+    let records = lift(false);
+    assert!(info(&records, "del_minus").ends_with(";CIPOS=-10,50;CIEND=-20,5"));
+    assert!(info(&records, "del_plus").ends_with(";CIPOS=-10,50;CIEND=-20,5"));
+    
+    let records = lift(true);
+    // Negative strand: [100, 500] lands on [9501, 9901]; the intervals are
+    // mirrored and trade places with POS and END
+    let minus = records.iter().find(|r| r[2] == "del_minus").unwrap();
+    assert_eq!(minus[1], "9501");
+    assert_eq!(info(&records, "del_minus"), "SVTYPE=DEL;END=9901;CIEND=-50,10;CIPOS=-5,20");
+    // Positive strand: shifted by 1000, the intervals are already relative to the new POS
+    let plus = records.iter().find(|r| r[2] == "del_plus").unwrap();
+    assert_eq!(plus[1], "1100");
+    assert!(info(&records, "del_plus").ends_with(";CIPOS=-10,50;CIEND=-20,5"));
+}
+
+/// END= of multi-base records is moved with POS, keeping the record length
+#[test]
+fn test_vcf_sv_end_update() {
+    use fast_crossmap::core::CompatMode;
+    
+    // chr1 shifts by +1000, chr2 maps to the minus strand
+    let chain = fast_crossmap::core::parse_chain_bytes(b"\
+chain 1000 chr1 10000 + 0 9000 chr1 10000 + 1000 10000 1
+9000
+
+chain 1000 chr2 10000 + 0 10000 chr2 10000 - 0 10000 2
+10000
+").unwrap();
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("sv.vcf");
+    std::fs::write(&input_path, "\
+##fileformat=VCFv4.2
+#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO
+chr1\t100\tdel\tA\t<DEL>\t.\tPASS\tSVTYPE=DEL;END=500;CIEND=-20,5
+chr1\t200\tdup\tC\t<DUP>\t.\tPASS\tEND=2200;SVTYPE=DUP
+chr1\t300\tsnv\tG\tT\t.\tPASS\tEND=300
+chr2\t100\tdel_minus\tA\t<DEL>\t.\tPASS\tSVTYPE=DEL;END=150
+").unwrap();
+    
+    let lift = |compat_mode: CompatMode| {
+        let mapper = CoordinateMapper::with_compat_mode(
+            ChainIndex::from_chain_data(chain.clone()), ChromStyle::AsIs, compat_mode,
+        );
+        let output_path = dir.path().join(format!("sv_{:?}.vcf", compat_mode));
+        let options = VcfConvertOptions { threads: 1, ..Default::default() };
+        convert_vcf_with_options(&input_path, &output_path, &mapper, None, &options).unwrap();
+        let output = std::fs::read_to_string(&output_path).unwrap();
+        output
+            .lines()
+            .filter(|l| !l.starts_with('#'))
+            .map(|l| {
+                let fields: Vec<&str> = l.split('\t').collect();
+                (fields[2].to_string(), fields[1].to_string(), fields[7].to_string())
+            })
+            .collect::<Vec<_>>()
+    };
+    
+    let records = lift(CompatMode::Improved);
+    assert_eq!(records, vec![
+        ("del".to_string(), "1100".to_string(), "SVTYPE=DEL;END=1500;CIEND=-20,5".to_string()),
+        ("dup".to_string(), "1200".to_string(), "END=3200;SVTYPE=DUP".to_string()),
+        ("snv".to_string(), "1300".to_string(), "END=1300".to_string()),
+        // On the minus strand the deleted bases 100-150 land at 9851-9901
+        ("del_minus".to_string(), "9851".to_string(), "SVTYPE=DEL;END=9901".to_string()),
+    ]);
+    
+    // Strict mode keeps CrossMap's END (the lifted POS)
+    let records = lift(CompatMode::Strict);
+    assert_eq!(records[0].2, "SVTYPE=DEL;END=1100;CIEND=-20,5");
+    assert_eq!(records[1].2, "END=1200;SVTYPE=DUP");
+}
+
+/// bgzip-compressed input converts like the plain-text file
+#[test]
+fn test_vcf_bgzf_input() {
+    use fast_crossmap::core::bgzf::BgzfWriter;
+    use std::io::Write;
+    
+    let chain = fast_crossmap::core::parse_chain_bytes(b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let mut vcf = String::from("##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n");
+    for i in 0..3000u64 {
+        let chrom = if i % 10 == 0 { "chr9" } else { "chr1" };
+        vcf.push_str(&format!("{}\t{}\tv{}\tA\tG\t50\tPASS\tDP={}\n", chrom, i * 3 + 1, i, i));
+    }
+    
+    let dir = tempfile::tempdir().unwrap();
+    let plain = dir.path().join("in.vcf");
+    let compressed = dir.path().join("in.vcf.gz");
+    std::fs::write(&plain, &vcf).unwrap();
+    let mut writer = BgzfWriter::new(std::fs::File::create(&compressed).unwrap());
+    writer.write_all(vcf.as_bytes()).unwrap();
+    writer.finish().unwrap();
+    
+    for threads in [1, 4] {
+        let plain_out = dir.path().join(format!("plain_{}.vcf", threads));
+        let bgzf_out = dir.path().join(format!("bgzf_{}.vcf", threads));
+        let expected = convert_vcf(&plain, &plain_out, &mapper, None, false, threads).unwrap();
+        let stats = convert_vcf(&compressed, &bgzf_out, &mapper, None, false, threads).unwrap();
+        
+        assert_eq!((stats.total, stats.success, stats.failed), (expected.total, expected.success, expected.failed));
+        assert_eq!((stats.total, stats.failed), (3000, 300));
+        // Provenance headers carry a timestamp; compare the records
+        let records = |path: &std::path::Path| -> Vec<String> {
+            std::fs::read_to_string(path).unwrap().lines().filter(|l| !l.starts_with("##")).map(String::from).collect()
+        };
+        assert_eq!(records(&bgzf_out), records(&plain_out));
+    }
+}
+
+/// Compressed output decompresses to the plain output
+#[test]
+fn test_vcf_compressed_output_round_trip() {
+    use fast_crossmap::core::bgzf::BgzfReader;
+    use fast_crossmap::formats::{convert_vcf_with_options, VcfConvertOptions};
+    use std::io::Read;
+    
+    let chain = fast_crossmap::core::parse_chain_bytes(b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let mut vcf = String::from("##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n");
+    for i in 0..3000u64 {
+        let chrom = if i % 10 == 0 { "chr9" } else { "chr1" };
+        vcf.push_str(&format!("{}\t{}\tv{}\tA\tG\t50\tPASS\tDP={}\n", chrom, i * 3 + 1, i, i));
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("in.vcf");
+    std::fs::write(&input, &vcf).unwrap();
+    
+    for threads in [1, 4] {
+        let plain_out = dir.path().join(format!("plain_{}.vcf", threads));
+        let bgzf_out = dir.path().join(format!("bgzf_{}.vcf.gz", threads));
+        let options = VcfConvertOptions { threads, generate_provenance_headers: false, ..Default::default() };
+        let expected = convert_vcf_with_options(&input, &plain_out, &mapper, None, &options).unwrap();
+        let compress = VcfConvertOptions { compress: true, ..options };
+        let stats = convert_vcf_with_options(&input, &bgzf_out, &mapper, None, &compress).unwrap();
+        assert_eq!((stats.total, stats.success, stats.failed), (expected.total, expected.success, expected.failed));
+        
+        let mut text = String::new();
+        BgzfReader::new(std::fs::File::open(&bgzf_out).unwrap()).read_to_string(&mut text).unwrap();
+        assert_eq!(text, std::fs::read_to_string(&plain_out).unwrap());
+        // The unmap file is written uncompressed next to the output
+        let unmap = dir.path().join(format!("bgzf_{}.vcf.unmap", threads));
+        assert_eq!(std::fs::read_to_string(unmap).unwrap().lines().filter(|l| l.starts_with("chr9")).count(), 300);
+    }
+}
+
+/// A strand flip that turns ALT into the target REF is counted in
+/// `ref_eq_alt` unless `no_comp_allele` is set
+#[test]
+fn test_vcf_ref_eq_alt_counter() {
+    let chain = fast_crossmap::core::parse_chain_bytes(b"chain 1000 chr1 10000 + 0 10000 chr1 20000 - 5000 15000 1\n10000\n").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let ref_path = dir.path().join("ref.fa");
+    std::fs::write(&ref_path, format!(">chr1\n{}\n", "C".repeat(20000))).unwrap();
+    let input = dir.path().join("in.vcf");
+    // On the minus strand G becomes C (the target REF) while T becomes A
+    std::fs::write(&input, "##fileformat=VCFv4.2\n#CHROM\tPOS\tID\tREF\tALT\tQUAL\tFILTER\tINFO\n\
+        chr1\t100\tflip\tA\tG\t.\t.\t.\nchr1\t200\tkeep\tA\tT\t.\t.\t.\nchr1\t300\tflip2\tA\tG\t.\t.\t.\n").unwrap();
+    
+    for threads in [1, 4] {
+        let output = dir.path().join(format!("out_{}.vcf", threads));
+        let stats = convert_vcf(&input, &output, &mapper, Some(&ref_path), false, threads).unwrap();
+        assert_eq!((stats.total, stats.success, stats.failed, stats.ref_eq_alt), (3, 1, 2, 2));
+        let unmap = std::fs::read_to_string(dir.path().join(format!("out_{}.vcf.unmap", threads))).unwrap();
+        assert_eq!(unmap.lines().filter(|l| l.ends_with("Fail(REF==ALT)")).count(), 2);
+        
+        let stats = convert_vcf(&input, &output, &mapper, Some(&ref_path), true, threads).unwrap();
+        assert_eq!((stats.total, stats.success, stats.failed, stats.ref_eq_alt), (3, 3, 0, 0));
+        let lifted = std::fs::read_to_string(&output).unwrap();
+        assert!(lifted.lines().any(|l| l.contains("\tflip\tC\tC\t")));
+    }
+    
+    // Strict mode matches CrossMap: the record is kept with an empty ALT
+    let mut mapper = mapper;
+    mapper.set_compat_mode(fast_crossmap::core::CompatMode::Strict);
+    let output = dir.path().join("strict.vcf");
+    let stats = convert_vcf(&input, &output, &mapper, Some(&ref_path), false, 1).unwrap();
+    assert_eq!((stats.success, stats.ref_eq_alt), (3, 0));
+    assert!(std::fs::read_to_string(&output).unwrap().lines().any(|l| l.contains("\tflip\tC\t\t")));
+}
@@ -4,7 +4,7 @@
 //! **Validates: Requirements 9.1, 9.2, 9.3, 9.4, 9.5, 9.6**
 
 use fast_crossmap::core::{ChainIndex, CoordinateMapper, ChromStyle};
-use fast_crossmap::formats::wig::{WigReader, WigDeclaration, WigFormat, convert_wig, BedGraphRecord};
+use fast_crossmap::formats::wig::{WigReader, WigDeclaration, WigFormat, WigDataPoint, convert_wig, BedGraphRecord};
 use proptest::prelude::*;
 use std::io::Cursor;
 use std::path::PathBuf;
@@ -115,12 +115,19 @@ proptest! {
         
         let cursor = Cursor::new(wig_content.as_bytes());
         let reader = WigReader::new(std::io::BufReader::new(cursor));
-        let points: Vec<_> = reader.collect();
+        let mut points: Vec<WigDataPoint> = reader.map(|p| p.unwrap()).collect();
+        
+        // Dense data (step=1, span=1) is run-length encoded: expand runs to bases
+        if step == 1 && span == 1 {
+            points = points
+                .iter()
+                .flat_map(|p| (p.start..p.end).map(move |s| WigDataPoint { start: s, end: s + 1, ..p.clone() }))
+                .collect();
+        }
         
         prop_assert_eq!(points.len(), values.len());
         
         for (i, point) in points.iter().enumerate() {
-            let point = point.as_ref().unwrap();
             let expected_start = (start - 1) + (i as u64) * step;
             
             prop_assert_eq!(&point.chrom, &chrom);
@@ -401,3 +408,139 @@ variableStep chrom=chr2 span=10
     let _ = std::fs::remove_file(format!("{}.sorted.bgr", crossmap_output.display()));
     let _ = std::fs::remove_file(format!("{}.bw", crossmap_output.display()));
 }
+
+/// Run `f`, counting the WARN tracing events it emits
+fn count_warnings<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tracing::{Event, Level, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    
+    struct WarnCounter(Arc<AtomicUsize>);
+    
+    impl<S: Subscriber> Layer<S> for WarnCounter {
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            if *event.metadata().level() == Level::WARN {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+    
+    let warnings = Arc::new(AtomicUsize::new(0));
+    let subscriber = tracing_subscriber::registry().with(WarnCounter(warnings.clone()));
+    let result = tracing::subscriber::with_default(subscriber, f);
+    (result, warnings.load(Ordering::SeqCst))
+}
+
+/// Invalid data lines are skipped with a WARN tracing event
+#[test]
+fn test_wig_bad_record_emits_warning() {
+    use fast_crossmap::core::parse_chain_bytes;
+    
+    let chain = parse_chain_bytes(b"chain 1000 chr1 10000 + 0 10000 chr1 10000 + 0 10000 1\n10000\n").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("bad.wig");
+    let output_prefix = dir.path().join("bad_out");
+    std::fs::write(&input_path, "variableStep chrom=chr1 span=10\n100\t1.5\nnot_a_number\t2.5\n200\t3.5\n").unwrap();
+    
+    let (stats, warnings) = count_warnings(|| convert_wig(&input_path, &output_prefix, &mapper).unwrap());
+    
+    assert_eq!(stats.success, 2);
+    assert_eq!(warnings, 1, "bad record should emit one WARN event");
+}
+
+/// --add-track-header puts a track line first in the lifted output only
+#[test]
+fn test_wig_track_header() {
+    use fast_crossmap::core::parse_chain_bytes;
+    use fast_crossmap::formats::wig::{convert_wig_with_options, WigConvertOptions};
+    
+    let chain = parse_chain_bytes(b"chain 1000 chr1 10000 + 0 10000 chr1 20000 + 5000 15000 1\n10000\n").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("signal.wig");
+    std::fs::write(&input_path, "track type=wiggle_0 name=\"orig\"\nvariableStep chrom=chr1 span=10\n100\t1.5\n200\t2.5\nvariableStep chrom=chr2\n5\t1\n").unwrap();
+    
+    for threads in [1, 4] {
+        let output_prefix = dir.path().join(format!("lifted_{}", threads));
+        let options = WigConvertOptions { threads, write_track_header: true, ..Default::default() };
+        convert_wig_with_options(&input_path, &output_prefix, &mapper, &options).unwrap();
+        
+        let output = std::fs::read_to_string(format!("{}.wig", output_prefix.display())).unwrap();
+        let first = output.lines().find(|l| !l.starts_with('#')).unwrap();
+        assert_eq!(
+            first,
+            format!("track type=wiggle_0 name=\"lifted_{}\" description=\"Lifted from signal.wig\"", threads)
+        );
+        assert_eq!(output.lines().filter(|l| l.starts_with("track")).count(), 1);
+        assert!(output.lines().nth(1).unwrap().starts_with("variableStep chrom=chr1"));
+        
+        // The unmap file has no track line
+        let unmap = std::fs::read_to_string(format!("{}.unmap.wig", output_prefix.display())).unwrap();
+        assert!(!unmap.contains("track"));
+    }
+    
+    // Off by default
+    let output_prefix = dir.path().join("plain");
+    convert_wig(&input_path, &output_prefix, &mapper).unwrap();
+    let output = std::fs::read_to_string(format!("{}.wig", output_prefix.display())).unwrap();
+    assert!(output.starts_with("variableStep"));
+}
+
+/// Overlapping consecutive variableStep points are warned about
+#[test]
+fn test_wig_variable_step_overlap_warning() {
+    let data = "\
+variableStep chrom=chr1 span=10
+100\t1
+105\t2
+115\t3
+variableStep chrom=chr1 span=10
+110\t4
+";
+    let (points, warnings) = count_warnings(|| WigReader::new(Cursor::new(data)).collect::<Vec<_>>());
+    assert_eq!(points.len(), 4);
+    assert!(points.iter().all(|p| p.is_ok()));
+    // Only 100 -> 105 overlaps; a new declaration starts afresh
+    assert_eq!(warnings, 1);
+}
+
+/// Points past the source chromosome end: warned and lifted, or failed
+#[test]
+fn test_wig_span_exceeds_chromosome_modes() {
+    use fast_crossmap::core::parse_chain_bytes;
+    use fast_crossmap::formats::wig::{convert_wig_with_options, WigConvertOptions, WigErrorMode};
+    
+    let chain = parse_chain_bytes(b"chain 1000 chr1 1000 + 0 1000 chr1 2000 + 500 1500 1\n1000\n").unwrap();
+    let mapper = CoordinateMapper::new(ChainIndex::from_chain_data(chain), ChromStyle::AsIs);
+    
+    let dir = tempfile::tempdir().unwrap();
+    let input_path = dir.path().join("edge.wig");
+    std::fs::write(&input_path, "fixedStep chrom=chr1 start=971 step=20 span=20\n1\n2\n").unwrap();
+    
+    for threads in [1, 2] {
+        let output_prefix = dir.path().join(format!("warn_{}", threads));
+        let options = WigConvertOptions { threads, ..Default::default() };
+        let (stats, warnings) = count_warnings(|| {
+            convert_wig_with_options(&input_path, &output_prefix, &mapper, &options).unwrap()
+        });
+        // One from the reader, plus the mapper's own bounds check when it
+        // runs on this thread (worker threads don't see the test subscriber)
+        assert_eq!(warnings, if threads == 1 { 2 } else { 1 });
+        // The mapper lifts the part of the overhanging point inside the block
+        assert_eq!((stats.total, stats.success, stats.failed), (2, 2, 0));
+        
+        let output_prefix = dir.path().join(format!("error_{}", threads));
+        let options = WigConvertOptions { threads, error_mode: WigErrorMode::Error, ..Default::default() };
+        let stats = convert_wig_with_options(&input_path, &output_prefix, &mapper, &options).unwrap();
+        assert_eq!(stats.success, 1);
+        if threads == 1 {
+            assert_eq!((stats.total, stats.failed), (1, 1));
+        }
+        let output = std::fs::read_to_string(format!("{}.wig", output_prefix.display())).unwrap();
+        assert!(!output.contains("1490"), "{}", output);
+    }
+}